@@ -8,11 +8,12 @@ use poise::futures::{self, Stream, StreamExt};
 // is the partial input which the user has typed so far.
 //
 // As the return value of autocomplete functions, you can return a Stream, an Iterator, or an
-// IntoIterator (which includes e.g. Vec<T> and [T; N]).
+// IntoIterator (which includes e.g. Vec<T> and [T; N]). If generating the choices can fail, wrap
+// the return value in a Result with your error type; errors are passed to the on_error handler.
 //
 // As the type of the returned items, you can use either the type directly, or wrap it in
 // AutocompleteChoice. By wrapping in AutocompleteChoice, you can set a custom name for each choice
-// which will be displayed in the Discord UI.
+// which will be displayed in the Discord UI, and optionally localized names for other languages.
 
 async fn autocomplete_name(_ctx: Context<'_>, partial: String) -> impl Stream<Item = String> {
     futures::stream::iter(&["Amanda", "Bob", "Christian", "Danny", "Ester", "Falk"])
@@ -25,15 +26,16 @@ async fn autocomplete_number(
     _partial: u32,
 ) -> impl Iterator<Item = poise::AutocompleteChoice<u32>> {
     // Dummy choices
-    [1_u32, 2, 3, 4, 5]
-        .iter()
-        .map(|&n| poise::AutocompleteChoice {
-            name: format!(
+    [1_u32, 2, 3, 4, 5].iter().map(|&n| {
+        poise::AutocompleteChoice::new(
+            format!(
                 "{} (why did discord even give autocomplete choices separate labels)",
                 n
             ),
-            value: n,
-        })
+            n,
+        )
+        .name_localized("de", format!("{} (auf Deutsch)", n))
+    })
 }
 
 /// Greet a user. Showcasing autocomplete!
@@ -1,10 +1,22 @@
 use crate::{Context, Error};
 use poise::serenity_prelude as serenity;
 
+/// Reacts with an hourglass instead of replying when someone votes too quickly
+async fn vote_cooldown_hit(
+    _: poise::CooldownHit<'_, crate::Data, Error>,
+) -> poise::CooldownFeedback {
+    poise::CooldownFeedback::React(serenity::ReactionType::Unicode("⏳".into()))
+}
+
 /// Vote for something
 ///
 /// Enter `~vote pumpkin` to vote for pumpkins
-#[poise::command(prefix_command, slash_command)]
+#[poise::command(
+    prefix_command,
+    slash_command,
+    user_cooldown = "10s",
+    on_cooldown = "vote_cooldown_hit"
+)]
 pub async fn vote(
     ctx: Context<'_>,
     #[description = "What to vote for"] choice: String,
@@ -31,7 +43,13 @@ pub async fn vote(
 /// ~getvotes
 /// ~getvotes pumpkin
 /// ```
-#[poise::command(prefix_command, track_edits, aliases("votes"), slash_command)]
+#[poise::command(
+    prefix_command,
+    track_edits,
+    aliases("votes"),
+    examples("getvotes", "getvotes pumpkin"),
+    slash_command
+)]
 pub async fn getvotes(
     ctx: Context<'_>,
     #[description = "Choice to retrieve votes for"] choice: Option<String>,
@@ -89,6 +107,48 @@ pub async fn choice(
     Ok(())
 }
 
+#[derive(Debug, poise::SlashChoiceParameter)]
+#[payload_type = "&'static str"]
+pub enum Region {
+    #[name = "Europe"]
+    #[payload = "\"eu-west\""]
+    Europe,
+    #[name = "North America"]
+    #[name = "NA"]
+    #[payload = "\"us-east\""]
+    NorthAmerica,
+}
+
+/// Dummy command to test slash command choice parameters with attached data
+#[poise::command(prefix_command, slash_command)]
+pub async fn region(
+    ctx: Context<'_>,
+    #[description = "The region to deploy to"] region: Region,
+) -> Result<(), Error> {
+    ctx.say(format!("Deploying to {}", region.payload()))
+        .await?;
+    Ok(())
+}
+
+#[derive(Debug, poise::PrefixArgs)]
+pub struct SearchArgs {
+    query: String,
+    page: Option<u32>,
+    #[flag]
+    exact: bool,
+    #[named]
+    author: Option<serenity::UserId>,
+}
+
+/// Dummy command to test struct arguments
+///
+/// ~search cats exact author=123456789 2
+#[poise::command(prefix_command)]
+pub async fn search(ctx: Context<'_>, args: SearchArgs) -> Result<(), Error> {
+    ctx.say(format!("Searching with {:?}", args)).await?;
+    Ok(())
+}
+
 /// Boop the bot!
 #[poise::command(prefix_command, track_edits, slash_command)]
 pub async fn boop(ctx: Context<'_>) -> Result<(), Error> {
@@ -24,8 +24,13 @@ async fn help(
     poise::builtins::help(
         ctx,
         command.as_deref(),
-        "This is an example bot made to showcase features of my custom Discord bot framework",
-        poise::builtins::HelpResponseMode::Ephemeral,
+        poise::builtins::HelpConfiguration {
+            extra_text_at_bottom:
+                "This is an example bot made to showcase features of my custom Discord bot framework",
+            response_mode: poise::builtins::HelpResponseMode::Ephemeral,
+            filter_by_checks: true,
+            ..Default::default()
+        },
     )
     .await?;
     Ok(())
@@ -61,6 +66,8 @@ async fn main() {
                 poise::Prefix::Literal("hey bot"),
                 poise::Prefix::Literal("hey bot,"),
             ],
+            prefix_store: Some(Box::new(poise::InMemoryPrefixStore::new())),
+            unknown_command: Some(|x| Box::pin(poise::builtins::suggest_similar_commands(x))),
             ..Default::default()
         },
         on_error: |error, ctx| Box::pin(on_error(error, ctx)),
@@ -93,6 +100,8 @@ async fn main() {
         .command(commands::getvotes(), |f| f)
         .command(commands::add(), |f| f)
         .command(commands::choice(), |f| f)
+        .command(commands::region(), |f| f)
+        .command(commands::search(), |f| f)
         .command(commands::boop(), |f| f)
         .command(context_menu::user_info(), |f| f)
         .command(context_menu::echo(), |f| f)
@@ -6,7 +6,7 @@ use syn::spanned::Spanned as _;
 
 // ngl this is ugly
 // transforms a type of form `OuterType<T>` into `T`
-fn extract_type_parameter<'a>(outer_type: &str, t: &'a syn::Type) -> Option<&'a syn::Type> {
+pub fn extract_type_parameter<'a>(outer_type: &str, t: &'a syn::Type) -> Option<&'a syn::Type> {
     if let syn::Type::Path(path) = t {
         if path.path.segments.len() == 1 {
             let path = &path.path.segments[0];
@@ -40,6 +40,252 @@ fn extract_vec_type(t: &syn::Type) -> Option<&syn::Type> {
     extract_type_parameter("Vec", t)
 }
 
+/// Parses a `#[min = ...]` or `#[max = ...]` bound. Negative bounds must be given as a string,
+/// e.g. `#[min = "-5"]`, because attribute values can't be negative literals
+fn parse_number_bound(lit: &syn::Lit) -> Result<f64, syn::Error> {
+    match lit {
+        syn::Lit::Int(lit) => lit.base10_parse(),
+        syn::Lit::Float(lit) => lit.base10_parse(),
+        syn::Lit::Str(lit) => lit
+            .value()
+            .trim()
+            .parse()
+            .map_err(|_| syn::Error::new(lit.span(), "expected a number")),
+        _ => Err(syn::Error::new(lit.span(), "expected a number")),
+    }
+}
+
+/// Returns the `#[min]` and `#[max]` bounds of a parameter
+fn number_bounds(param: &CommandParameter) -> Result<(Option<f64>, Option<f64>), syn::Error> {
+    let min = param
+        .more
+        .min
+        .as_ref()
+        .map(parse_number_bound)
+        .transpose()?;
+    let max = param
+        .more
+        .max
+        .as_ref()
+        .map(parse_number_bound)
+        .transpose()?;
+    Ok((min, max))
+}
+
+/// Generates the code which checks the parsed arguments against `#[regex]`, `#[validate]`,
+/// `#[min]`, `#[max]`, `#[url_schemes]` and `#[url_hosts]` parameter attributes. Must be inserted
+/// after the arguments were parsed into variables
+fn generate_argument_validations(
+    parameters: &[CommandParameter],
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let mut validations = Vec::new();
+    for param in parameters {
+        let (min, max) = number_bounds(param)?;
+        let restricts_url = param.more.url_schemes.is_some() || param.more.url_hosts.is_some();
+        if param.more.regex.is_none()
+            && param.more.validate.is_none()
+            && min.is_none()
+            && max.is_none()
+            && !restricts_url
+        {
+            continue;
+        }
+
+        let name = &param.name;
+        let name_literal = proc_macro2::Literal::string(&name.to_string());
+        if let Some(regex) = &param.more.regex {
+            // Compile the pattern like the runtime does, so that mistakes are reported right here
+            if let Err(e) = regex::Regex::new(&format!("^(?:{})$", regex)) {
+                return Err(syn::Error::new(
+                    param.span,
+                    format!("invalid #[regex] pattern: {}", e),
+                ));
+            }
+        }
+        let regex_validation = param.more.regex.as_ref().map(|regex| {
+            quote::quote! {
+                static REGEX: ::poise::_ArgumentRegex = ::poise::_ArgumentRegex::new(#regex);
+                REGEX.validate(#name_literal, ::std::convert::AsRef::<str>::as_ref(value))?;
+            }
+        });
+        let fn_validation = param.more.validate.as_ref().map(|validate| {
+            quote::quote! {
+                ::poise::_validate_argument(#name_literal, value, #validate)?;
+            }
+        });
+        let range_validation = if min.is_some() || max.is_some() {
+            let min = wrap_option(min.map(proc_macro2::Literal::f64_unsuffixed));
+            let max = wrap_option(max.map(proc_macro2::Literal::f64_unsuffixed));
+            Some(quote::quote! {
+                ::poise::_validate_range(#name_literal, *value as f64, #min, #max)?;
+            })
+        } else {
+            None
+        };
+        let url_validation = if restricts_url {
+            let schemes = param.more.url_schemes.as_ref().map_or(&[][..], |x| &x.0);
+            let hosts = param.more.url_hosts.as_ref().map_or(&[][..], |x| &x.0);
+            Some(quote::quote! {
+                ::poise::_validate_url(#name_literal, value, &[ #( #schemes ),* ], &[ #( #hosts ),* ])?;
+            })
+        } else {
+            None
+        };
+        let validate_value = quote::quote! {
+            { #regex_validation #fn_validation #range_validation #url_validation }
+        };
+
+        // Optional and variadic arguments are validated per contained value
+        validations.push(if extract_option_type(&param.type_).is_some() {
+            quote::quote! { if let Some(value) = &#name #validate_value }
+        } else if extract_vec_type(&param.type_).is_some() {
+            quote::quote! { for value in &#name #validate_value }
+        } else {
+            quote::quote! { let value = &#name; #validate_value }
+        });
+    }
+    Ok(quote::quote! { #( { #validations } )* })
+}
+
+/// Returns the type which the raw argument is extracted as. For `#[parse_with]` parameters, that's
+/// `String` (wrapped in `Option` or `Vec` like the parameter type), otherwise the parameter type
+fn raw_argument_type(param: &CommandParameter) -> syn::Type {
+    if param.more.parse_with.is_none() {
+        return param.type_.clone();
+    }
+    if extract_option_type(&param.type_).is_some() {
+        syn::parse_quote! { Option<String> }
+    } else if extract_vec_type(&param.type_).is_some() {
+        syn::parse_quote! { Vec<String> }
+    } else {
+        syn::parse_quote! { String }
+    }
+}
+
+/// Generates the code which passes the raw string arguments of `#[parse_with]` parameters to the
+/// custom parser function, fills in `#[language]` defaults of code block parameters and applies
+/// `#[transform]` functions. Must be inserted after the arguments were parsed into variables and
+/// before they're validated
+fn generate_argument_transformations(parameters: &[CommandParameter]) -> proc_macro2::TokenStream {
+    let default_languages = parameters.iter().filter_map(|param| {
+        let language = param.more.language.as_ref()?;
+        let name = &param.name;
+        Some(if extract_option_type(&param.type_).is_some() {
+            quote::quote! { let #name = #name.map(|x| x.with_default_language(#language)); }
+        } else if extract_vec_type(&param.type_).is_some() {
+            quote::quote! { let #name = #name
+                .into_iter()
+                .map(|x| x.with_default_language(#language))
+                .collect::<Vec<_>>();
+            }
+        } else {
+            quote::quote! { let #name = #name.with_default_language(#language); }
+        })
+    });
+    let parsers = parameters.iter().filter_map(|param| {
+        let parser = param.more.parse_with.as_ref()?;
+        let name = &param.name;
+        Some(if extract_option_type(&param.type_).is_some() {
+            quote::quote! { let #name = match #name {
+                Some(raw) => Some(#parser(ctx.into(), &raw)?),
+                None => None,
+            }; }
+        } else if extract_vec_type(&param.type_).is_some() {
+            quote::quote! { let #name = #name
+                .iter()
+                .map(|raw| #parser(ctx.into(), raw))
+                .collect::<Result<Vec<_>, _>>()?;
+            }
+        } else {
+            quote::quote! { let #name = #parser(ctx.into(), &#name)?; }
+        })
+    });
+    let transforms = parameters.iter().filter_map(|param| {
+        let transform = param.more.transform.as_ref()?;
+        let name = &param.name;
+        Some(if extract_option_type(&param.type_).is_some() {
+            quote::quote! { let #name = #name.map(#transform); }
+        } else if extract_vec_type(&param.type_).is_some() {
+            quote::quote! { let #name = #name.into_iter().map(#transform).collect::<Vec<_>>(); }
+        } else {
+            quote::quote! { let #name = #transform(#name); }
+        })
+    });
+    quote::quote! { #( #parsers )* #( #default_languages )* #( #transforms )* }
+}
+
+/// Generates the code which resolves `#[id_kind(...)]` restricted `poise::AnyId` arguments. Must be
+/// inserted after the arguments were parsed into variables
+fn generate_id_kind_resolutions(
+    parameters: &[CommandParameter],
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let mut resolutions = Vec::new();
+    for param in parameters {
+        let kinds = match &param.more.id_kind {
+            Some(kinds) => kinds
+                .0
+                .iter()
+                .map(|kind| match kind.as_str() {
+                    "user" => Ok(quote::quote! { ::poise::IdKind::User }),
+                    "role" => Ok(quote::quote! { ::poise::IdKind::Role }),
+                    "channel" => Ok(quote::quote! { ::poise::IdKind::Channel }),
+                    "message" => Ok(quote::quote! { ::poise::IdKind::Message }),
+                    _ => Err(syn::Error::new(
+                        param.span,
+                        "id_kind must be one of \"user\", \"role\", \"channel\" or \"message\"",
+                    )),
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            None => continue,
+        };
+
+        let name = &param.name;
+        let name_literal = proc_macro2::Literal::string(&name.to_string());
+        let resolve = quote::quote! { {
+            let ctx = ::poise::Context::from(ctx);
+            ::poise::_resolve_id_kind(
+                ctx.discord(),
+                ctx.guild_id(),
+                ctx.channel_id(),
+                #name_literal,
+                id,
+                &[ #( #kinds, )* ],
+            ).await?
+        } };
+
+        // Optional and variadic arguments are resolved per contained value
+        resolutions.push(if extract_option_type(&param.type_).is_some() {
+            quote::quote! { let #name = match #name {
+                Some(id) => Some(#resolve),
+                None => None,
+            }; }
+        } else if extract_vec_type(&param.type_).is_some() {
+            quote::quote! { let #name = {
+                let mut ids = Vec::new();
+                for id in #name {
+                    ids.push(#resolve);
+                }
+                ids
+            }; }
+        } else {
+            quote::quote! { let id = #name; let #name = #resolve; }
+        });
+    }
+    Ok(quote::quote! { #( #resolutions )* })
+}
+
+// Whether the type is `Attachment` or some path ending in it like `serenity::Attachment`
+fn is_attachment_type(t: &syn::Type) -> bool {
+    match t {
+        syn::Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map_or(false, |segment| segment.ident == "Attachment"),
+        _ => false,
+    }
+}
+
 struct AllLifetimesToStatic;
 impl syn::fold::Fold for AllLifetimesToStatic {
     fn fold_lifetime(&mut self, _: syn::Lifetime) -> syn::Lifetime {
@@ -47,10 +293,11 @@ impl syn::fold::Fold for AllLifetimesToStatic {
     }
 }
 
+/// A list of strings in an attribute, e.g. `aliases("foo", "bar")`
 #[derive(Debug, Default)]
-struct Aliases(Vec<String>);
+struct StringList(Vec<String>);
 
-impl darling::FromMeta for Aliases {
+impl darling::FromMeta for StringList {
     fn from_list(items: &[::syn::NestedMeta]) -> darling::Result<Self> {
         items
             .iter()
@@ -60,6 +307,24 @@ impl darling::FromMeta for Aliases {
     }
 }
 
+/// Role names and IDs of the `required_roles("Moderator", 123456789)` attribute
+#[derive(Default, Debug)]
+struct RoleList(Vec<syn::Lit>);
+
+impl darling::FromMeta for RoleList {
+    fn from_list(items: &[::syn::NestedMeta]) -> darling::Result<Self> {
+        items
+            .iter()
+            .map(|item| match item {
+                syn::NestedMeta::Lit(lit @ syn::Lit::Str(_))
+                | syn::NestedMeta::Lit(lit @ syn::Lit::Int(_)) => Ok(lit.clone()),
+                _ => Err(darling::Error::custom("expected a role name or ID").with_span(item)),
+            })
+            .collect::<darling::Result<Vec<_>>>()
+            .map(Self)
+    }
+}
+
 /// Representation of the command attribute arguments (`#[command(...)]`)
 #[derive(Default, Debug, darling::FromMeta)]
 #[darling(default)]
@@ -68,18 +333,48 @@ pub struct CommandOptions {
     slash_command: bool,
     context_menu_command: Option<String>,
 
-    aliases: Aliases,
+    aliases: StringList,
+    usage: Option<String>,
+    examples: StringList,
     track_edits: bool,
     broadcast_typing: bool,
+    broadcast_typing_after: Option<String>,
+    global_cooldown: Option<String>,
+    user_cooldown: Option<String>,
+    guild_cooldown: Option<String>,
+    channel_cooldown: Option<String>,
+    member_cooldown: Option<String>,
+    custom_cooldown: Option<String>,
+    cooldown_key: Option<syn::Path>,
+    bucket: Option<String>,
+    timeout: Option<String>,
+    max_concurrent: Option<u32>,
+    concurrency_scope: Option<String>,
+    max_queued: Option<u32>,
+    on_cooldown: Option<syn::Path>,
     explanation_fn: Option<syn::Path>,
-    check: Option<syn::Path>,
+    check: Option<String>,
+    check_cache_ttl: Option<String>,
     on_error: Option<syn::Path>,
     rename: Option<String>,
     discard_spare_arguments: bool,
     hide_in_help: bool,
+    respond_in_thread: Option<bool>,
     ephemeral: bool,
     required_permissions: Option<syn::Ident>,
+    required_roles: RoleList,
     owners_only: bool,
+    allowed_channel_types: Option<StringList>,
+    nsfw_only: bool,
+}
+
+/// Representation of the `#[autocomplete_cache(...)]` parameter attribute arguments
+#[derive(Default, Debug, darling::FromMeta)]
+#[darling(default)]
+struct AutocompleteCacheOptions {
+    /// In seconds
+    ttl: Option<u64>,
+    max_entries: Option<usize>,
 }
 
 /// Representation of the function parameter attribute arguments
@@ -88,8 +383,23 @@ pub struct CommandOptions {
 struct ParamOptions {
     description: Option<String>,
     autocomplete: Option<syn::Path>,
+    autocomplete_fallback: Option<syn::Path>,
+    autocomplete_cache: Option<AutocompleteCacheOptions>,
+    max_count: Option<usize>,
+    custom_data: Option<String>,
+    regex: Option<String>,
+    validate: Option<syn::Path>,
+    min: Option<syn::Lit>,
+    max: Option<syn::Lit>,
+    url_schemes: Option<StringList>,
+    url_hosts: Option<StringList>,
+    parse_with: Option<syn::Path>,
+    transform: Option<syn::Path>,
+    id_kind: Option<StringList>,
+    language: Option<String>,
     lazy: bool,
     flag: bool,
+    named: bool,
     rest: bool,
 }
 
@@ -112,6 +422,291 @@ pub struct Invocation<'a> {
     more: &'a CommandOptions,
 }
 
+/// Parses a duration like `500ms`, `2s`, `5m` or `1h` into milliseconds
+fn parse_duration_millis(duration: &str) -> Option<u64> {
+    let duration = duration.trim();
+    let unit_start = duration.find(|c: char| !c.is_ascii_digit())?;
+    let (number, unit) = duration.split_at(unit_start);
+    let millis_per_unit = match unit.trim() {
+        "ms" => 1,
+        "s" => 1000,
+        "m" => 60 * 1000,
+        "h" => 60 * 60 * 1000,
+        _ => return None,
+    };
+    number.parse::<u64>().ok()?.checked_mul(millis_per_unit)
+}
+
+/// Generates a `Option<std::time::Duration>` expression from a duration attribute value like
+/// `"2s"`. See [`parse_duration_millis`]
+fn generate_duration(
+    attribute_name: &str,
+    duration: &Option<String>,
+) -> Result<proc_macro2::TokenStream, darling::Error> {
+    let duration = match duration {
+        Some(x) => x,
+        None => return Ok(quote::quote! { None }),
+    };
+    match parse_duration_millis(duration) {
+        Some(millis) => Ok(quote::quote! { Some(std::time::Duration::from_millis(#millis)) }),
+        None => Err(syn::Error::new(
+            proc_macro2::Span::call_site(),
+            format!(
+                "invalid `{}` duration {:?}; expected a number with unit ms, s, m or h, like \"2s\"",
+                attribute_name, duration
+            ),
+        )
+        .into()),
+    }
+}
+
+/// Generates the static `ConcurrencyLimiter` from the `max_concurrent`, `concurrency_scope` and
+/// `max_queued` attributes, which is shared by all command types. Must be inserted into the
+/// generated command function
+fn generate_concurrency_limiter(
+    args: &CommandOptions,
+) -> Result<Option<proc_macro2::TokenStream>, syn::Error> {
+    let max = match args.max_concurrent {
+        Some(x) => x,
+        None => {
+            if args.concurrency_scope.is_some() || args.max_queued.is_some() {
+                return Err(syn::Error::new(
+                    proc_macro2::Span::call_site(),
+                    "`concurrency_scope` and `max_queued` require `max_concurrent`",
+                ));
+            }
+            return Ok(None);
+        }
+    };
+    let scope = match args.concurrency_scope.as_deref().unwrap_or("global") {
+        "global" => quote::quote! { Global },
+        "user" => quote::quote! { User },
+        "guild" => quote::quote! { Guild },
+        _ => {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "concurrency_scope must be one of \"global\", \"user\" or \"guild\"",
+            ))
+        }
+    };
+    let max_queued = args.max_queued.unwrap_or(0);
+    Ok(Some(quote::quote! {
+        static CONCURRENCY_LIMITER: ::poise::ConcurrencyLimiter =
+            ::poise::ConcurrencyLimiter::new(::poise::ConcurrencyLimit {
+                max: #max,
+                scope: ::poise::ConcurrencyScope::#scope,
+                max_queued: #max_queued,
+            });
+    }))
+}
+
+/// Generates the `concurrency` command option expression from the `max_concurrent` attribute
+fn generate_concurrency(inv: &Invocation) -> proc_macro2::TokenStream {
+    if inv.more.max_concurrent.is_none() {
+        return quote::quote! { None };
+    }
+    quote::quote! { Some(&CONCURRENCY_LIMITER) }
+}
+
+/// Generates the `required_roles` command option expression from the `required_roles(...)`
+/// attribute
+fn generate_required_roles(inv: &Invocation) -> proc_macro2::TokenStream {
+    let roles = inv.more.required_roles.0.iter().map(|role| match role {
+        syn::Lit::Int(role_id) => quote::quote! {
+            ::poise::RequiredRole::Id(::poise::serenity_prelude::RoleId(#role_id))
+        },
+        name => quote::quote! { ::poise::RequiredRole::Name(#name) },
+    });
+    quote::quote! { &[ #( #roles, )* ] }
+}
+
+/// Generates the `check` command option expression from the `check` attribute, which is either a
+/// path to a check function or a combination of check functions with `all(...)`, `any(...)` and
+/// `not(...)`, see `poise::checks`. With the `check_cache_ttl` attribute, the check results are
+/// cached with the `check` attribute as check ID, see `poise::cached_check`
+fn generate_check(inv: &Invocation) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let check = match &inv.more.check {
+        Some(x) => x,
+        None if inv.more.check_cache_ttl.is_some() => {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "`check_cache_ttl` requires the `check` attribute",
+            ))
+        }
+        None => return Ok(quote::quote! { None }),
+    };
+    let mut check_future = match syn::parse_str::<syn::Expr>(check)? {
+        syn::Expr::Path(check) => quote::quote! { #check(ctx.into()) },
+        check => {
+            let composed = generate_composed_check(&check)?;
+            quote::quote! { ::poise::checks::run(#composed) }
+        }
+    };
+    if let Some(ttl) = &inv.more.check_cache_ttl {
+        let millis = parse_duration_millis(ttl).ok_or_else(|| {
+            syn::Error::new(
+                proc_macro2::Span::call_site(),
+                format!(
+                    "invalid `check_cache_ttl` duration {:?}; expected a number with unit ms, s, m \
+                        or h, like \"5m\"",
+                    ttl
+                ),
+            )
+        })?;
+        check_future = quote::quote! {
+            ::poise::cached_check(
+                ctx.into(),
+                #check,
+                std::time::Duration::from_millis(#millis),
+                #check_future,
+            )
+        };
+    }
+    // Box::pin the check in order to store it in a struct
+    Ok(quote::quote! { Some(|ctx| Box::pin(#check_future)) })
+}
+
+/// Translates a combination of check functions into calls to the `poise::checks` combinators
+fn generate_composed_check(check: &syn::Expr) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let combination_error = || {
+        syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "check must be a path to a check function or a combination of them with `all(...)`, \
+                `any(...)` and `not(...)`",
+        )
+    };
+
+    match check {
+        syn::Expr::Path(path) => {
+            let name = path
+                .path
+                .segments
+                .last()
+                .map(|segment| segment.ident.to_string())
+                .ok_or_else(combination_error)?;
+            Ok(quote::quote! { ::poise::checks::check(#name, #path(ctx.into())) })
+        }
+        syn::Expr::Paren(paren) => generate_composed_check(&paren.expr),
+        syn::Expr::Call(call) => {
+            let combinator = match &*call.func {
+                syn::Expr::Path(path) if path.path.segments.len() == 1 => {
+                    path.path.segments[0].ident.to_string()
+                }
+                _ => return Err(combination_error()),
+            };
+            let checks = call
+                .args
+                .iter()
+                .map(generate_composed_check)
+                .collect::<Result<Vec<_>, _>>()?;
+            match (combinator.as_str(), checks.as_slice()) {
+                ("all", _) => Ok(quote::quote! { ::poise::checks::all(vec![ #( #checks, )* ]) }),
+                ("any", _) => Ok(quote::quote! { ::poise::checks::any(vec![ #( #checks, )* ]) }),
+                ("not", [check]) => Ok(quote::quote! { ::poise::checks::not(#check) }),
+                ("not", _) => Err(syn::Error::new(
+                    proc_macro2::Span::call_site(),
+                    "`not(...)` takes exactly one check",
+                )),
+                _ => Err(combination_error()),
+            }
+        }
+        _ => Err(combination_error()),
+    }
+}
+
+/// Generates the `allowed_channel_types` command option expression from the
+/// `allowed_channel_types(...)` attribute. The framework checks it before running the command
+fn generate_allowed_channel_types(
+    inv: &Invocation,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let channel_types = match &inv.more.allowed_channel_types {
+        Some(x) => x,
+        None => return Ok(quote::quote! { None }),
+    };
+    let channel_types = channel_types
+        .0
+        .iter()
+        .map(|channel_type| {
+            let variant =
+                match channel_type.as_str() {
+                    "text" => quote::quote! { Text },
+                    "private" => quote::quote! { Private },
+                    "voice" => quote::quote! { Voice },
+                    "category" => quote::quote! { Category },
+                    "news" => quote::quote! { News },
+                    "store" => quote::quote! { Store },
+                    "news_thread" => quote::quote! { NewsThread },
+                    "public_thread" => quote::quote! { PublicThread },
+                    "private_thread" => quote::quote! { PrivateThread },
+                    "stage" => quote::quote! { Stage },
+                    _ => return Err(syn::Error::new(
+                        proc_macro2::Span::call_site(),
+                        "allowed_channel_types must be one of \"text\", \"private\", \"voice\", \
+                            \"category\", \"news\", \"store\", \"news_thread\", \"public_thread\", \
+                            \"private_thread\" or \"stage\"",
+                    )),
+                };
+            Ok(quote::quote! { ::poise::serenity_prelude::ChannelType::#variant })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(quote::quote! { Some(&[ #( #channel_types, )* ]) })
+}
+
+/// Generates the `cooldowns`, `cooldown_key` and `on_cooldown` command option expressions from the
+/// `*_cooldown`, `cooldown_key` and `on_cooldown` attributes
+fn generate_cooldowns(
+    inv: &Invocation,
+) -> Result<
+    (
+        proc_macro2::TokenStream,
+        proc_macro2::TokenStream,
+        proc_macro2::TokenStream,
+    ),
+    darling::Error,
+> {
+    let global = generate_duration("global_cooldown", &inv.more.global_cooldown)?;
+    let user = generate_duration("user_cooldown", &inv.more.user_cooldown)?;
+    let guild = generate_duration("guild_cooldown", &inv.more.guild_cooldown)?;
+    let channel = generate_duration("channel_cooldown", &inv.more.channel_cooldown)?;
+    let member = generate_duration("member_cooldown", &inv.more.member_cooldown)?;
+    let custom = generate_duration("custom_cooldown", &inv.more.custom_cooldown)?;
+    let cooldowns = quote::quote! {
+        ::poise::Cooldowns::new(::poise::CooldownConfig {
+            global: #global,
+            user: #user,
+            guild: #guild,
+            channel: #channel,
+            member: #member,
+            custom: #custom,
+        })
+    };
+
+    // A key without a custom cooldown or vice versa would be silently ignored
+    let cooldown_key = match (&inv.more.cooldown_key, &inv.more.custom_cooldown) {
+        (Some(cooldown_key), Some(_)) => quote::quote! { Some(|ctx| #cooldown_key(ctx)) },
+        (Some(cooldown_key), None) => {
+            return Err(darling::Error::custom(
+                "`cooldown_key` requires `custom_cooldown`, which is the cooldown per key",
+            )
+            .with_span(cooldown_key))
+        }
+        (None, Some(_)) => {
+            return Err(darling::Error::custom(
+                "`custom_cooldown` requires `cooldown_key`, which computes the key",
+            ))
+        }
+        (None, None) => quote::quote! { None },
+    };
+
+    let on_cooldown = match &inv.more.on_cooldown {
+        Some(on_cooldown) => quote::quote! { Some(|hit| Box::pin(#on_cooldown(hit))) },
+        None => quote::quote! { None },
+    };
+
+    Ok((cooldowns, cooldown_key, on_cooldown))
+}
+
 fn extract_help_from_doc_comments(attrs: &[syn::Attribute]) -> (Option<String>, Option<String>) {
     let mut doc_lines = String::new();
     for attr in attrs {
@@ -217,6 +812,8 @@ pub fn command(
         required_permissions: &required_permissions,
     };
 
+    let concurrency_limiter = generate_concurrency_limiter(&args)?;
+
     let prefix_command_spec = wrap_option(if args.prefix_command {
         Some(prefix::generate_prefix_command_spec(&invocation)?)
     } else {
@@ -247,6 +844,7 @@ pub fn command(
             <#ctx_type_with_static as poise::_GetGenerics>::E,
         > {
             #function
+            #concurrency_limiter
 
             ::poise::CommandDefinition {
                 prefix: #prefix_command_spec,
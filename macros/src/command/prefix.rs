@@ -1,6 +1,11 @@
 use syn::spanned::Spanned as _;
 
-use super::{wrap_option, Invocation};
+use super::{
+    extract_option_type, extract_vec_type, generate_allowed_channel_types,
+    generate_argument_transformations, generate_argument_validations, generate_check,
+    generate_concurrency, generate_cooldowns, generate_duration, generate_id_kind_resolutions,
+    generate_required_roles, is_attachment_type, raw_argument_type, wrap_option, Invocation,
+};
 
 pub fn generate_prefix_command_spec(
     inv: &Invocation,
@@ -14,13 +19,8 @@ pub fn generate_prefix_command_spec(
         },
     };
 
-    // Box::pin the check and on_error callbacks in order to store them in a struct
-    let check = match &inv.more.check {
-        Some(check) => {
-            quote::quote! { Some(|ctx| Box::pin(#check(ctx.into()))) }
-        }
-        None => quote::quote! { None },
-    };
+    let check = generate_check(inv)?;
+    // Box::pin the on_error callback in order to store it in a struct
     let on_error = match &inv.more.on_error {
         Some(on_error) => {
             quote::quote! { Some(|err, ctx| Box::pin(#on_error(err, ctx.into()))) }
@@ -28,84 +28,249 @@ pub fn generate_prefix_command_spec(
         None => quote::quote! { None },
     };
 
-    let wildcard_arg = if inv.more.discard_spare_arguments {
+    // #[rest] parameters consume the entire remaining input, so they must come last. #[named]
+    // parameters don't count because they're taken out of the input beforehand
+    let positional_params = inv.parameters.iter().filter(|p| !p.more.named);
+    if let Some(p) = positional_params
+        .clone()
+        .rev()
+        .skip(1)
+        .find(|p| p.more.rest)
+    {
+        return Err(
+            syn::Error::new(p.span, "#[rest] can only be used on the last parameter").into(),
+        );
+    }
+    let ends_with_rest = positional_params.last().map_or(false, |p| p.more.rest);
+
+    // A trailing #[rest] parameter already consumes all spare arguments
+    let wildcard_arg = if inv.more.discard_spare_arguments && !ends_with_rest {
         Some(quote::quote! { #[rest] (String), })
     } else {
         None
     };
 
-    let param_specs =
-        inv.parameters
-            .iter()
-            .map(|p| {
-                enum Modifier {
-                    None,
-                    Lazy,
-                    Flag,
-                    Rest,
-                }
+    // #[named] parameters are filled from `--name value` options instead of positional arguments
+    let (named_params, positional_params) = inv
+        .parameters
+        .iter()
+        .partition::<Vec<_>, _>(|p| p.more.named);
+    let mut named_types = Vec::new();
+    for p in &named_params {
+        if p.more.lazy || p.more.rest || p.more.flag {
+            return Err(syn::Error::new(
+                p.span,
+                "#[named] can't be used together with #[lazy], #[rest] or #[flag]",
+            )
+            .into());
+        }
+        // #[parse_with] parameters are filled with the string value
+        match extract_option_type(&raw_argument_type(p)) {
+            Some(inner_type) => named_types.push(inner_type.clone()),
+            None => {
+                return Err(
+                    syn::Error::new(p.type_.span(), "Must use Option for named parameters").into(),
+                )
+            }
+        }
+    }
+    let named_names = named_params
+        .iter()
+        .map(|p| proc_macro2::Literal::string(&p.name.to_string()))
+        .collect::<Vec<_>>();
+    let named_idents = named_params.iter().map(|p| &p.name).collect::<Vec<_>>();
+    let named_indices = 0..named_params.len();
 
-                let modifier =
-                    match (p.more.lazy, p.more.rest, p.more.flag) {
-                        (false, false, false) => Modifier::None,
-                        (true, false, false) => Modifier::Lazy,
-                        (false, true, false) => Modifier::Rest,
-                        (false, false, true) => Modifier::Flag,
-                        _ => return Err(syn::Error::new(
-                            p.span,
-                            "modifiers like #[lazy] or #[rest] currently cannot be used together",
-                        )
-                        .into()),
-                    };
+    // #[flag] parameters can also be given as `--name` anywhere in the arguments
+    let flag_params = inv.parameters.iter().filter(|p| p.more.flag);
+    let flag_names = flag_params
+        .clone()
+        .map(|p| proc_macro2::Literal::string(&p.name.to_string()))
+        .collect::<Vec<_>>();
+    let flag_idents = flag_params.map(|p| &p.name).collect::<Vec<_>>();
+    let flag_indices = 0..flag_idents.len();
 
-                let type_ = &p.type_;
-                Ok(match modifier {
-                    Modifier::Flag => {
-                        if p.type_ != syn::parse_quote! { bool } {
-                            return Err(
-                                syn::Error::new(p.type_.span(), "Must use bool for flags").into()
-                            );
-                        }
-                        let literal = proc_macro2::Literal::string(&p.name.to_string());
-                        quote::quote! { #[flag] (#literal) }
+    let unix_options = if named_idents.is_empty() && flag_idents.is_empty() {
+        None
+    } else {
+        Some(quote::quote! {
+            let unix_options = ::poise::_extract_unix_options(
+                args,
+                &[ #( #flag_names, )* ],
+                &[ #( #named_names, )* ],
+                parsing_options,
+            );
+            let args = unix_options.args.as_str();
+        })
+    };
+    let unix_option_values = quote::quote! {
+        #( let #flag_idents = #flag_idents || unix_options.flags[#flag_indices]; )*
+        #(
+            let #named_idents = match &unix_options.named[#named_indices] {
+                Some(value) => Some(
+                    ::poise::parse_prefix_args!(
+                        ctx.discord, ctx.msg, value, parsing_options => #[rest] (#named_types)
+                    )
+                        .await
+                        .map_err(|e| e.with_parameter_names(&[#named_names]))?
+                        .0,
+                ),
+                None => None,
+            };
+        )*
+    };
+
+    let param_specs = positional_params
+        .iter()
+        .map(|p| {
+            enum Modifier {
+                None,
+                Lazy,
+                Flag,
+                Rest,
+                Attachments,
+            }
+
+            // Vec<Attachment> parameters are filled with the message attachments instead of
+            // being parsed from the message content
+            let attachments = extract_vec_type(&p.type_).map_or(false, is_attachment_type);
+
+            if p.more.parse_with.is_some() && (p.more.flag || attachments) {
+                return Err(syn::Error::new(
+                    p.span,
+                    "parse_with can't be used on flags or attachment parameters",
+                )
+                .into());
+            }
+
+            let modifier = match (p.more.lazy, p.more.rest, p.more.flag, attachments) {
+                (false, false, false, false) => Modifier::None,
+                (true, false, false, false) => Modifier::Lazy,
+                (false, true, false, false) => Modifier::Rest,
+                (false, false, true, false) => Modifier::Flag,
+                (false, false, false, true) => Modifier::Attachments,
+                _ => {
+                    return Err(syn::Error::new(
+                        p.span,
+                        "modifiers like #[lazy] or #[rest] currently cannot be used together \
+                            or on attachment parameters",
+                    )
+                    .into())
+                }
+            };
+
+            // #[parse_with] parameters are popped as strings
+            let type_ = raw_argument_type(p);
+            Ok(match modifier {
+                Modifier::Flag => {
+                    if p.type_ != syn::parse_quote! { bool } {
+                        return Err(
+                            syn::Error::new(p.type_.span(), "Must use bool for flags").into()
+                        );
                     }
-                    Modifier::Lazy => quote::quote! { #[lazy] (#type_) },
-                    Modifier::Rest => quote::quote! { #[rest] (#type_) },
-                    Modifier::None => quote::quote! { (#type_) },
-                })
+                    let literal = proc_macro2::Literal::string(&p.name.to_string());
+                    quote::quote! { #[flag] (#literal) }
+                }
+                Modifier::Lazy => quote::quote! { #[lazy] (#type_) },
+                Modifier::Rest => quote::quote! { #[rest] (#type_) },
+                Modifier::Attachments => quote::quote! { #[attachments] (#type_) },
+                Modifier::None => quote::quote! { (#type_) },
             })
-            .collect::<Result<Vec<_>, darling::Error>>()?;
+        })
+        .collect::<Result<Vec<_>, darling::Error>>()?;
 
     let command_name = &inv.command_name;
     let track_edits = inv.more.track_edits;
     let broadcast_typing = inv.more.broadcast_typing;
+    let broadcast_typing_after =
+        generate_duration("broadcast_typing_after", &inv.more.broadcast_typing_after)?;
     let aliases = &inv.more.aliases.0;
+    let usage = wrap_option(inv.more.usage.as_ref());
+    let examples = &inv.more.examples.0;
+    let parameter_help = inv.parameters.iter().map(|p| {
+        let name = p.name.to_string();
+        let description = wrap_option(p.more.description.as_ref());
+        let required = !p.more.flag
+            && extract_option_type(&p.type_).is_none()
+            && extract_vec_type(&p.type_).is_none();
+        let named = p.more.named;
+        quote::quote! {
+            ::poise::ParameterHelp {
+                name: #name,
+                description: #description,
+                required: #required,
+                named: #named,
+            }
+        }
+    });
     let hide_in_help = &inv.more.hide_in_help;
+    let respond_in_thread = wrap_option(inv.more.respond_in_thread);
+    let (cooldowns, cooldown_key, on_cooldown) = generate_cooldowns(inv)?;
+    let bucket = wrap_option(inv.more.bucket.as_ref());
+    let timeout = generate_duration("timeout", &inv.more.timeout)?;
+    let concurrency = generate_concurrency(inv);
+    let allowed_channel_types = generate_allowed_channel_types(inv)?;
+    let nsfw_only = inv.more.nsfw_only;
     let param_names = inv.parameters.iter().map(|p| &p.name).collect::<Vec<_>>();
+    let positional_param_names = positional_params
+        .iter()
+        .map(|p| &p.name)
+        .collect::<Vec<_>>();
+    let positional_param_name_literals = positional_params
+        .iter()
+        .map(|p| proc_macro2::Literal::string(&p.name.to_string()));
     let required_permissions = inv.required_permissions;
+    let required_roles = generate_required_roles(inv);
     let owners_only = inv.more.owners_only;
+    let argument_transformations = generate_argument_transformations(inv.parameters);
+    let id_kind_resolutions = generate_id_kind_resolutions(inv.parameters)?;
+    let argument_validations = generate_argument_validations(inv.parameters)?;
     Ok(quote::quote! {
         ::poise::PrefixCommand {
             name: #command_name,
             action: |ctx, args| Box::pin(async move {
-                let ( #( #param_names, )* .. ) = ::poise::parse_prefix_args!(
-                    ctx.discord, ctx.msg, args =>
+                let parsing_options = &ctx.framework.options().prefix_options.parsing_options;
+                #unix_options
+                let ( #( #positional_param_names, )* .. ) = ::poise::parse_prefix_args!(
+                    ctx.discord, ctx.msg, args, parsing_options =>
                     #( #param_specs, )*
                     #wildcard_arg
-                ).await?;
+                )
+                    .await
+                    .map_err(|e| e.with_parameter_names(&[ #( #positional_param_name_literals, )* ]))?;
+                #unix_option_values
+                #argument_transformations
+                #id_kind_resolutions
+                #argument_validations
+                ::poise::_start_cooldowns(ctx.into()).await;
                 inner(ctx.into(), #( #param_names, )* ).await
             }),
             options: ::poise::PrefixCommandOptions {
                 track_edits: #track_edits,
                 broadcast_typing: #broadcast_typing,
+                broadcast_typing_after: #broadcast_typing_after,
                 aliases: &[ #( #aliases, )* ],
+                usage: #usage,
+                examples: &[ #( #examples, )* ],
+                parameters: &[ #( #parameter_help, )* ],
                 inline_help: #description,
                 multiline_help: #explanation,
                 check: #check,
+                cooldowns: #cooldowns,
+                cooldown_key: #cooldown_key,
+                bucket: #bucket,
+                timeout: #timeout,
+                concurrency: #concurrency,
+                group: None,
+                on_cooldown: #on_cooldown,
                 on_error: #on_error,
+                respond_in_thread: #respond_in_thread,
                 hide_in_help: #hide_in_help,
                 required_permissions: #required_permissions,
+                required_roles: #required_roles,
                 owners_only: #owners_only,
+                allowed_channel_types: #allowed_channel_types,
+                nsfw_only: #nsfw_only,
             }
         }
     })
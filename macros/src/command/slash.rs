@@ -1,15 +1,16 @@
 use syn::spanned::Spanned as _;
 
-use super::{extract_option_type, extract_vec_type, Invocation};
+use super::{
+    extract_option_type, extract_vec_type, generate_allowed_channel_types,
+    generate_argument_transformations, generate_argument_validations, generate_check,
+    generate_concurrency, generate_cooldowns, generate_duration, generate_id_kind_resolutions,
+    generate_required_roles, is_attachment_type, number_bounds, raw_argument_type, wrap_option,
+    Invocation,
+};
 
-fn generate_options(inv: &Invocation) -> proc_macro2::TokenStream {
-    // Box::pin the check and on_error callbacks in order to store them in a struct
-    let check = match &inv.more.check {
-        Some(check) => {
-            quote::quote! { Some(|ctx| Box::pin(#check(ctx.into()))) }
-        }
-        None => quote::quote! { None },
-    };
+fn generate_options(inv: &Invocation) -> Result<proc_macro2::TokenStream, darling::Error> {
+    let check = generate_check(inv)?;
+    // Box::pin the on_error callback in order to store it in a struct
     let on_error = match &inv.more.on_error {
         Some(on_error) => quote::quote! {
             Some(|err, ctx| Box::pin(#on_error(err, ::poise::CommandErrorContext::Application(ctx))))
@@ -19,18 +20,39 @@ fn generate_options(inv: &Invocation) -> proc_macro2::TokenStream {
 
     let ephemeral = inv.more.ephemeral;
     let required_permissions = inv.required_permissions;
+    let required_roles = generate_required_roles(inv);
     let owners_only = inv.more.owners_only;
-    quote::quote! {
+    let (cooldowns, cooldown_key, on_cooldown) = generate_cooldowns(inv)?;
+    let bucket = wrap_option(inv.more.bucket.as_ref());
+    let timeout = generate_duration("timeout", &inv.more.timeout)?;
+    let concurrency = generate_concurrency(inv);
+    let allowed_channel_types = generate_allowed_channel_types(inv)?;
+    let nsfw_only = inv.more.nsfw_only;
+    Ok(quote::quote! {
         ::poise::ApplicationCommandOptions {
             check: #check,
             on_error: #on_error,
+            cooldowns: #cooldowns,
+            cooldown_key: #cooldown_key,
+            bucket: #bucket,
+            timeout: #timeout,
+            concurrency: #concurrency,
+            group: None,
+            on_cooldown: #on_cooldown,
             ephemeral: #ephemeral,
             required_permissions: #required_permissions,
+            required_roles: #required_roles,
             owners_only: #owners_only,
+            allowed_channel_types: #allowed_channel_types,
+            nsfw_only: #nsfw_only,
         }
-    }
+    })
 }
 
+/// Number of attachment options a `Vec<Attachment>` parameter is expanded into without
+/// `#[max_count]`: the maximum number of attachments of a message
+const DEFAULT_ATTACHMENT_OPTIONS: usize = 10;
+
 pub fn generate_slash_command_spec(
     inv: &Invocation,
 ) -> Result<proc_macro2::TokenStream, darling::Error> {
@@ -51,10 +73,39 @@ pub fn generate_slash_command_spec(
             )
         })?;
 
+        // Vec<Attachment> parameters are expanded into numbered attachment options, see below
+        let is_attachments = extract_vec_type(&param.type_).map_or(false, is_attachment_type);
+        if is_attachment_type(&param.type_)
+            || extract_option_type(&param.type_).map_or(false, is_attachment_type)
+        {
+            return Err(syn::Error::new(
+                param.span,
+                "attachment parameters must be Vec<Attachment> in slash commands",
+            )
+            .into());
+        }
+        if is_attachments
+            && (param.more.parse_with.is_some()
+                || param.more.autocomplete.is_some()
+                || param.more.autocomplete_fallback.is_some())
+        {
+            return Err(syn::Error::new(
+                param.span,
+                "parse_with and autocomplete can't be used on attachment parameters",
+            )
+            .into());
+        }
+
+        if param.more.flag && param.more.parse_with.is_some() {
+            return Err(syn::Error::new(param.span, "parse_with can't be used on flags").into());
+        }
+
+        // #[parse_with] parameters are registered and extracted as string options
+        let raw_type = raw_argument_type(param);
         let (mut required, type_) =
-            match extract_option_type(&param.type_).or_else(|| extract_vec_type(&param.type_)) {
+            match extract_option_type(&raw_type).or_else(|| extract_vec_type(&raw_type)) {
                 Some(t) => (false, t),
-                None => (true, &param.type_),
+                None => (true, &raw_type),
             };
 
         // Don't require user to input a value for flags - use false as default value (see below)
@@ -63,72 +114,219 @@ pub fn generate_slash_command_spec(
         }
 
         let param_name = &param.name;
-        let autocomplete_callback = match &param.more.autocomplete {
+        // Converts a stream or iterator of values or AutocompleteChoice's into JSON choices
+        let choices_to_json = quote::quote! {
+            .map(|value| poise::AutocompleteChoice::<#type_>::from(value))
+            .map(|choice| {
+                let mut choice_json = ::poise::serde_json::json!({
+                    "name": choice.name,
+                    "value": (&&&&&std::marker::PhantomData::<#type_>).into_json(choice.value),
+                });
+                if !choice.name_localizations.is_empty() {
+                    choice_json["name_localizations"] =
+                        ::poise::serde_json::json!(choice.name_localizations);
+                }
+                choice_json
+            })
+        };
+        // Variadic parameters with a max_count are expanded into multiple numbered options. The
+        // autocomplete callback of those additionally receives the index of the focused option.
+        // Attachment parameters are always expanded, by default into as many options as a message
+        // can have attachments
+        let max_count = match param.more.max_count {
+            None if is_attachments => Some(DEFAULT_ATTACHMENT_OPTIONS),
+            max_count => max_count,
+        };
+        let variadic_count = match max_count {
+            Some(max_count) => {
+                if extract_vec_type(&param.type_).is_none() {
+                    return Err(syn::Error::new(
+                        param.span,
+                        "max_count can only be used on Vec parameters",
+                    )
+                    .into());
+                }
+                if max_count == 0 || max_count > 25 {
+                    return Err(syn::Error::new(
+                        param.span,
+                        "max_count must be between 1 and 25 (Discord's option limit)",
+                    )
+                    .into());
+                }
+                Some(max_count)
+            }
+            None => None,
+        };
+
+        let autocomplete_callback = |index: Option<usize>| match &param.more.autocomplete {
+            // Attachments can't be autocompleted
+            _ if is_attachments => quote::quote! { None },
             Some(autocomplete_fn) => {
+                let index_arg = index.map(|index| quote::quote! { , #index });
                 quote::quote! { Some(|
                     ctx: poise::ApplicationContext<'_, _, _>,
-                    interaction: &poise::serenity_prelude::AutocompleteInteraction,
-                    options: &[poise::serenity_prelude::ApplicationCommandInteractionDataOption],
+                    partial_input: &poise::serde_json::Value,
                 | Box::pin(async move {
                     use ::poise::futures::{Stream, StreamExt};
 
-                    let choice = match options
-                        .iter()
-                        .find(|option| option.focused && option.name == stringify!(#param_name))
-                    {
-                        Some(x) => x,
-                        None => return Ok(()),
-                    };
-
-                    let json_value = choice.value
-                        .as_ref()
-                        .ok_or(::poise::SlashArgError::CommandStructureMismatch("expected argument value"))?;
-                    let partial_input = (&&&&&std::marker::PhantomData::<#type_>).extract_partial(json_value)?;
+                    let partial_input = (&&&&&std::marker::PhantomData::<#type_>).extract_partial(partial_input)?;
 
-                    let choices_stream = ::poise::into_stream!(
-                        #autocomplete_fn(ctx.into(), partial_input).await
-                    );
-                    let choices_json = choices_stream
+                    // Autocomplete functions may return a Result to signal failure
+                    let choices = match ::poise::into_result!(
+                        #autocomplete_fn(ctx.into(), partial_input #index_arg).await
+                    ) {
+                        Ok(choices) => choices,
+                        Err(e) => return Err(e),
+                    };
+                    let choices_json = ::poise::into_stream!(choices)
                         .take(25)
-                        .map(|value| poise::AutocompleteChoice::from(value))
-                        .map(|choice| serde_json::json!({
-                            "name": choice.name,
-                            "value": (&&&&&std::marker::PhantomData::<#type_>).into_json(choice.value),
-                        }))
+                        #choices_to_json
                         .collect()
                         .await;
-                    let choices_json = poise::serde_json::Value::Array(choices_json);
-
-                    if let Err(e) = interaction
-                        .create_autocomplete_response(
-                            &ctx.discord.http,
-                            |b| b.set_choices(choices_json),
-                        )
-                        .await
-                    {
-                        println!("Warning: couldn't send autocomplete response: {}", e);
-                    }
 
-                    Ok(())
+                    Ok(poise::serde_json::Value::Array(choices_json))
                 })) }
             }
+            // Some types come with their own autocompletion, e.g. choice enums with too many
+            // variants for Discord's choices
+            None => quote::quote! {
+                if (&&&&&std::marker::PhantomData::<#type_>).autocomplete("").is_some() {
+                    Some(|
+                        _: poise::ApplicationContext<'_, _, _>,
+                        partial_input: &poise::serde_json::Value,
+                    | Box::pin(async move {
+                        let partial_input = partial_input.as_str().unwrap_or("");
+                        let choices = (&&&&&std::marker::PhantomData::<#type_>)
+                            .autocomplete(partial_input)
+                            .unwrap_or_default();
+                        let choices_json = choices
+                            .into_iter()
+                            .take(25)
+                            .map(|choice| ::poise::serde_json::json!({
+                                "name": choice,
+                                "value": choice,
+                            }))
+                            .collect();
+
+                        Ok(poise::serde_json::Value::Array(choices_json))
+                    }))
+                } else {
+                    None
+                }
+            },
+        };
+        let autocomplete_fallback = match &param.more.autocomplete_fallback {
+            Some(fallback_fn) => quote::quote! { Some(|| poise::serde_json::Value::Array(
+                #fallback_fn()
+                    .into_iter()
+                    .take(25)
+                    #choices_to_json
+                    .collect()
+            )) },
+            None => quote::quote! { None },
+        };
+        let autocomplete_cache = match &param.more.autocomplete_cache {
+            Some(cache) => {
+                let ttl = cache.ttl.unwrap_or(60);
+                let max_entries = cache.max_entries.unwrap_or(1000);
+                quote::quote! { Some(::poise::AutocompleteCache::new(
+                    std::time::Duration::from_secs(#ttl),
+                    #max_entries,
+                )) }
+            }
             None => quote::quote! { None },
         };
 
+        let custom_data = match &param.more.custom_data {
+            Some(custom_data) => {
+                let custom_data = syn::parse_str::<syn::Expr>(custom_data).map_err(|e| {
+                    syn::Error::new(param.span, format!("invalid custom_data expression: {}", e))
+                })?;
+                quote::quote! { Some(Box::new(#custom_data)) }
+            }
+            None => quote::quote! { None },
+        };
+
+        // Forward #[min] and #[max] to Discord. Whole numbers are sent as JSON integers, because
+        // Discord rejects float bounds on integer options
+        let (min, max) = number_bounds(param)?;
+        let bound_to_json = |bound: f64| {
+            if bound.fract() == 0.0 {
+                let bound = proc_macro2::Literal::i64_unsuffixed(bound as i64);
+                quote::quote! { ::poise::serde_json::json!(#bound) }
+            } else {
+                let bound = proc_macro2::Literal::f64_unsuffixed(bound);
+                quote::quote! { ::poise::serde_json::json!(#bound) }
+            }
+        };
+        let min_value = min
+            .map(bound_to_json)
+            .map(|min| quote::quote! { o.0.insert("min_value", #min); });
+        let max_value = max
+            .map(bound_to_json)
+            .map(|max| quote::quote! { o.0.insert("max_value", #max); });
+
         let is_autocomplete = param.more.autocomplete.is_some();
-        parameter_structs.push((
-            quote::quote! {
-                ::poise::SlashCommandParameter {
-                    builder: |o| (&&&&&std::marker::PhantomData::<#type_>).create(o)
-                        .required(#required)
-                        .name(stringify!(#param_name))
-                        .description(#description)
-                        .set_autocomplete(#is_autocomplete),
-                    autocomplete_callback: #autocomplete_callback,
+        // Some types can't be autocompleted in their native option type, e.g. users. Register
+        // those as string options instead
+        let autocomplete_kind_override = if is_autocomplete {
+            Some(quote::quote! {
+                if (&&&&&std::marker::PhantomData::<#type_>).autocomplete_as_string() {
+                    o.kind(::poise::serenity_prelude::ApplicationCommandOptionType::String);
                 }
-            },
-            required,
-        ));
+            })
+        } else {
+            None
+        };
+        let create_option = |option_name: &str| {
+            if is_attachments {
+                return quote::quote! {
+                    ::poise::_create_attachment_option(o)
+                        .required(false)
+                        .name(#option_name)
+                        .description(#description)
+                };
+            }
+            quote::quote! {
+                let o = (&&&&&std::marker::PhantomData::<#type_>).create(o);
+                #autocomplete_kind_override
+                #min_value
+                #max_value
+                o.required(#required)
+                    .name(#option_name)
+                    .description(#description)
+                    .set_autocomplete(
+                        #is_autocomplete
+                            || (&&&&&std::marker::PhantomData::<#type_>)
+                                .autocomplete("")
+                                .is_some(),
+                    )
+            }
+        };
+        let option_names_and_indices = match variadic_count {
+            Some(count) => (0..count)
+                .map(|i| (format!("{}{}", param_name, i + 1), Some(i)))
+                .collect(),
+            None => vec![(param_name.to_string(), None)],
+        };
+        for (option_name, index) in option_names_and_indices {
+            let autocomplete_callback = autocomplete_callback(index);
+            let create_option = create_option(&option_name);
+            parameter_structs.push((
+                quote::quote! {
+                    ::poise::SlashCommandParameter {
+                        name: #option_name,
+                        builder: |o| { #create_option },
+                        autocomplete_callback: #autocomplete_callback,
+                        autocomplete_fallback: #autocomplete_fallback,
+                        autocomplete_cache: #autocomplete_cache,
+                        custom_data: #custom_data,
+                        autocomplete_closure: None,
+                    }
+                },
+                required,
+            ));
+        }
     }
     // Sort the parameters so that optional parameters come last - Discord requires this order
     parameter_structs.sort_by_key(|(_, required)| !required);
@@ -138,15 +336,39 @@ pub fn generate_slash_command_spec(
         .collect::<Vec<_>>();
 
     let param_names = inv.parameters.iter().map(|p| &p.name).collect::<Vec<_>>();
-    let param_types = inv
+    // Attachments are looked up in the resolved interaction data, so they're extracted separately
+    let (attachment_params, parsed_params) = inv
         .parameters
         .iter()
-        .map(|p| match p.more.flag {
-            true => syn::parse_quote! { FLAG },
-            false => p.type_.clone(),
+        .partition::<Vec<_>, _>(|p| extract_vec_type(&p.type_).map_or(false, is_attachment_type));
+    let parsed_param_names = parsed_params.iter().map(|p| &p.name).collect::<Vec<_>>();
+    let attachment_extractions = attachment_params.iter().map(|p| {
+        let name = &p.name;
+        let name_literal = proc_macro2::Literal::string(&name.to_string());
+        let count = p.more.max_count.unwrap_or(DEFAULT_ATTACHMENT_OPTIONS);
+        quote::quote! {
+            let #name = ::poise::_extract_attachments(
+                ctx.interaction.data(), args, #name_literal, #count
+            )?;
+        }
+    });
+    let param_types = parsed_params
+        .iter()
+        .map(|p| {
+            let type_ = raw_argument_type(p);
+            if p.more.flag {
+                quote::quote! { FLAG }
+            } else if let Some(max_count) = p.more.max_count {
+                quote::quote! { VARIADIC(#max_count) #type_ }
+            } else {
+                quote::quote! { #type_ }
+            }
         })
         .collect::<Vec<_>>();
-    let options = generate_options(inv);
+    let argument_transformations = generate_argument_transformations(inv.parameters);
+    let id_kind_resolutions = generate_id_kind_resolutions(inv.parameters)?;
+    let argument_validations = generate_argument_validations(inv.parameters)?;
+    let options = generate_options(inv)?;
     Ok(quote::quote! {
         ::poise::SlashCommand {
             name: #command_name,
@@ -159,11 +381,15 @@ pub fn generate_slash_command_spec(
                 // idk why this can't be put in the macro itself (where the lint is triggered) and
                 // why clippy doesn't turn off this lint inside macros in the first place
                 #[allow(clippy::needless_question_mark)]
-
-                let ( #( #param_names, )* ) = ::poise::parse_slash_args!(
+                let ( #( #parsed_param_names, )* ) = ::poise::parse_slash_args!(
                     ctx.discord, ctx.interaction.guild_id(), ctx.interaction.channel_id(), args =>
-                    #( (#param_names: #param_types), )*
+                    #( (#parsed_param_names: #param_types), )*
                 ).await?;
+                #( #attachment_extractions )*
+                #argument_transformations
+                #id_kind_resolutions
+                #argument_validations
+                ::poise::_start_cooldowns(ctx.into()).await;
 
                 inner(ctx.into(), #( #param_names, )*).await
             }),
@@ -185,13 +411,21 @@ pub fn generate_context_menu_command_spec(
     }
 
     let param_type = &inv.parameters[0].type_;
+    // The value is passed through the #[transform] function like in the other command types
+    let transform = match &inv.parameters[0].more.transform {
+        Some(transform) => quote::quote! { #transform },
+        None => quote::quote! { std::convert::identity },
+    };
 
-    let options = generate_options(inv);
+    let options = generate_options(inv)?;
     Ok(quote::quote! {
         ::poise::ContextMenuCommand {
             name: #name,
             action: <#param_type as ::poise::ContextMenuParameter<_, _>>::to_action(|ctx, value| {
-                Box::pin(async move { inner(ctx.into(), value).await })
+                Box::pin(async move {
+                    ::poise::_start_cooldowns(ctx.into()).await;
+                    inner(ctx.into(), #transform(value)).await
+                })
             }),
             options: #options,
         }
@@ -1,4 +1,5 @@
 mod command;
+mod prefix_args;
 mod slash_choice_parameter;
 
 use proc_macro::TokenStream;
@@ -19,7 +20,7 @@ pub fn command(args: TokenStream, function: TokenStream) -> TokenStream {
     }
 }
 
-#[proc_macro_derive(SlashChoiceParameter, attributes(name))]
+#[proc_macro_derive(SlashChoiceParameter, attributes(name, payload, payload_type))]
 pub fn slash_choice_parameter(input: TokenStream) -> TokenStream {
     let enum_ = syn::parse_macro_input!(input as syn::DeriveInput);
 
@@ -28,3 +29,13 @@ pub fn slash_choice_parameter(input: TokenStream) -> TokenStream {
         Err(e) => e.write_errors().into(),
     }
 }
+
+#[proc_macro_derive(PrefixArgs, attributes(flag, named, lazy, rest))]
+pub fn prefix_args(input: TokenStream) -> TokenStream {
+    let struct_ = syn::parse_macro_input!(input as syn::DeriveInput);
+
+    match prefix_args::prefix_args(struct_) {
+        Ok(x) => x,
+        Err(e) => e.write_errors().into(),
+    }
+}
@@ -0,0 +1,212 @@
+//! Implements the #[derive(PrefixArgs)] derive macro
+
+use proc_macro::TokenStream;
+use syn::ext::IdentExt as _;
+use syn::spanned::Spanned as _;
+
+/// Representation of the struct field attribute arguments
+#[derive(Default, Debug, darling::FromMeta)]
+#[darling(default)]
+struct FieldAttribute {
+    flag: bool,
+    named: bool,
+    lazy: bool,
+    rest: bool,
+}
+
+pub fn prefix_args(input: syn::DeriveInput) -> Result<TokenStream, darling::Error> {
+    let fields = match input.data {
+        syn::Data::Struct(syn::DataStruct {
+            fields: syn::Fields::Named(fields),
+            ..
+        }) => fields.named,
+        _ => {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                "Only structs with named fields can be used for prefix args",
+            )
+            .into())
+        }
+    };
+
+    let mut field_idents = Vec::new();
+    let mut field_locals = Vec::new();
+    let mut flag_idents = Vec::new();
+    let mut flag_names = Vec::new();
+    let mut named_idents = Vec::new();
+    let mut named_names = Vec::new();
+    let mut named_types = Vec::new();
+    let mut positional_idents = Vec::new();
+    let mut positional_names = Vec::new();
+    let mut positional_specs = Vec::new();
+
+    for field in fields {
+        let ident = field.ident.expect("named fields have an identifier");
+        let name = proc_macro2::Literal::string(&ident.unraw().to_string());
+        // The field values are stored in prefixed local variables, so that fields can have any
+        // name, including the names of the variables used by the generated code
+        let local = syn::Ident::new(&format!("__poise_field_{}", ident.unraw()), ident.span());
+
+        // Skip doc comments and other unrelated attributes
+        let attrs = field
+            .attrs
+            .into_iter()
+            .filter(|attr| {
+                ["flag", "named", "lazy", "rest"]
+                    .iter()
+                    .any(|name| attr.path.is_ident(name))
+            })
+            .map(|attr| attr.parse_meta().map(syn::NestedMeta::Meta))
+            .collect::<Result<Vec<_>, _>>()?;
+        let attrs = <FieldAttribute as darling::FromMeta>::from_list(&attrs)?;
+
+        let type_ = field.ty;
+        match (attrs.flag, attrs.named, attrs.lazy, attrs.rest) {
+            (true, false, false, false) => {
+                if type_ != syn::parse_quote! { bool } {
+                    return Err(syn::Error::new(type_.span(), "Must use bool for flags").into());
+                }
+                flag_idents.push(local.clone());
+                flag_names.push(name);
+            }
+            (false, true, false, false) => {
+                let inner_type = match crate::command::extract_type_parameter("Option", &type_) {
+                    Some(t) => t.clone(),
+                    None => {
+                        return Err(syn::Error::new(
+                            type_.span(),
+                            "Must use Option for named arguments",
+                        )
+                        .into())
+                    }
+                };
+                named_idents.push(local.clone());
+                named_names.push(name);
+                named_types.push(inner_type);
+            }
+            (false, false, false, false) => {
+                positional_idents.push(local.clone());
+                positional_names.push(name);
+                positional_specs.push(quote::quote! { (#type_) });
+            }
+            (false, false, true, false) => {
+                positional_idents.push(local.clone());
+                positional_names.push(name);
+                positional_specs.push(quote::quote! { #[lazy] (#type_) });
+            }
+            (false, false, false, true) => {
+                positional_idents.push(local.clone());
+                positional_names.push(name);
+                positional_specs.push(quote::quote! { #[rest] (#type_) });
+            }
+            _ => {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "#[flag], #[named], #[lazy] and #[rest] cannot be used together",
+                )
+                .into())
+            }
+        }
+        field_idents.push(ident);
+        field_locals.push(local);
+    }
+
+    // Only generate the named argument and flag checks if there are any, to not trigger unused
+    // variable warnings
+    let named_check = if named_idents.is_empty() {
+        None
+    } else {
+        Some(quote::quote! {
+            if let Some((rest, (key, value))) = poise::KeyValueArgs::pop_single_key_value_pair(&args, options) {
+                #(
+                    if key == #named_names {
+                        #named_idents = Some(value);
+                        args = poise::ArgString(rest.0.trim_start());
+                        continue;
+                    }
+                )*
+            }
+        })
+    };
+    let (token_pattern, flag_check) = if flag_idents.is_empty() {
+        (quote::quote! { _ }, None)
+    } else {
+        let flag_check = quote::quote! {
+            #(
+                if token.eq_ignore_ascii_case(#flag_names) {
+                    #flag_idents = true;
+                    args = rest;
+                    continue;
+                }
+            )*
+        };
+        (quote::quote! { token }, Some(flag_check))
+    };
+
+    let struct_ident = &input.ident;
+    Ok(quote::quote! {
+        #[poise::async_trait]
+        impl<'a> poise::PopArgumentAsync<'a> for #struct_ident {
+            type Err = poise::ArgumentParseError;
+
+            async fn async_pop_from(
+                ctx: &poise::serenity_prelude::Context,
+                msg: &poise::serenity_prelude::Message,
+                args: &poise::ArgString<'a>,
+            ) -> Result<(poise::ArgString<'a>, Self), Self::Err> {
+                Self::async_pop_from_with_options(
+                    ctx, msg, args, &poise::PrefixParsingOptions::default()
+                ).await
+            }
+
+            async fn async_pop_from_with_options(
+                ctx: &poise::serenity_prelude::Context,
+                msg: &poise::serenity_prelude::Message,
+                args: &poise::ArgString<'a>,
+                options: &poise::PrefixParsingOptions,
+            ) -> Result<(poise::ArgString<'a>, Self), Self::Err> {
+                #( let mut #flag_idents = false; )*
+                #( let mut #named_idents = None::<String>; )*
+
+                // Pick out flags and named arguments anywhere in the input. Everything else is
+                // kept verbatim and parsed into the positional fields afterwards
+                let mut positional = Vec::new();
+                let mut args = poise::ArgString(args.0.trim_start());
+                while !args.0.is_empty() {
+                    #named_check
+
+                    let (rest, #token_pattern) = match <String as poise::PopArgument>::pop_from_with_options(&args, options) {
+                        Ok(x) => x,
+                        Err(_) => break,
+                    };
+                    let rest = poise::ArgString(rest.0.trim_start());
+                    #flag_check
+
+                    positional.push(args.0[..args.0.len() - rest.0.len()].trim_end());
+                    args = rest;
+                }
+                let positional = positional.join(" ");
+
+                let ( #( #positional_idents, )* ) = poise::parse_prefix_args!(
+                    ctx, msg, &positional, options => #( #positional_specs, )*
+                )
+                    .await
+                    .map_err(|e| e.with_parameter_names(&[ #( #positional_names, )* ]))?;
+                #(
+                    let #named_idents = match #named_idents {
+                        Some(value) => Some(
+                            poise::parse_prefix_args!(ctx, msg, &value, options => #[rest] (#named_types))
+                                .await
+                                .map_err(|e| e.with_parameter_names(&[#named_names]))?
+                                .0,
+                        ),
+                        None => None,
+                    };
+                )*
+
+                Ok((args, Self { #( #field_idents: #field_locals, )* }))
+            }
+        }
+    }
+    .into())
+}
@@ -8,6 +8,8 @@ use syn::spanned::Spanned as _;
 struct VariantAttribute {
     #[darling(multiple)]
     name: Vec<String>,
+    /// Expression which the generated `payload()` method returns for this variant
+    payload: Option<String>,
 }
 
 pub fn slash_choice_parameter(input: syn::DeriveInput) -> Result<TokenStream, darling::Error> {
@@ -22,9 +24,30 @@ pub fn slash_choice_parameter(input: syn::DeriveInput) -> Result<TokenStream, da
         }
     };
 
+    // Type of the per-variant payloads, given via `#[payload_type = "..."]` on the enum
+    let mut payload_type = None;
+    for attr in &input.attrs {
+        if attr.path.is_ident("payload_type") {
+            match attr.parse_meta()? {
+                syn::Meta::NameValue(syn::MetaNameValue {
+                    lit: syn::Lit::Str(lit),
+                    ..
+                }) => payload_type = Some(lit.parse::<syn::Type>()?),
+                other => {
+                    return Err(syn::Error::new(
+                        other.span(),
+                        "expected `#[payload_type = \"...\"]`",
+                    )
+                    .into())
+                }
+            }
+        }
+    }
+
     let mut variant_idents: Vec<proc_macro2::Ident> = Vec::new();
     let mut display_strings: Vec<String> = Vec::new();
     let mut more_display_strings = Vec::new();
+    let mut payloads = Vec::new();
 
     for variant in enum_.variants {
         if !matches!(&variant.fields, syn::Fields::Unit) {
@@ -40,49 +63,130 @@ pub fn slash_choice_parameter(input: syn::DeriveInput) -> Result<TokenStream, da
             .into_iter()
             .map(|attr| attr.parse_meta().map(syn::NestedMeta::Meta))
             .collect::<Result<Vec<_>, _>>()?;
-        let mut names = <VariantAttribute as darling::FromMeta>::from_list(&attrs)?.name;
+        let attrs = <VariantAttribute as darling::FromMeta>::from_list(&attrs)?;
+        let mut names = attrs.name;
 
         if names.is_empty() {
             return Err(syn::Error::new(variant.ident.span(), "Missing `name` attribute").into());
         }
 
+        match (&payload_type, attrs.payload) {
+            (Some(_), Some(payload)) => payloads.push(syn::parse_str::<syn::Expr>(&payload)?),
+            (None, None) => {}
+            (Some(_), None) => {
+                return Err(syn::Error::new(
+                    variant.ident.span(),
+                    "Missing `payload` attribute (required by `payload_type`)",
+                )
+                .into())
+            }
+            (None, Some(_)) => {
+                return Err(syn::Error::new(
+                    variant.ident.span(),
+                    "`payload` requires a `#[payload_type = \"...\"]` attribute on the enum",
+                )
+                .into())
+            }
+        }
+
         variant_idents.push(variant.ident);
         display_strings.push(names.remove(0));
         more_display_strings.push(names);
     }
 
     let enum_ident = &input.ident;
-    let indices1 = 0_u64..(variant_idents.len() as _);
-    let indices2 = 0_i32..(variant_idents.len() as _);
-    Ok(quote::quote! {
-        #[poise::async_trait]
-        impl poise::SlashArgument for #enum_ident {
-            async fn extract(
-                _: &poise::serenity_prelude::Context,
-                _: Option<poise::serenity_prelude::GuildId>,
-                _: Option<poise::serenity_prelude::ChannelId>,
-                value: &poise::serde_json::Value,
-            ) -> Result<Self, poise::SlashArgError> {
-                let choice_key = value
-                    .as_u64()
-                    .ok_or(poise::SlashArgError::CommandStructureMismatch(
-                        "expected u64",
-                    ))?;
-
-                match choice_key {
-                    #( #indices1 => Ok(Self::#variant_idents), )*
-                    _ => Err(poise::SlashArgError::CommandStructureMismatch("out of bounds choice key")),
+    let payload_method = payload_type.map(|payload_type| {
+        quote::quote! {
+            impl #enum_ident {
+                /// Returns the data attached to this choice via `#[payload = "..."]`
+                pub fn payload(&self) -> #payload_type {
+                    match self {
+                        #( Self::#variant_idents => #payloads, )*
+                    }
                 }
             }
+        }
+    });
+
+    // Discord allows at most 25 choices. Larger enums are registered as autocompleted string
+    // options instead, and parsed back by name
+    let slash_argument_impl = if variant_idents.len() > 25 {
+        quote::quote! {
+            #[poise::async_trait]
+            impl poise::SlashArgument for #enum_ident {
+                async fn extract(
+                    _: &poise::serenity_prelude::Context,
+                    _: Option<poise::serenity_prelude::GuildId>,
+                    _: Option<poise::serenity_prelude::ChannelId>,
+                    value: &poise::serde_json::Value,
+                ) -> Result<Self, poise::SlashArgError> {
+                    let choice_name = value
+                        .as_str()
+                        .ok_or(poise::SlashArgError::CommandStructureMismatch(
+                            "expected string",
+                        ))?;
+
+                    choice_name
+                        .parse()
+                        .map_err(|e: poise::InvalidChoice| poise::SlashArgError::Parse(e.into()))
+                }
 
-            fn create(
-                builder: &mut poise::serenity_prelude::CreateApplicationCommandOption,
-            ) -> &mut poise::serenity_prelude::CreateApplicationCommandOption {
-                builder
-                    .kind(poise::serenity_prelude::ApplicationCommandOptionType::Integer)
-                    #( .add_int_choice(#display_strings, #indices2 as i32) )*
+                fn create(
+                    builder: &mut poise::serenity_prelude::CreateApplicationCommandOption,
+                ) -> &mut poise::serenity_prelude::CreateApplicationCommandOption {
+                    builder.kind(poise::serenity_prelude::ApplicationCommandOptionType::String)
+                }
+
+                fn autocomplete(partial: &str) -> Option<Vec<String>> {
+                    let names: &[&str] = &[ #( #display_strings, )* ];
+                    Some(
+                        poise::fuzzy_filter(partial, names.iter().copied())
+                            .into_iter()
+                            .map(String::from)
+                            .collect(),
+                    )
+                }
             }
         }
+    } else {
+        let indices1 = 0_u64..(variant_idents.len() as _);
+        let indices2 = 0_i32..(variant_idents.len() as _);
+        quote::quote! {
+            #[poise::async_trait]
+            impl poise::SlashArgument for #enum_ident {
+                async fn extract(
+                    _: &poise::serenity_prelude::Context,
+                    _: Option<poise::serenity_prelude::GuildId>,
+                    _: Option<poise::serenity_prelude::ChannelId>,
+                    value: &poise::serde_json::Value,
+                ) -> Result<Self, poise::SlashArgError> {
+                    let choice_key = value
+                        .as_u64()
+                        .ok_or(poise::SlashArgError::CommandStructureMismatch(
+                            "expected u64",
+                        ))?;
+
+                    match choice_key {
+                        #( #indices1 => Ok(Self::#variant_idents), )*
+                        _ => Err(poise::SlashArgError::CommandStructureMismatch("out of bounds choice key")),
+                    }
+                }
+
+                fn create(
+                    builder: &mut poise::serenity_prelude::CreateApplicationCommandOption,
+                ) -> &mut poise::serenity_prelude::CreateApplicationCommandOption {
+                    builder
+                        .kind(poise::serenity_prelude::ApplicationCommandOptionType::Integer)
+                        #( .add_int_choice(#display_strings, #indices2 as i32) )*
+                }
+            }
+        }
+    };
+
+    Ok(quote::quote! {
+        #payload_method
+
+        #slash_argument_impl
 
         impl std::str::FromStr for #enum_ident {
             type Err = poise::InvalidChoice;
@@ -0,0 +1,109 @@
+//! Periodic tasks run by the framework, see [`BackgroundTask`]
+
+use crate::serenity_prelude as serenity;
+use crate::BoxFuture;
+
+/// When a [`BackgroundTask`] runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskSchedule {
+    /// Runs right after the bot is ready and then repeatedly with this period between the starts
+    /// of two runs. If a run takes longer than the period, the next one starts right after it
+    Every(std::time::Duration),
+    /// Runs once a day at the given time in UTC. `hour` must be below 24 and `minute` below 60,
+    /// which [`crate::FrameworkBuilder::background_task`] asserts
+    DailyAt {
+        /// Hour of the day, 0-23
+        hour: u32,
+        /// Minute of the hour, 0-59
+        minute: u32,
+    },
+}
+
+/// The next point in time after `now` at which the clock shows `hour:minute` in UTC
+fn next_daily_run(
+    now: chrono::DateTime<chrono::Utc>,
+    hour: u32,
+    minute: u32,
+) -> chrono::DateTime<chrono::Utc> {
+    // Out of range times are rejected in FrameworkBuilder::background_task, fall back to midnight
+    // for schedules which bypassed it
+    let today = now
+        .date()
+        .and_hms_opt(hour, minute, 0)
+        .unwrap_or_else(|| now.date().and_hms(0, 0, 0));
+    if today > now {
+        today
+    } else {
+        today + chrono::Duration::days(1)
+    }
+}
+
+impl TaskSchedule {
+    /// When to run the task next, given when the previous run started, if any
+    pub(crate) fn next_run(&self, last_run: Option<tokio::time::Instant>) -> tokio::time::Instant {
+        let now = tokio::time::Instant::now();
+        match *self {
+            Self::Every(period) => last_run.map_or(now, |last_run| last_run + period),
+            Self::DailyAt { hour, minute } => {
+                let utc_now = chrono::Utc::now();
+                let delay = (next_daily_run(utc_now, hour, minute) - utc_now)
+                    .to_std()
+                    .unwrap_or_default();
+                now + delay
+            }
+        }
+    }
+}
+
+/// Passed to the action of a [`BackgroundTask`] on every run
+pub struct TaskContext<'a, U, E> {
+    /// The serenity cache
+    pub cache: &'a std::sync::Arc<serenity::cache::Cache>,
+    /// The serenity HTTP client
+    pub http: &'a std::sync::Arc<serenity::Http>,
+    /// The user data, see [`crate::FrameworkBuilder::user_data_setup`]
+    pub data: &'a U,
+    /// The framework running the task
+    pub framework: &'a crate::Framework<U, E>,
+}
+
+/// An async task which the framework runs on a schedule, for example to post daily statistics
+/// or expire database entries, registered with [`crate::FrameworkBuilder::background_task`].
+///
+/// Tasks start once the bot is ready, see [`crate::Framework::wait_until_ready`]. They're
+/// cancelled when the framework shuts down, and don't start new runs once
+/// [`crate::Framework::shutdown_graceful`] was called. Errors are passed to [`crate::FrameworkOptions::on_error`] with
+/// [`crate::ErrorContext::BackgroundTask`], and the task keeps running on schedule.
+pub struct BackgroundTask<U, E> {
+    /// Name of the task, passed to the error handler
+    pub name: &'static str,
+    /// When the task runs
+    pub schedule: TaskSchedule,
+    /// The task itself, called on every run
+    pub action: for<'a> fn(TaskContext<'a, U, E>) -> BoxFuture<'a, Result<(), E>>,
+}
+
+#[cfg(test)]
+#[test]
+fn test_next_daily_run() {
+    use chrono::TimeZone as _;
+
+    let now = chrono::Utc.ymd(2021, 12, 31).and_hms(10, 30, 0);
+    assert_eq!(
+        next_daily_run(now, 12, 0),
+        chrono::Utc.ymd(2021, 12, 31).and_hms(12, 0, 0)
+    );
+    assert_eq!(
+        next_daily_run(now, 10, 30),
+        chrono::Utc.ymd(2022, 1, 1).and_hms(10, 30, 0)
+    );
+    assert_eq!(
+        next_daily_run(now, 8, 0),
+        chrono::Utc.ymd(2022, 1, 1).and_hms(8, 0, 0)
+    );
+    // Doesn't panic on out of range times
+    assert_eq!(
+        next_daily_run(now, 24, 60),
+        chrono::Utc.ymd(2022, 1, 1).and_hms(0, 0, 0)
+    );
+}
@@ -3,35 +3,257 @@
 //! This file provides sample commands and utility functions like help menus or error handlers to
 //! use as a starting point for the framework.
 
+mod command_management;
+pub use command_management::*;
+
+mod error_report;
+pub use error_report::*;
+
+#[cfg(feature = "owner_commands")]
+mod owner;
+#[cfg(feature = "owner_commands")]
+pub use owner::*;
+
+#[cfg(feature = "collector")]
+mod wizard;
+#[cfg(feature = "collector")]
+pub use wizard::*;
+
 use crate::serenity_prelude as serenity;
+use std::collections::HashMap;
 
 type BoxErrorSendSync = Box<dyn std::error::Error + Send + Sync>;
 
+/// Message templates for the user-facing replies of [`on_error_with_messages`],
+/// [`cooldown_feedback`], [`missing_permissions_reply`] and [`missing_roles_reply`], to brand or
+/// translate them.
+///
+/// Placeholders in curly braces are replaced with the respective values. Start from
+/// [`Self::DEFAULT`] to only change some of the messages, also in statics:
+/// ```rust
+/// static MESSAGES: poise::builtins::ErrorMessages<'static> = poise::builtins::ErrorMessages {
+///     command_error: "Oops, `{command}` failed: {error}",
+///     cooldown: "Slow down! Try again in {remaining}",
+///     ..poise::builtins::ErrorMessages::DEFAULT
+/// };
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct ErrorMessages<'a> {
+    /// Reply to an [`crate::ArgumentParseError`]. Placeholders: `{command}`, `{error}`, `{usage}`
+    pub argument_parse_error: &'a str,
+    /// Reply to an [`crate::ArgumentParseError`] which can point out the offending argument.
+    /// Placeholders: `{command}`, `{error}`, `{diagram}`, `{usage}`
+    pub argument_parse_error_with_diagram: &'a str,
+    /// Inserted as `{usage}` if the command has no detailed help
+    pub default_usage: &'a str,
+    /// Reply to any other error returned by a command. Placeholders: `{command}`, `{error}`
+    pub command_error: &'a str,
+    /// Reply when a command is on cooldown. Placeholders: `{command}`, `{remaining}` (formatted
+    /// with [`humanize_duration`])
+    pub cooldown: &'a str,
+    /// Reply when the user isn't allowed to run an application command. Placeholders:
+    /// `{command}`, `{permissions}`
+    pub missing_permissions: &'a str,
+    /// Reply when the user lacks some of the required roles of a command. Placeholders:
+    /// `{command}`, `{roles}` (the missing roles, comma separated)
+    pub missing_roles: &'a str,
+    /// Locale in which durations are inserted, see [`humanize_duration`]. Guilds with a
+    /// [locale](crate::GuildSettings::locale) in their settings use that one instead
+    pub locale: &'a str,
+}
+
+impl ErrorMessages<'static> {
+    /// The English messages used by [`on_error`] and the framework defaults
+    pub const DEFAULT: Self = Self {
+        argument_parse_error: "**{error}**\n{usage}",
+        argument_parse_error_with_diagram: "**{error}**\n```\n{diagram}\n```\n{usage}",
+        default_usage: "Please check the help menu for usage information",
+        command_error: "{error}",
+        cooldown: "You're too fast. Please wait {remaining} before retrying",
+        missing_permissions: "You don't have the required permissions for `/{command}`",
+        missing_roles: "You need the following roles to use `{command}`: {roles}",
+        locale: "en",
+    };
+}
+
+impl Default for ErrorMessages<'static> {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Singular and plural names of days, hours, minutes and seconds in the given locale
+fn duration_units(locale: &str) -> [(&'static str, &'static str); 4] {
+    match locale.split('-').next() {
+        Some("de") => [
+            ("Tag", "Tage"),
+            ("Stunde", "Stunden"),
+            ("Minute", "Minuten"),
+            ("Sekunde", "Sekunden"),
+        ],
+        Some("es") => [
+            ("día", "días"),
+            ("hora", "horas"),
+            ("minuto", "minutos"),
+            ("segundo", "segundos"),
+        ],
+        Some("fr") => [
+            ("jour", "jours"),
+            ("heure", "heures"),
+            ("minute", "minutes"),
+            ("seconde", "secondes"),
+        ],
+        _ => [
+            ("day", "days"),
+            ("hour", "hours"),
+            ("minute", "minutes"),
+            ("second", "seconds"),
+        ],
+    }
+}
+
+/// Formats a duration for humans, like `2 hours 5 minutes`.
+///
+/// Only the two largest units are shown, and partial seconds are rounded up, so that a remaining
+/// cooldown is never displayed as `0 seconds`. `locale` is a Discord locale code like `en-US` or
+/// `de`; English, German, Spanish and French are supported, other locales fall back to English.
+///
+/// ```rust
+/// # use std::time::Duration;
+/// use poise::builtins::humanize_duration;
+/// assert_eq!(humanize_duration(Duration::from_secs(7500), "en-US"), "2 hours 5 minutes");
+/// assert_eq!(humanize_duration(Duration::from_millis(1200), "de"), "2 Sekunden");
+/// ```
+pub fn humanize_duration(duration: std::time::Duration, locale: &str) -> String {
+    let mut secs = duration.as_secs();
+    if duration.subsec_nanos() > 0 {
+        secs += 1;
+    }
+    let amounts = [secs / 86400, secs / 3600 % 24, secs / 60 % 60, secs % 60];
+
+    let parts = amounts
+        .iter()
+        .zip(duration_units(locale).iter())
+        .skip_while(|&(&amount, _)| amount == 0)
+        .take(2)
+        .filter(|&(&amount, _)| amount > 0)
+        .map(|(&amount, &(singular, plural))| {
+            format!("{} {}", amount, if amount == 1 { singular } else { plural })
+        })
+        .collect::<Vec<_>>();
+    if parts.is_empty() {
+        return format!("0 {}", duration_units(locale)[3].1);
+    }
+    parts.join(" ")
+}
+
+/// Replaces each `{key}` in the template with its value. The template is scanned once, so values
+/// containing `{key}` placeholders themselves are inserted as is. Unknown placeholders are kept
+fn fill_template(template: &str, values: &[(&str, &str)]) -> String {
+    let mut text = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        text += &rest[..start];
+        rest = &rest[start..];
+        let value = rest.find('}').and_then(|end| {
+            let key = &rest[1..end];
+            let (_, value) = values.iter().find(|(k, _)| *k == key)?;
+            Some((value, end))
+        });
+        match value {
+            Some((value, end)) => {
+                text += value;
+                rest = &rest[end + 1..];
+            }
+            None => {
+                text.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    text += rest;
+    text
+}
+
 /// An error handler that prints the error into the console and also into the Discord chat.
 /// If the user invoked the command wrong
-/// (i.e. an [`crate::ArgumentParseError`]), the command help is displayed and the user is directed
-/// to the help menu.
+/// (i.e. an [`crate::ArgumentParseError`]), the offending argument is pointed out, the command help
+/// is displayed and the user is directed to the help menu. Other command errors are also posted
+/// into the [`crate::GuildSettings::mod_log_channel`] of the guild, if set.
+///
+/// To customize the messages, use [`on_error_with_messages`].
 pub async fn on_error<D>(e: BoxErrorSendSync, ctx: crate::ErrorContext<'_, D, BoxErrorSendSync>) {
+    on_error_with_messages(e, ctx, &ErrorMessages::DEFAULT).await;
+}
+
+/// Like [`on_error`], but with custom reply messages
+pub async fn on_error_with_messages<D>(
+    e: BoxErrorSendSync,
+    ctx: crate::ErrorContext<'_, D, BoxErrorSendSync>,
+    messages: &ErrorMessages<'_>,
+) {
     println!("Encountered an error: {:?}", e);
     match ctx {
         crate::ErrorContext::Command(ctx) => {
-            let user_error_msg = if let Some(crate::ArgumentParseError(e)) = e.downcast_ref() {
+            let command_name = ctx.ctx().command().map_or("", |command| command.name());
+            let user_error_msg = if let Some(e) = e.downcast_ref::<crate::ArgumentParseError>() {
                 // If we caught an argument parse error, give a helpful error message with the
                 // command explanation if available
 
-                let mut usage = "Please check the help menu for usage information".into();
+                let mut usage = messages.default_usage.to_owned();
                 if let crate::CommandErrorContext::Prefix(ctx) = &ctx {
                     if let Some(multiline_help) = &ctx.command.options.multiline_help {
                         usage = multiline_help();
                     }
                 }
-                format!("**{}**\n{}", e, usage)
+                let error = e.to_string();
+                match e.caret_diagram() {
+                    Some(diagram) => fill_template(
+                        messages.argument_parse_error_with_diagram,
+                        &[
+                            ("command", command_name),
+                            ("error", &error),
+                            ("diagram", &diagram),
+                            ("usage", &usage),
+                        ],
+                    ),
+                    None => fill_template(
+                        messages.argument_parse_error,
+                        &[
+                            ("command", command_name),
+                            ("error", &error),
+                            ("usage", &usage),
+                        ],
+                    ),
+                }
             } else {
-                e.to_string()
+                fill_template(
+                    messages.command_error,
+                    &[("command", command_name), ("error", &e.to_string())],
+                )
             };
             if let Err(e) = crate::say_reply(ctx.ctx(), user_error_msg).await {
                 println!("Error while user command error: {}", e);
             }
+
+            // Argument parse errors are the user's fault and not interesting to moderators
+            if e.downcast_ref::<crate::ArgumentParseError>().is_none() {
+                let mod_log_channel = ctx
+                    .ctx()
+                    .guild_settings()
+                    .await
+                    .and_then(|settings| settings.mod_log_channel);
+                if let Some(channel_id) = mod_log_channel {
+                    let embed = error_report_embed(&e, &crate::ErrorContext::Command(ctx.clone()));
+                    let discord = ctx.ctx().discord();
+                    if let Err(e) = channel_id
+                        .send_message(discord, |m| m.set_embed(embed))
+                        .await
+                    {
+                        println!("Error while posting command error to mod log: {}", e);
+                    }
+                }
+            }
         }
         crate::ErrorContext::Listener(event) => {
             println!("Error in listener while processing {:?}: {}", event, e)
@@ -45,9 +267,98 @@ pub async fn on_error<D>(e: BoxErrorSendSync, ctx: crate::ErrorContext<'_, D, Bo
             )
         }
         crate::ErrorContext::Setup => println!("Setup failed: {}", e),
+        crate::ErrorContext::BackgroundTask(name) => {
+            println!("Error in background task {:?}: {}", name, e)
+        }
     }
 }
 
+/// The reply to an invocation of a command that is on cooldown, for use in
+/// [`crate::FrameworkOptions::on_cooldown`]. Ephemeral for application commands.
+///
+/// ```rust
+/// # type Error = Box<dyn std::error::Error + Send + Sync>;
+/// # use poise::builtins::ErrorMessages;
+/// # static MESSAGES: ErrorMessages<'static> = ErrorMessages::DEFAULT;
+/// let options = poise::FrameworkOptions::<(), Error> {
+///     on_cooldown: |hit| {
+///         Box::pin(async move { poise::builtins::cooldown_feedback(&hit, &MESSAGES).await })
+///     },
+///     ..Default::default()
+/// };
+/// ```
+///
+/// The remaining time is formatted in the [locale](crate::GuildSettings::locale) of the guild, if
+/// set, or else in [`ErrorMessages::locale`].
+pub async fn cooldown_feedback<U, E>(
+    hit: &crate::CooldownHit<'_, U, E>,
+    messages: &ErrorMessages<'_>,
+) -> crate::CooldownFeedback {
+    let settings = hit.ctx.guild_settings().await;
+    let locale = settings_locale(settings.as_ref(), messages.locale);
+    crate::CooldownFeedback::EphemeralReply(fill_template(
+        messages.cooldown,
+        &[
+            ("command", hit.command.name()),
+            (
+                "remaining",
+                &humanize_duration(hit.remaining_cooldown, locale),
+            ),
+        ],
+    ))
+}
+
+/// The locale from the guild settings, if any, or else the given fallback
+fn settings_locale<'a>(settings: Option<&'a crate::GuildSettings>, fallback: &'a str) -> &'a str {
+    settings
+        .and_then(|settings| settings.locale.as_deref())
+        .unwrap_or(fallback)
+}
+
+/// Tells the user which of the command's required roles they're missing, for use in
+/// [`crate::FrameworkOptions::on_missing_roles`]. Ephemeral for application commands.
+///
+/// Role IDs are inserted as mentions, which don't ping anyone by default, see
+/// [`crate::FrameworkOptions::allowed_mentions`].
+pub async fn missing_roles_reply<U, E>(
+    missing: &crate::MissingRoles<'_, U, E>,
+    messages: &ErrorMessages<'_>,
+) {
+    let command_name = missing.ctx.command().map_or("", |command| command.name());
+    let roles = missing
+        .missing
+        .iter()
+        .map(|role| role.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let response = fill_template(
+        messages.missing_roles,
+        &[("command", command_name), ("roles", &roles)],
+    );
+    let _: Result<_, _> =
+        crate::send_reply(missing.ctx, |f| f.content(response).ephemeral(true)).await;
+}
+
+/// Tells the user that they aren't allowed to run the application command, for use in
+/// [`crate::ApplicationFrameworkOptions::missing_permissions_handler`]
+pub async fn missing_permissions_reply<U, E>(
+    ctx: crate::ApplicationContext<'_, U, E>,
+    messages: &ErrorMessages<'_>,
+) {
+    let response = fill_template(
+        messages.missing_permissions,
+        &[
+            ("command", ctx.command.slash_or_context_menu_name()),
+            (
+                "permissions",
+                &format!("{:?}", ctx.command.options().required_permissions),
+            ),
+        ],
+    );
+    let _: Result<_, _> =
+        crate::send_application_reply(ctx, |f| f.content(response).ephemeral(true)).await;
+}
+
 /// An autocomplete function that can be used for the command parameter in your help function.
 ///
 /// See examples/framework_usage for an example
@@ -58,13 +369,116 @@ pub async fn autocomplete_command<U, E>(
     // We only consider prefix commands here because, bad as it is, that's what other builtins
     // do to. For example the help command only shows commands that have a prefix version.
     // Once a better command structure design is adopted, this issue should be solved
-    ctx.framework()
+    let names = ctx
+        .framework()
+        .prefix_commands()
+        .into_iter()
+        .map(|cmd| cmd.command.name);
+    filter_by_name(&partial, names)
+        .into_iter()
+        .map(|name| name.to_owned())
+}
+
+/// Levenshtein distance between two strings, compared case-insensitively
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.to_lowercase().chars().collect::<Vec<_>>();
+    let b = b.to_lowercase().chars().collect::<Vec<_>>();
+
+    let mut previous_row = (0..=b.len()).collect::<Vec<_>>();
+    let mut current_row = vec![0; b.len() + 1];
+    for (i, a_char) in a.iter().enumerate() {
+        current_row[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution_cost = if a_char == b_char { 0 } else { 1 };
+            current_row[j + 1] = (previous_row[j] + substitution_cost)
+                .min(previous_row[j + 1] + 1)
+                .min(current_row[j] + 1);
+        }
+        std::mem::swap(&mut previous_row, &mut current_row);
+    }
+    previous_row[b.len()]
+}
+
+/// How well the name matches the query, lower is better, or None if it doesn't match at all
+fn name_match_score(query: &str, name: &str) -> Option<(u8, usize)> {
+    let query = query.to_lowercase();
+    let name = name.to_lowercase();
+    let length_difference = name.chars().count().saturating_sub(query.chars().count());
+
+    if name == query {
+        return Some((0, 0));
+    }
+    if name.starts_with(&query) {
+        return Some((1, length_difference));
+    }
+    if name.contains(&query) {
+        return Some((2, length_difference));
+    }
+
+    // Allow roughly one typo per three characters
+    let max_distance = std::cmp::max(1, query.chars().count() / 3);
+    let distance = edit_distance(&name, &query);
+    if distance <= max_distance {
+        return Some((3, distance));
+    }
+    None
+}
+
+/// Filters the items by how well their names match the query, best matches first.
+///
+/// Names are compared case-insensitively. Exact matches come first, then names starting with the
+/// query, then names containing it, and finally names within roughly one typo per three
+/// characters of it. Within each group, closer matches come first; equally good matches keep
+/// their order. An empty query matches everything.
+///
+/// The framework uses this to suggest similar commands for unknown prefix commands, and so do
+/// [`help`] and [`autocomplete_command`]. Use it for your own autocomplete callbacks to get
+/// consistent matching:
+/// ```rust
+/// let fruits = ["Apple", "Pineapple", "Banana", "Apricot"];
+/// assert_eq!(poise::builtins::filter_by_name("ap", fruits.iter()).len(), 3);
+/// assert_eq!(poise::builtins::filter_by_name("bnana", fruits.iter()), [&"Banana"]);
+/// ```
+pub fn filter_by_name<T: AsRef<str>>(query: &str, items: impl IntoIterator<Item = T>) -> Vec<T> {
+    let mut matches = items
+        .into_iter()
+        .filter_map(|item| Some((name_match_score(query, item.as_ref())?, item)))
+        .collect::<Vec<_>>();
+    matches.sort_by_key(|&(score, _)| score);
+    matches.into_iter().map(|(_, item)| item).collect()
+}
+
+/// A handler for [`crate::PrefixFrameworkOptions::unknown_command`] which replies with similar
+/// command names, like "Unknown command `bna`. Did you mean `ban`?". Stays silent if there are no
+/// similar commands.
+///
+/// ```rust,ignore
+/// poise::PrefixFrameworkOptions {
+///     unknown_command: Some(|x| Box::pin(poise::builtins::suggest_similar_commands(x))),
+///     ..Default::default()
+/// }
+/// ```
+pub async fn suggest_similar_commands<U, E>(unknown: crate::UnknownCommand<'_, U, E>) {
+    let suggestion = match unknown.suggestions.first() {
+        Some(x) => x,
+        None => return,
+    };
+    let prefix = unknown
+        .ctx
+        .framework
         .options()
         .prefix_options
-        .commands
-        .iter()
-        .filter(move |cmd| cmd.command.name.starts_with(&partial))
-        .map(|cmd| cmd.command.name.to_string())
+        .prefix
+        .as_deref();
+    let text = format!(
+        "Unknown command `{}`. Did you mean `{}{}`?",
+        unknown.command_name,
+        prefix.unwrap_or(""),
+        suggestion
+    );
+    if let Err(e) = crate::say_reply(crate::Context::Prefix(unknown.ctx), text).await {
+        println!("Error while suggesting similar commands: {}", e);
+    }
 }
 
 /// Whether to send the help message as ephemeral (if possible), or as a normal message
@@ -76,6 +490,162 @@ pub enum HelpResponseMode {
     Ephemeral,
 }
 
+/// A piece of text in the output of the [`help`] and [`paginated_help`] builtins, passed to
+/// [`HelpConfiguration::localizer`] for translation
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+#[non_exhaustive]
+pub enum HelpText<'a> {
+    /// Heading of the commands without category: `Commands`
+    UncategorizedHeading,
+    /// Heading of the commands in a category: the category name
+    Category(&'a str),
+    /// Single-line description of a command, from its first doc comment line
+    CommandDescription {
+        /// Name of the command
+        command: &'a str,
+        /// The description
+        description: &'a str,
+    },
+    /// Detailed help of a command, from its doc comments or `explanation_fn`
+    CommandExplanation {
+        /// Name of the command
+        command: &'a str,
+        /// The explanation
+        explanation: &'a str,
+    },
+    /// Description of a command parameter, from its `#[description]` attribute
+    ParameterDescription {
+        /// Name of the command
+        command: &'a str,
+        /// Name of the parameter
+        parameter: &'a str,
+        /// The description
+        description: &'a str,
+    },
+    /// [`HelpConfiguration::extra_text_at_bottom`]
+    ExtraTextAtBottom(&'a str),
+    /// Shown for commands without help text: `No help available`
+    NoHelpAvailable,
+    /// Reply when the requested command doesn't exist: ``No such command `name`.``
+    NoSuchCommand(&'a str),
+    /// Appended to [`Self::NoSuchCommand`] with the name of a similar command:
+    /// ``Did you mean `name`?``
+    DidYouMean(&'a str),
+    /// Shown when there are no commands to list: `No commands`
+    NoCommands,
+    /// `Usage`
+    UsageHeading,
+    /// `Parameters`
+    ParametersHeading,
+    /// `Examples`
+    ExamplesHeading,
+    /// `Aliases`
+    AliasesHeading,
+    /// `Cooldown`
+    CooldownHeading,
+    /// `globally`
+    CooldownGlobal,
+    /// `per user`
+    CooldownPerUser,
+    /// `per server`
+    CooldownPerGuild,
+    /// `per channel`
+    CooldownPerChannel,
+    /// `per server member`
+    CooldownPerMember,
+    /// Label of the button to the previous page: `Previous`
+    PreviousButton,
+    /// Label of the button to the next page: `Next`
+    NextButton,
+    /// Placeholder of the category select menu: `Jump to category`
+    CategorySelectPlaceholder,
+}
+
+impl HelpText<'_> {
+    /// The English text, which is used when the localizer returns None
+    pub fn default_text(self) -> String {
+        match self {
+            Self::UncategorizedHeading => "Commands".into(),
+            Self::Category(name) => name.into(),
+            Self::CommandDescription { description, .. } => description.into(),
+            Self::CommandExplanation { explanation, .. } => explanation.into(),
+            Self::ParameterDescription { description, .. } => description.into(),
+            Self::ExtraTextAtBottom(text) => text.into(),
+            Self::NoHelpAvailable => "No help available".into(),
+            Self::NoSuchCommand(name) => format!("No such command `{}`.", name),
+            Self::DidYouMean(name) => format!("Did you mean `{}`?", name),
+            Self::NoCommands => "No commands".into(),
+            Self::UsageHeading => "Usage".into(),
+            Self::ParametersHeading => "Parameters".into(),
+            Self::ExamplesHeading => "Examples".into(),
+            Self::AliasesHeading => "Aliases".into(),
+            Self::CooldownHeading => "Cooldown".into(),
+            Self::CooldownGlobal => "globally".into(),
+            Self::CooldownPerUser => "per user".into(),
+            Self::CooldownPerGuild => "per server".into(),
+            Self::CooldownPerChannel => "per channel".into(),
+            Self::CooldownPerMember => "per server member".into(),
+            Self::PreviousButton => "Previous".into(),
+            Self::NextButton => "Next".into(),
+            Self::CategorySelectPlaceholder => "Jump to category".into(),
+        }
+    }
+}
+
+/// Configuration for the [`help`] and [`paginated_help`] builtins
+#[derive(Copy, Clone)]
+pub struct HelpConfiguration<'a> {
+    /// Extra text displayed at the bottom of the command list
+    pub extra_text_at_bottom: &'a str,
+    /// Whether to send the help message as ephemeral (if possible)
+    pub response_mode: HelpResponseMode,
+    /// Whether to hide commands the invoking user can't run, because of
+    /// [`crate::FrameworkOptions::command_check`], the command's own check, its required
+    /// permissions or `owners_only`
+    ///
+    /// Prefix command checks need an invocation message, so in application command help
+    /// invocations the check of the slash command with the same name is used instead, if any.
+    pub filter_by_checks: bool,
+    /// Translates the text of the help output, including category names and command and
+    /// parameter descriptions. Returning None falls back to [`HelpText::default_text`].
+    ///
+    /// To respond in the invoking user's language, look up the locale in your help command and
+    /// capture it in the closure:
+    /// ```rust
+    /// # let locale = "de";
+    /// let localizer = |text: poise::builtins::HelpText<'_>| match (locale, text) {
+    ///     ("de", poise::builtins::HelpText::UncategorizedHeading) => Some("Befehle".into()),
+    ///     ("de", poise::builtins::HelpText::UsageHeading) => Some("Verwendung".into()),
+    ///     _ => None,
+    /// };
+    /// let config = poise::builtins::HelpConfiguration {
+    ///     localizer: Some(&localizer),
+    ///     ..Default::default()
+    /// };
+    /// ```
+    pub localizer: Option<&'a (dyn Fn(HelpText<'_>) -> Option<String> + Send + Sync)>,
+}
+
+impl Default for HelpConfiguration<'_> {
+    fn default() -> Self {
+        Self {
+            extra_text_at_bottom: "",
+            response_mode: HelpResponseMode::Default,
+            filter_by_checks: false,
+            localizer: None,
+        }
+    }
+}
+
+impl HelpConfiguration<'_> {
+    /// Runs the text through [`Self::localizer`]
+    fn text(&self, text: HelpText<'_>) -> String {
+        self.localizer
+            .and_then(|localizer| localizer(text))
+            .unwrap_or_else(|| text.default_text())
+    }
+}
+
 /// A help command that outputs text in a code block, groups commands by categories, and annotates
 /// commands with a slash if they exist as slash commands.
 ///
@@ -91,11 +661,14 @@ pub enum HelpResponseMode {
 /// ) -> Result<(), Error> {
 ///     let bottom_text = "Type ?help command for more info on a command.
 /// You can edit your message to the bot and the bot will edit its response.";
-///     poise::samples::help(
+///     poise::builtins::help(
 ///         ctx,
 ///         command.as_deref(),
-///         bottom_text,
-///         poise::samples::HelpResponseMode::Ephemeral,
+///         poise::builtins::HelpConfiguration {
+///             extra_text_at_bottom: bottom_text,
+///             response_mode: poise::builtins::HelpResponseMode::Ephemeral,
+///             ..Default::default()
+///         },
 ///     )
 ///     .await?;
 ///     Ok(())
@@ -134,87 +707,88 @@ pub enum HelpResponseMode {
 pub async fn help<D, E>(
     ctx: crate::Context<'_, D, E>,
     command: Option<&str>,
-    extra_text_at_bottom: &str,
-    response_mode: HelpResponseMode,
+    config: HelpConfiguration<'_>,
 ) -> Result<(), serenity::Error> {
-    let reply = if let Some(command) = command {
-        if let Some(command) = ctx
+    let settings = ctx.guild_settings().await;
+    let prefix = help_prefix(ctx, settings.as_ref()).await;
+    let reply = if let Some(command_name) = command {
+        let case_insensitive = ctx
             .framework()
             .options()
             .prefix_options
-            .commands
-            .iter()
-            .map(|cmd_meta| &cmd_meta.command)
-            .find(|cmd| cmd.name == command)
-        {
-            match command.options.multiline_help {
-                Some(f) => f(),
-                None => command
-                    .options
-                    .inline_help
-                    .unwrap_or("No help available")
-                    .to_owned(),
-            }
-        } else {
-            format!("No such command `{}`", command)
-        }
-    } else {
-        let is_also_a_slash_command = |command_name| {
-            let application_commands = &ctx.framework().options().application_options.commands;
-            application_commands.iter().any(|c| match c {
-                crate::ApplicationCommandTree::Slash(cmd) => match cmd {
-                    crate::SlashCommandMeta::Command(cmd) => cmd.name == command_name,
-                    crate::SlashCommandMeta::CommandGroup { name, .. } => name == &command_name,
-                },
-                _ => false,
+            .case_insensitive_commands;
+        let commands = ctx.framework().prefix_commands();
+        // Subcommands are looked up by their qualified name, like `help mod ban`, and belong to
+        // the category of their top-level command
+        let category_disabled = command_name
+            .split_whitespace()
+            .next()
+            .and_then(|first_name| {
+                commands
+                    .iter()
+                    .find(|cmd_meta| cmd_meta.command.is_named(first_name, case_insensitive))
             })
+            .map_or(true, |cmd_meta| {
+                is_category_disabled(settings.as_ref(), cmd_meta.category)
+            });
+        let mut command = match ctx.framework().find_command(command_name) {
+            Some(crate::CommandRef::Prefix(command)) if !category_disabled => Some(command),
+            _ => None,
         };
-
-        let mut categories: Vec<(Option<&str>, Vec<&crate::PrefixCommand<_, _>>)> = Vec::new();
-        for cmd_meta in &ctx.framework().options().prefix_options.commands {
-            if let Some((_, commands)) = categories
-                .iter_mut()
-                .find(|(key, _)| *key == cmd_meta.category)
+        let mut permission_cache = HashMap::new();
+        if let Some(cmd) = command {
+            if crate::is_gated_off(ctx, crate::CommandRef::Prefix(cmd)).await
+                || (config.filter_by_checks
+                    && !can_run_command(ctx, cmd, &mut permission_cache).await)
             {
-                commands.push(&cmd_meta.command);
-            } else {
-                categories.push((cmd_meta.category, vec![&cmd_meta.command]));
+                command = None;
             }
         }
-
+        if let Some(command) = command {
+            let qualified_name =
+                crate::qualified_command_name(ctx.framework(), crate::CommandRef::Prefix(command));
+            let locale = settings_locale(settings.as_ref(), "en");
+            command_help_page(command, &qualified_name, &prefix, locale, &config)
+        } else {
+            let mut text = config.text(HelpText::NoSuchCommand(command_name));
+            let candidates = commands
+                .iter()
+                .filter(|cmd_meta| !is_category_disabled(settings.as_ref(), cmd_meta.category))
+                .map(|cmd_meta| &cmd_meta.command)
+                .filter(|cmd| !cmd.options.hide_in_help)
+                .map(CommandName);
+            for CommandName(suggestion) in filter_by_name(command_name, candidates) {
+                if crate::is_gated_off(ctx, crate::CommandRef::Prefix(suggestion)).await {
+                    continue;
+                }
+                if !config.filter_by_checks
+                    || can_run_command(ctx, suggestion, &mut permission_cache).await
+                {
+                    text += " ";
+                    text += &config.text(HelpText::DidYouMean(suggestion.name));
+                    break;
+                }
+            }
+            text
+        }
+    } else {
         let mut menu = String::from("```\n");
+        let categories = help_categories(ctx, settings.as_ref(), config.filter_by_checks).await;
         for (category_name, commands) in categories {
-            menu += category_name.unwrap_or("Commands");
+            menu += &category_heading(category_name, &config);
             menu += ":\n";
             for command in commands {
-                if command.options.hide_in_help {
-                    continue;
-                }
-
-                let prefix = if is_also_a_slash_command(command.name) {
-                    "/"
-                } else if let Some(prefix) = &ctx.framework().options().prefix_options.prefix {
-                    prefix
-                } else {
-                    ""
-                };
-
-                menu += &format!(
-                    "  {}{:<12}{}\n",
-                    prefix,
-                    command.name,
-                    command.options.inline_help.unwrap_or("")
-                );
+                menu += &help_line(ctx, command, &prefix, &config);
             }
         }
         menu += "\n";
-        menu += extra_text_at_bottom;
+        menu += &config.text(HelpText::ExtraTextAtBottom(config.extra_text_at_bottom));
         menu += "\n```";
 
         menu
     };
 
-    let ephemeral = match response_mode {
+    let ephemeral = match config.response_mode {
         HelpResponseMode::Default => false,
         HelpResponseMode::Ephemeral => true,
     };
@@ -224,113 +798,1962 @@ pub async fn help<D, E>(
     Ok(())
 }
 
-/// Generic function to register application commands. Only allows server owners to invoke.
-///
-/// If you want, you can copy paste this help message:
-///
-/// ```ignore
-/// Register application commands in this guild or globally
-///
-/// Run with no arguments to register in guild, run with argument "global" to register globally.
-/// ```
-pub async fn register_application_commands<U, E>(
+/// Matches a command by its name in [`filter_by_name`]
+struct CommandName<'a, U, E>(&'a crate::PrefixCommand<U, E>);
+
+impl<U, E> AsRef<str> for CommandName<'_, U, E> {
+    fn as_ref(&self) -> &str {
+        self.0.name
+    }
+}
+
+/// The prefix shown in the help menus, resolved like in prefix dispatch: the prefix from the
+/// [`crate::PrefixFrameworkOptions::prefix_store`], if any, or else the prefix from the guild
+/// settings, or else [`crate::PrefixFrameworkOptions::prefix`]
+async fn help_prefix<U, E>(
     ctx: crate::Context<'_, U, E>,
-    global: bool,
-) -> Result<(), serenity::Error> {
-    let guild = match ctx.guild() {
-        Some(x) => x,
+    settings: Option<&crate::GuildSettings>,
+) -> String {
+    let prefix_options = &ctx.framework().options().prefix_options;
+    if let (Some(store), Some(guild_id)) = (&prefix_options.prefix_store, ctx.guild_id()) {
+        if let Some(prefix) = store.get(guild_id).await {
+            return prefix;
+        }
+    }
+    settings
+        .and_then(|settings| settings.prefix.as_deref())
+        .or_else(|| prefix_options.prefix.as_deref())
+        .unwrap_or("")
+        .to_owned()
+}
+
+/// Whether the given category is in the disabled categories of the guild settings
+fn is_category_disabled(settings: Option<&crate::GuildSettings>, category: Option<&str>) -> bool {
+    match (settings, category) {
+        (Some(settings), Some(category)) => {
+            settings.disabled_categories.iter().any(|c| c == category)
+        }
+        _ => false,
+    }
+}
+
+/// The detailed help of a single command: its description, usage syntax, parameters, examples,
+/// aliases and cooldowns
+fn command_help_page<U, E>(
+    command: &crate::PrefixCommand<U, E>,
+    qualified_name: &str,
+    prefix: &str,
+    locale: &str,
+    config: &HelpConfiguration<'_>,
+) -> String {
+    let options = &command.options;
+
+    let mut help = match (options.multiline_help, options.inline_help) {
+        (Some(f), _) => config.text(HelpText::CommandExplanation {
+            command: command.name,
+            explanation: &f(),
+        }),
+        (None, Some(description)) => config.text(HelpText::CommandDescription {
+            command: command.name,
+            description,
+        }),
+        (None, None) => config.text(HelpText::NoHelpAvailable),
+    };
+
+    let usage = match options.usage {
+        Some(usage) => usage.to_owned(),
         None => {
-            crate::say_reply(ctx, "Must be called in guild").await?;
-            return Ok(());
+            let mut usage = qualified_name.to_owned();
+            for param in options.parameters {
+                if param.named {
+                    usage += &format!(" [--{} <{}>]", param.name, param.name);
+                } else if param.required {
+                    usage += &format!(" <{}>", param.name);
+                } else {
+                    usage += &format!(" [{}]", param.name);
+                }
+            }
+            usage
         }
     };
+    help += &format!(
+        "\n\n{}: `{}{}`",
+        config.text(HelpText::UsageHeading),
+        prefix,
+        usage
+    );
 
-    if ctx.author().id != guild.owner_id {
-        crate::say_reply(ctx, "Can only be used by server owner").await?;
-        return Ok(());
+    if options.parameters.iter().any(|p| p.description.is_some()) {
+        help += &format!("\n\n{}:", config.text(HelpText::ParametersHeading));
+        for param in options.parameters {
+            match param.description {
+                Some(description) => {
+                    let description = config.text(HelpText::ParameterDescription {
+                        command: command.name,
+                        parameter: param.name,
+                        description,
+                    });
+                    help += &format!("\n- `{}`: {}", param.name, description);
+                }
+                None => help += &format!("\n- `{}`", param.name),
+            }
+        }
     }
 
-    let mut commands_builder = serenity::CreateApplicationCommands::default();
-    let commands = &ctx.framework().options().application_options.commands;
-    for cmd in commands {
-        commands_builder.create_application_command(|f| cmd.create(f));
+    if !options.examples.is_empty() {
+        help += &format!("\n\n{}:", config.text(HelpText::ExamplesHeading));
+        for example in options.examples {
+            help += &format!("\n- `{}{}`", prefix, example);
+        }
     }
 
-    crate::say_reply(ctx, format!("Registering {} commands...", commands.len())).await?;
-    let json_value = serde_json::Value::Array(commands_builder.0);
-    if global {
-        ctx.discord()
-            .http
-            .create_global_application_commands(&json_value)
-            .await?;
-    } else {
-        ctx.discord()
-            .http
-            .create_guild_application_commands(guild.id.0, &json_value)
-            .await?;
+    if !options.aliases.is_empty() {
+        help += &format!(
+            "\n\n{}: {}",
+            config.text(HelpText::AliasesHeading),
+            options.aliases.join(", ")
+        );
     }
-    crate::say_reply(ctx, "Done!").await?;
 
-    Ok(())
+    let cooldowns = options.cooldowns.config();
+    let cooldowns = [
+        (cooldowns.global, HelpText::CooldownGlobal),
+        (cooldowns.user, HelpText::CooldownPerUser),
+        (cooldowns.guild, HelpText::CooldownPerGuild),
+        (cooldowns.channel, HelpText::CooldownPerChannel),
+        (cooldowns.member, HelpText::CooldownPerMember),
+    ]
+    .iter()
+    .filter_map(|&(duration, scope)| {
+        Some(format!(
+            "{} {}",
+            humanize_duration(duration?, locale),
+            config.text(scope)
+        ))
+    })
+    .collect::<Vec<_>>();
+    if !cooldowns.is_empty() {
+        help += &format!(
+            "\n\n{}: {}",
+            config.text(HelpText::CooldownHeading),
+            cooldowns.join(", ")
+        );
+    }
+
+    help
 }
 
-/// Lists servers of which the bot is a member of, including their member counts, sorted
-/// descendingly by member count.
-///
-/// Non-[public](https://support.discord.com/hc/en-us/articles/360030843331-Enabling-Server-Discovery)
-/// guilds are hidden to preserve privacy. When the command is invoked by the bot
-/// owner as an application command, the response will be made ephemeral and private guilds are
-/// unhidden.
+/// Whether the invoking user passes the command's required permissions and roles, `owners_only`
+/// and checks.
+/// Checks which return an error count as failed.
 ///
-/// Example:
-/// > I am currently in three servers!
-/// > - **A public server** (7123 members)
-/// > - [3 private servers with 456 members total]
-pub async fn servers<U, E>(ctx: crate::Context<'_, U, E>) -> Result<(), serenity::Error> {
-    let mut show_private_guilds = false;
-    if let crate::Context::Application(_) = ctx {
-        if let Ok(app) = ctx.discord().http.get_current_application_info().await {
-            if app.owner.id == ctx.author().id {
-                show_private_guilds = true;
-            }
+/// Permission lookups may need an HTTP request, so their results are cached in `permission_cache`
+/// across calls.
+async fn can_run_command<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    command: &crate::PrefixCommand<U, E>,
+    permission_cache: &mut HashMap<(serenity::Permissions, bool), bool>,
+) -> bool {
+    let key = (
+        command.options.required_permissions,
+        command.options.owners_only,
+    );
+    let passes_permissions = match permission_cache.get(&key) {
+        Some(&x) => x,
+        None => {
+            let x = crate::framework::check_required_permissions_and_owners_only(ctx, key.0, key.1)
+                .await;
+            permission_cache.insert(key, x);
+            x
         }
+    };
+    if !passes_permissions {
+        return false;
+    }
+    if !crate::missing_roles(ctx, command.options.required_roles)
+        .await
+        .is_empty()
+    {
+        return false;
     }
 
-    struct Guild {
-        name: String,
-        num_members: u64,
-        is_public: bool,
+    let global_check = ctx.framework().options().command_check;
+    match ctx {
+        crate::Context::Prefix(ctx) => {
+            let ctx = crate::PrefixContext {
+                command: Some(command),
+                ..ctx
+            };
+            if let Some(check) = global_check {
+                if !check(crate::Context::Prefix(ctx)).await.unwrap_or(false) {
+                    return false;
+                }
+            }
+            match command.options.check {
+                Some(check) => check(ctx).await.unwrap_or(false),
+                None => true,
+            }
+        }
+        crate::Context::Application(ctx) => {
+            // Prefix command checks need a message, so use the slash command of the same name
+            let slash_command =
+                ctx.framework
+                    .application_commands()
+                    .into_iter()
+                    .find_map(|c| match c {
+                        crate::ApplicationCommandTree::Slash(crate::SlashCommandMeta::Command(
+                            cmd,
+                        )) if cmd.name == command.name => Some(cmd),
+                        _ => None,
+                    });
+            let ctx = match slash_command {
+                Some(cmd) => crate::ApplicationContext {
+                    command: crate::ApplicationCommand::Slash(cmd),
+                    ..ctx
+                },
+                None => ctx,
+            };
+            if let Some(check) = global_check {
+                if !check(crate::Context::Application(ctx))
+                    .await
+                    .unwrap_or(false)
+                {
+                    return false;
+                }
+            }
+            match slash_command.and_then(|cmd| cmd.options.check) {
+                Some(check) => check(ctx).await.unwrap_or(false),
+                None => true,
+            }
+        }
     }
+}
 
-    let guild_ids = ctx.discord().cache.guilds();
-    let mut guilds = guild_ids
-        .into_iter()
-        .filter_map(|guild_id| {
-            ctx.discord().cache.guild_field(guild_id, |guild| Guild {
-                name: guild.name.clone(),
-                num_members: guild.member_count,
-                is_public: guild.features.iter().any(|x| x == "DISCOVERABLE"),
-            })
-        })
-        .collect::<Vec<_>>();
-    guilds.sort_by_key(|guild| u64::MAX - guild.num_members); // descending sort
+/// The prefix commands to list in the help menu grouped by category, in the order of first
+/// appearance. Leaves out commands with `hide_in_help`, commands in categories disabled by the
+/// guild settings, commands gated off by [`crate::FrameworkOptions::feature_gate`], commands the
+/// user can't run if `filter_by_checks` is set, and categories left empty by that
+async fn help_categories<'a, U, E>(
+    ctx: crate::Context<'a, U, E>,
+    settings: Option<&crate::GuildSettings>,
+    filter_by_checks: bool,
+) -> Vec<(Option<&'static str>, Vec<&'a crate::PrefixCommand<U, E>>)> {
+    let mut permission_cache = HashMap::new();
+    let mut categories: Vec<(Option<&str>, Vec<&crate::PrefixCommand<_, _>>)> = Vec::new();
+    for cmd_meta in ctx.framework().prefix_commands() {
+        if cmd_meta.command.options.hide_in_help
+            || is_category_disabled(settings, cmd_meta.category)
+            || crate::is_gated_off(ctx, crate::CommandRef::Prefix(&cmd_meta.command)).await
+            || (filter_by_checks
+                && !can_run_command(ctx, &cmd_meta.command, &mut permission_cache).await)
+        {
+            continue;
+        }
 
-    let mut num_private_guilds = 0;
-    let mut num_private_guild_members = 0;
-    let mut response = format!("I am currently in {} servers!\n", guilds.len());
-    for guild in guilds {
-        if guild.is_public || show_private_guilds {
-            response += &format!("- **{}** ({} members)\n", guild.name, guild.num_members);
+        if let Some((_, commands)) = categories
+            .iter_mut()
+            .find(|(key, _)| *key == cmd_meta.category)
+        {
+            commands.push(&cmd_meta.command);
         } else {
-            num_private_guilds += 1;
-            num_private_guild_members += guild.num_members;
+            categories.push((cmd_meta.category, vec![&cmd_meta.command]));
         }
     }
-    if num_private_guilds > 0 {
-        response += &format!(
-            "- [{} private servers with {} members total]\n",
-            num_private_guilds, num_private_guild_members
-        );
+    categories
+}
+
+/// The heading of a category in a help menu
+fn category_heading(category_name: Option<&str>, config: &HelpConfiguration<'_>) -> String {
+    match category_name {
+        Some(name) => config.text(HelpText::Category(name)),
+        None => config.text(HelpText::UncategorizedHeading),
+    }
+}
+
+/// The line of a help menu listing the given command, annotated with a slash if it also exists as a
+/// slash command
+fn help_line<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    command: &crate::PrefixCommand<U, E>,
+    default_prefix: &str,
+    config: &HelpConfiguration<'_>,
+) -> String {
+    let is_also_a_slash_command =
+        ctx.framework()
+            .application_commands()
+            .into_iter()
+            .any(|c| match c {
+                crate::ApplicationCommandTree::Slash(cmd) => match cmd {
+                    crate::SlashCommandMeta::Command(cmd) => cmd.name == command.name,
+                    crate::SlashCommandMeta::CommandGroup { name, .. } => name == &command.name,
+                },
+                _ => false,
+            });
+
+    let prefix = if is_also_a_slash_command {
+        "/"
+    } else {
+        default_prefix
+    };
+
+    let description = match command.options.inline_help {
+        Some(description) => config.text(HelpText::CommandDescription {
+            command: command.name,
+            description,
+        }),
+        None => String::new(),
+    };
+    format!("  {}{:<12}{}\n", prefix, command.name, description)
+}
+
+/// Like [`help`] without a specific command, but shows one category at a time, split into pages
+/// of at most `commands_per_page` commands. Users can flip through the pages with previous/next
+/// buttons and jump to a category with a select menu.
+///
+/// Only the user who invoked the help command can use the buttons. After `timeout` without
+/// interaction, the buttons are removed. With [`HelpResponseMode::Ephemeral`], the menu is sent
+/// as an ephemeral message for application commands.
+///
+/// ```rust,no_run
+/// # type Error = Box<dyn std::error::Error>;
+/// # type Context<'a> = poise::Context<'a, (), Error>;
+/// /// Show this menu
+/// #[poise::command(prefix_command, slash_command)]
+/// pub async fn help(ctx: Context<'_>) -> Result<(), Error> {
+///     poise::builtins::paginated_help(
+///         ctx,
+///         poise::builtins::HelpConfiguration {
+///             extra_text_at_bottom: "Type ?help command for more info on a command.",
+///             response_mode: poise::builtins::HelpResponseMode::Ephemeral,
+///             ..Default::default()
+///         },
+///         10,
+///         std::time::Duration::from_secs(120),
+///     )
+///     .await?;
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "collector")]
+pub async fn paginated_help<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    config: HelpConfiguration<'_>,
+    commands_per_page: usize,
+    timeout: std::time::Duration,
+) -> Result<(), serenity::Error> {
+    struct Page {
+        category_index: usize,
+        content: String,
+    }
+
+    let settings = ctx.guild_settings().await;
+    let prefix = help_prefix(ctx, settings.as_ref()).await;
+    let categories = help_categories(ctx, settings.as_ref(), config.filter_by_checks)
+        .await
+        .into_iter()
+        .map(|(category_name, commands)| (category_heading(category_name, &config), commands))
+        .collect::<Vec<_>>();
+
+    let extra_text_at_bottom =
+        config.text(HelpText::ExtraTextAtBottom(config.extra_text_at_bottom));
+    let mut pages = Vec::new();
+    for (category_index, (category_name, commands)) in categories.iter().enumerate() {
+        let chunks = commands.chunks(std::cmp::max(1, commands_per_page));
+        let num_chunks = chunks.len();
+        for (chunk_index, chunk) in chunks.enumerate() {
+            let mut content = format!("```\n{}", category_name);
+            if num_chunks > 1 {
+                content += &format!(" ({}/{})", chunk_index + 1, num_chunks);
+            }
+            content += ":\n";
+            for command in chunk {
+                content += &help_line(ctx, command, &prefix, &config);
+            }
+            content += "\n";
+            content += &extra_text_at_bottom;
+            content += "\n```";
+            pages.push(Page {
+                category_index,
+                content,
+            });
+        }
+    }
+    if pages.is_empty() {
+        pages.push(Page {
+            category_index: 0,
+            content: format!(
+                "```\n{}\n\n{}\n```",
+                config.text(HelpText::NoCommands),
+                extra_text_at_bottom
+            ),
+        });
+    }
+
+    let id = ctx.id();
+    let prev_id = format!("{}prev", id);
+    let next_id = format!("{}next", id);
+    let category_id = format!("{}category", id);
+    let previous_label = config.text(HelpText::PreviousButton);
+    let next_label = config.text(HelpText::NextButton);
+    let category_placeholder = config.text(HelpText::CategorySelectPlaceholder);
+    let create_components = |components: &mut serenity::CreateComponents, page_index: usize| {
+        let page = &pages[page_index];
+        components.create_action_row(|row| {
+            row.create_button(|b| {
+                b.custom_id(&prev_id)
+                    .label(&previous_label)
+                    .style(serenity::ButtonStyle::Secondary)
+                    .disabled(page_index == 0)
+            })
+            .create_button(|b| {
+                b.custom_id(&next_id)
+                    .label(&next_label)
+                    .style(serenity::ButtonStyle::Secondary)
+                    .disabled(page_index + 1 == pages.len())
+            })
+        });
+        if categories.len() > 1 {
+            components.create_action_row(|row| {
+                row.create_select_menu(|menu| {
+                    menu.custom_id(&category_id)
+                        .placeholder(&category_placeholder)
+                        .options(|options| {
+                            // Select menus can hold at most 25 options
+                            for (i, (category_name, _)) in categories.iter().enumerate().take(25) {
+                                options.create_option(|o| {
+                                    o.label(category_name)
+                                        .value(i)
+                                        .default_selection(i == page.category_index)
+                                });
+                            }
+                            options
+                        })
+                })
+            });
+        }
+    };
+
+    let ephemeral = match config.response_mode {
+        HelpResponseMode::Default => false,
+        HelpResponseMode::Ephemeral => true,
+    };
+    let mut page_index = 0;
+    let reply = crate::send_reply(ctx, |f| {
+        f.content(&pages[page_index].content)
+            .components(|c| {
+                create_components(c, page_index);
+                c
+            })
+            .ephemeral(ephemeral)
+    })
+    .await?;
+
+    let custom_ids = [prev_id.clone(), next_id.clone(), category_id.clone()];
+    while let Some(mci) = serenity::CollectComponentInteraction::new(ctx.discord())
+        .author_id(ctx.author().id)
+        .timeout(timeout)
+        .filter(move |mci| custom_ids.contains(&mci.data.custom_id))
+        .await
+    {
+        if mci.data.custom_id == prev_id {
+            page_index = page_index.saturating_sub(1);
+        } else if mci.data.custom_id == next_id {
+            page_index = std::cmp::min(page_index + 1, pages.len() - 1);
+        } else if let Some(category_index) = mci
+            .data
+            .values
+            .first()
+            .and_then(|x| x.parse::<usize>().ok())
+        {
+            if let Some(first_page) = pages
+                .iter()
+                .position(|page| page.category_index == category_index)
+            {
+                page_index = first_page;
+            }
+        }
+
+        // Updating through the interaction response also works for ephemeral messages
+        mci.create_interaction_response(ctx.discord(), |ir| {
+            ir.kind(serenity::InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|d| {
+                    d.content(&pages[page_index].content).components(|c| {
+                        create_components(c, page_index);
+                        c
+                    })
+                })
+        })
+        .await?;
+    }
+
+    // Remove the buttons after the timeout
+    if let Some(mut reply) = reply {
+        reply.edit(ctx, |f| f.components(|c| c)).await?;
+    }
+
+    Ok(())
+}
+
+/// A page shown by [`paginate`]
+#[derive(Debug, Clone)]
+pub enum PaginationPage {
+    /// Page consisting of message text
+    Text(String),
+    /// Page consisting of an embed
+    Embed(serenity::CreateEmbed),
+}
+
+impl From<String> for PaginationPage {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl From<&str> for PaginationPage {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_owned())
+    }
+}
+
+impl From<serenity::CreateEmbed> for PaginationPage {
+    fn from(embed: serenity::CreateEmbed) -> Self {
+        Self::Embed(embed)
+    }
+}
+
+/// Behavior of [`paginate`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PaginationOptions {
+    /// Whether to send the pages as an ephemeral message (if possible)
+    pub ephemeral: bool,
+    /// How long to wait for a button press before disabling the buttons
+    pub timeout: std::time::Duration,
+    /// Whether only the invoking user can flip pages. Otherwise, everyone can
+    pub author_only: bool,
+}
+
+impl Default for PaginationOptions {
+    fn default() -> Self {
+        Self {
+            ephemeral: false,
+            timeout: std::time::Duration::from_secs(120),
+            author_only: true,
+        }
+    }
+}
+
+/// Sends the first of the given pages with previous/next buttons and a page indicator, and flips
+/// through the pages as the buttons are pressed. Once nobody pressed a button for
+/// [`PaginationOptions::timeout`], the buttons are disabled and this function returns.
+///
+/// ```rust,no_run
+/// # type Error = Box<dyn std::error::Error + Send + Sync>;
+/// # type Context<'a> = poise::Context<'a, (), Error>;
+/// /// Show the rules of this server
+/// #[poise::command(prefix_command, slash_command)]
+/// pub async fn rules(ctx: Context<'_>) -> Result<(), Error> {
+///     let pages: Vec<poise::builtins::PaginationPage> =
+///         vec!["Be nice".into(), "No spam".into(), "Have fun".into()];
+///     let options = poise::builtins::PaginationOptions {
+///         ephemeral: true,
+///         ..Default::default()
+///     };
+///     poise::builtins::paginate(ctx, &pages, options).await?;
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "collector")]
+pub async fn paginate<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    pages: &[PaginationPage],
+    options: PaginationOptions,
+) -> Result<(), serenity::Error> {
+    if pages.is_empty() {
+        return Ok(());
+    }
+
+    let id = ctx.id();
+    let prev_id = format!("{}prev", id);
+    let next_id = format!("{}next", id);
+    let create_components =
+        |components: &mut serenity::CreateComponents, page_index: usize, disabled: bool| {
+            components.create_action_row(|row| {
+                row.create_button(|b| {
+                    b.custom_id(&prev_id)
+                        .label("Previous")
+                        .style(serenity::ButtonStyle::Secondary)
+                        .disabled(disabled || page_index == 0)
+                })
+                .create_button(|b| {
+                    // Buttons need a custom ID even if they can't be pressed
+                    b.custom_id(format!("{}page", id))
+                        .label(format!("{}/{}", page_index + 1, pages.len()))
+                        .style(serenity::ButtonStyle::Secondary)
+                        .disabled(true)
+                })
+                .create_button(|b| {
+                    b.custom_id(&next_id)
+                        .label("Next")
+                        .style(serenity::ButtonStyle::Secondary)
+                        .disabled(disabled || page_index + 1 == pages.len())
+                })
+            });
+        };
+
+    let mut page_index = 0;
+    let reply = crate::send_reply(ctx, |f| {
+        match &pages[page_index] {
+            PaginationPage::Text(text) => f.content(text),
+            PaginationPage::Embed(embed) => {
+                f.embed = Some(embed.clone());
+                f
+            }
+        }
+        .components(|c| {
+            create_components(c, page_index, false);
+            c
+        })
+        .ephemeral(options.ephemeral)
+    })
+    .await?;
+
+    let custom_ids = [prev_id.clone(), next_id.clone()];
+    let author_id = ctx.author().id;
+    let author_only = options.author_only;
+    while let Some(mci) = serenity::CollectComponentInteraction::new(ctx.discord())
+        .timeout(options.timeout)
+        .filter(move |mci| {
+            custom_ids.contains(&mci.data.custom_id) && (!author_only || mci.user.id == author_id)
+        })
+        .await
+    {
+        if mci.data.custom_id == prev_id {
+            page_index = page_index.saturating_sub(1);
+        } else {
+            page_index = std::cmp::min(page_index + 1, pages.len() - 1);
+        }
+
+        // Updating through the interaction response also works for ephemeral messages
+        mci.create_interaction_response(ctx.discord(), |ir| {
+            ir.kind(serenity::InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|d| {
+                    match &pages[page_index] {
+                        PaginationPage::Text(text) => d.content(text).embeds(Vec::new()),
+                        PaginationPage::Embed(embed) => d.content("").embeds(vec![embed.clone()]),
+                    }
+                    .components(|c| {
+                        create_components(c, page_index, false);
+                        c
+                    })
+                })
+        })
+        .await?;
+    }
+
+    // Disable the buttons after the timeout
+    if let Some(mut reply) = reply {
+        reply
+            .edit(ctx, |f| {
+                f.components(|c| {
+                    create_components(c, page_index, true);
+                    c
+                })
+            })
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Behavior of [`confirm`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ConfirmationOptions {
+    /// Label of the button which confirms
+    pub confirm_label: String,
+    /// Label of the button which cancels
+    pub cancel_label: String,
+    /// How long to wait for a button press before treating the prompt as cancelled
+    pub timeout: std::time::Duration,
+    /// Whether to send the prompt as an ephemeral message (if possible)
+    pub ephemeral: bool,
+}
+
+impl Default for ConfirmationOptions {
+    fn default() -> Self {
+        Self {
+            confirm_label: "Confirm".into(),
+            cancel_label: "Cancel".into(),
+            timeout: std::time::Duration::from_secs(60),
+            ephemeral: false,
+        }
+    }
+}
+
+/// Asks the invoking user to confirm something with Confirm/Cancel buttons, which only they can
+/// press. Returns true if they confirmed, and false if they cancelled or didn't respond within
+/// [`ConfirmationOptions::timeout`]. Either way, the buttons are disabled afterwards.
+///
+/// ```rust,no_run
+/// # type Error = Box<dyn std::error::Error + Send + Sync>;
+/// # type Context<'a> = poise::Context<'a, (), Error>;
+/// /// Delete all your data
+/// #[poise::command(prefix_command, slash_command)]
+/// pub async fn forget_me(ctx: Context<'_>) -> Result<(), Error> {
+///     let options = poise::builtins::ConfirmationOptions {
+///         confirm_label: "Delete".into(),
+///         ..Default::default()
+///     };
+///     if poise::builtins::confirm(ctx, "Really delete all your data?", options).await? {
+///         // ...
+///         poise::say_reply(ctx, "Your data was deleted").await?;
+///     }
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "collector")]
+pub async fn confirm<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    prompt: impl Into<String>,
+    options: ConfirmationOptions,
+) -> Result<bool, serenity::Error> {
+    let confirm_id = format!("{}confirm", ctx.id());
+    let cancel_id = format!("{}cancel", ctx.id());
+    let create_components = |components: &mut serenity::CreateComponents, disabled: bool| {
+        components.create_action_row(|row| {
+            row.create_button(|b| {
+                b.custom_id(&confirm_id)
+                    .label(&options.confirm_label)
+                    .style(serenity::ButtonStyle::Success)
+                    .disabled(disabled)
+            })
+            .create_button(|b| {
+                b.custom_id(&cancel_id)
+                    .label(&options.cancel_label)
+                    .style(serenity::ButtonStyle::Danger)
+                    .disabled(disabled)
+            })
+        });
+    };
+
+    let reply = crate::send_reply(ctx, |f| {
+        f.content(prompt)
+            .components(|c| {
+                create_components(c, false);
+                c
+            })
+            .ephemeral(options.ephemeral)
+    })
+    .await?;
+
+    let custom_ids = [confirm_id.clone(), cancel_id.clone()];
+    let mci = serenity::CollectComponentInteraction::new(ctx.discord())
+        .author_id(ctx.author().id)
+        .timeout(options.timeout)
+        .filter(move |mci| custom_ids.contains(&mci.data.custom_id))
+        .await;
+
+    // Disable the buttons
+    match &mci {
+        Some(mci) => {
+            mci.create_interaction_response(ctx.discord(), |ir| {
+                ir.kind(serenity::InteractionResponseType::UpdateMessage)
+                    .interaction_response_data(|d| {
+                        d.components(|c| {
+                            create_components(c, true);
+                            c
+                        })
+                    })
+            })
+            .await?;
+        }
+        None => {
+            if let Some(mut reply) = reply {
+                reply
+                    .edit(ctx, |f| {
+                        f.components(|c| {
+                            create_components(c, true);
+                            c
+                        })
+                    })
+                    .await?;
+            }
+        }
+    }
+
+    Ok(mci.map_or(false, |mci| mci.data.custom_id == confirm_id))
+}
+
+/// Generic function to register application commands. Only allows server owners to invoke.
+///
+/// If you want, you can copy paste this help message:
+///
+/// ```ignore
+/// Register application commands in this guild or globally
+///
+/// Run with no arguments to register in guild, run with argument "global" to register globally.
+/// ```
+pub async fn register_application_commands<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    global: bool,
+) -> Result<(), serenity::Error> {
+    let guild_id = match registration_guild(ctx).await? {
+        Some(x) => x,
+        None => return Ok(()),
+    };
+    if !check_valid_for_registration(ctx).await? {
+        return Ok(());
+    }
+
+    let commands = application_commands_json(ctx.framework().application_commands());
+    crate::say_reply(ctx, format!("Registering {} commands...", commands.len())).await?;
+    let guild_id = if global { None } else { Some(guild_id) };
+    overwrite_application_commands(ctx, guild_id, commands).await?;
+    crate::say_reply(ctx, "Done!").await?;
+
+    Ok(())
+}
+
+/// The guild of the invocation, if the invoking user is its owner. Otherwise tells the user why
+/// they can't register commands
+async fn registration_guild<U, E>(
+    ctx: crate::Context<'_, U, E>,
+) -> Result<Option<serenity::GuildId>, serenity::Error> {
+    let guild = match ctx.guild() {
+        Some(x) => x,
+        None => {
+            crate::say_reply(ctx, "Must be called in guild").await?;
+            return Ok(None);
+        }
+    };
+
+    if ctx.author().id != guild.owner_id {
+        crate::say_reply(ctx, "Can only be used by server owner").await?;
+        return Ok(None);
+    }
+
+    Ok(Some(guild.id))
+}
+
+/// Checks the application commands with [`crate::Framework::validate`]. If they're invalid, lists
+/// the violations to the user and returns false
+async fn check_valid_for_registration<U, E>(
+    ctx: crate::Context<'_, U, E>,
+) -> Result<bool, serenity::Error> {
+    let errors = match ctx.framework().validate() {
+        Ok(()) => return Ok(true),
+        Err(errors) => errors,
+    };
+
+    let mut content = String::from("Can't register the commands, Discord would reject them:\n");
+    for error in &errors {
+        let line = format!("- {}\n", error);
+        if content.len() + line.len() > 1900 {
+            content += "...";
+            break;
+        }
+        content += &line;
+    }
+    crate::say_reply(ctx, content).await?;
+    Ok(false)
+}
+
+/// The framework's application commands in the JSON format of the Discord API
+pub(crate) fn application_commands_json<'a, U: 'a, E: 'a>(
+    commands: impl IntoIterator<Item = &'a crate::ApplicationCommandTree<U, E>>,
+) -> Vec<serde_json::Value> {
+    let mut commands_builder = serenity::CreateApplicationCommands::default();
+    for cmd in commands {
+        commands_builder.create_application_command(|f| cmd.create(f));
+    }
+    commands_builder.0
+}
+
+/// Replaces the application commands registered in the given guild, or globally if None
+async fn overwrite_application_commands<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    guild_id: Option<serenity::GuildId>,
+    commands: Vec<serde_json::Value>,
+) -> Result<(), serenity::Error> {
+    let json_value = serde_json::Value::Array(commands);
+    match guild_id {
+        Some(guild_id) => {
+            ctx.discord()
+                .http
+                .create_guild_application_commands(guild_id.0, &json_value)
+                .await?;
+        }
+        None => {
+            ctx.discord()
+                .http
+                .create_global_application_commands(&json_value)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// A change to a single registered application command, see [`ApplicationCommandsDiff`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApplicationCommandChange {
+    /// A field of the command itself changed, like `description` or `default_permission`
+    Field {
+        /// Name of the field in the Discord API
+        field: &'static str,
+        /// Currently registered value
+        old: serde_json::Value,
+        /// Value in the framework
+        new: serde_json::Value,
+    },
+    /// A parameter or subcommand was added
+    OptionAdded {
+        /// Space separated names of the option and its parent subcommands
+        path: String,
+    },
+    /// A parameter or subcommand was removed
+    OptionRemoved {
+        /// Space separated names of the option and its parent subcommands
+        path: String,
+    },
+    /// A field of a parameter or subcommand changed, like `required`, `choices` or `min_value`
+    OptionField {
+        /// Space separated names of the option and its parent subcommands
+        path: String,
+        /// Name of the field in the Discord API
+        field: String,
+        /// Currently registered value
+        old: serde_json::Value,
+        /// Value in the framework
+        new: serde_json::Value,
+    },
+    /// The parameters or subcommands of a command or subcommand are in a different order. Only
+    /// options which exist in both versions are listed
+    OptionsReordered {
+        /// Space separated names of the parent subcommands, empty for the command itself
+        path: String,
+        /// Currently registered order
+        old: Vec<String>,
+        /// Order in the framework
+        new: Vec<String>,
+    },
+}
+
+/// The changes to a single registered application command, see [`ApplicationCommandsDiff`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApplicationCommandChanges {
+    /// The command, like `/ping` or `Info (user context menu)`
+    pub command: String,
+    /// What changed
+    pub changes: Vec<ApplicationCommandChange>,
+}
+
+/// Differences between the currently registered application commands and the framework's, as
+/// computed by [`diff_application_commands`]
+///
+/// Commands are identified by name and type. Changes in the order of commands are ignored, changes
+/// in the order of options aren't, since Discord displays options in registration order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ApplicationCommandsDiff {
+    /// Commands which aren't registered yet, like `/ping` or `Info (user context menu)`
+    pub added: Vec<String>,
+    /// Registered commands which the framework doesn't have anymore
+    pub removed: Vec<String>,
+    /// Registered commands which differ from the framework's version
+    pub changed: Vec<ApplicationCommandChanges>,
+}
+
+impl ApplicationCommandsDiff {
+    /// Whether the registered commands already match the framework's
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Formatted like a diff, with one line per change
+impl std::fmt::Display for ApplicationCommandsDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for command in &self.added {
+            writeln!(f, "+ {}", command)?;
+        }
+        for command in &self.removed {
+            writeln!(f, "- {}", command)?;
+        }
+        for command in &self.changed {
+            writeln!(f, "! {}", command.command)?;
+            for change in &command.changes {
+                match change {
+                    ApplicationCommandChange::Field { field, old, new } => {
+                        writeln!(f, "!   {}: {} -> {}", field, old, new)?
+                    }
+                    ApplicationCommandChange::OptionAdded { path } => {
+                        writeln!(f, "+   option {}", path)?
+                    }
+                    ApplicationCommandChange::OptionRemoved { path } => {
+                        writeln!(f, "-   option {}", path)?
+                    }
+                    ApplicationCommandChange::OptionField {
+                        path,
+                        field,
+                        old,
+                        new,
+                    } => writeln!(f, "!   option {} {}: {} -> {}", path, field, old, new)?,
+                    ApplicationCommandChange::OptionsReordered { path, old, new } => {
+                        if !path.is_empty() {
+                            write!(f, "!   option order of {}: ", path)?;
+                        } else {
+                            write!(f, "!   option order: ")?;
+                        }
+                        writeln!(f, "{} -> {}", old.join(", "), new.join(", "))?
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compares the given fields of two JSON objects, treating missing fields as their default value
+fn diff_json_fields(
+    old: &serde_json::Value,
+    new: &serde_json::Value,
+    fields: &[(&'static str, serde_json::Value)],
+) -> Vec<(&'static str, serde_json::Value, serde_json::Value)> {
+    fields
+        .iter()
+        .map(|(field, default)| {
+            let get = |object: &serde_json::Value| {
+                object
+                    .get(field)
+                    .filter(|value| !value.is_null())
+                    .cloned()
+                    .unwrap_or_else(|| default.clone())
+            };
+            (*field, get(old), get(new))
+        })
+        .filter(|(_, old, new)| old != new)
+        .collect()
+}
+
+/// Compares all fields of two JSON objects except the given ones, treating missing and null fields
+/// as their default value in `defaults` and numbers by value, as Discord may return `1` as `1.0`
+fn diff_json_objects(
+    old: &serde_json::Value,
+    new: &serde_json::Value,
+    ignored: &[&str],
+    defaults: &[(&str, serde_json::Value)],
+) -> Vec<(String, serde_json::Value, serde_json::Value)> {
+    let keys = |object: &serde_json::Value| {
+        object
+            .as_object()
+            .map(|object| object.keys().cloned().collect::<Vec<_>>())
+            .unwrap_or_default()
+    };
+    let mut fields = keys(old);
+    fields.extend(keys(new));
+    fields.sort();
+    fields.dedup();
+
+    fields
+        .into_iter()
+        .filter(|field| !ignored.contains(&field.as_str()))
+        .filter_map(|field| {
+            let get = |object: &serde_json::Value| {
+                object
+                    .get(&field)
+                    .filter(|value| !value.is_null())
+                    .cloned()
+                    .or_else(|| {
+                        defaults
+                            .iter()
+                            .find(|(name, _)| *name == field)
+                            .map(|(_, default)| default.clone())
+                    })
+                    .unwrap_or(serde_json::Value::Null)
+            };
+            let (old, new) = (get(old), get(new));
+            let equal = match (old.as_f64(), new.as_f64()) {
+                (Some(a), Some(b)) => a == b,
+                _ => old == new,
+            };
+            if equal {
+                None
+            } else {
+                Some((field, old, new))
+            }
+        })
+        .collect()
+}
+
+fn json_name(value: &serde_json::Value) -> &str {
+    value.get("name").and_then(|x| x.as_str()).unwrap_or("")
+}
+
+fn json_options(value: &serde_json::Value) -> &[serde_json::Value] {
+    value
+        .get("options")
+        .and_then(|x| x.as_array())
+        .map_or(&[], |x| x)
+}
+
+fn diff_application_command_options(
+    changes: &mut Vec<ApplicationCommandChange>,
+    parent_path: &str,
+    old_options: &[serde_json::Value],
+    new_options: &[serde_json::Value],
+) {
+    let path_of = |option: &serde_json::Value| {
+        if parent_path.is_empty() {
+            json_name(option).to_owned()
+        } else {
+            format!("{} {}", parent_path, json_name(option))
+        }
+    };
+
+    for new_option in new_options {
+        let path = path_of(new_option);
+        let old_option = match old_options
+            .iter()
+            .find(|x| json_name(x) == json_name(new_option))
+        {
+            Some(x) => x,
+            None => {
+                changes.push(ApplicationCommandChange::OptionAdded { path });
+                continue;
+            }
+        };
+
+        let defaults = [
+            ("required", false.into()),
+            ("autocomplete", false.into()),
+            ("choices", serde_json::Value::Array(Vec::new())),
+            ("channel_types", serde_json::Value::Array(Vec::new())),
+        ];
+        let fields = diff_json_objects(old_option, new_option, &["name", "options"], &defaults);
+        for (field, old, new) in fields {
+            changes.push(ApplicationCommandChange::OptionField {
+                path: path.clone(),
+                field,
+                old,
+                new,
+            });
+        }
+
+        diff_application_command_options(
+            changes,
+            &path,
+            json_options(old_option),
+            json_options(new_option),
+        );
+    }
+
+    for old_option in old_options {
+        if !new_options
+            .iter()
+            .any(|x| json_name(x) == json_name(old_option))
+        {
+            changes.push(ApplicationCommandChange::OptionRemoved {
+                path: path_of(old_option),
+            });
+        }
+    }
+
+    // Order of the options which weren't added or removed
+    let common_order = |options: &[serde_json::Value], others: &[serde_json::Value]| {
+        options
+            .iter()
+            .map(json_name)
+            .filter(|name| others.iter().any(|x| json_name(x) == *name))
+            .map(String::from)
+            .collect::<Vec<_>>()
+    };
+    let old_order = common_order(old_options, new_options);
+    let new_order = common_order(new_options, old_options);
+    if old_order != new_order {
+        changes.push(ApplicationCommandChange::OptionsReordered {
+            path: parent_path.to_owned(),
+            old: old_order,
+            new: new_order,
+        });
+    }
+}
+
+fn application_command_kind(command: &serde_json::Value) -> u64 {
+    command.get("type").and_then(|x| x.as_u64()).unwrap_or(1)
+}
+
+fn is_same_application_command(a: &serde_json::Value, b: &serde_json::Value) -> bool {
+    json_name(a) == json_name(b) && application_command_kind(a) == application_command_kind(b)
+}
+
+/// How a command is referred to in [`ApplicationCommandsDiff`]
+fn application_command_label(command: &serde_json::Value) -> String {
+    match application_command_kind(command) {
+        2 => format!("{} (user context menu)", json_name(command)),
+        3 => format!("{} (message context menu)", json_name(command)),
+        _ => format!("/{}", json_name(command)),
+    }
+}
+
+/// The changes between two versions of the same application command
+fn diff_application_command(
+    old_command: &serde_json::Value,
+    new_command: &serde_json::Value,
+) -> Vec<ApplicationCommandChange> {
+    let fields = [
+        ("description", "".into()),
+        ("default_permission", true.into()),
+    ];
+    let mut changes = diff_json_fields(old_command, new_command, &fields)
+        .into_iter()
+        .map(|(field, old, new)| ApplicationCommandChange::Field { field, old, new })
+        .collect();
+    diff_application_command_options(
+        &mut changes,
+        "",
+        json_options(old_command),
+        json_options(new_command),
+    );
+    changes
+}
+
+/// Computes which changes registering `new` would make to the `registered` application commands.
+/// Both are in the JSON format of the Discord API.
+///
+/// Used by [`register_application_commands_diff`]; useful for building a custom registration
+/// command.
+pub fn diff_application_commands(
+    registered: &[serde_json::Value],
+    new: &[serde_json::Value],
+) -> ApplicationCommandsDiff {
+    let mut diff = ApplicationCommandsDiff::default();
+    for new_command in new {
+        let old_command = match registered
+            .iter()
+            .find(|x| is_same_application_command(x, new_command))
+        {
+            Some(x) => x,
+            None => {
+                diff.added.push(application_command_label(new_command));
+                continue;
+            }
+        };
+
+        let changes = diff_application_command(old_command, new_command);
+        if !changes.is_empty() {
+            diff.changed.push(ApplicationCommandChanges {
+                command: application_command_label(new_command),
+                changes,
+            });
+        }
+    }
+    for old_command in registered {
+        if !new
+            .iter()
+            .any(|x| is_same_application_command(x, old_command))
+        {
+            diff.removed.push(application_command_label(old_command));
+        }
+    }
+    diff
+}
+
+/// Brings the application commands registered in the given guild, or globally if None, in line with
+/// `commands`, making only the necessary create, edit and delete requests. Returns what was
+/// changed.
+///
+/// Unlike [`register_application_commands`], which overwrites all commands at once, this is cheap
+/// when nothing changed: one request to fetch the registered commands. That makes it suitable
+/// for running on every startup.
+///
+/// ```rust,no_run
+/// # type Error = Box<dyn std::error::Error + Send + Sync>;
+/// poise::Framework::<(), Error>::build().user_data_setup(move |ctx, _ready, framework| {
+///     Box::pin(async move {
+///         let commands = framework.application_commands();
+///         let changes = poise::builtins::sync_commands(&ctx.http, None, commands).await?;
+///         println!("Synced application commands:\n{}", changes);
+///         Ok(())
+///     })
+/// });
+/// ```
+pub async fn sync_commands<'a, U: 'a, E: 'a>(
+    http: &serenity::Http,
+    guild_id: Option<serenity::GuildId>,
+    commands: impl IntoIterator<Item = &'a crate::ApplicationCommandTree<U, E>>,
+) -> Result<ApplicationCommandsDiff, serenity::Error> {
+    let new_commands = application_commands_json(commands);
+    let registered = match guild_id {
+        Some(guild_id) => http.get_guild_application_commands(guild_id.0).await?,
+        None => http.get_global_application_commands().await?,
+    };
+    let registered = registered
+        .into_iter()
+        .map(|command| Ok((command.id, serde_json::to_value(&command)?)))
+        .collect::<Result<Vec<_>, serenity::Error>>()?;
+
+    let (requests, diff) = plan_command_sync(&registered, &new_commands);
+    for request in requests {
+        match (request, guild_id) {
+            (SyncRequest::Create(command), Some(guild_id)) => {
+                http.create_guild_application_command(guild_id.0, command)
+                    .await?;
+            }
+            (SyncRequest::Create(command), None) => {
+                http.create_global_application_command(command).await?;
+            }
+            (SyncRequest::Edit(id, command), Some(guild_id)) => {
+                http.edit_guild_application_command(guild_id.0, id.0, command)
+                    .await?;
+            }
+            (SyncRequest::Edit(id, command), None) => {
+                http.edit_global_application_command(id.0, command).await?;
+            }
+            (SyncRequest::Delete(id), Some(guild_id)) => {
+                http.delete_guild_application_command(guild_id.0, id.0)
+                    .await?;
+            }
+            (SyncRequest::Delete(id), None) => {
+                http.delete_global_application_command(id.0).await?;
+            }
+        }
+    }
+    Ok(diff)
+}
+
+/// A request which [`sync_commands`] makes to Discord
+#[derive(Debug, Clone, PartialEq)]
+enum SyncRequest<'a> {
+    Create(&'a serde_json::Value),
+    Edit(serenity::CommandId, &'a serde_json::Value),
+    Delete(serenity::CommandId),
+}
+
+/// The requests which turn the registered commands into the new commands, and the resulting diff
+fn plan_command_sync<'a>(
+    registered: &'a [(serenity::CommandId, serde_json::Value)],
+    new_commands: &'a [serde_json::Value],
+) -> (Vec<SyncRequest<'a>>, ApplicationCommandsDiff) {
+    let mut requests = Vec::new();
+    let mut diff = ApplicationCommandsDiff::default();
+    for new_command in new_commands {
+        let (old_id, old_command) = match registered
+            .iter()
+            .find(|(_, x)| is_same_application_command(x, new_command))
+        {
+            Some(x) => x,
+            None => {
+                requests.push(SyncRequest::Create(new_command));
+                diff.added.push(application_command_label(new_command));
+                continue;
+            }
+        };
+
+        let changes = diff_application_command(old_command, new_command);
+        if changes.is_empty() {
+            continue;
+        }
+        requests.push(SyncRequest::Edit(*old_id, new_command));
+        diff.changed.push(ApplicationCommandChanges {
+            command: application_command_label(new_command),
+            changes,
+        });
+    }
+
+    for (old_id, old_command) in registered {
+        if new_commands
+            .iter()
+            .any(|x| is_same_application_command(x, old_command))
+        {
+            continue;
+        }
+        requests.push(SyncRequest::Delete(*old_id));
+        diff.removed.push(application_command_label(old_command));
+    }
+
+    (requests, diff)
+}
+
+/// Replaces the content of a reply sent earlier
+async fn edit_reply_content<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    reply: &mut Option<crate::ReplyHandle<'_>>,
+    content: String,
+) -> Result<(), serenity::Error> {
+    if let Some(reply) = reply {
+        reply.edit(ctx, |f| f.content(content)).await?;
+    }
+    Ok(())
+}
+
+/// Registers the given application commands in each of the given guilds, replacing the commands
+/// registered there before. Guild commands update instantly, which makes this handy for testing a
+/// bot on several servers at once. Anyone who can invoke the calling command can use this, so
+/// restrict it to bot owners, for example with `owners_only` like below.
+///
+/// Guilds are processed in batches of five concurrent requests; serenity's HTTP client waits for
+/// rate limits where needed. A progress message is updated after each batch and finally replaced
+/// with a summary that lists the guilds where registration failed.
+///
+/// Returns the result of each guild's registration.
+///
+/// ```rust,no_run
+/// # type Error = Box<dyn std::error::Error + Send + Sync>;
+/// # type Context<'a> = poise::Context<'a, (), Error>;
+/// # use poise::serenity_prelude as serenity;
+/// /// Register application commands in all test servers
+/// #[poise::command(prefix_command, owners_only, hide_in_help)]
+/// pub async fn register_test(ctx: Context<'_>) -> Result<(), Error> {
+///     let test_guilds = [serenity::GuildId(123), serenity::GuildId(456)];
+///     let commands = ctx.framework().application_commands();
+///     poise::builtins::register_in_guilds(ctx, &test_guilds, commands).await?;
+///     Ok(())
+/// }
+/// ```
+pub async fn register_in_guilds<'a, U: 'a, E: 'a>(
+    ctx: crate::Context<'_, U, E>,
+    guild_ids: &[serenity::GuildId],
+    commands: impl IntoIterator<Item = &'a crate::ApplicationCommandTree<U, E>>,
+) -> Result<Vec<(serenity::GuildId, Result<(), serenity::Error>)>, serenity::Error> {
+    /// How many guilds are registered in concurrently
+    const BATCH_SIZE: usize = 5;
+    /// How many failures are listed in the summary, to stay below the message length limit
+    const MAX_LISTED_FAILURES: usize = 10;
+
+    let commands = application_commands_json(commands);
+    let num_commands = commands.len();
+    let commands = serde_json::Value::Array(commands);
+    let progress = |done: usize| {
+        format!(
+            "Registering {} commands in {} guilds... ({}/{})",
+            num_commands,
+            guild_ids.len(),
+            done,
+            guild_ids.len()
+        )
+    };
+    let mut reply = crate::say_reply(ctx, progress(0)).await?;
+
+    let mut results = Vec::with_capacity(guild_ids.len());
+    for batch in guild_ids.chunks(BATCH_SIZE) {
+        let batch_results = futures::future::join_all(batch.iter().map(|&guild_id| {
+            let commands = &commands;
+            async move {
+                let result = ctx
+                    .discord()
+                    .http
+                    .create_guild_application_commands(guild_id.0, commands)
+                    .await
+                    .map(|_| ());
+                (guild_id, result)
+            }
+        }))
+        .await;
+        results.extend(batch_results);
+
+        if results.len() < guild_ids.len() {
+            edit_reply_content(ctx, &mut reply, progress(results.len())).await?;
+        }
+    }
+
+    let failures = results
+        .iter()
+        .filter_map(|(guild_id, result)| Some((guild_id, result.as_ref().err()?)))
+        .collect::<Vec<_>>();
+    let mut summary = format!(
+        "Registered {} commands in {} of {} guilds",
+        num_commands,
+        guild_ids.len() - failures.len(),
+        guild_ids.len()
+    );
+    for (guild_id, error) in failures.iter().take(MAX_LISTED_FAILURES) {
+        summary += &format!("\n- {}: {}", guild_id, error);
+    }
+    if failures.len() > MAX_LISTED_FAILURES {
+        summary += &format!("\n- ...and {} more", failures.len() - MAX_LISTED_FAILURES);
+    }
+    edit_reply_content(ctx, &mut reply, summary).await?;
+
+    Ok(results)
+}
+
+/// Like [`register_application_commands`], but first shows the invoking server owner what would
+/// change and only registers the commands after they confirm with a button. Does nothing if the
+/// registered commands are already up to date, or if nobody confirms within `timeout`.
+///
+/// ```rust,no_run
+/// # type Error = Box<dyn std::error::Error + Send + Sync>;
+/// # type Context<'a> = poise::Context<'a, (), Error>;
+/// /// Register application commands in this guild or globally
+/// #[poise::command(prefix_command, hide_in_help)]
+/// async fn register(ctx: Context<'_>, #[flag] global: bool) -> Result<(), Error> {
+///     let timeout = std::time::Duration::from_secs(60);
+///     poise::builtins::register_application_commands_diff(ctx, global, timeout).await?;
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "collector")]
+pub async fn register_application_commands_diff<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    global: bool,
+    timeout: std::time::Duration,
+) -> Result<(), serenity::Error> {
+    let guild_id = match registration_guild(ctx).await? {
+        Some(x) => x,
+        None => return Ok(()),
+    };
+    let guild_id = if global { None } else { Some(guild_id) };
+    if !check_valid_for_registration(ctx).await? {
+        return Ok(());
+    }
+
+    let registered = match guild_id {
+        Some(guild_id) => {
+            ctx.discord()
+                .http
+                .get_guild_application_commands(guild_id.0)
+                .await?
+        }
+        None => ctx.discord().http.get_global_application_commands().await?,
+    };
+    let registered = registered
+        .iter()
+        .map(serde_json::to_value)
+        .collect::<Result<Vec<_>, _>>()?;
+    let commands = application_commands_json(ctx.framework().application_commands());
+
+    let diff = diff_application_commands(&registered, &commands);
+    if diff.is_empty() {
+        crate::say_reply(ctx, "Application commands are already up to date").await?;
+        return Ok(());
+    }
+
+    let mut diff_text = diff.to_string();
+    if diff_text.len() > 1800 {
+        let mut end = 1800;
+        while !diff_text.is_char_boundary(end) {
+            end -= 1;
+        }
+        diff_text.truncate(end);
+        diff_text += "\n...";
+    }
+    let content = format!(
+        "Registering {} commands {} would make these changes:\n```diff\n{}```",
+        commands.len(),
+        if global { "globally" } else { "in this guild" },
+        diff_text,
+    );
+
+    let apply_id = format!("{}apply", ctx.id());
+    let cancel_id = format!("{}cancel", ctx.id());
+    let reply = crate::send_reply(ctx, |f| {
+        f.content(&content).components(|c| {
+            c.create_action_row(|row| {
+                row.create_button(|b| {
+                    b.custom_id(&apply_id)
+                        .label("Apply")
+                        .style(serenity::ButtonStyle::Success)
+                })
+                .create_button(|b| {
+                    b.custom_id(&cancel_id)
+                        .label("Cancel")
+                        .style(serenity::ButtonStyle::Secondary)
+                })
+            })
+        })
+    })
+    .await?;
+
+    let custom_ids = [apply_id.clone(), cancel_id];
+    let mci = serenity::CollectComponentInteraction::new(ctx.discord())
+        .author_id(ctx.author().id)
+        .timeout(timeout)
+        .filter(move |mci| custom_ids.contains(&mci.data.custom_id))
+        .await;
+
+    let outcome = match &mci {
+        Some(mci) if mci.data.custom_id == apply_id => {
+            overwrite_application_commands(ctx, guild_id, commands).await?;
+            "Done!"
+        }
+        Some(_) => "Cancelled",
+        None => "Timed out, no changes made",
+    };
+    let content = format!("{}\n{}", content, outcome);
+    match &mci {
+        Some(mci) => {
+            mci.create_interaction_response(ctx.discord(), |ir| {
+                ir.kind(serenity::InteractionResponseType::UpdateMessage)
+                    .interaction_response_data(|d| d.content(&content).components(|c| c))
+            })
+            .await?
+        }
+        None => {
+            if let Some(mut reply) = reply {
+                reply
+                    .edit(ctx, |f| f.content(content).components(|c| c))
+                    .await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Sets the prefix of the guild the command was invoked in, using the framework's
+/// [`crate::PrefixFrameworkOptions::prefix_store`].
+///
+/// This function doesn't check permissions. Restrict who can invoke your command, for example with
+/// `required_permissions = "MANAGE_GUILD"`:
+///
+/// ```rust
+/// # type Error = Box<dyn std::error::Error + Send + Sync>;
+/// # type Context<'a> = poise::Context<'a, (), Error>;
+/// /// Change the prefix of this server
+/// #[poise::command(prefix_command, slash_command, required_permissions = "MANAGE_GUILD")]
+/// pub async fn setprefix(
+///     ctx: Context<'_>,
+///     #[description = "The new prefix"] prefix: String,
+/// ) -> Result<(), Error> {
+///     poise::builtins::set_prefix(ctx, prefix).await
+/// }
+/// ```
+pub async fn set_prefix<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    prefix: String,
+) -> Result<(), BoxErrorSendSync> {
+    let (store, guild_id) = match prefix_store_and_guild(ctx).await? {
+        Some(x) => x,
+        None => return Ok(()),
+    };
+    if prefix.trim().is_empty() {
+        crate::say_reply(ctx, "Prefix must not be empty").await?;
+        return Ok(());
+    }
+
+    store.set(guild_id, prefix.clone()).await?;
+    crate::say_reply(ctx, format!("Prefix set to `{}`", prefix)).await?;
+    Ok(())
+}
+
+/// Removes the custom prefix of the guild the command was invoked in, so that the default prefixes
+/// apply again. Like [`set_prefix`], this function doesn't check permissions.
+pub async fn reset_prefix<U, E>(ctx: crate::Context<'_, U, E>) -> Result<(), BoxErrorSendSync> {
+    let (store, guild_id) = match prefix_store_and_guild(ctx).await? {
+        Some(x) => x,
+        None => return Ok(()),
+    };
+
+    store.remove(guild_id).await?;
+    crate::say_reply(ctx, "Prefix reset to the default").await?;
+    Ok(())
+}
+
+/// Shared by the prefix store builtins. Replies with an explanation and returns None if the
+/// command wasn't invoked in a guild or no prefix store is configured
+async fn prefix_store_and_guild<'a, U, E>(
+    ctx: crate::Context<'a, U, E>,
+) -> Result<Option<(&'a dyn crate::PrefixStore, serenity::GuildId)>, serenity::Error> {
+    let guild_id = match ctx.guild_id() {
+        Some(x) => x,
+        None => {
+            crate::say_reply(ctx, "Must be called in guild").await?;
+            return Ok(None);
+        }
+    };
+    match &ctx.framework().options().prefix_options.prefix_store {
+        Some(store) => Ok(Some((&**store, guild_id))),
+        None => {
+            crate::say_reply(ctx, "Custom prefixes are not enabled for this bot").await?;
+            Ok(None)
+        }
+    }
+}
+
+/// Disables a command or a whole category of commands in the guild the command was invoked in, or
+/// only in the current channel if `channel_only` is true. Requires
+/// [`crate::FrameworkOptions::command_filter_store`] to be set.
+///
+/// `name` is looked up among the commands first, by qualified name like `mod ban` for
+/// subcommands, and among the categories second. The command invoking this function can't be
+/// disabled.
+///
+/// Like [`set_prefix`], this function doesn't check permissions:
+///
+/// ```rust
+/// # type Error = Box<dyn std::error::Error + Send + Sync>;
+/// # type Context<'a> = poise::Context<'a, (), Error>;
+/// /// Disable a command or category in this server
+/// #[poise::command(prefix_command, slash_command, required_permissions = "MANAGE_GUILD")]
+/// pub async fn disable(
+///     ctx: Context<'_>,
+///     #[description = "Command or category"] name: String,
+///     #[description = "Only disable in this channel"] channel_only: Option<bool>,
+/// ) -> Result<(), Error> {
+///     poise::builtins::disable_command(ctx, &name, channel_only.unwrap_or(false)).await
+/// }
+/// ```
+pub async fn disable_command<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    name: &str,
+    channel_only: bool,
+) -> Result<(), BoxErrorSendSync> {
+    set_command_disabled(ctx, name, channel_only, true).await
+}
+
+/// Re-enables a command or category disabled with [`disable_command`], in the guild or, if
+/// `channel_only` is true, in the current channel
+pub async fn enable_command<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    name: &str,
+    channel_only: bool,
+) -> Result<(), BoxErrorSendSync> {
+    set_command_disabled(ctx, name, channel_only, false).await
+}
+
+/// Shared implementation of [`disable_command`] and [`enable_command`]
+async fn set_command_disabled<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    name: &str,
+    channel_only: bool,
+    disabled: bool,
+) -> Result<(), BoxErrorSendSync> {
+    let options = ctx.framework().options();
+    let store = match &options.command_filter_store {
+        Some(x) => x,
+        None => {
+            crate::say_reply(ctx, "Disabling commands is not enabled for this bot").await?;
+            return Ok(());
+        }
+    };
+    let scope = match (channel_only, ctx.guild_id()) {
+        (false, Some(guild_id)) => crate::CommandFilterScope::Guild(guild_id),
+        (false, None) => {
+            crate::say_reply(ctx, "Must be called in guild").await?;
+            return Ok(());
+        }
+        (true, _) => crate::CommandFilterScope::Channel(ctx.channel_id()),
+    };
+
+    let name = name.trim();
+    let command = name
+        .parse::<crate::CommandMention>()
+        .ok()
+        .and_then(|x| x.find(ctx.framework()))
+        .map(|command| crate::qualified_command_name(ctx.framework(), command));
+    let category = ctx
+        .framework()
+        .prefix_commands()
+        .into_iter()
+        .filter_map(|x| x.category)
+        .find(|category| category.eq_ignore_ascii_case(name));
+    let target = if let Some(command_name) = command {
+        let own_name = ctx
+            .command()
+            .map(|x| crate::qualified_command_name(ctx.framework(), x));
+        if own_name.as_ref() == Some(&command_name) {
+            crate::say_reply(ctx, "This command can't be disabled").await?;
+            return Ok(());
+        }
+        crate::CommandFilterTarget::Command(command_name)
+    } else if let Some(category) = category {
+        crate::CommandFilterTarget::Category(category.to_owned())
+    } else {
+        crate::say_reply(ctx, format!("No command or category named `{}`", name)).await?;
+        return Ok(());
+    };
+
+    let response = format!(
+        "{} {} {}",
+        match &target {
+            crate::CommandFilterTarget::Command(name) => format!("Command `{}`", name),
+            crate::CommandFilterTarget::Category(name) => format!("Category `{}`", name),
+        },
+        if disabled { "disabled" } else { "enabled" },
+        if channel_only {
+            "in this channel"
+        } else {
+            "in this server"
+        },
+    );
+    store.set_disabled(scope, target, disabled).await?;
+    crate::say_reply(ctx, response).await?;
+    Ok(())
+}
+
+/// Lists the commands and categories disabled with [`disable_command`] in the guild and channel
+/// the command was invoked in. Requires [`crate::FrameworkOptions::command_filter_store`] to be set
+/// and to support [`crate::CommandFilterStore::disabled_entries`].
+pub async fn list_disabled_commands<U, E>(
+    ctx: crate::Context<'_, U, E>,
+) -> Result<(), BoxErrorSendSync> {
+    let store = match &ctx.framework().options().command_filter_store {
+        Some(x) => x,
+        None => {
+            crate::say_reply(ctx, "Disabling commands is not enabled for this bot").await?;
+            return Ok(());
+        }
+    };
+
+    let entries = store
+        .disabled_entries(ctx.guild_id(), ctx.channel_id())
+        .await?;
+    if entries.is_empty() {
+        crate::say_reply(ctx, "No commands are disabled here").await?;
+        return Ok(());
+    }
+
+    let mut response = String::from("Disabled here:\n");
+    for (scope, target) in entries {
+        response += &format!(
+            "- {} {}\n",
+            match target {
+                crate::CommandFilterTarget::Command(name) => format!("Command `{}`", name),
+                crate::CommandFilterTarget::Category(name) => format!("Category `{}`", name),
+            },
+            match scope {
+                crate::CommandFilterScope::Guild(_) => "in this server",
+                crate::CommandFilterScope::Channel(_) => "in this channel",
+            },
+        );
+    }
+    crate::say_reply(ctx, response).await?;
+    Ok(())
+}
+
+/// Shows the most used commands with their error rates, and the most active users, within the last
+/// `window`, according to [`crate::CommandMetrics::window_stats`]. Helps bot owners decide which
+/// commands are worth maintaining.
+///
+/// Users are shown by their tag if cached, but never mentioned.
+///
+/// Example:
+/// ```text
+/// Command usage in the last 1 day
+///
+/// Top commands:
+///   ping          3001   0.0% errors
+///   play           790   1.5% errors
+///
+/// Top users:
+///   someone#1234   420
+/// ```
+pub async fn command_leaderboard<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    window: std::time::Duration,
+) -> Result<(), serenity::Error> {
+    /// How many commands and users are listed
+    const MAX_ENTRIES: usize = 10;
+
+    let stats = ctx.framework().metrics().window_stats(window);
+
+    let mut response = format!(
+        "```\nCommand usage in the last {}\n",
+        humanize_duration(window, "en")
+    );
+    if stats.commands.is_empty() {
+        response += "\nNo commands were used\n";
+    } else {
+        response += "\nTop commands:\n";
+        for (name, counts) in stats.commands.iter().take(MAX_ENTRIES) {
+            let error_rate = counts.errors as f64 / counts.invocations as f64 * 100.0;
+            response += &format!(
+                "  {:<12} {:>5} {:>5.1}% errors\n",
+                name, counts.invocations, error_rate
+            );
+        }
+
+        response += "\nTop users:\n";
+        for (user_id, counts) in stats.users.iter().take(MAX_ENTRIES) {
+            let user = match ctx.discord().cache.user(*user_id) {
+                Some(user) => user.tag(),
+                None => user_id.to_string(),
+            };
+            response += &format!("  {:<20} {:>5}\n", user, counts.invocations);
+        }
+    }
+    response += "```";
+
+    crate::say_reply(ctx, response).await?;
+    Ok(())
+}
+
+/// Sends the text as a reply, split into multiple messages if it exceeds Discord's message length
+/// limit. Shorthand for [`crate::CreateReply::chunk_content`].
+///
+/// ```rust,no_run
+/// # type Error = Box<dyn std::error::Error + Send + Sync>;
+/// # type Context<'a> = poise::Context<'a, (), Error>;
+/// /// Show the bot's log
+/// #[poise::command(prefix_command, owners_only)]
+/// pub async fn log(ctx: Context<'_>) -> Result<(), Error> {
+///     let log = std::fs::read_to_string("bot.log")?;
+///     poise::builtins::say_chunked(ctx, format!("```\n{}\n```", log)).await?;
+///     Ok(())
+/// }
+/// ```
+pub async fn say_chunked<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    text: impl Into<String>,
+) -> Result<Option<crate::ReplyHandle<'_>>, serenity::Error> {
+    crate::send_reply(ctx, |f| f.content(text.into()).chunk_content(true)).await
+}
+
+/// Lists servers of which the bot is a member of, including their member counts, sorted
+/// descendingly by member count.
+///
+/// Non-[public](https://support.discord.com/hc/en-us/articles/360030843331-Enabling-Server-Discovery)
+/// guilds are hidden to preserve privacy. When the command is invoked by the bot
+/// owner as an application command, the response will be made ephemeral and private guilds are
+/// unhidden.
+///
+/// Example:
+/// > I am currently in three servers!
+/// > - **A public server** (7123 members)
+/// > - [3 private servers with 456 members total]
+pub async fn servers<U, E>(ctx: crate::Context<'_, U, E>) -> Result<(), serenity::Error> {
+    let mut show_private_guilds = false;
+    if let crate::Context::Application(_) = ctx {
+        if let Ok(app) = ctx.discord().http.get_current_application_info().await {
+            if app.owner.id == ctx.author().id {
+                show_private_guilds = true;
+            }
+        }
+    }
+
+    struct Guild {
+        name: String,
+        num_members: u64,
+        is_public: bool,
+    }
+
+    let guild_ids = ctx.discord().cache.guilds();
+    let mut guilds = guild_ids
+        .into_iter()
+        .filter_map(|guild_id| {
+            ctx.discord().cache.guild_field(guild_id, |guild| Guild {
+                name: guild.name.clone(),
+                num_members: guild.member_count,
+                is_public: guild.features.iter().any(|x| x == "DISCOVERABLE"),
+            })
+        })
+        .collect::<Vec<_>>();
+    guilds.sort_by_key(|guild| u64::MAX - guild.num_members); // descending sort
+
+    let mut num_private_guilds = 0;
+    let mut num_private_guild_members = 0;
+    let mut response = format!("I am currently in {} servers!\n", guilds.len());
+    for guild in guilds {
+        if guild.is_public || show_private_guilds {
+            response += &format!("- **{}** ({} members)\n", guild.name, guild.num_members);
+        } else {
+            num_private_guilds += 1;
+            num_private_guild_members += guild.num_members;
+        }
+    }
+    if num_private_guilds > 0 {
+        response += &format!(
+            "- [{} private servers with {} members total]\n",
+            num_private_guilds, num_private_guild_members
+        );
     }
 
     if show_private_guilds {
@@ -341,3 +2764,277 @@ pub async fn servers<U, E>(ctx: crate::Context<'_, U, E>) -> Result<(), serenity
 
     Ok(())
 }
+
+/// The resident memory of this process in kilobytes. Only available on Linux
+fn memory_usage_kb() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    line.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// Shows uptime, guild count, shard count and latency, cache size, memory use and the most invoked
+/// commands according to [`crate::Framework::metrics`]. Meant as a quick diagnostic for bot
+/// owners; consider making the command `owners_only`.
+///
+/// Example:
+/// ```text
+/// Uptime:   2 days 3 hours
+/// Guilds:   1234
+/// Shards:   2 (latency of this shard: 42 ms)
+/// Cached:   56789 users, 9876 channels
+/// Memory:   81.3 MB
+///
+/// Commands: 5432 invocations, 12 errors
+///   ping          3001 (0 errors)
+///   help          1204 (0 errors)
+///   play           790 (12 errors)
+/// ```
+pub async fn botstats<U, E>(ctx: crate::Context<'_, U, E>) -> Result<(), serenity::Error> {
+    /// How many of the most invoked commands are listed
+    const MAX_COMMANDS: usize = 10;
+
+    let framework = ctx.framework();
+    let cache = &ctx.discord().cache;
+
+    let shards = framework.shard_statuses().await;
+    let latency = shards
+        .iter()
+        .find(|status| status.shard_id == ctx.discord().shard_id)
+        .and_then(|status| status.latency);
+    let latency = match latency {
+        Some(latency) => format!("{} ms", latency.as_millis()),
+        None => "unknown".to_owned(),
+    };
+    let memory = match memory_usage_kb() {
+        Some(kb) => format!("{:.1} MB", kb as f64 / 1024.0),
+        None => "unknown".to_owned(),
+    };
+
+    let mut response = String::from("```\n");
+    response += &format!(
+        "Uptime:   {}\n",
+        humanize_duration(framework.uptime(), "en")
+    );
+    response += &format!("Guilds:   {}\n", cache.guild_count());
+    response += &format!(
+        "Shards:   {} (latency of this shard: {})\n",
+        shards.len(),
+        latency
+    );
+    response += &format!(
+        "Cached:   {} users, {} channels\n",
+        cache.user_count(),
+        cache.guild_channel_count()
+    );
+    response += &format!("Memory:   {}\n", memory);
+
+    let metrics = framework.metrics();
+    let total = metrics.total();
+    response += &format!(
+        "\nCommands: {} invocations, {} errors\n",
+        total.invocations, total.errors
+    );
+    for (name, counts) in metrics.invocation_counts().into_iter().take(MAX_COMMANDS) {
+        response += &format!(
+            "  {:<12} {:>5} ({} errors)\n",
+            name, counts.invocations, counts.errors
+        );
+    }
+    response += "```";
+
+    crate::say_reply(ctx, response).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+#[test]
+fn test_diff_application_commands() {
+    use serde_json::json;
+
+    let registered = [
+        json!({"id": "1", "name": "ping", "description": "Ping", "version": "1"}),
+        json!({"id": "2", "name": "ban", "description": "Ban", "options": [
+            {"type": 6, "name": "user", "description": "User", "required": true},
+            {"type": 3, "name": "reason", "description": "Reason"},
+        ]}),
+        json!({"id": "3", "name": "old", "description": "Old"}),
+    ];
+    let new = [
+        json!({"name": "ping", "description": "Ping"}),
+        json!({"name": "ban", "description": "Ban someone", "options": [
+            {"type": 6, "name": "user", "description": "User", "required": true},
+            {"type": 4, "name": "days", "description": "Days"},
+        ]}),
+        json!({"name": "Info", "type": 2}),
+    ];
+
+    let diff = diff_application_commands(&registered, &new);
+    assert_eq!(diff.added, ["Info (user context menu)"]);
+    assert_eq!(diff.removed, ["/old"]);
+    assert_eq!(
+        diff.changed,
+        [ApplicationCommandChanges {
+            command: "/ban".into(),
+            changes: vec![
+                ApplicationCommandChange::Field {
+                    field: "description",
+                    old: "Ban".into(),
+                    new: "Ban someone".into(),
+                },
+                ApplicationCommandChange::OptionAdded {
+                    path: "days".into(),
+                },
+                ApplicationCommandChange::OptionRemoved {
+                    path: "reason".into(),
+                },
+            ],
+        }]
+    );
+
+    assert!(diff_application_commands(&new, &new).is_empty());
+
+    let registered = [json!({"name": "roll", "description": "Roll", "options": [
+        {"type": 4, "name": "min", "description": "Min", "min_value": 1.0},
+        {"type": 4, "name": "max", "description": "Max", "max_value": 100},
+    ]})];
+    let new = [json!({"name": "roll", "description": "Roll", "options": [
+        {"type": 4, "name": "max", "description": "Max", "max_value": 1000},
+        {"type": 4, "name": "min", "description": "Min", "min_value": 1},
+    ]})];
+    let diff = diff_application_commands(&registered, &new);
+    assert_eq!(
+        diff.changed[0].changes,
+        [
+            ApplicationCommandChange::OptionField {
+                path: "max".into(),
+                field: "max_value".into(),
+                old: 100.into(),
+                new: 1000.into(),
+            },
+            ApplicationCommandChange::OptionsReordered {
+                path: "".into(),
+                old: vec!["min".into(), "max".into()],
+                new: vec!["max".into(), "min".into()],
+            },
+        ]
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_plan_command_sync() {
+    use serde_json::json;
+
+    let registered = [
+        (
+            serenity::CommandId(1),
+            json!({"id": "1", "name": "ping", "description": "Ping"}),
+        ),
+        (
+            serenity::CommandId(2),
+            json!({"id": "2", "name": "stale", "description": "Stale"}),
+        ),
+        (
+            serenity::CommandId(3),
+            json!({"id": "3", "name": "ban", "description": "Ban"}),
+        ),
+    ];
+    let new_commands = [
+        json!({"name": "ping", "description": "Ping"}),
+        json!({"name": "ban", "description": "Ban a user"}),
+        json!({"name": "new", "description": "New"}),
+    ];
+
+    let (requests, diff) = plan_command_sync(&registered, &new_commands);
+    assert_eq!(
+        requests,
+        [
+            SyncRequest::Edit(serenity::CommandId(3), &new_commands[1]),
+            SyncRequest::Create(&new_commands[2]),
+            SyncRequest::Delete(serenity::CommandId(2)),
+        ]
+    );
+    assert_eq!(diff.added, ["/new"]);
+    assert_eq!(diff.removed, ["/stale"]);
+    assert_eq!(diff.changed.len(), 1);
+    assert_eq!(diff.changed[0].command, "/ban");
+}
+
+#[cfg(test)]
+#[test]
+fn test_fill_template() {
+    assert_eq!(
+        fill_template(
+            "**{error}** in `{command}`\n{usage}",
+            &[
+                ("command", "ban"),
+                ("error", "Invalid user"),
+                ("usage", "~ban <user>")
+            ],
+        ),
+        "**Invalid user** in `ban`\n~ban <user>",
+    );
+    assert_eq!(fill_template("{unknown}", &[("error", "x")]), "{unknown}");
+    assert_eq!(
+        fill_template(
+            "{error} {{command} {usage}",
+            &[("error", "wrong {usage}"), ("usage", "~ban")],
+        ),
+        "wrong {usage} {{command} ~ban",
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_format_duration() {
+    use std::time::Duration;
+
+    assert_eq!(humanize_duration(Duration::from_secs(0), "en"), "0 seconds");
+    assert_eq!(
+        humanize_duration(Duration::from_millis(300), "en"),
+        "1 second"
+    );
+    assert_eq!(
+        humanize_duration(Duration::from_secs(61), "en"),
+        "1 minute 1 second"
+    );
+    assert_eq!(humanize_duration(Duration::from_secs(3605), "en"), "1 hour");
+    assert_eq!(
+        humanize_duration(Duration::from_secs(2 * 86400 + 3 * 3600 + 59), "en-GB"),
+        "2 days 3 hours"
+    );
+    assert_eq!(
+        humanize_duration(Duration::from_secs(120), "fr"),
+        "2 minutes"
+    );
+    assert_eq!(
+        humanize_duration(Duration::from_secs(3600), "de"),
+        "1 Stunde"
+    );
+    assert_eq!(humanize_duration(Duration::from_secs(5), "xx"), "5 seconds");
+}
+
+#[cfg(test)]
+#[test]
+fn test_edit_distance() {
+    assert_eq!(edit_distance("ban", "ban"), 0);
+    assert_eq!(edit_distance("ban", "BAN"), 0);
+    assert_eq!(edit_distance("bna", "ban"), 2);
+    assert_eq!(edit_distance("bn", "ban"), 1);
+    assert_eq!(edit_distance("bann", "ban"), 1);
+    assert_eq!(edit_distance("", "ban"), 3);
+    assert_eq!(edit_distance("kitten", "sitting"), 3);
+}
+
+#[cfg(test)]
+#[test]
+fn test_filter_by_name() {
+    let names = ["ban", "unban", "banner", "kick", "BAN_LIST", "help"];
+    assert_eq!(
+        filter_by_name("ban", names.iter().copied()),
+        ["ban", "banner", "BAN_LIST", "unban"]
+    );
+    assert_eq!(filter_by_name("kik", names.iter().copied()), ["kick"]);
+    assert_eq!(filter_by_name("", names.iter().copied()).len(), names.len());
+    assert!(filter_by_name("xyz", names.iter().copied()).is_empty());
+}
@@ -0,0 +1,291 @@
+//! Ready-made `commands enable|disable|list` command group, see [`command_management`]
+
+use super::BoxErrorSendSync;
+use crate::serenity_prelude as serenity;
+
+/// Only members who can manage the guild may use the command management commands
+const REQUIRED_PERMISSIONS: serenity::Permissions = serenity::Permissions::MANAGE_GUILD;
+
+/// The `commands` command group, which lets guild admins disable and re-enable commands without
+/// any code on your side. Both a prefix and a slash command are generated. Requires
+/// [`crate::FrameworkOptions::command_filter_store`] to be set.
+///
+/// Register it together with its subcommands:
+/// ```rust
+/// # type Error = Box<dyn std::error::Error + Send + Sync>;
+/// poise::Framework::<(), Error>::build().command(
+///     poise::builtins::command_management(),
+///     poise::builtins::command_management_subcommands,
+/// );
+/// ```
+///
+/// - `commands disable <name> [channel]`: see [`super::disable_command`]
+/// - `commands enable <name> [channel]`: see [`super::enable_command`]
+/// - `commands list`: see [`super::list_disabled_commands`]. Also runs when `commands` is invoked
+///   as a prefix command without subcommand
+///
+/// All of them require the Manage Server permission.
+pub fn command_management<U, E>() -> crate::CommandDefinition<U, E>
+where
+    U: Send + Sync,
+    E: From<BoxErrorSendSync>,
+{
+    crate::CommandDefinition {
+        prefix: Some(prefix_command("commands", list_prefix)),
+        slash: Some(slash_command(
+            "commands",
+            "Enable or disable commands in this server",
+            Vec::new(),
+            list_slash,
+        )),
+        context_menu: None,
+    }
+}
+
+/// Adds the `enable`, `disable` and `list` subcommands to [`command_management`]
+pub fn command_management_subcommands<U, E>(
+    builder: &mut crate::CommandBuilder<U, E>,
+) -> &mut crate::CommandBuilder<U, E>
+where
+    U: Send + Sync,
+    E: From<BoxErrorSendSync>,
+{
+    let toggle_parameters = || {
+        vec![
+            slash_parameter("name", |o| {
+                o.kind(serenity::ApplicationCommandOptionType::String)
+                    .name("name")
+                    .description("Command or category")
+                    .required(true)
+            }),
+            slash_parameter("channel_only", |o| {
+                o.kind(serenity::ApplicationCommandOptionType::Boolean)
+                    .name("channel_only")
+                    .description("Only in this channel instead of the whole server")
+                    .required(false)
+            }),
+        ]
+    };
+
+    builder
+        .subcommand(
+            crate::CommandDefinition {
+                prefix: Some(prefix_command("enable", enable_prefix)),
+                slash: Some(slash_command(
+                    "enable",
+                    "Enable a disabled command or category",
+                    toggle_parameters(),
+                    enable_slash,
+                )),
+                context_menu: None,
+            },
+            |f| f,
+        )
+        .subcommand(
+            crate::CommandDefinition {
+                prefix: Some(prefix_command("disable", disable_prefix)),
+                slash: Some(slash_command(
+                    "disable",
+                    "Disable a command or category",
+                    toggle_parameters(),
+                    disable_slash,
+                )),
+                context_menu: None,
+            },
+            |f| f,
+        )
+        .subcommand(
+            crate::CommandDefinition {
+                prefix: Some(prefix_command("list", list_prefix)),
+                slash: Some(slash_command(
+                    "list",
+                    "List disabled commands and categories",
+                    Vec::new(),
+                    list_slash,
+                )),
+                context_menu: None,
+            },
+            |f| f,
+        )
+}
+
+pub(super) type PrefixAction<U, E> =
+    for<'a> fn(crate::PrefixContext<'a, U, E>, &'a str) -> crate::BoxFuture<'a, Result<(), E>>;
+pub(super) type SlashAction<U, E> = for<'a> fn(
+    crate::ApplicationContext<'a, U, E>,
+    &'a [serenity::ApplicationCommandInteractionDataOption],
+) -> crate::BoxFuture<'a, Result<(), E>>;
+
+fn prefix_command<U, E>(
+    name: &'static str,
+    action: PrefixAction<U, E>,
+) -> crate::PrefixCommand<U, E> {
+    crate::PrefixCommand {
+        name,
+        action,
+        options: crate::PrefixCommandOptions {
+            required_permissions: REQUIRED_PERMISSIONS,
+            ..Default::default()
+        },
+    }
+}
+
+fn slash_command<U, E>(
+    name: &'static str,
+    description: &'static str,
+    parameters: Vec<crate::SlashCommandParameter<U, E>>,
+    action: SlashAction<U, E>,
+) -> crate::SlashCommand<U, E> {
+    crate::SlashCommand {
+        name,
+        description,
+        parameters,
+        action,
+        options: crate::ApplicationCommandOptions {
+            required_permissions: REQUIRED_PERMISSIONS,
+            ..Default::default()
+        },
+    }
+}
+
+fn slash_parameter<U, E>(
+    name: &'static str,
+    builder: fn(
+        &mut serenity::CreateApplicationCommandOption,
+    ) -> &mut serenity::CreateApplicationCommandOption,
+) -> crate::SlashCommandParameter<U, E> {
+    crate::SlashCommandParameter {
+        name,
+        builder,
+        autocomplete_callback: None,
+        autocomplete_fallback: None,
+        autocomplete_cache: None,
+        custom_data: None,
+        autocomplete_closure: None,
+    }
+}
+
+fn boxed(e: impl std::error::Error + Send + Sync + 'static) -> BoxErrorSendSync {
+    Box::new(e)
+}
+
+/// Parses `<name> [channel]`
+async fn parse_toggle_prefix_args<U, E>(
+    ctx: crate::PrefixContext<'_, U, E>,
+    args: &str,
+) -> Result<(String, bool), BoxErrorSendSync> {
+    let (name, channel_only) = crate::parse_prefix_args!(
+        ctx.discord, ctx.msg, args => (String), (#[flag] "channel")
+    )
+    .await
+    .map_err(|e| boxed(e.with_parameter_names(&["name", "channel"])))?;
+    Ok((name, channel_only))
+}
+
+/// Extracts the `name` and `channel_only` options
+async fn parse_toggle_slash_args<U, E>(
+    ctx: crate::ApplicationContext<'_, U, E>,
+    args: &[serenity::ApplicationCommandInteractionDataOption],
+) -> Result<(String, bool), BoxErrorSendSync> {
+    let (name, channel_only) = crate::parse_slash_args!(
+        ctx.discord, ctx.interaction.guild_id(), ctx.interaction.channel_id(), args =>
+        (name: String), (channel_only: Option<bool>)
+    )
+    .await
+    .map_err(boxed)?;
+    Ok((name, channel_only.unwrap_or(false)))
+}
+
+fn enable_prefix<'a, U, E>(
+    ctx: crate::PrefixContext<'a, U, E>,
+    args: &'a str,
+) -> crate::BoxFuture<'a, Result<(), E>>
+where
+    U: Send + Sync,
+    E: From<BoxErrorSendSync>,
+{
+    Box::pin(async move {
+        let (name, channel_only) = parse_toggle_prefix_args(ctx, args).await?;
+        super::enable_command(ctx.into(), &name, channel_only)
+            .await
+            .map_err(E::from)
+    })
+}
+
+fn disable_prefix<'a, U, E>(
+    ctx: crate::PrefixContext<'a, U, E>,
+    args: &'a str,
+) -> crate::BoxFuture<'a, Result<(), E>>
+where
+    U: Send + Sync,
+    E: From<BoxErrorSendSync>,
+{
+    Box::pin(async move {
+        let (name, channel_only) = parse_toggle_prefix_args(ctx, args).await?;
+        super::disable_command(ctx.into(), &name, channel_only)
+            .await
+            .map_err(E::from)
+    })
+}
+
+fn list_prefix<'a, U, E>(
+    ctx: crate::PrefixContext<'a, U, E>,
+    _: &'a str,
+) -> crate::BoxFuture<'a, Result<(), E>>
+where
+    U: Send + Sync,
+    E: From<BoxErrorSendSync>,
+{
+    Box::pin(async move {
+        super::list_disabled_commands(ctx.into())
+            .await
+            .map_err(E::from)
+    })
+}
+
+fn enable_slash<'a, U, E>(
+    ctx: crate::ApplicationContext<'a, U, E>,
+    args: &'a [serenity::ApplicationCommandInteractionDataOption],
+) -> crate::BoxFuture<'a, Result<(), E>>
+where
+    U: Send + Sync,
+    E: From<BoxErrorSendSync>,
+{
+    Box::pin(async move {
+        let (name, channel_only) = parse_toggle_slash_args(ctx, args).await?;
+        super::enable_command(ctx.into(), &name, channel_only)
+            .await
+            .map_err(E::from)
+    })
+}
+
+fn disable_slash<'a, U, E>(
+    ctx: crate::ApplicationContext<'a, U, E>,
+    args: &'a [serenity::ApplicationCommandInteractionDataOption],
+) -> crate::BoxFuture<'a, Result<(), E>>
+where
+    U: Send + Sync,
+    E: From<BoxErrorSendSync>,
+{
+    Box::pin(async move {
+        let (name, channel_only) = parse_toggle_slash_args(ctx, args).await?;
+        super::disable_command(ctx.into(), &name, channel_only)
+            .await
+            .map_err(E::from)
+    })
+}
+
+fn list_slash<'a, U, E>(
+    ctx: crate::ApplicationContext<'a, U, E>,
+    _: &'a [serenity::ApplicationCommandInteractionDataOption],
+) -> crate::BoxFuture<'a, Result<(), E>>
+where
+    U: Send + Sync,
+    E: From<BoxErrorSendSync>,
+{
+    Box::pin(async move {
+        super::list_disabled_commands(ctx.into())
+            .await
+            .map_err(E::from)
+    })
+}
@@ -0,0 +1,260 @@
+//! Forwarding errors to a Discord channel or webhook, see [`ErrorReporter`]
+
+use super::BoxErrorSendSync;
+use crate::serenity_prelude as serenity;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Where an [`ErrorReporter`] sends its reports
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ErrorReportDestination {
+    /// Send reports as messages into this channel. The bot needs permission to send embeds there
+    Channel(serenity::ChannelId),
+    /// Execute this webhook with the reports
+    Webhook {
+        /// ID of the webhook
+        id: serenity::WebhookId,
+        /// Token of the webhook, i.e. the last part of the webhook URL
+        token: String,
+    },
+}
+
+#[derive(Debug, Default)]
+struct ReportState {
+    last_report: Option<Instant>,
+    suppressed_reports: usize,
+}
+
+/// Sends error reports to a Discord channel or webhook, at most one per `min_interval`. Reports
+/// in between are dropped and their number is mentioned in the next report; this keeps an error
+/// that repeats in a loop from flooding the destination.
+///
+/// The framework's `on_error` callback can't capture variables, so store the reporter somewhere
+/// reachable, for example in a static:
+/// ```rust,no_run
+/// # type Error = Box<dyn std::error::Error + Send + Sync>;
+/// use poise::serenity_prelude as serenity;
+///
+/// static ERROR_REPORTER: once_cell::sync::OnceCell<poise::builtins::ErrorReporter> =
+///     once_cell::sync::OnceCell::new();
+///
+/// # let http = std::sync::Arc::new(serenity::Http::new_with_token(""));
+/// ERROR_REPORTER.get_or_init(|| {
+///     poise::builtins::ErrorReporter::new(
+///         http,
+///         poise::builtins::ErrorReportDestination::Channel(serenity::ChannelId(123)),
+///         std::time::Duration::from_secs(10),
+///     )
+/// });
+///
+/// let options = poise::FrameworkOptions::<(), Error> {
+///     on_error: |error, ctx| {
+///         Box::pin(async move {
+///             let reporter = ERROR_REPORTER.get().expect("error reporter not initialized");
+///             poise::builtins::on_error_with_report(error, ctx, reporter).await;
+///         })
+///     },
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug)]
+pub struct ErrorReporter {
+    http: Arc<serenity::Http>,
+    destination: ErrorReportDestination,
+    min_interval: Duration,
+    state: parking_lot::Mutex<ReportState>,
+}
+
+impl ErrorReporter {
+    /// Create a reporter which sends at most one report per `min_interval` to `destination`
+    pub fn new(
+        http: Arc<serenity::Http>,
+        destination: ErrorReportDestination,
+        min_interval: Duration,
+    ) -> Self {
+        Self {
+            http,
+            destination,
+            min_interval,
+            state: Default::default(),
+        }
+    }
+
+    /// Where this reporter sends its reports
+    pub fn destination(&self) -> &ErrorReportDestination {
+        &self.destination
+    }
+
+    /// Reports the error with an embed built by [`error_report_embed`]
+    pub async fn report_error<U, E>(
+        &self,
+        error: &E,
+        ctx: &crate::ErrorContext<'_, U, E>,
+    ) -> Result<(), serenity::Error>
+    where
+        E: std::fmt::Display + std::fmt::Debug,
+    {
+        self.report_embed(error_report_embed(error, ctx)).await
+    }
+
+    /// Sends the embed to the destination, unless the last report was less than `min_interval`
+    /// ago
+    pub async fn report_embed(
+        &self,
+        mut embed: serenity::CreateEmbed,
+    ) -> Result<(), serenity::Error> {
+        let suppressed_reports = {
+            let mut state = self.state.lock();
+            let now = Instant::now();
+            if let Some(last_report) = state.last_report {
+                if now.saturating_duration_since(last_report) < self.min_interval {
+                    state.suppressed_reports += 1;
+                    return Ok(());
+                }
+            }
+            state.last_report = Some(now);
+            std::mem::take(&mut state.suppressed_reports)
+        };
+
+        if suppressed_reports > 0 {
+            embed.footer(|f| {
+                f.text(format!(
+                    "{} more errors occured since the last report",
+                    suppressed_reports
+                ))
+            });
+        }
+
+        match &self.destination {
+            ErrorReportDestination::Channel(channel_id) => {
+                channel_id
+                    .send_message(&*self.http, |m| m.set_embed(embed))
+                    .await?;
+            }
+            ErrorReportDestination::Webhook { id, token } => {
+                let embed = crate::serenity::utils::hashmap_to_json_map(embed.0);
+                let map = serde_json::json!({ "embeds": [embed] });
+                if let serde_json::Value::Object(map) = map {
+                    self.http.execute_webhook(id.0, token, false, &map).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Cuts the text down to at most `max_len` bytes, without splitting characters
+fn truncate(mut text: String, max_len: usize) -> String {
+    if text.len() > max_len {
+        let mut end = max_len - 3;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+        text += "...";
+    }
+    text
+}
+
+/// Describes the error and where it occured in an embed: the command and its invocation, the
+/// invoking user, guild and channel, and the `Debug` representation of the error, which includes
+/// a backtrace for some error types like `anyhow::Error`.
+pub fn error_report_embed<U, E>(
+    error: &E,
+    ctx: &crate::ErrorContext<'_, U, E>,
+) -> serenity::CreateEmbed
+where
+    E: std::fmt::Display + std::fmt::Debug,
+{
+    let mut embed = serenity::CreateEmbed::default();
+    embed
+        .description(truncate(error.to_string(), 4000))
+        .colour(0xED4245)
+        .timestamp(&chrono::Utc::now());
+
+    let invocation_ctx = match ctx {
+        crate::ErrorContext::Setup => {
+            embed.title("Error in user data setup");
+            None
+        }
+        crate::ErrorContext::Listener(event) => {
+            embed.title(truncate(
+                format!("Error in event listener: {}", event.name()),
+                256,
+            ));
+            None
+        }
+        crate::ErrorContext::Command(ctx) => {
+            let ctx = ctx.ctx();
+            let title = match ctx.command() {
+                Some(command) => format!(
+                    "Error in command `{}`",
+                    crate::qualified_command_name(ctx.framework(), command)
+                ),
+                None => "Error in command".to_owned(),
+            };
+            embed.title(truncate(title, 256));
+            Some(ctx)
+        }
+        crate::ErrorContext::Autocomplete(ctx) => {
+            let ctx = crate::Context::Application(ctx.ctx);
+            let name = match ctx.command() {
+                Some(command) => crate::qualified_command_name(ctx.framework(), command),
+                None => String::new(),
+            };
+            embed.title(truncate(
+                format!("Error in autocomplete of `{}`", name),
+                256,
+            ));
+            Some(ctx)
+        }
+        crate::ErrorContext::BackgroundTask(name) => {
+            embed.title(truncate(
+                format!("Error in background task `{}`", name),
+                256,
+            ));
+            None
+        }
+    };
+
+    if let Some(ctx) = invocation_ctx {
+        let author = ctx.author();
+        embed.field("User", format!("{} ({})", author.tag(), author.id), true);
+        match ctx.guild_id() {
+            Some(guild_id) => embed.field("Guild", guild_id, true),
+            None => embed.field("Guild", "DM", true),
+        };
+        embed.field("Channel", format!("<#{0}> ({0})", ctx.channel_id()), true);
+        if let crate::Context::Prefix(ctx) = ctx {
+            embed.field(
+                "Invocation",
+                format!("```\n{}\n```", truncate(ctx.msg.content.clone(), 1000)),
+                false,
+            );
+        }
+    }
+
+    embed.field(
+        "Details",
+        format!("```\n{}\n```", truncate(format!("{:?}", error), 1000)),
+        false,
+    );
+    embed
+}
+
+/// Reports the error with [`ErrorReporter::report_error`], then handles it like
+/// [`super::on_error`].
+///
+/// Argument parse errors are the invoking user's fault and not reported.
+pub async fn on_error_with_report<D>(
+    error: BoxErrorSendSync,
+    ctx: crate::ErrorContext<'_, D, BoxErrorSendSync>,
+    reporter: &ErrorReporter,
+) {
+    if !error.is::<crate::ArgumentParseError>() {
+        if let Err(e) = reporter.report_error(&error, &ctx).await {
+            println!("Failed to report error: {}", e);
+        }
+    }
+    super::on_error(error, ctx).await;
+}
@@ -0,0 +1,243 @@
+//! Owner-only maintenance commands, see [`owner_commands`]
+
+use super::command_management::{PrefixAction, SlashAction};
+use super::BoxErrorSendSync;
+use crate::serenity_prelude as serenity;
+
+/// Replies and returns false if the invoking user isn't in [`crate::FrameworkOptions::owners`]
+async fn check_owner<U, E>(ctx: crate::Context<'_, U, E>) -> Result<bool, serenity::Error> {
+    if ctx.framework().options().owners.contains(&ctx.author().id) {
+        return Ok(true);
+    }
+    crate::say_reply(ctx, "Can only be used by bot owners").await?;
+    Ok(false)
+}
+
+/// Disconnects all shards gracefully, see [`crate::Framework::shutdown`]. Only bot owners can
+/// invoke this.
+pub async fn shutdown<U, E>(ctx: crate::Context<'_, U, E>) -> Result<(), serenity::Error> {
+    if !check_owner(ctx).await? {
+        return Ok(());
+    }
+    crate::say_reply(ctx, "Shutting down...").await?;
+    ctx.framework().shutdown().await;
+    Ok(())
+}
+
+/// Lists the connection status and heartbeat latency of each shard, see
+/// [`crate::Framework::shard_statuses`]. Only bot owners can invoke this.
+pub async fn shard_status<U, E>(ctx: crate::Context<'_, U, E>) -> Result<(), serenity::Error> {
+    if !check_owner(ctx).await? {
+        return Ok(());
+    }
+
+    let mut response = String::from("```\n");
+    for status in ctx.framework().shard_statuses().await {
+        let latency = match status.latency {
+            Some(latency) => format!("{} ms", latency.as_millis()),
+            None => "unknown".to_owned(),
+        };
+        let current = if status.shard_id == ctx.discord().shard_id {
+            " (this shard)"
+        } else {
+            ""
+        };
+        response += &format!(
+            "Shard {}: {:?}, latency {}{}\n",
+            status.shard_id, status.stage, latency, current
+        );
+    }
+    response += "```";
+    crate::say_reply(ctx, response).await?;
+    Ok(())
+}
+
+/// Shows how many guilds, channels and users are in the cache. Only bot owners can invoke this.
+pub async fn cache_stats<U, E>(ctx: crate::Context<'_, U, E>) -> Result<(), serenity::Error> {
+    if !check_owner(ctx).await? {
+        return Ok(());
+    }
+
+    let cache = &ctx.discord().cache;
+    let response = format!(
+        "Cached: {} guilds, {} channels, {} users",
+        cache.guild_count(),
+        cache.guild_channel_count(),
+        cache.user_count(),
+    );
+    crate::say_reply(ctx, response).await?;
+    Ok(())
+}
+
+/// Brings the globally registered application commands in line with the framework's using
+/// [`super::sync_commands`] and reports what changed. Only bot owners can invoke this.
+pub async fn reregister_commands<U, E>(
+    ctx: crate::Context<'_, U, E>,
+) -> Result<(), serenity::Error> {
+    if !check_owner(ctx).await? {
+        return Ok(());
+    }
+
+    let commands = ctx.framework().application_commands();
+    let changes = super::sync_commands(&ctx.discord().http, None, commands).await?;
+    if changes.is_empty() {
+        crate::say_reply(ctx, "Application commands are already up to date").await?;
+    } else {
+        crate::say_reply(
+            ctx,
+            format!("Synced application commands:\n```diff\n{}```", changes),
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// The `owner` command group, with `shutdown`, `shards`, `cache` and `register` subcommands
+/// wrapping [`shutdown`], [`shard_status`], [`cache_stats`] and [`reregister_commands`]. Both a
+/// prefix and a slash command are generated. All of them are `owners_only` and hidden in help.
+///
+/// Register it together with its subcommands:
+/// ```rust
+/// # type Error = Box<dyn std::error::Error + Send + Sync>;
+/// poise::Framework::<(), Error>::build().command(
+///     poise::builtins::owner_commands(),
+///     poise::builtins::owner_subcommands,
+/// );
+/// ```
+pub fn owner_commands<U, E>() -> crate::CommandDefinition<U, E>
+where
+    U: Send + Sync,
+    E: From<BoxErrorSendSync>,
+{
+    crate::CommandDefinition {
+        prefix: Some(prefix_command("owner", shard_status_prefix)),
+        slash: Some(slash_command(
+            "owner",
+            "Bot maintenance commands",
+            shard_status_slash,
+        )),
+        context_menu: None,
+    }
+}
+
+/// Adds the subcommands to [`owner_commands`]
+pub fn owner_subcommands<U, E>(
+    builder: &mut crate::CommandBuilder<U, E>,
+) -> &mut crate::CommandBuilder<U, E>
+where
+    U: Send + Sync,
+    E: From<BoxErrorSendSync>,
+{
+    let subcommands: [(_, _, PrefixAction<U, E>, SlashAction<U, E>); 4] = [
+        (
+            "shutdown",
+            "Disconnect the bot",
+            shutdown_prefix,
+            shutdown_slash,
+        ),
+        (
+            "shards",
+            "Show shard status and latency",
+            shard_status_prefix,
+            shard_status_slash,
+        ),
+        (
+            "cache",
+            "Show cache statistics",
+            cache_stats_prefix,
+            cache_stats_slash,
+        ),
+        (
+            "register",
+            "Sync application commands globally",
+            reregister_commands_prefix,
+            reregister_commands_slash,
+        ),
+    ];
+    for &(name, description, prefix_action, slash_action) in &subcommands {
+        builder.subcommand(
+            crate::CommandDefinition {
+                prefix: Some(prefix_command(name, prefix_action)),
+                slash: Some(slash_command(name, description, slash_action)),
+                context_menu: None,
+            },
+            |f| f,
+        );
+    }
+    builder
+}
+
+fn prefix_command<U, E>(
+    name: &'static str,
+    action: PrefixAction<U, E>,
+) -> crate::PrefixCommand<U, E> {
+    crate::PrefixCommand {
+        name,
+        action,
+        options: crate::PrefixCommandOptions {
+            owners_only: true,
+            hide_in_help: true,
+            ..Default::default()
+        },
+    }
+}
+
+fn slash_command<U, E>(
+    name: &'static str,
+    description: &'static str,
+    action: SlashAction<U, E>,
+) -> crate::SlashCommand<U, E> {
+    crate::SlashCommand {
+        name,
+        description,
+        parameters: Vec::new(),
+        action,
+        options: crate::ApplicationCommandOptions {
+            owners_only: true,
+            ephemeral: true,
+            ..Default::default()
+        },
+    }
+}
+
+/// Generates prefix and slash command actions which call the given builtin
+macro_rules! owner_command_actions {
+    ($( $builtin:ident => $prefix_action:ident, $slash_action:ident; )*) => { $(
+        fn $prefix_action<'a, U, E>(
+            ctx: crate::PrefixContext<'a, U, E>,
+            _: &'a str,
+        ) -> crate::BoxFuture<'a, Result<(), E>>
+        where
+            U: Send + Sync,
+            E: From<BoxErrorSendSync>,
+        {
+            Box::pin(async move {
+                $builtin(ctx.into())
+                    .await
+                    .map_err(|e| E::from(Box::new(e) as BoxErrorSendSync))
+            })
+        }
+
+        fn $slash_action<'a, U, E>(
+            ctx: crate::ApplicationContext<'a, U, E>,
+            _: &'a [serenity::ApplicationCommandInteractionDataOption],
+        ) -> crate::BoxFuture<'a, Result<(), E>>
+        where
+            U: Send + Sync,
+            E: From<BoxErrorSendSync>,
+        {
+            Box::pin(async move {
+                $builtin(ctx.into())
+                    .await
+                    .map_err(|e| E::from(Box::new(e) as BoxErrorSendSync))
+            })
+        }
+    )* };
+}
+
+owner_command_actions! {
+    shutdown => shutdown_prefix, shutdown_slash;
+    shard_status => shard_status_prefix, shard_status_slash;
+    cache_stats => cache_stats_prefix, cache_stats_slash;
+    reregister_commands => reregister_commands_prefix, reregister_commands_slash;
+}
@@ -0,0 +1,303 @@
+//! Multi-step interactive dialogs, see [`wizard`]
+
+use crate::serenity_prelude as serenity;
+
+/// A single step of a [`wizard`], operating on the wizard state `S`
+pub trait WizardStep<S>: Send + Sync {
+    /// The message content while this step is shown
+    fn content(&self, state: &S) -> String;
+
+    /// Adds this step's components, like buttons or select menus, to the message. Their custom IDs
+    /// must start with `custom_id_prefix`, otherwise interactions with them are ignored.
+    ///
+    /// Messages can have at most five action rows, and the wizard uses the last one for its
+    /// navigation buttons, so at most four are available here.
+    ///
+    /// The default implementation adds no components.
+    fn create_components(
+        &self,
+        state: &S,
+        custom_id_prefix: &str,
+        components: &mut serenity::CreateComponents,
+    ) {
+        let _ = (state, custom_id_prefix, components);
+    }
+
+    /// Called when the user interacts with one of the components added in
+    /// [`Self::create_components`]. The message is re-rendered afterwards.
+    ///
+    /// The default implementation does nothing.
+    fn handle_interaction(
+        &self,
+        state: &mut S,
+        interaction: &serenity::MessageComponentInteraction,
+    ) {
+        let _ = (state, interaction);
+    }
+
+    /// Whether the user may continue to the next step. While this returns false, the next button
+    /// is disabled.
+    ///
+    /// The default implementation always returns true.
+    fn is_complete(&self, state: &S) -> bool {
+        let _ = state;
+        true
+    }
+}
+
+/// Behavior of [`wizard`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WizardOptions {
+    /// Label of the button to the previous step
+    pub back_label: String,
+    /// Label of the button to the next step
+    pub next_label: String,
+    /// Label of the button to the next step, on the last step
+    pub finish_label: String,
+    /// Label of the button which aborts the wizard
+    pub cancel_label: String,
+    /// Message content after the user cancelled the wizard
+    pub cancelled_message: String,
+    /// Message content after the wizard timed out
+    pub timed_out_message: String,
+    /// How long to wait for each interaction before the wizard times out
+    pub timeout: std::time::Duration,
+    /// Whether to send the wizard as an ephemeral message (if possible)
+    pub ephemeral: bool,
+}
+
+impl Default for WizardOptions {
+    fn default() -> Self {
+        Self {
+            back_label: "Back".into(),
+            next_label: "Next".into(),
+            finish_label: "Finish".into(),
+            cancel_label: "Cancel".into(),
+            cancelled_message: "Cancelled".into(),
+            timed_out_message: "Timed out".into(),
+            timeout: std::time::Duration::from_secs(120),
+            ephemeral: false,
+        }
+    }
+}
+
+/// Walks the invoking user through the given steps in a single message, with back, next and cancel
+/// buttons below each step's own components. The state is passed from step to step, starting with
+/// `initial_state`.
+///
+/// Only the invoking user can interact with the wizard. Returns the final state when the user
+/// finishes the last step, or None if they cancelled or didn't interact within
+/// [`WizardOptions::timeout`]. Either way, all components are removed afterwards.
+///
+/// ```rust,no_run
+/// # type Error = Box<dyn std::error::Error + Send + Sync>;
+/// # type Context<'a> = poise::Context<'a, (), Error>;
+/// use poise::builtins::WizardStep;
+/// use poise::serenity_prelude as serenity;
+///
+/// #[derive(Default)]
+/// struct Setup {
+///     language: Option<String>,
+/// }
+///
+/// struct LanguageStep;
+/// impl WizardStep<Setup> for LanguageStep {
+///     fn content(&self, state: &Setup) -> String {
+///         format!("Pick a language (currently {:?})", state.language)
+///     }
+///
+///     fn create_components(
+///         &self,
+///         _: &Setup,
+///         custom_id_prefix: &str,
+///         components: &mut serenity::CreateComponents,
+///     ) {
+///         components.create_action_row(|row| {
+///             row.create_select_menu(|menu| {
+///                 menu.custom_id(format!("{}language", custom_id_prefix))
+///                     .options(|o| {
+///                         o.create_option(|o| o.label("English").value("en"))
+///                             .create_option(|o| o.label("Deutsch").value("de"))
+///                     })
+///             })
+///         });
+///     }
+///
+///     fn handle_interaction(
+///         &self,
+///         state: &mut Setup,
+///         mci: &serenity::MessageComponentInteraction,
+///     ) {
+///         state.language = mci.data.values.first().cloned();
+///     }
+///
+///     fn is_complete(&self, state: &Setup) -> bool {
+///         state.language.is_some()
+///     }
+/// }
+///
+/// struct SummaryStep;
+/// impl WizardStep<Setup> for SummaryStep {
+///     fn content(&self, state: &Setup) -> String {
+///         let language = state.language.as_deref().unwrap_or("");
+///         format!("Language: {}. Save these settings?", language)
+///     }
+/// }
+///
+/// /// Configure the bot for this server
+/// #[poise::command(slash_command)]
+/// pub async fn setup(ctx: Context<'_>) -> Result<(), Error> {
+///     let steps: Vec<Box<dyn WizardStep<Setup>>> =
+///         vec![Box::new(LanguageStep), Box::new(SummaryStep)];
+///     let options = poise::builtins::WizardOptions {
+///         ephemeral: true,
+///         ..Default::default()
+///     };
+///     let setup = poise::builtins::wizard(ctx, &steps, Setup::default(), options).await?;
+///     if let Some(setup) = setup {
+///         // ... save settings
+///     }
+///     Ok(())
+/// }
+/// ```
+pub async fn wizard<U, E, S>(
+    ctx: crate::Context<'_, U, E>,
+    steps: &[Box<dyn WizardStep<S>>],
+    initial_state: S,
+    options: WizardOptions,
+) -> Result<Option<S>, serenity::Error>
+where
+    S: Send + Sync,
+{
+    enum Outcome {
+        Continue,
+        Finished,
+        Cancelled,
+    }
+
+    if steps.is_empty() {
+        return Ok(Some(initial_state));
+    }
+
+    let id_prefix = format!("{}wizard", ctx.id());
+    let step_prefix = format!("{}step", id_prefix);
+    let back_id = format!("{}back", id_prefix);
+    let next_id = format!("{}next", id_prefix);
+    let cancel_id = format!("{}cancel", id_prefix);
+    let create_components =
+        |components: &mut serenity::CreateComponents, state: &S, step_index: usize| {
+            let step = &steps[step_index];
+            step.create_components(state, &step_prefix, components);
+            let next_label = if step_index + 1 == steps.len() {
+                &options.finish_label
+            } else {
+                &options.next_label
+            };
+            components.create_action_row(|row| {
+                row.create_button(|b| {
+                    b.custom_id(&back_id)
+                        .label(&options.back_label)
+                        .style(serenity::ButtonStyle::Secondary)
+                        .disabled(step_index == 0)
+                })
+                .create_button(|b| {
+                    b.custom_id(&next_id)
+                        .label(next_label)
+                        .style(serenity::ButtonStyle::Primary)
+                        .disabled(!step.is_complete(state))
+                })
+                .create_button(|b| {
+                    b.custom_id(&cancel_id)
+                        .label(&options.cancel_label)
+                        .style(serenity::ButtonStyle::Danger)
+                })
+            });
+        };
+
+    let mut state = initial_state;
+    let mut step_index = 0;
+    let reply = crate::send_reply(ctx, |f| {
+        f.content(steps[step_index].content(&state))
+            .components(|c| {
+                create_components(c, &state, step_index);
+                c
+            })
+            .ephemeral(options.ephemeral)
+    })
+    .await?;
+
+    loop {
+        let filter_prefix = id_prefix.clone();
+        let mci = serenity::CollectComponentInteraction::new(ctx.discord())
+            .author_id(ctx.author().id)
+            .timeout(options.timeout)
+            .filter(move |mci| mci.data.custom_id.starts_with(&filter_prefix))
+            .await;
+        let mci = match mci {
+            Some(x) => x,
+            None => {
+                // Remove the components after the timeout
+                let content = &options.timed_out_message;
+                if let Some(mut reply) = reply {
+                    reply
+                        .edit(ctx, |f| f.content(content).components(|c| c))
+                        .await?;
+                }
+                return Ok(None);
+            }
+        };
+
+        let is_last_step = step_index + 1 == steps.len();
+        let outcome = if mci.data.custom_id == cancel_id {
+            Outcome::Cancelled
+        } else if mci.data.custom_id == next_id && steps[step_index].is_complete(&state) {
+            if is_last_step {
+                Outcome::Finished
+            } else {
+                step_index += 1;
+                Outcome::Continue
+            }
+        } else if mci.data.custom_id == back_id {
+            step_index = step_index.saturating_sub(1);
+            Outcome::Continue
+        } else {
+            if mci.data.custom_id.starts_with(&step_prefix) {
+                steps[step_index].handle_interaction(&mut state, &mci);
+            }
+            Outcome::Continue
+        };
+
+        // Updating through the interaction response also works for ephemeral messages
+        let final_content = match outcome {
+            Outcome::Continue => None,
+            Outcome::Finished => Some(steps[step_index].content(&state)),
+            Outcome::Cancelled => Some(options.cancelled_message.clone()),
+        };
+        match final_content {
+            Some(content) => {
+                mci.create_interaction_response(ctx.discord(), |ir| {
+                    ir.kind(serenity::InteractionResponseType::UpdateMessage)
+                        .interaction_response_data(|d| d.content(content).components(|c| c))
+                })
+                .await?;
+                return Ok(match outcome {
+                    Outcome::Finished => Some(state),
+                    _ => None,
+                });
+            }
+            None => {
+                mci.create_interaction_response(ctx.discord(), |ir| {
+                    ir.kind(serenity::InteractionResponseType::UpdateMessage)
+                        .interaction_response_data(|d| {
+                            d.content(steps[step_index].content(&state))
+                                .components(|c| {
+                                    create_components(c, &state, step_index);
+                                    c
+                                })
+                        })
+                })
+                .await?;
+            }
+        }
+    }
+}
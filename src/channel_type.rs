@@ -0,0 +1,141 @@
+//! Runtime support for the `allowed_channel_types(...)` and `nsfw_only` command attributes
+
+use crate::serenity_prelude as serenity;
+
+/// Returned when a command is invoked in a channel whose type isn't in the command's
+/// `allowed_channel_types(...)` attribute.
+///
+/// Passed to [`crate::FrameworkOptions::on_channel_type_not_allowed`], which is called before the
+/// command runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelTypeNotAllowed {
+    /// Type of the channel the command was invoked in, or None if the channel couldn't be fetched
+    pub channel_type: Option<serenity::ChannelType>,
+    /// The channel types the command may be invoked in
+    pub allowed: &'static [serenity::ChannelType],
+}
+
+impl std::fmt::Display for ChannelTypeNotAllowed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.channel_type {
+            Some(channel_type) => write!(
+                f,
+                "This command can't be used in {} channels",
+                channel_type.name()
+            ),
+            None => f.write_str("This command can't be used in this channel"),
+        }
+    }
+}
+
+impl std::error::Error for ChannelTypeNotAllowed {}
+
+/// The channel types the command may be used in, as configured in its options
+fn allowed_channel_types<U, E>(
+    command: crate::CommandRef<'_, U, E>,
+) -> Option<&'static [serenity::ChannelType]> {
+    match command {
+        crate::CommandRef::Prefix(command) => command.options.allowed_channel_types,
+        crate::CommandRef::Application(command) => command.options().allowed_channel_types,
+    }
+}
+
+/// Checks that the command's `allowed_channel_types` include the type of the channel it was
+/// invoked in.
+///
+/// The channel is looked up in the cache if possible and via HTTP otherwise.
+async fn channel_type_allowed<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    allowed: &'static [serenity::ChannelType],
+) -> Result<(), ChannelTypeNotAllowed> {
+    let channel_type = match ctx.channel_id().to_channel(ctx.discord()).await {
+        Ok(serenity::Channel::Guild(channel)) => Some(channel.kind),
+        Ok(serenity::Channel::Private(_)) => Some(serenity::ChannelType::Private),
+        Ok(serenity::Channel::Category(_)) => Some(serenity::ChannelType::Category),
+        _ => None,
+    };
+    match channel_type {
+        Some(channel_type) if allowed.contains(&channel_type) => Ok(()),
+        _ => Err(ChannelTypeNotAllowed {
+            channel_type,
+            allowed,
+        }),
+    }
+}
+
+/// Checks the command's [allowed channel types](crate::PrefixCommandOptions::allowed_channel_types)
+/// and calls [`crate::FrameworkOptions::on_channel_type_not_allowed`] if the channel isn't one of
+/// them. Returns whether the command may run.
+pub(crate) async fn check_channel_type<'a, U, E>(
+    ctx: crate::Context<'a, U, E>,
+    command: crate::CommandRef<'a, U, E>,
+) -> bool {
+    let allowed = match allowed_channel_types(command) {
+        Some(x) => x,
+        None => return true,
+    };
+    match channel_type_allowed(ctx, allowed).await {
+        Ok(()) => true,
+        Err(error) => {
+            (ctx.framework().options().on_channel_type_not_allowed)(ctx, error).await;
+            false
+        }
+    }
+}
+
+/// Passed to [`crate::FrameworkOptions::on_nsfw_channel_required`] when a command with the
+/// `nsfw_only` attribute is invoked outside of an NSFW channel. Threads and forum posts count as
+/// NSFW if their parent channel is. DMs don't count as NSFW.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NsfwChannelRequired;
+
+impl std::fmt::Display for NsfwChannelRequired {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("This command can only be used in NSFW channels")
+    }
+}
+
+impl std::error::Error for NsfwChannelRequired {}
+
+/// Whether the given guild channel is NSFW. For threads, the parent channel is looked up
+async fn is_nsfw_channel(ctx: &serenity::Context, channel: serenity::GuildChannel) -> bool {
+    if !crate::is_thread(channel.kind) {
+        return channel.nsfw;
+    }
+
+    let parent_id = match channel.parent_id {
+        Some(x) => x,
+        None => return false,
+    };
+    match parent_id.to_channel(ctx).await {
+        Ok(serenity::Channel::Guild(parent)) => parent.nsfw,
+        _ => false,
+    }
+}
+
+/// Checks the command's [`nsfw_only`](crate::PrefixCommandOptions::nsfw_only) option and calls
+/// [`crate::FrameworkOptions::on_nsfw_channel_required`] if it wasn't invoked in an NSFW channel.
+/// Returns whether the command may run.
+///
+/// The channels are looked up in the cache if possible and via HTTP otherwise.
+pub(crate) async fn check_nsfw_channel<'a, U, E>(
+    ctx: crate::Context<'a, U, E>,
+    command: crate::CommandRef<'a, U, E>,
+) -> bool {
+    let nsfw_only = match command {
+        crate::CommandRef::Prefix(command) => command.options.nsfw_only,
+        crate::CommandRef::Application(command) => command.options().nsfw_only,
+    };
+    if !nsfw_only {
+        return true;
+    }
+
+    let is_nsfw = match ctx.channel_id().to_channel(ctx.discord()).await {
+        Ok(serenity::Channel::Guild(channel)) => is_nsfw_channel(ctx.discord(), channel).await,
+        _ => false,
+    };
+    if !is_nsfw {
+        (ctx.framework().options().on_nsfw_channel_required)(ctx, NsfwChannelRequired).await;
+    }
+    is_nsfw
+}
@@ -0,0 +1,154 @@
+//! Caching the results of expensive checks, see [`CheckCache`]
+
+use crate::serenity_prelude as serenity;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Identifies a cached check result
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CheckCacheKey {
+    user_id: serenity::UserId,
+    guild_id: Option<serenity::GuildId>,
+    check_id: &'static str,
+}
+
+/// Results of checks which opted into caching with the `check_cache_ttl` command attribute or
+/// [`cached_check`], per user, guild and check. Access it with [`crate::Framework::check_cache`].
+///
+/// Only successful check results are cached; checks which return an error run again on the next
+/// invocation. When the data a check depends on changes, for example when a user's roles are
+/// edited in the database, invalidate the affected entries so the new data takes effect before the
+/// TTL runs out.
+#[derive(Debug, Default)]
+pub struct CheckCache {
+    entries: parking_lot::Mutex<HashMap<CheckCacheKey, (bool, Instant)>>,
+}
+
+impl CheckCache {
+    /// The cached result of the given check for the given user and guild, unless it expired
+    pub fn get(
+        &self,
+        check_id: &'static str,
+        user_id: serenity::UserId,
+        guild_id: Option<serenity::GuildId>,
+    ) -> Option<bool> {
+        let key = CheckCacheKey {
+            user_id,
+            guild_id,
+            check_id,
+        };
+        match self.entries.lock().get(&key) {
+            Some(&(passed, expires_at)) if expires_at > Instant::now() => Some(passed),
+            _ => None,
+        }
+    }
+
+    /// Caches the result of the given check for the given user and guild for the duration of `ttl`
+    pub fn insert(
+        &self,
+        check_id: &'static str,
+        user_id: serenity::UserId,
+        guild_id: Option<serenity::GuildId>,
+        passed: bool,
+        ttl: Duration,
+    ) {
+        let key = CheckCacheKey {
+            user_id,
+            guild_id,
+            check_id,
+        };
+        self.entries
+            .lock()
+            .insert(key, (passed, Instant::now() + ttl));
+    }
+
+    /// Forgets the cached results of all checks for the given user, in all guilds
+    pub fn invalidate_user(&self, user_id: serenity::UserId) {
+        self.entries.lock().retain(|key, _| key.user_id != user_id);
+    }
+
+    /// Forgets the cached results of all checks for all users of the given guild
+    pub fn invalidate_guild(&self, guild_id: serenity::GuildId) {
+        self.entries
+            .lock()
+            .retain(|key, _| key.guild_id != Some(guild_id));
+    }
+
+    /// Forgets the cached results of the given check for all users
+    pub fn invalidate_check(&self, check_id: &str) {
+        self.entries
+            .lock()
+            .retain(|key, _| key.check_id != check_id);
+    }
+
+    /// Forgets all cached results
+    pub fn clear(&self) {
+        self.entries.lock().clear();
+    }
+
+    /// Removes expired entries. Called periodically by the framework
+    pub(crate) fn purge(&self) {
+        let now = Instant::now();
+        self.entries
+            .lock()
+            .retain(|_, &mut (_, expires_at)| expires_at > now);
+    }
+}
+
+/// Runs the check unless its result for the invoking user and guild is in the
+/// [`crate::Framework::check_cache`], and caches the result for the duration of `ttl`.
+///
+/// `check_id` identifies the check in the cache and in [`CheckCache::invalidate_check`]. Commands
+/// can opt into caching their check with the `check_cache_ttl = "5m"` attribute, which uses the
+/// `check` attribute as check ID. Use this function to cache handwritten checks:
+/// ```rust
+/// # type Error = Box<dyn std::error::Error + Send + Sync>;
+/// # type Context<'a> = poise::Context<'a, (), Error>;
+/// # async fn has_premium_in_database(ctx: Context<'_>) -> Result<bool, Error> { Ok(true) }
+/// async fn is_premium(ctx: Context<'_>) -> Result<bool, Error> {
+///     let ttl = std::time::Duration::from_secs(300);
+///     poise::cached_check(ctx, "is_premium", ttl, has_premium_in_database(ctx)).await
+/// }
+/// ```
+pub async fn cached_check<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    check_id: &'static str,
+    ttl: Duration,
+    check: impl std::future::Future<Output = Result<bool, E>>,
+) -> Result<bool, E> {
+    let cache = ctx.framework().check_cache();
+    let user_id = ctx.author().id;
+    let guild_id = ctx.guild_id();
+    if let Some(passed) = cache.get(check_id, user_id, guild_id) {
+        return Ok(passed);
+    }
+
+    let passed = check.await?;
+    cache.insert(check_id, user_id, guild_id, passed, ttl);
+    Ok(passed)
+}
+
+#[cfg(test)]
+#[test]
+fn test_check_cache() {
+    let cache = CheckCache::default();
+    let user = serenity::UserId(1);
+    let guild = Some(serenity::GuildId(2));
+    let ttl = Duration::from_secs(60);
+
+    cache.insert("is_admin", user, guild, true, ttl);
+    cache.insert("is_premium", user, None, false, ttl);
+    cache.insert("is_banned", user, guild, false, Duration::from_secs(0));
+    assert_eq!(cache.get("is_admin", user, guild), Some(true));
+    assert_eq!(cache.get("is_admin", user, None), None);
+    assert_eq!(cache.get("is_premium", user, None), Some(false));
+    // Expired
+    assert_eq!(cache.get("is_banned", user, guild), None);
+
+    cache.invalidate_guild(serenity::GuildId(2));
+    assert_eq!(cache.get("is_admin", user, guild), None);
+    assert_eq!(cache.get("is_premium", user, None), Some(false));
+
+    cache.invalidate_user(user);
+    assert_eq!(cache.get("is_premium", user, None), None);
+}
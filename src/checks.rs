@@ -0,0 +1,223 @@
+//! Combinators to compose command checks declaratively, like
+//! `#[poise::command(check = "any(is_admin, is_moderator)")]`
+//!
+//! Inside the `check` attribute, `all(...)`, `any(...)` and `not(...)` can be nested arbitrarily
+//! and take paths to check functions, which are invoked like a single `check = "path"`. A composed
+//! check doesn't return false when it fails, but a [`ChecksFailed`] error which describes the
+//! missing requirements, so that the error handler can show them to the user. Like argument parse
+//! errors, it's converted into the user error type via `?`, so composed checks require
+//! `E: From<ChecksFailed>`.
+//!
+//! The combinators can also be used in handwritten checks:
+//! ```rust
+//! # type Error = Box<dyn std::error::Error + Send + Sync>;
+//! # type Context<'a> = poise::Context<'a, (), Error>;
+//! # async fn is_admin(ctx: Context<'_>) -> Result<bool, Error> { Ok(true) }
+//! # async fn is_moderator(ctx: Context<'_>) -> Result<bool, Error> { Ok(true) }
+//! use poise::checks::{any, check, run};
+//!
+//! async fn is_staff(ctx: Context<'_>) -> Result<bool, Error> {
+//!     run(any(vec![
+//!         check("is_admin", is_admin(ctx)),
+//!         check("is_moderator", is_moderator(ctx)),
+//!     ]))
+//!     .await
+//! }
+//! ```
+
+use crate::BoxFuture;
+
+/// A requirement of a composed check which the invoking user didn't meet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Requirement {
+    /// A single check, by the name of its function
+    Check(&'static str),
+    /// The inner requirement must not be met
+    Not(Box<Requirement>),
+    /// All of these requirements must be met
+    All(Vec<Requirement>),
+    /// One of these requirements must be met
+    Any(Vec<Requirement>),
+}
+
+impl std::fmt::Display for Requirement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn fmt_list(
+            f: &mut std::fmt::Formatter<'_>,
+            requirements: &[Requirement],
+            separator: &str,
+        ) -> std::fmt::Result {
+            for (i, requirement) in requirements.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(separator)?;
+                }
+                match requirement {
+                    Requirement::All(_) | Requirement::Any(_) => write!(f, "({})", requirement)?,
+                    _ => write!(f, "{}", requirement)?,
+                }
+            }
+            Ok(())
+        }
+
+        match self {
+            Self::Check(name) => f.write_str(name),
+            Self::Not(requirement) => match &**requirement {
+                Self::All(_) | Self::Any(_) => write!(f, "not ({})", requirement),
+                _ => write!(f, "not {}", requirement),
+            },
+            Self::All(requirements) => fmt_list(f, requirements, " and "),
+            Self::Any(requirements) => fmt_list(f, requirements, " or "),
+        }
+    }
+}
+
+/// Returned by a composed check when the invoking user doesn't meet its requirements
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChecksFailed {
+    /// The requirements which weren't met
+    pub missing: Requirement,
+}
+
+impl std::fmt::Display for ChecksFailed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "You don't meet the requirements of this command: {}",
+            self.missing
+        )
+    }
+}
+
+impl std::error::Error for ChecksFailed {}
+
+/// A check composed with the functions of this module. Evaluated with [`run`]
+pub struct ComposedCheck<'a, E> {
+    /// Describes this check, used in the missing requirements of [`not`]
+    requirement: Requirement,
+    /// Resolves to the missing requirements, or None if the check passed
+    missing: BoxFuture<'a, Result<Option<Requirement>, E>>,
+}
+
+/// A single check, described by `name` in the missing requirements
+pub fn check<'a, E: 'a>(
+    name: &'static str,
+    check: impl std::future::Future<Output = Result<bool, E>> + Send + 'a,
+) -> ComposedCheck<'a, E> {
+    ComposedCheck {
+        requirement: Requirement::Check(name),
+        missing: Box::pin(async move {
+            Ok(if check.await? {
+                None
+            } else {
+                Some(Requirement::Check(name))
+            })
+        }),
+    }
+}
+
+/// Passes if the inner check fails
+pub fn not<'a, E: 'a>(check: ComposedCheck<'a, E>) -> ComposedCheck<'a, E> {
+    let requirement = Requirement::Not(Box::new(check.requirement));
+    let missing = requirement.clone();
+    ComposedCheck {
+        requirement,
+        missing: Box::pin(async move {
+            Ok(match check.missing.await? {
+                Some(_) => None,
+                None => Some(missing),
+            })
+        }),
+    }
+}
+
+/// Passes if all checks pass. All checks are evaluated in order, so that every missing
+/// requirement is reported
+pub fn all<'a, E: 'a>(checks: Vec<ComposedCheck<'a, E>>) -> ComposedCheck<'a, E> {
+    let requirement = Requirement::All(checks.iter().map(|c| c.requirement.clone()).collect());
+    ComposedCheck {
+        requirement,
+        missing: Box::pin(async move {
+            let mut missing = Vec::new();
+            for check in checks {
+                missing.extend(check.missing.await?);
+            }
+            Ok(match missing.len() {
+                0 => None,
+                1 => missing.pop(),
+                _ => Some(Requirement::All(missing)),
+            })
+        }),
+    }
+}
+
+/// Passes if at least one check passes. The checks are evaluated in order until one passes
+pub fn any<'a, E: 'a>(checks: Vec<ComposedCheck<'a, E>>) -> ComposedCheck<'a, E> {
+    let requirement = Requirement::Any(checks.iter().map(|c| c.requirement.clone()).collect());
+    ComposedCheck {
+        requirement,
+        missing: Box::pin(async move {
+            let mut missing = Vec::new();
+            for check in checks {
+                match check.missing.await? {
+                    Some(requirement) => missing.push(requirement),
+                    None => return Ok(None),
+                }
+            }
+            Ok(match missing.len() {
+                1 => missing.pop(),
+                _ => Some(Requirement::Any(missing)),
+            })
+        }),
+    }
+}
+
+/// Evaluates the composed check. Returns true if it passed and a [`ChecksFailed`] error with the
+/// missing requirements otherwise
+pub async fn run<E: From<ChecksFailed>>(check: ComposedCheck<'_, E>) -> Result<bool, E> {
+    match check.missing.await? {
+        None => Ok(true),
+        Some(missing) => Err(ChecksFailed { missing }.into()),
+    }
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn test_check_combinators() {
+    #[derive(Debug)]
+    struct Error(ChecksFailed);
+    impl From<ChecksFailed> for Error {
+        fn from(e: ChecksFailed) -> Self {
+            Self(e)
+        }
+    }
+    let missing = |result: Result<bool, Error>| result.unwrap_err().0.missing.to_string();
+
+    assert!(run::<Error>(any(vec![
+        check("is_admin", async { Ok(false) }),
+        check("is_moderator", async { Ok(true) }),
+    ]))
+    .await
+    .unwrap());
+
+    let composed = all(vec![
+        any(vec![
+            check("is_admin", async { Ok(false) }),
+            check("is_moderator", async { Ok(false) }),
+        ]),
+        not(check("is_banned", async { Ok(true) })),
+        check("in_guild", async { Ok(true) }),
+    ]);
+    assert_eq!(
+        missing(run(composed).await),
+        "(is_admin or is_moderator) and not is_banned"
+    );
+
+    let composed = not(all(vec![
+        check("is_admin", async { Ok(true) }),
+        check("is_moderator", async { Ok(true) }),
+    ]));
+    assert_eq!(
+        missing(run(composed).await),
+        "not (is_admin and is_moderator)"
+    );
+}
@@ -0,0 +1,267 @@
+//! Disabling commands in specific guilds or channels, see [`CommandFilterStore`]
+
+use crate::serenity_prelude as serenity;
+
+/// Error type returned by [`CommandFilterStore`] implementations
+pub type CommandFilterStoreError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Where a command or category is disabled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CommandFilterScope {
+    /// In all channels of a guild
+    Guild(serenity::GuildId),
+    /// In a single channel
+    Channel(serenity::ChannelId),
+}
+
+/// What is disabled
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CommandFilterTarget {
+    /// A single command, by its qualified name, i.e. the names of its parent commands and its own
+    /// name separated by spaces, like `mod ban`
+    Command(String),
+    /// All commands of a category
+    Category(String),
+}
+
+/// Storage backend for disabled commands, used by [`crate::FrameworkOptions::command_filter_store`]
+///
+/// Both prefix and application command dispatch consult the store before running a command. The
+/// store is asked with the qualified name of the invoked command (see
+/// [`CommandFilterTarget::Command`]) and its category, so that subcommands can be disabled
+/// separately from top-level commands of the same name. Categories are assigned
+/// to prefix commands only; application commands use the category of the prefix command with the
+/// same name.
+///
+/// Implement this trait to persist the settings in a database. [`InMemoryCommandFilterStore`] is
+/// provided for bots which don't need persistence.
+#[async_trait::async_trait]
+pub trait CommandFilterStore: Send + Sync {
+    /// Whether the given command is disabled in the given guild or channel, either by name or by
+    /// category.
+    ///
+    /// This is called for every command invocation, so it should be fast. Errors can't be
+    /// reported from here; implementations should log them and return false.
+    async fn is_disabled(
+        &self,
+        guild_id: Option<serenity::GuildId>,
+        channel_id: serenity::ChannelId,
+        command_name: &str,
+        category: Option<&str>,
+    ) -> bool;
+
+    /// Disables or re-enables the given command or category in the given scope
+    async fn set_disabled(
+        &self,
+        scope: CommandFilterScope,
+        target: CommandFilterTarget,
+        disabled: bool,
+    ) -> Result<(), CommandFilterStoreError>;
+
+    /// All commands and categories disabled in the given guild or channel, used by
+    /// [`crate::builtins::list_disabled_commands`]
+    ///
+    /// The default implementation returns an error, for stores which can't list their entries.
+    async fn disabled_entries(
+        &self,
+        guild_id: Option<serenity::GuildId>,
+        channel_id: serenity::ChannelId,
+    ) -> Result<Vec<(CommandFilterScope, CommandFilterTarget)>, CommandFilterStoreError> {
+        let _ = (guild_id, channel_id);
+        Err("This bot can't list disabled commands".into())
+    }
+}
+
+/// A [`CommandFilterStore`] which keeps the settings in memory. They are lost when the bot
+/// restarts.
+#[derive(Debug, Default)]
+pub struct InMemoryCommandFilterStore {
+    disabled:
+        parking_lot::RwLock<std::collections::HashSet<(CommandFilterScope, CommandFilterTarget)>>,
+}
+
+impl InMemoryCommandFilterStore {
+    /// Create a store in which nothing is disabled
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait::async_trait]
+impl CommandFilterStore for InMemoryCommandFilterStore {
+    async fn is_disabled(
+        &self,
+        guild_id: Option<serenity::GuildId>,
+        channel_id: serenity::ChannelId,
+        command_name: &str,
+        category: Option<&str>,
+    ) -> bool {
+        let disabled = self.disabled.read();
+
+        let scopes = guild_id
+            .map(CommandFilterScope::Guild)
+            .into_iter()
+            .chain(std::iter::once(CommandFilterScope::Channel(channel_id)));
+        let targets = std::iter::once(CommandFilterTarget::Command(command_name.to_owned()))
+            .chain(category.map(|x| CommandFilterTarget::Category(x.to_owned())))
+            .collect::<Vec<_>>();
+
+        for scope in scopes {
+            for target in &targets {
+                if disabled.contains(&(scope, target.clone())) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    async fn set_disabled(
+        &self,
+        scope: CommandFilterScope,
+        target: CommandFilterTarget,
+        disabled: bool,
+    ) -> Result<(), CommandFilterStoreError> {
+        let mut entries = self.disabled.write();
+        if disabled {
+            entries.insert((scope, target));
+        } else {
+            entries.remove(&(scope, target));
+        }
+        Ok(())
+    }
+
+    async fn disabled_entries(
+        &self,
+        guild_id: Option<serenity::GuildId>,
+        channel_id: serenity::ChannelId,
+    ) -> Result<Vec<(CommandFilterScope, CommandFilterTarget)>, CommandFilterStoreError> {
+        let mut entries = self
+            .disabled
+            .read()
+            .iter()
+            .filter(|(scope, _)| match *scope {
+                CommandFilterScope::Guild(x) => Some(x) == guild_id,
+                CommandFilterScope::Channel(x) => x == channel_id,
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        // HashSet iteration order is random
+        entries.sort_by_key(|(scope, target)| {
+            (
+                matches!(scope, CommandFilterScope::Channel(_)),
+                match target {
+                    CommandFilterTarget::Command(name) | CommandFilterTarget::Category(name) => {
+                        name.clone()
+                    }
+                },
+            )
+        });
+        Ok(entries)
+    }
+}
+
+/// The category of the command, looked up among the prefix commands because only those have
+/// categories. Subcommands without a category of their own inherit the category of their parent
+pub(crate) fn command_category<U, E>(
+    framework: &crate::Framework<U, E>,
+    command: crate::CommandRef<'_, U, E>,
+) -> Option<&'static str> {
+    let qualified_name = crate::qualified_command_name(framework, command);
+    let mut category = None;
+    let mut level = framework.prefix_commands();
+    for name in qualified_name.split(' ') {
+        let meta = level.into_iter().find(|meta| meta.command.name == name)?;
+        category = meta.category.or(category);
+        level = meta.subcommands.iter().collect();
+    }
+    category
+}
+
+/// Whether the invoked command is disabled according to
+/// [`crate::FrameworkOptions::command_filter_store`] or the disabled categories of
+/// [`crate::FrameworkOptions::guild_settings`]. If so, calls
+/// [`crate::FrameworkOptions::on_command_disabled`]
+pub(crate) async fn check_command_disabled<'a, U, E>(
+    ctx: crate::Context<'a, U, E>,
+    command: crate::CommandRef<'a, U, E>,
+) -> bool {
+    let framework = ctx.framework();
+    let category = command_category(framework, command);
+
+    let mut disabled = match &framework.options().command_filter_store {
+        Some(store) => {
+            let qualified_name = crate::qualified_command_name(framework, command);
+            store
+                .is_disabled(ctx.guild_id(), ctx.channel_id(), &qualified_name, category)
+                .await
+        }
+        None => false,
+    };
+    if !disabled {
+        if let (Some(category), Some(settings)) = (category, ctx.guild_settings().await) {
+            disabled = settings.disabled_categories.iter().any(|c| c == category);
+        }
+    }
+    if disabled {
+        (framework.options().on_command_disabled)(ctx).await;
+    }
+    disabled
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn test_in_memory_command_filter_store() {
+    let store = InMemoryCommandFilterStore::new();
+    let guild = Some(serenity::GuildId(1));
+    let channel = serenity::ChannelId(2);
+    let other_channel = serenity::ChannelId(3);
+
+    store
+        .set_disabled(
+            CommandFilterScope::Channel(channel),
+            CommandFilterTarget::Command("ban".into()),
+            true,
+        )
+        .await
+        .unwrap();
+    assert!(store.is_disabled(guild, channel, "ban", None).await);
+    assert!(!store.is_disabled(guild, other_channel, "ban", None).await);
+    assert!(!store.is_disabled(guild, channel, "kick", None).await);
+
+    store
+        .set_disabled(
+            CommandFilterScope::Guild(serenity::GuildId(1)),
+            CommandFilterTarget::Category("Moderation".into()),
+            true,
+        )
+        .await
+        .unwrap();
+    assert!(
+        store
+            .is_disabled(guild, other_channel, "kick", Some("Moderation"))
+            .await
+    );
+    assert!(
+        !store
+            .is_disabled(None, other_channel, "kick", Some("Moderation"))
+            .await
+    );
+    assert_eq!(
+        store.disabled_entries(guild, other_channel).await.unwrap(),
+        [(
+            CommandFilterScope::Guild(serenity::GuildId(1)),
+            CommandFilterTarget::Category("Moderation".into()),
+        )]
+    );
+
+    store
+        .set_disabled(
+            CommandFilterScope::Channel(channel),
+            CommandFilterTarget::Command("ban".into()),
+            false,
+        )
+        .await
+        .unwrap();
+    assert!(!store.is_disabled(guild, channel, "ban", None).await);
+}
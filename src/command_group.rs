@@ -0,0 +1,174 @@
+//! Sets of commands with their own prefix, check, error handler and cooldowns, see
+//! [`CommandGroup`]
+
+use crate::BoxFuture;
+
+/// A set of commands which share settings, mounted into the framework with
+/// [`crate::FrameworkOptions::mount`]. Lets reusable command libraries, like a music module, ship
+/// their commands with their own prefix and error handler, independent of the bot they're used in.
+///
+/// The group settings apply in addition to the framework settings:
+/// - [`Self::check`] runs after [`crate::FrameworkOptions::command_check`] and before the
+///   command's own check
+/// - [`Self::on_error`] handles the errors of commands without their own error handler, instead of
+///   [`crate::FrameworkOptions::on_error`]
+/// - [`Self::cooldowns`] applies to commands without their own cooldowns
+///
+/// ```rust
+/// # type Error = Box<dyn std::error::Error + Send + Sync>;
+/// # #[poise::command(prefix_command, slash_command)]
+/// # async fn play(ctx: poise::Context<'_, (), Error>) -> Result<(), Error> { Ok(()) }
+/// let mut music = poise::CommandGroup::new("music");
+/// music.prefix = Some("m!".into());
+/// music.cooldowns.user = Some(std::time::Duration::from_secs(3));
+/// music.command(play(), |f| f);
+///
+/// let mut options = poise::FrameworkOptions::<(), Error>::default();
+/// options.mount(music);
+/// ```
+pub struct CommandGroup<U, E> {
+    name: &'static str,
+    /// If Some, the prefix commands of this group are invoked with this prefix instead of the
+    /// framework prefixes, and messages with this prefix only invoke commands of this group. If
+    /// None, they're invoked like other commands
+    pub prefix: Option<String>,
+    /// If this function returns false, commands of this group won't be executed
+    pub check: Option<fn(crate::Context<'_, U, E>) -> BoxFuture<'_, Result<bool, E>>>,
+    /// Called for errors of commands of this group which don't have their own error handler
+    pub on_error: Option<fn(E, crate::CommandErrorContext<'_, U, E>) -> BoxFuture<'_, ()>>,
+    /// Cooldowns of the commands of this group which don't configure any cooldowns themselves.
+    /// Each command still has its own cooldowns, so using one command doesn't put the others on
+    /// cooldown
+    pub cooldowns: crate::CooldownConfig,
+    prefix_commands: Vec<crate::PrefixCommandMeta<U, E>>,
+    application_commands: Vec<crate::ApplicationCommandTree<U, E>>,
+}
+
+impl<U, E> CommandGroup<U, E> {
+    /// Create an empty group without a prefix, check, error handler or cooldowns
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            prefix: None,
+            check: None,
+            on_error: None,
+            cooldowns: crate::CooldownConfig::default(),
+            prefix_commands: Vec::new(),
+            application_commands: Vec::new(),
+        }
+    }
+
+    /// Name of this group, stored in the options of its commands
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Add a command to this group, like [`crate::FrameworkOptions::command`]
+    pub fn command(
+        &mut self,
+        definition: crate::CommandDefinition<U, E>,
+        meta_builder: impl FnOnce(&mut crate::CommandBuilder<U, E>) -> &mut crate::CommandBuilder<U, E>,
+    ) -> &mut Self {
+        let (prefix_command, application_commands) =
+            crate::CommandBuilder::build(definition, meta_builder);
+        self.prefix_commands.extend(prefix_command);
+        self.application_commands.extend(application_commands);
+        self
+    }
+
+    /// Assigns this group and its default cooldowns to all its commands and subcommands, and
+    /// returns them
+    pub(crate) fn take_commands(
+        &mut self,
+    ) -> (
+        Vec<crate::PrefixCommandMeta<U, E>>,
+        Vec<crate::ApplicationCommandTree<U, E>>,
+    ) {
+        fn assign_prefix<U, E>(
+            meta: &mut crate::PrefixCommandMeta<U, E>,
+            name: &'static str,
+            cooldowns: &crate::CooldownConfig,
+        ) {
+            let options = &mut meta.command.options;
+            options.group = Some(name);
+            if *options.cooldowns.config() == crate::CooldownConfig::default() {
+                options.cooldowns = crate::Cooldowns::new(cooldowns.clone());
+            }
+            for subcommand in &mut meta.subcommands {
+                assign_prefix(subcommand, name, cooldowns);
+            }
+        }
+
+        fn assign_application<U, E>(
+            options: &mut crate::ApplicationCommandOptions<U, E>,
+            name: &'static str,
+            cooldowns: &crate::CooldownConfig,
+        ) {
+            options.group = Some(name);
+            if *options.cooldowns.config() == crate::CooldownConfig::default() {
+                options.cooldowns = crate::Cooldowns::new(cooldowns.clone());
+            }
+        }
+
+        fn assign_slash<U, E>(
+            meta: &mut crate::SlashCommandMeta<U, E>,
+            name: &'static str,
+            cooldowns: &crate::CooldownConfig,
+        ) {
+            match meta {
+                crate::SlashCommandMeta::Command(command) => {
+                    assign_application(&mut command.options, name, cooldowns)
+                }
+                crate::SlashCommandMeta::CommandGroup { subcommands, .. } => {
+                    for subcommand in subcommands {
+                        assign_slash(subcommand, name, cooldowns);
+                    }
+                }
+            }
+        }
+
+        let mut prefix_commands = std::mem::take(&mut self.prefix_commands);
+        for meta in &mut prefix_commands {
+            assign_prefix(meta, self.name, &self.cooldowns);
+        }
+        let mut application_commands = std::mem::take(&mut self.application_commands);
+        for command in &mut application_commands {
+            match command {
+                crate::ApplicationCommandTree::Slash(meta) => {
+                    assign_slash(meta, self.name, &self.cooldowns)
+                }
+                crate::ApplicationCommandTree::ContextMenu(command) => {
+                    assign_application(&mut command.options, self.name, &self.cooldowns)
+                }
+            }
+        }
+        (prefix_commands, application_commands)
+    }
+}
+
+/// The mounted group of the given command, if any
+pub(crate) fn command_group<'a, U, E>(
+    framework: &'a crate::Framework<U, E>,
+    command: crate::CommandRef<'_, U, E>,
+) -> Option<&'a CommandGroup<U, E>> {
+    let name = match command {
+        crate::CommandRef::Prefix(command) => command.options.group,
+        crate::CommandRef::Application(command) => command.options().group,
+    }?;
+    framework
+        .options()
+        .command_groups
+        .iter()
+        .find(|group| group.name == name)
+}
+
+/// Runs the check of the command's group, if any
+pub(crate) async fn check_command_group<U, E>(ctx: crate::Context<'_, U, E>) -> Result<bool, E> {
+    let check = ctx
+        .command()
+        .and_then(|command| command_group(ctx.framework(), command)?.check);
+    match check {
+        Some(check) => check(ctx).await,
+        None => Ok(true),
+    }
+}
@@ -0,0 +1,42 @@
+//! Catching panics in command bodies, see [`CommandPanic`]
+
+/// Passed to [`crate::FrameworkOptions::on_command_panic`] when a command panicked.
+///
+/// The panic is caught by the framework, so the bot keeps running, and other invocations aren't
+/// affected. Since a panic doesn't produce a value of the user error type, it isn't passed to
+/// [`crate::FrameworkOptions::on_error`].
+pub struct CommandPanic<'a, U, E> {
+    /// Context of the invocation which panicked
+    pub ctx: crate::Context<'a, U, E>,
+    /// The value the command panicked with
+    pub payload: Box<dyn std::any::Any + Send>,
+}
+
+impl<U, E> CommandPanic<'_, U, E> {
+    /// The panic message, if the command panicked with a string, like `panic!` and `unwrap` do
+    pub fn message(&self) -> Option<&str> {
+        if let Some(message) = self.payload.downcast_ref::<&'static str>() {
+            Some(message)
+        } else {
+            self.payload
+                .downcast_ref::<String>()
+                .map(|message| message.as_str())
+        }
+    }
+}
+
+/// Runs the command future, or passes its panic to [`crate::FrameworkOptions::on_command_panic`]
+/// and returns None if it panicked
+pub(crate) async fn catch_command_panic<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    action: crate::BoxFuture<'_, Result<(), E>>,
+) -> Option<Result<(), E>> {
+    let payload = match futures::FutureExt::catch_unwind(std::panic::AssertUnwindSafe(action)).await
+    {
+        Ok(result) => return Some(result),
+        Err(payload) => payload,
+    };
+
+    (ctx.framework().options().on_command_panic)(CommandPanic { ctx, payload }).await;
+    None
+}
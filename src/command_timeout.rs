@@ -0,0 +1,42 @@
+//! Cancelling commands which run for too long, see [`CommandTimeout`]
+
+/// Passed to [`crate::FrameworkOptions::on_command_timeout`] when a command ran for longer than
+/// its timeout and was cancelled.
+///
+/// The timeout is configured per command with the `timeout = "30s"` attribute, or for all
+/// commands with [`crate::FrameworkOptions::command_timeout`].
+pub struct CommandTimeout<'a, U, E> {
+    /// Context of the invocation which timed out
+    pub ctx: crate::Context<'a, U, E>,
+    /// How long the command was allowed to run
+    pub timeout: std::time::Duration,
+}
+
+/// The timeout which applies to the given command, if any
+pub(crate) fn command_timeout<U, E>(ctx: crate::Context<'_, U, E>) -> Option<std::time::Duration> {
+    let timeout = match ctx.command()? {
+        crate::CommandRef::Prefix(command) => command.options.timeout,
+        crate::CommandRef::Application(command) => command.options().timeout,
+    };
+    timeout.or(ctx.framework().options().command_timeout)
+}
+
+/// Runs the command future. If it doesn't finish within the timeout of the command, it's dropped,
+/// the timeout is passed to [`crate::FrameworkOptions::on_command_timeout`] and None is returned
+pub(crate) async fn run_with_timeout<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    action: impl std::future::Future<Output = Option<Result<(), E>>>,
+) -> Option<Result<(), E>> {
+    let timeout = match command_timeout(ctx) {
+        Some(x) => x,
+        None => return action.await,
+    };
+
+    match tokio::time::timeout(timeout, action).await {
+        Ok(result) => result,
+        Err(_) => {
+            (ctx.framework().options().on_command_timeout)(CommandTimeout { ctx, timeout }).await;
+            None
+        }
+    }
+}
@@ -0,0 +1,246 @@
+//! Runtime support for the `max_concurrent` command attribute, see [`ConcurrencyLimiter`]
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+/// Who shares the concurrently running invocations of a [`ConcurrencyLimit`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConcurrencyScope {
+    /// At most [`ConcurrencyLimit::max`] invocations run at once in total
+    Global,
+    /// At most [`ConcurrencyLimit::max`] invocations run at once per user
+    User,
+    /// At most [`ConcurrencyLimit::max`] invocations run at once per guild. In DMs, per user
+    Guild,
+}
+
+/// How many invocations of a command may run at the same time, see [`ConcurrencyLimiter`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ConcurrencyLimit {
+    /// How many invocations may run at once in each scope
+    pub max: u32,
+    /// Who shares the running invocations
+    pub scope: ConcurrencyScope,
+    /// How many excess invocations may wait for a running invocation to finish in each scope.
+    /// Further invocations fail with [`ConcurrencyLimitReached`]. If zero, excess invocations fail
+    /// right away
+    pub max_queued: u32,
+}
+
+/// Passed to [`crate::FrameworkOptions::on_concurrency_limit_reached`] when a command is invoked
+/// while the maximum number of invocations is already running and the queue is full, see
+/// [`ConcurrencyLimit`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConcurrencyLimitReached {
+    /// The limit of the command
+    pub limit: ConcurrencyLimit,
+}
+
+impl std::fmt::Display for ConcurrencyLimitReached {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let scope = match self.limit.scope {
+            ConcurrencyScope::Global => "",
+            ConcurrencyScope::User => " for you",
+            ConcurrencyScope::Guild => " in this server",
+        };
+        write!(
+            f,
+            "This command is already running {} times{}, try again once one of them finished",
+            self.limit.max, scope
+        )
+    }
+}
+
+impl std::error::Error for ConcurrencyLimitReached {}
+
+/// The running and waiting invocations in one scope
+struct ScopeState {
+    semaphore: Arc<tokio::sync::Semaphore>,
+    queued: AtomicU32,
+}
+
+/// Limits how many invocations of a command run at the same time, for example so that heavy
+/// commands like image rendering can't be invoked dozens of times in parallel. Generated by the
+/// `max_concurrent` command attribute and shared by the prefix, slash and context menu versions of
+/// the command.
+pub struct ConcurrencyLimiter {
+    limit: ConcurrencyLimit,
+    scopes: once_cell::sync::OnceCell<
+        parking_lot::Mutex<HashMap<crate::CooldownBucket, Arc<ScopeState>>>,
+    >,
+}
+
+/// Held while an invocation runs. Dropping it lets the next invocation in the scope run
+pub struct ConcurrencyPermit {
+    _permit: tokio::sync::OwnedSemaphorePermit,
+    /// Keeps the scope from being pruned while the invocation runs
+    _state: Arc<ScopeState>,
+}
+
+/// Frees the queue slot of a waiting invocation, even if it's cancelled while waiting
+struct QueueSlot<'a>(&'a AtomicU32);
+
+impl Drop for QueueSlot<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl ConcurrencyLimiter {
+    /// Create a limiter with the given limit and no running invocations
+    pub const fn new(limit: ConcurrencyLimit) -> Self {
+        Self {
+            limit,
+            scopes: once_cell::sync::OnceCell::new(),
+        }
+    }
+
+    /// The limit of this limiter
+    pub fn limit(&self) -> &ConcurrencyLimit {
+        &self.limit
+    }
+
+    /// The scope which the given invocation falls into
+    fn scope_of<U, E>(&self, ctx: crate::Context<'_, U, E>) -> crate::CooldownBucket {
+        let user_id = ctx.author().id;
+        match (self.limit.scope, ctx.guild_id()) {
+            (ConcurrencyScope::Global, _) => crate::CooldownBucket::Global,
+            (ConcurrencyScope::Guild, Some(guild_id)) => crate::CooldownBucket::Guild(guild_id),
+            (ConcurrencyScope::User, _) | (ConcurrencyScope::Guild, None) => {
+                crate::CooldownBucket::User(user_id)
+            }
+        }
+    }
+
+    fn scope_state(&self, scope: crate::CooldownBucket) -> Arc<ScopeState> {
+        let max = self.limit.max as usize;
+        let mut scopes = self.scopes.get_or_init(Default::default).lock();
+        // Scopes without running or waiting invocations are only referenced by the map
+        scopes.retain(|_, state| Arc::strong_count(state) > 1);
+        scopes
+            .entry(scope)
+            .or_insert_with(|| {
+                Arc::new(ScopeState {
+                    semaphore: Arc::new(tokio::sync::Semaphore::new(max)),
+                    queued: AtomicU32::new(0),
+                })
+            })
+            .clone()
+    }
+
+    async fn scope_acquire(
+        &self,
+        scope: crate::CooldownBucket,
+    ) -> Result<ConcurrencyPermit, ConcurrencyLimitReached> {
+        let state = self.scope_state(scope);
+        let reached = ConcurrencyLimitReached { limit: self.limit };
+        if let Ok(permit) = state.semaphore.clone().try_acquire_owned() {
+            return Ok(ConcurrencyPermit {
+                _permit: permit,
+                _state: state,
+            });
+        }
+
+        let max_queued = self.limit.max_queued;
+        state
+            .queued
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |queued| {
+                if queued < max_queued {
+                    Some(queued + 1)
+                } else {
+                    None
+                }
+            })
+            .map_err(|_| reached.clone())?;
+        let queue_slot = QueueSlot(&state.queued);
+
+        let permit = state.semaphore.clone().acquire_owned().await;
+        drop(queue_slot);
+        match permit {
+            Ok(permit) => Ok(ConcurrencyPermit {
+                _permit: permit,
+                _state: state,
+            }),
+            // The semaphore is never closed
+            Err(_) => Err(reached),
+        }
+    }
+
+    /// Waits until the given invocation may run, if there's room in the queue. The returned permit
+    /// must be held while the invocation runs
+    pub async fn acquire<U, E>(
+        &self,
+        ctx: crate::Context<'_, U, E>,
+    ) -> Result<ConcurrencyPermit, ConcurrencyLimitReached> {
+        self.scope_acquire(self.scope_of(ctx)).await
+    }
+
+    /// How many invocations are currently running in the scope of the given invocation
+    pub fn running<U, E>(&self, ctx: crate::Context<'_, U, E>) -> u32 {
+        let available = self
+            .scope_state(self.scope_of(ctx))
+            .semaphore
+            .available_permits();
+        self.limit.max.saturating_sub(available as u32)
+    }
+}
+
+/// Waits until the invoked command may run according to its
+/// [concurrency limit](crate::PrefixCommandOptions::concurrency), if any. Called in dispatch
+/// before the command runs, so waiting doesn't count towards its timeout.
+///
+/// If the queue is full, calls [`crate::FrameworkOptions::on_concurrency_limit_reached`] and
+/// returns Err, in which case the invocation must be dropped
+pub(crate) async fn acquire_concurrency_permit<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    command: crate::CommandRef<'_, U, E>,
+) -> Result<Option<ConcurrencyPermit>, ()> {
+    let limiter = match command {
+        crate::CommandRef::Prefix(command) => command.options.concurrency,
+        crate::CommandRef::Application(command) => command.options().concurrency,
+    };
+    let limiter = match limiter {
+        Some(x) => x,
+        None => return Ok(None),
+    };
+    match limiter.acquire(ctx).await {
+        Ok(permit) => Ok(Some(permit)),
+        Err(error) => {
+            (ctx.framework().options().on_concurrency_limit_reached)(ctx, error).await;
+            Err(())
+        }
+    }
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn test_concurrency_limiter() {
+    let limiter = ConcurrencyLimiter::new(ConcurrencyLimit {
+        max: 1,
+        scope: ConcurrencyScope::User,
+        max_queued: 1,
+    });
+    let user = crate::CooldownBucket::User(crate::serenity_prelude::UserId(1));
+    let other_user = crate::CooldownBucket::User(crate::serenity_prelude::UserId(2));
+
+    let permit = limiter.scope_acquire(user.clone()).await.unwrap();
+    // Other scopes have their own limit
+    let other_permit = limiter.scope_acquire(other_user).await.unwrap();
+
+    // The second invocation queues, the third one fails
+    let queued = limiter.scope_acquire(user.clone());
+    futures::pin_mut!(queued);
+    assert!(futures::poll!(&mut queued).is_pending());
+    assert!(limiter.scope_acquire(user.clone()).await.is_err());
+
+    drop(permit);
+    let permit = queued.await.unwrap();
+    assert_eq!(limiter.scopes.get().unwrap().lock().len(), 2);
+
+    // Idle scopes are forgotten
+    drop(permit);
+    drop(other_permit);
+    let _permit = limiter.scope_acquire(user.clone()).await.unwrap();
+    assert_eq!(limiter.scopes.get().unwrap().lock().len(), 1);
+}
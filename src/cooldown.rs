@@ -0,0 +1,668 @@
+//! Per-command invocation cooldowns and what to tell users who hit them
+
+use crate::serenity_prelude as serenity;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// How long to wait before a command may be invoked again, for each scope. A scope with None
+/// has no cooldown.
+///
+/// If multiple scopes apply, the invocation is rejected until all of them have expired.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CooldownConfig {
+    /// Cooldown shared by everyone
+    pub global: Option<Duration>,
+    /// Cooldown per user, across all guilds
+    pub user: Option<Duration>,
+    /// Cooldown per guild. Doesn't apply in DMs
+    pub guild: Option<Duration>,
+    /// Cooldown per channel
+    pub channel: Option<Duration>,
+    /// Cooldown per user in a specific guild. Doesn't apply in DMs
+    pub member: Option<Duration>,
+    /// Cooldown per key returned by the command's `cooldown_key` function, see [`CooldownKey`].
+    /// Doesn't apply to commands without one
+    pub custom: Option<Duration>,
+}
+
+/// Identifies a custom cooldown scope, for cooldowns not covered by the fixed scopes in
+/// [`CooldownConfig`]. Returned by the `cooldown_key` function of a command, and the
+/// [`CooldownConfig::custom`] cooldown applies per key.
+///
+/// The key function runs when cooldowns are checked, before the arguments are parsed, so it can't
+/// see the command arguments.
+///
+/// For example, to put a command on cooldown per tenant of a multi-tenant bot, where a tenant is a
+/// guild, or a user in DMs:
+/// ```rust
+/// # type Error = Box<dyn std::error::Error + Send + Sync>;
+/// fn tenant_cooldown_key(ctx: poise::Context<'_, (), Error>) -> poise::CooldownKey {
+///     let tenant = match ctx.guild_id() {
+///         Some(guild_id) => format!("guild:{}", guild_id),
+///         None => format!("user:{}", ctx.author().id),
+///     };
+///     tenant.into()
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CooldownKey(pub String);
+
+impl From<String> for CooldownKey {
+    fn from(key: String) -> Self {
+        Self(key)
+    }
+}
+
+impl From<&str> for CooldownKey {
+    fn from(key: &str) -> Self {
+        Self(key.to_owned())
+    }
+}
+
+/// A single cooldown scope of an invocation, like the invoking user or the channel
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CooldownBucket {
+    /// See [`CooldownConfig::global`]
+    Global,
+    /// See [`CooldownConfig::user`]
+    User(serenity::UserId),
+    /// See [`CooldownConfig::guild`]
+    Guild(serenity::GuildId),
+    /// See [`CooldownConfig::channel`]
+    Channel(serenity::ChannelId),
+    /// See [`CooldownConfig::member`]
+    Member(serenity::GuildId, serenity::UserId),
+    /// See [`CooldownConfig::custom`]
+    Custom(CooldownKey),
+}
+
+/// When the cooldown of each bucket expires. The in-memory cooldown logic shared by [`Cooldowns`]
+/// and [`InMemoryCooldownStore`]
+#[derive(Debug)]
+struct Expirations<K>(parking_lot::Mutex<HashMap<K, Instant>>);
+
+impl<K: std::hash::Hash + Eq> Default for Expirations<K> {
+    fn default() -> Self {
+        Self(Default::default())
+    }
+}
+
+impl<K: std::hash::Hash + Eq + Clone> Expirations<K> {
+    fn remaining_locked(
+        expirations: &HashMap<K, Instant>,
+        buckets: &[(K, Duration)],
+        now: Instant,
+    ) -> Option<Duration> {
+        buckets
+            .iter()
+            .filter_map(|(bucket, _)| expirations.get(bucket)?.checked_duration_since(now))
+            .filter(|&remaining| remaining > Duration::from_secs(0))
+            .max()
+    }
+
+    fn start_locked(
+        expirations: &mut HashMap<K, Instant>,
+        buckets: &[(K, Duration)],
+        now: Instant,
+    ) {
+        for (bucket, cooldown) in buckets {
+            expirations.insert(bucket.clone(), now + *cooldown);
+        }
+    }
+
+    /// How long until all cooldowns of the given buckets have expired, if any is still running
+    fn remaining(&self, buckets: &[(K, Duration)]) -> Option<Duration> {
+        Self::remaining_locked(&self.0.lock(), buckets, Instant::now())
+    }
+
+    /// Starts the cooldown of each bucket with the given duration
+    fn start(&self, buckets: &[(K, Duration)]) {
+        Self::start_locked(&mut self.0.lock(), buckets, Instant::now());
+    }
+
+    /// Starts the cooldowns if none of them is running, atomically. Otherwise, returns how long
+    /// until they have expired
+    fn try_start(&self, buckets: &[(K, Duration)]) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut expirations = self.0.lock();
+        match Self::remaining_locked(&expirations, buckets, now) {
+            Some(remaining) => Err(remaining),
+            None => {
+                Self::start_locked(&mut expirations, buckets, now);
+                Ok(())
+            }
+        }
+    }
+
+    /// Forgets the expired cooldowns
+    fn purge(&self) {
+        let now = Instant::now();
+        self.0.lock().retain(|_, expiration| *expiration > now);
+    }
+}
+
+/// Keeps track of when the cooldowns of a command expire in each cooldown scope
+#[derive(Debug, Default)]
+pub struct Cooldowns {
+    config: CooldownConfig,
+    expirations: Expirations<CooldownBucket>,
+}
+
+impl Cooldowns {
+    /// Create a new cooldown tracker with the given cooldown durations
+    pub fn new(config: CooldownConfig) -> Self {
+        Self {
+            config,
+            expirations: Default::default(),
+        }
+    }
+
+    /// The cooldown durations of this tracker
+    pub fn config(&self) -> &CooldownConfig {
+        &self.config
+    }
+
+    /// Forgets the invocations whose cooldown has expired. The framework calls this periodically
+    /// for the cooldowns of all registered commands.
+    pub fn purge(&self) {
+        self.expirations.purge();
+    }
+
+    /// The buckets which the given invocation falls into and their cooldowns, leaving out scopes
+    /// without a cooldown. The custom scope only applies if a key is given
+    pub fn buckets<U, E>(
+        &self,
+        ctx: crate::Context<'_, U, E>,
+        key: Option<&CooldownKey>,
+    ) -> Vec<(CooldownBucket, Duration)> {
+        let user_id = ctx.author().id;
+        let guild_id = ctx.guild_id();
+        let buckets = vec![
+            (Some(CooldownBucket::Global), self.config.global),
+            (Some(CooldownBucket::User(user_id)), self.config.user),
+            (guild_id.map(CooldownBucket::Guild), self.config.guild),
+            (
+                Some(CooldownBucket::Channel(ctx.channel_id())),
+                self.config.channel,
+            ),
+            (
+                guild_id.map(|guild_id| CooldownBucket::Member(guild_id, user_id)),
+                self.config.member,
+            ),
+            (key.cloned().map(CooldownBucket::Custom), self.config.custom),
+        ];
+
+        buckets
+            .into_iter()
+            .filter_map(|(bucket, cooldown)| Some((bucket?, cooldown?)))
+            .collect()
+    }
+
+    /// If the command is still on cooldown for the given invocation, returns how long until it can
+    /// be invoked again
+    pub fn remaining_cooldown<U, E>(&self, ctx: crate::Context<'_, U, E>) -> Option<Duration> {
+        self.remaining_cooldown_with_key(ctx, None)
+    }
+
+    /// Like [`Self::remaining_cooldown`], but also considering the [`CooldownConfig::custom`]
+    /// cooldown of the given key
+    pub fn remaining_cooldown_with_key<U, E>(
+        &self,
+        ctx: crate::Context<'_, U, E>,
+        key: Option<&CooldownKey>,
+    ) -> Option<Duration> {
+        self.expirations.remaining(&self.buckets(ctx, key))
+    }
+
+    /// Record an invocation of the command, starting the cooldowns for its scopes
+    pub fn start_cooldown<U, E>(&self, ctx: crate::Context<'_, U, E>) {
+        self.start_cooldown_with_key(ctx, None);
+    }
+
+    /// Like [`Self::start_cooldown`], but also starting the [`CooldownConfig::custom`] cooldown
+    /// of the given key
+    pub fn start_cooldown_with_key<U, E>(
+        &self,
+        ctx: crate::Context<'_, U, E>,
+        key: Option<&CooldownKey>,
+    ) {
+        self.expirations.start(&self.buckets(ctx, key));
+    }
+
+    /// Starts the cooldowns if the command isn't on cooldown for the given invocation. Otherwise,
+    /// returns how long until it can be invoked again
+    pub fn try_start_cooldown<U, E>(&self, ctx: crate::Context<'_, U, E>) -> Result<(), Duration> {
+        self.try_start_cooldown_with_key(ctx, None)
+    }
+
+    /// Like [`Self::try_start_cooldown`], but also considering the [`CooldownConfig::custom`]
+    /// cooldown of the given key
+    pub fn try_start_cooldown_with_key<U, E>(
+        &self,
+        ctx: crate::Context<'_, U, E>,
+        key: Option<&CooldownKey>,
+    ) -> Result<(), Duration> {
+        self.expirations.try_start(&self.buckets(ctx, key))
+    }
+}
+
+/// Error type returned by [`CooldownStore`] implementations
+pub type CooldownStoreError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Storage backend for cooldowns, used by [`crate::FrameworkOptions::cooldown_store`].
+///
+/// By default, each command tracks its cooldowns in memory in its [`Cooldowns`], so they reset
+/// when the bot restarts, and users can dodge long cooldowns that way. Implement this trait to
+/// persist them, for example in Redis or a database. [`InMemoryCooldownStore`] is provided as a
+/// reference implementation.
+///
+/// Commands are identified by their kind and [qualified name](crate::Framework::find_command), like
+/// `prefix:ban`, `slash:mod ban` or `context_menu:User info`, because the prefix, slash and context
+/// menu versions of a command have separate cooldowns.
+#[async_trait::async_trait]
+pub trait CooldownStore: Send + Sync {
+    /// If the command is still on cooldown in any of the given buckets, returns how long until it
+    /// can be invoked again
+    ///
+    /// Called for every invocation of a command with cooldowns, before it runs. If this fails, a
+    /// warning is logged and the invocation is allowed.
+    async fn remaining_cooldown(
+        &self,
+        command_id: &str,
+        buckets: &[(CooldownBucket, Duration)],
+    ) -> Result<Option<Duration>, CooldownStoreError>;
+
+    /// Checks the cooldowns like [`Self::remaining_cooldown`] and, if the command isn't on
+    /// cooldown, starts the cooldown of each bucket with the given duration. This must happen
+    /// atomically, so that concurrent invocations can't both pass.
+    ///
+    /// Called once an invocation which passed [`Self::remaining_cooldown`] actually runs, i.e.
+    /// after its arguments were parsed. If this fails, a warning is logged.
+    async fn try_start_cooldown(
+        &self,
+        command_id: &str,
+        buckets: &[(CooldownBucket, Duration)],
+    ) -> Result<Option<Duration>, CooldownStoreError>;
+}
+
+/// A [`CooldownStore`] which keeps the cooldowns in memory. They are lost when the bot restarts.
+///
+/// Behaves exactly like the default cooldown tracking without a store, which shares its
+/// implementation.
+#[derive(Debug, Default)]
+pub struct InMemoryCooldownStore {
+    /// When the cooldown of each command and bucket expires
+    expirations: Expirations<(String, CooldownBucket)>,
+}
+
+impl InMemoryCooldownStore {
+    /// Create an empty cooldown store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The buckets keyed by command
+    fn command_buckets(
+        command_id: &str,
+        buckets: &[(CooldownBucket, Duration)],
+    ) -> Vec<((String, CooldownBucket), Duration)> {
+        buckets
+            .iter()
+            .map(|(bucket, cooldown)| ((command_id.to_owned(), bucket.clone()), *cooldown))
+            .collect()
+    }
+}
+
+#[async_trait::async_trait]
+impl CooldownStore for InMemoryCooldownStore {
+    async fn remaining_cooldown(
+        &self,
+        command_id: &str,
+        buckets: &[(CooldownBucket, Duration)],
+    ) -> Result<Option<Duration>, CooldownStoreError> {
+        Ok(self
+            .expirations
+            .remaining(&Self::command_buckets(command_id, buckets)))
+    }
+
+    async fn try_start_cooldown(
+        &self,
+        command_id: &str,
+        buckets: &[(CooldownBucket, Duration)],
+    ) -> Result<Option<Duration>, CooldownStoreError> {
+        // Forget expired cooldowns, which are irrelevant from now on
+        self.expirations.purge();
+        Ok(self
+            .expirations
+            .try_start(&Self::command_buckets(command_id, buckets))
+            .err())
+    }
+}
+
+/// Identifies the command in a [`CooldownStore`] by its qualified name
+fn cooldown_command_id<U, E>(
+    framework: &crate::Framework<U, E>,
+    command: crate::CommandRef<'_, U, E>,
+) -> String {
+    let kind = match command {
+        crate::CommandRef::Prefix(_) => "prefix",
+        crate::CommandRef::Application(crate::ApplicationCommand::Slash(_)) => "slash",
+        crate::CommandRef::Application(crate::ApplicationCommand::ContextMenu(_)) => "context_menu",
+    };
+    format!(
+        "{}:{}",
+        kind,
+        crate::qualified_command_name(framework, command)
+    )
+}
+
+/// What to do when a user invokes a command which is still on cooldown. Returned by
+/// [`crate::FrameworkOptions::on_cooldown`] and the per-command `on_cooldown` callbacks.
+#[derive(Debug, Clone)]
+pub enum CooldownFeedback {
+    /// Silently ignore the invocation
+    ///
+    /// Discord shows "Interaction failed" for ignored application commands
+    Ignore,
+    /// Respond with a message
+    Reply(String),
+    /// Respond with a message only the invoking user can see. Prefix commands can't send
+    /// ephemeral messages, so this is a normal reply for them
+    EphemeralReply(String),
+    /// React to the invocation message with the given emoji. Application commands have no message
+    /// to react to, so they get an ephemeral reply with the emoji instead
+    React(serenity::ReactionType),
+    /// Send a direct message to the invoking user
+    ///
+    /// Discord shows "Interaction failed" for application commands answered this way
+    DirectMessage(String),
+}
+
+/// Passed to `on_cooldown` callbacks when an invocation is rejected because of a cooldown
+pub struct CooldownHit<'a, U, E> {
+    /// Context of the rejected invocation
+    pub ctx: crate::Context<'a, U, E>,
+    /// The command which is on cooldown
+    pub command: crate::CommandRef<'a, U, E>,
+    /// How long until the command can be invoked again
+    pub remaining_cooldown: Duration,
+    /// If the invocation was rejected by a [`crate::RateLimitBucket`] rather than the cooldowns
+    /// of the command itself, the name of the bucket
+    pub bucket: Option<&'a str>,
+}
+
+/// The invocations which passed [`check_cooldowns`] and whose cooldowns haven't started yet, keyed
+/// by [`crate::Context::id`]
+#[derive(Debug, Default)]
+pub(crate) struct PendingCooldowns(parking_lot::Mutex<HashSet<u64>>);
+
+impl PendingCooldowns {
+    fn insert(&self, invocation_id: u64) {
+        self.0.lock().insert(invocation_id);
+    }
+
+    /// Returns whether the invocation was pending
+    fn remove(&self, invocation_id: u64) -> bool {
+        self.0.lock().remove(&invocation_id)
+    }
+}
+
+/// Checks the cooldowns and rate limit bucket of the invoked command. If it is on cooldown, asks
+/// the `on_cooldown` callback what to do, carries that out and returns false.
+///
+/// The cooldowns aren't started here, but only once the command actually runs, see
+/// [`_start_cooldowns`] and [`finish_cooldowns`]
+pub(crate) async fn check_cooldowns<'a, U, E>(
+    ctx: crate::Context<'a, U, E>,
+    command: crate::CommandRef<'a, U, E>,
+    cooldowns: &Cooldowns,
+    cooldown_key: Option<fn(crate::Context<'_, U, E>) -> CooldownKey>,
+    bucket: Option<&'a str>,
+    on_cooldown: Option<
+        for<'b> fn(CooldownHit<'b, U, E>) -> crate::BoxFuture<'b, CooldownFeedback>,
+    >,
+) -> bool {
+    let rejection = match remaining_cooldown(ctx, command, cooldowns, cooldown_key).await {
+        Some(remaining_cooldown) => Some((remaining_cooldown, None)),
+        None => bucket.and_then(|bucket| {
+            let remaining_cooldown = crate::bucket_retry_after(ctx, bucket)?;
+            Some((remaining_cooldown, Some(bucket)))
+        }),
+    };
+    let (remaining_cooldown, bucket) = match rejection {
+        Some(x) => x,
+        None => {
+            ctx.framework().pending_cooldowns().insert(ctx.id());
+            return true;
+        }
+    };
+    #[cfg(feature = "tracing")]
+    tracing::info!(?remaining_cooldown, ?bucket, "command on cooldown");
+
+    let on_cooldown = on_cooldown.unwrap_or(ctx.framework().options().on_cooldown);
+    let feedback = on_cooldown(CooldownHit {
+        ctx,
+        command,
+        remaining_cooldown,
+        bucket,
+    })
+    .await;
+    if let Err(e) = send_cooldown_feedback(ctx, feedback).await {
+        println!("Warning: couldn't send cooldown feedback: {}", e);
+    }
+    false
+}
+
+/// Returns the remaining cooldown of the command, if it's on cooldown
+async fn remaining_cooldown<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    command: crate::CommandRef<'_, U, E>,
+    cooldowns: &Cooldowns,
+    cooldown_key: Option<fn(crate::Context<'_, U, E>) -> CooldownKey>,
+) -> Option<Duration> {
+    let key = cooldown_key.map(|cooldown_key| cooldown_key(ctx));
+    match &ctx.framework().options().cooldown_store {
+        Some(store) => {
+            let buckets = cooldowns.buckets(ctx, key.as_ref());
+            if buckets.is_empty() {
+                return None;
+            }
+            match store
+                .remaining_cooldown(&cooldown_command_id(ctx.framework(), command), &buckets)
+                .await
+            {
+                Ok(remaining_cooldown) => remaining_cooldown,
+                Err(e) => {
+                    println!("Warning: couldn't check cooldowns: {}", e);
+                    None
+                }
+            }
+        }
+        None => cooldowns.remaining_cooldown_with_key(ctx, key.as_ref()),
+    }
+}
+
+/// Starts the cooldowns of the invoked command and records a use of its rate limit bucket, if the
+/// invocation passed [`check_cooldowns`] and they haven't been started yet.
+///
+/// Called by the command macro once the arguments are parsed, so that invocations with invalid
+/// arguments don't put the command on cooldown.
+#[doc(hidden)]
+pub async fn _start_cooldowns<U, E>(ctx: crate::Context<'_, U, E>) {
+    if !ctx.framework().pending_cooldowns().remove(ctx.id()) {
+        return;
+    }
+
+    let (command, cooldowns, cooldown_key, bucket) = match ctx {
+        crate::Context::Prefix(prefix_ctx) => match prefix_ctx.command {
+            Some(command) => (
+                crate::CommandRef::Prefix(command),
+                &command.options.cooldowns,
+                command.options.cooldown_key,
+                command.options.bucket,
+            ),
+            None => return,
+        },
+        crate::Context::Application(application_ctx) => (
+            crate::CommandRef::Application(application_ctx.command),
+            &application_ctx.command.options().cooldowns,
+            application_ctx.command.options().cooldown_key,
+            application_ctx.command.options().bucket,
+        ),
+    };
+    if let Some(bucket) = bucket {
+        crate::record_bucket_use(ctx, bucket);
+    }
+    let key = cooldown_key.map(|cooldown_key| cooldown_key(ctx));
+    match &ctx.framework().options().cooldown_store {
+        Some(store) => {
+            let buckets = cooldowns.buckets(ctx, key.as_ref());
+            if buckets.is_empty() {
+                return;
+            }
+            // A concurrent invocation may have started the cooldown since the check; this one
+            // runs regardless
+            if let Err(e) = store
+                .try_start_cooldown(&cooldown_command_id(ctx.framework(), command), &buckets)
+                .await
+            {
+                println!("Warning: couldn't start cooldowns: {}", e);
+            }
+        }
+        None => cooldowns.start_cooldown_with_key(ctx, key.as_ref()),
+    }
+}
+
+/// Called by the dispatch code after the command returned. Commands which didn't start their
+/// cooldowns via [`_start_cooldowns`], like manually implemented ones, start them here if they
+/// succeeded
+pub(crate) async fn finish_cooldowns<U, E>(ctx: crate::Context<'_, U, E>, succeeded: bool) {
+    if succeeded {
+        _start_cooldowns(ctx).await;
+    } else {
+        ctx.framework().pending_cooldowns().remove(ctx.id());
+    }
+}
+
+/// Forgets the expired cooldowns of all commands of the framework and the expired uses of its rate
+/// limit buckets
+pub(crate) fn purge_cooldowns<U, E>(framework: &crate::Framework<U, E>) {
+    for bucket in framework.options().rate_limit_buckets.values() {
+        bucket.purge();
+    }
+    for (_, command) in framework.command_paths() {
+        match command {
+            crate::CommandRef::Prefix(command) => command.options.cooldowns.purge(),
+            crate::CommandRef::Application(command) => command.options().cooldowns.purge(),
+        }
+    }
+}
+
+async fn send_cooldown_feedback<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    feedback: CooldownFeedback,
+) -> Result<(), serenity::Error> {
+    match feedback {
+        CooldownFeedback::Ignore => {}
+        CooldownFeedback::Reply(text) => {
+            ctx.say(text).await?;
+        }
+        CooldownFeedback::EphemeralReply(text) => {
+            crate::send_reply(ctx, |f| f.content(text).ephemeral(true)).await?;
+        }
+        CooldownFeedback::React(reaction) => match ctx {
+            crate::Context::Prefix(ctx) => {
+                ctx.msg.react(ctx.discord, reaction).await?;
+            }
+            crate::Context::Application(_) => {
+                crate::send_reply(ctx, |f| f.content(reaction.to_string()).ephemeral(true)).await?;
+            }
+        },
+        CooldownFeedback::DirectMessage(text) => {
+            ctx.author()
+                .direct_message(ctx.discord(), |f| f.content(text))
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[test]
+fn test_purge_cooldowns() {
+    let cooldowns = Cooldowns::new(CooldownConfig {
+        user: Some(Duration::from_secs(60)),
+        ..Default::default()
+    });
+    let user = CooldownBucket::User(serenity::UserId(1));
+    cooldowns.expirations.start(&[
+        (user.clone(), Duration::from_secs(60)),
+        (CooldownBucket::Global, Duration::from_secs(0)),
+    ]);
+
+    cooldowns.purge();
+    let expirations = cooldowns.expirations.0.lock();
+    assert!(expirations.contains_key(&user));
+    assert!(!expirations.contains_key(&CooldownBucket::Global));
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn test_in_memory_cooldown_store() {
+    let store = InMemoryCooldownStore::new();
+    let user = CooldownBucket::User(serenity::UserId(1));
+    let other_user = CooldownBucket::User(serenity::UserId(2));
+    let buckets = [(user.clone(), Duration::from_secs(60))];
+
+    assert_eq!(
+        store
+            .try_start_cooldown("slash:ban", &buckets)
+            .await
+            .unwrap(),
+        None
+    );
+    let remaining = store
+        .try_start_cooldown("slash:ban", &buckets)
+        .await
+        .unwrap();
+    assert!(remaining.unwrap() > Duration::from_secs(50));
+    assert!(store
+        .remaining_cooldown("slash:ban", &buckets)
+        .await
+        .unwrap()
+        .is_some());
+
+    // Other commands and buckets are unaffected
+    assert_eq!(
+        store
+            .try_start_cooldown("prefix:ban", &buckets)
+            .await
+            .unwrap(),
+        None
+    );
+    let other_buckets = [(other_user, Duration::from_secs(60))];
+    assert_eq!(
+        store
+            .try_start_cooldown("slash:ban", &other_buckets)
+            .await
+            .unwrap(),
+        None
+    );
+
+    // Expired cooldowns don't count
+    let short_buckets = [(CooldownBucket::Global, Duration::from_secs(0))];
+    assert_eq!(
+        store
+            .try_start_cooldown("slash:kick", &short_buckets)
+            .await
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        store
+            .try_start_cooldown("slash:kick", &short_buckets)
+            .await
+            .unwrap(),
+        None
+    );
+}
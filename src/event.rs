@@ -1,4 +1,5 @@
-//! Provides a utility EventHandler that generates [`Event`] enum instances for incoming events.
+//! Provides a utility EventHandler that generates [`Event`] enum instances for incoming events,
+//! and the [`EventSubscriber`] trait to handle them with one method per event type.
 
 use crate::BoxFuture;
 use serde_json::Value;
@@ -52,6 +53,75 @@ macro_rules! event {
                 }
             }
         }
+
+        /// Receives Discord events through one method per event type, as an alternative to
+        /// matching on [`Event`] in [`crate::FrameworkOptions::listener`]. All methods do nothing
+        /// by default, so implementors only override the events they're interested in.
+        ///
+        /// Register subscribers in [`crate::FrameworkOptions::event_subscribers`]. Each subscriber
+        /// receives every event, after the listener. Errors are passed to
+        /// [`crate::FrameworkOptions::on_error`] with [`crate::ErrorContext::Listener`].
+        ///
+        /// ```rust
+        /// # type Error = Box<dyn std::error::Error + Send + Sync>;
+        /// use poise::serenity_prelude as serenity;
+        ///
+        /// struct WelcomeMessages;
+        ///
+        /// #[poise::async_trait]
+        /// impl poise::EventSubscriber<(), Error> for WelcomeMessages {
+        ///     async fn guild_member_addition(
+        ///         &self,
+        ///         ctx: &serenity::Context,
+        ///         _: &poise::Framework<(), Error>,
+        ///         _: &(),
+        ///         _: &serenity::GuildId,
+        ///         new_member: &serenity::Member,
+        ///     ) -> Result<(), Error> {
+        ///         new_member.user.direct_message(ctx, |m| m.content("Welcome!")).await?;
+        ///         Ok(())
+        ///     }
+        /// }
+        ///
+        /// let mut options = poise::FrameworkOptions::<(), Error>::default();
+        /// options.event_subscribers.push(Box::new(WelcomeMessages));
+        /// ```
+        #[async_trait::async_trait]
+        pub trait EventSubscriber<U, E>: Send + Sync {
+            $(
+                #[allow(missing_docs)]
+                async fn $fn_name $(<$lt2>)? (
+                    &self,
+                    ctx: &serenity::prelude::Context,
+                    framework: &crate::Framework<U, E>,
+                    data: &U,
+                    $( $arg_name: &$arg_type, )*
+                ) -> Result<(), E>
+                where
+                    U: Send + Sync,
+                {
+                    let _ = (ctx, framework, data, $( $arg_name, )* );
+                    Ok(())
+                }
+            )*
+        }
+
+        /// Calls the method of the subscriber which corresponds to the event
+        pub(crate) async fn dispatch_to_subscriber<U: Send + Sync, E>(
+            subscriber: &dyn EventSubscriber<U, E>,
+            ctx: &serenity::prelude::Context,
+            event: &Event<'_>,
+            framework: &crate::Framework<U, E>,
+            data: &U,
+        ) -> Result<(), E> {
+            match event {
+                $(
+                    Event::$variant_name { $( $arg_name, )* } => {
+                        subscriber.$fn_name(ctx, framework, data, $( $arg_name, )* ).await
+                    }
+                )*
+            }
+        }
     };
 }
 
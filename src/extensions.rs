@@ -0,0 +1,76 @@
+//! State stored in the framework by type, next to the user data, see [`Extensions`]
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+
+/// A map holding at most one value of each type, added with
+/// [`crate::FrameworkBuilder::extension`] and accessed with [`crate::Context::data_ext`].
+///
+/// The user data type `U` belongs to the bot, so libraries which ship poise commands can't add
+/// their own state to it, and requiring trait bounds on it is awkward. Instead, they can store
+/// their state here under a type they own.
+///
+/// ```rust
+/// struct MusicState {
+///     volume: std::sync::atomic::AtomicU8,
+/// }
+///
+/// let mut extensions = poise::Extensions::default();
+/// extensions.insert(MusicState { volume: 50.into() });
+/// assert!(extensions.get::<MusicState>().is_some());
+/// assert!(extensions.get::<String>().is_none());
+/// ```
+#[derive(Default)]
+pub struct Extensions {
+    values: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+}
+
+impl std::fmt::Debug for Extensions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Extensions")
+            .field("len", &self.values.len())
+            .finish()
+    }
+}
+
+impl Extensions {
+    /// Stores the value, returning the previous value of the same type, if any
+    pub fn insert<T: Any + Send + Sync>(&mut self, value: T) -> Option<T> {
+        let previous = self.values.insert(TypeId::of::<T>(), Box::new(value))?;
+        previous.downcast().ok().map(|previous| *previous)
+    }
+
+    /// The stored value of the given type, if any
+    pub fn get<T: Any + Send + Sync>(&self) -> Option<&T> {
+        self.values.get(&TypeId::of::<T>())?.downcast_ref()
+    }
+
+    /// The stored value of the given type, mutably, if any
+    pub fn get_mut<T: Any + Send + Sync>(&mut self) -> Option<&mut T> {
+        self.values.get_mut(&TypeId::of::<T>())?.downcast_mut()
+    }
+
+    /// Removes and returns the stored value of the given type, if any
+    pub fn remove<T: Any + Send + Sync>(&mut self) -> Option<T> {
+        let value = self.values.remove(&TypeId::of::<T>())?;
+        value.downcast().ok().map(|value| *value)
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_extensions() {
+    #[derive(Debug, PartialEq)]
+    struct Counter(u32);
+
+    let mut extensions = Extensions::default();
+    assert_eq!(extensions.insert(Counter(1)), None);
+    assert_eq!(extensions.insert(Counter(2)), Some(Counter(1)));
+    extensions.insert("text");
+
+    extensions.get_mut::<Counter>().unwrap().0 += 1;
+    assert_eq!(extensions.get::<Counter>(), Some(&Counter(3)));
+    assert_eq!(extensions.get::<&str>(), Some(&"text"));
+    assert_eq!(extensions.remove::<Counter>(), Some(Counter(3)));
+    assert_eq!(extensions.get::<Counter>(), None);
+}
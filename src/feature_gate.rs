@@ -0,0 +1,174 @@
+//! Enabling commands only for some guilds or users, see [`FeatureGate`]
+
+use crate::serenity_prelude as serenity;
+use std::collections::{HashMap, HashSet};
+
+/// Decides at dispatch time whether a command is available to the invoking user, used by
+/// [`crate::FrameworkOptions::feature_gate`]. Meant for gradual rollouts and beta commands.
+///
+/// Both prefix and application command dispatch ask the gate before running a command. Commands
+/// which are gated off are hidden in the help builtins, and invoking them calls the
+/// [`crate::FrameworkOptions::on_command_gated`] callback instead of running the command; no
+/// error is passed to [`crate::FrameworkOptions::on_error`].
+///
+/// Implement this trait to read the rollouts from a database or a feature flag service.
+/// [`InMemoryFeatureGate`] is provided for rollouts configured in code.
+#[async_trait::async_trait]
+pub trait FeatureGate: Send + Sync {
+    /// Whether the given command is enabled for the given user in the given guild. The command is
+    /// identified by its qualified name, like `mod ban` for subcommands.
+    ///
+    /// This is called for every command invocation and for every command listed in the help, so
+    /// it should be fast. Errors can't be reported from here; implementations should log them and
+    /// decide whether to fail open or closed.
+    async fn is_enabled(
+        &self,
+        command_name: &str,
+        guild_id: Option<serenity::GuildId>,
+        user_id: serenity::UserId,
+    ) -> bool;
+}
+
+/// Who a gated command is enabled for, see [`InMemoryFeatureGate`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeatureRollout {
+    /// The command is enabled for everyone in these guilds
+    pub guilds: HashSet<serenity::GuildId>,
+    /// The command is enabled for these users everywhere
+    pub users: HashSet<serenity::UserId>,
+    /// The command is enabled for this percentage of all users, 0-100. Which users are included
+    /// is derived from the user ID and the command name, so it stays the same across invocations
+    /// and restarts, raising the percentage only adds users, and different commands are rolled out
+    /// to different users
+    pub percentage: u8,
+}
+
+/// A stable value from 0 to 99 for the given user and command, see
+/// [`FeatureRollout::percentage`]. Uses FNV-1a, since std's hashers may change between Rust
+/// releases
+fn rollout_position(command_name: &str, user_id: serenity::UserId) -> u64 {
+    let user_id_bytes = user_id.0.to_le_bytes();
+    let bytes = command_name.bytes().chain(user_id_bytes.iter().copied());
+    let hash = bytes.fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    hash % 100
+}
+
+impl FeatureRollout {
+    /// Whether this rollout of the given command includes the given user in the given guild
+    pub fn includes(
+        &self,
+        command_name: &str,
+        guild_id: Option<serenity::GuildId>,
+        user_id: serenity::UserId,
+    ) -> bool {
+        guild_id.map_or(false, |guild_id| self.guilds.contains(&guild_id))
+            || self.users.contains(&user_id)
+            || rollout_position(command_name, user_id) < u64::from(self.percentage)
+    }
+}
+
+/// A [`FeatureGate`] which keeps the rollouts in memory. Commands without a rollout are enabled
+/// for everyone
+#[derive(Debug, Default)]
+pub struct InMemoryFeatureGate {
+    rollouts: parking_lot::RwLock<HashMap<String, FeatureRollout>>,
+}
+
+impl InMemoryFeatureGate {
+    /// Create a gate in which all commands are enabled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Restricts the command with the given qualified name to the users included in the rollout,
+    /// replacing any previous rollout of the command
+    pub fn set_rollout(&self, command_name: impl Into<String>, rollout: FeatureRollout) {
+        self.rollouts.write().insert(command_name.into(), rollout);
+    }
+
+    /// Enables the given command for everyone
+    pub fn remove_rollout(&self, command_name: &str) {
+        self.rollouts.write().remove(command_name);
+    }
+}
+
+#[async_trait::async_trait]
+impl FeatureGate for InMemoryFeatureGate {
+    async fn is_enabled(
+        &self,
+        command_name: &str,
+        guild_id: Option<serenity::GuildId>,
+        user_id: serenity::UserId,
+    ) -> bool {
+        match self.rollouts.read().get(command_name) {
+            Some(rollout) => rollout.includes(command_name, guild_id, user_id),
+            None => true,
+        }
+    }
+}
+
+/// Whether the given command is gated off for the invoking user by
+/// [`crate::FrameworkOptions::feature_gate`]
+pub(crate) async fn is_gated_off<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    command: crate::CommandRef<'_, U, E>,
+) -> bool {
+    let gate = match &ctx.framework().options().feature_gate {
+        Some(x) => x,
+        None => return false,
+    };
+    let command_name = crate::qualified_command_name(ctx.framework(), command);
+    !gate
+        .is_enabled(&command_name, ctx.guild_id(), ctx.author().id)
+        .await
+}
+
+/// Whether the invoked command is gated off for the invoking user. If so, calls
+/// [`crate::FrameworkOptions::on_command_gated`]
+pub(crate) async fn check_feature_gate<'a, U, E>(
+    ctx: crate::Context<'a, U, E>,
+    command: crate::CommandRef<'a, U, E>,
+) -> bool {
+    let gated_off = is_gated_off(ctx, command).await;
+    if gated_off {
+        (ctx.framework().options().on_command_gated)(ctx).await;
+    }
+    gated_off
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn test_in_memory_feature_gate() {
+    let gate = InMemoryFeatureGate::new();
+    let guild = Some(serenity::GuildId(1));
+    let beta_tester = serenity::UserId(205);
+    let other_user = serenity::UserId(150);
+
+    gate.set_rollout(
+        "beta",
+        FeatureRollout {
+            users: std::iter::once(beta_tester).collect(),
+            percentage: 10,
+            ..Default::default()
+        },
+    );
+    assert!(gate.is_enabled("beta", guild, beta_tester).await);
+    assert!(!gate.is_enabled("beta", guild, other_user).await);
+    // User 214 is within the first 10 percent of `beta`, but not of `alpha`
+    let lucky_user = serenity::UserId(214);
+    assert!(gate.is_enabled("beta", None, lucky_user).await);
+    gate.set_rollout(
+        "alpha",
+        FeatureRollout {
+            percentage: 10,
+            ..Default::default()
+        },
+    );
+    assert!(!gate.is_enabled("alpha", None, lucky_user).await);
+    assert!(gate.is_enabled("stable", guild, other_user).await);
+
+    gate.remove_rollout("beta");
+    assert!(gate.is_enabled("beta", guild, other_user).await);
+}
@@ -31,6 +31,10 @@ pub struct FrameworkBuilder<U, E> {
         crate::CommandDefinition<U, E>,
         Box<dyn FnOnce(&mut crate::CommandBuilder<U, E>) -> &mut crate::CommandBuilder<U, E>>,
     )>,
+    extensions: crate::Extensions,
+    background_tasks: Vec<crate::BackgroundTask<U, E>>,
+    ready_hooks: Vec<crate::ReadyHook<U, E>>,
+    command_groups: Vec<crate::CommandGroup<U, E>>,
 }
 
 impl<U, E> Default for FrameworkBuilder<U, E> {
@@ -42,6 +46,10 @@ impl<U, E> Default for FrameworkBuilder<U, E> {
             token: Default::default(),
             intents: Default::default(),
             commands: Default::default(),
+            extensions: Default::default(),
+            background_tasks: Default::default(),
+            ready_hooks: Default::default(),
+            command_groups: Default::default(),
         }
     }
 }
@@ -105,6 +113,87 @@ impl<U, E> FrameworkBuilder<U, E> {
         self
     }
 
+    /// Mount a group of commands with its own settings, see [`crate::FrameworkOptions::mount`]
+    pub fn mount(mut self, group: crate::CommandGroup<U, E>) -> Self {
+        self.command_groups.push(group);
+        self
+    }
+
+    /// Store a value of a type which isn't part of the user data, for example the state of a
+    /// library which ships poise commands. Commands can access it with
+    /// [`crate::Context::data_ext`]. Replaces any previous value of the same type.
+    ///
+    /// See [`crate::Extensions`]
+    pub fn extension<T: std::any::Any + Send + Sync>(mut self, value: T) -> Self {
+        self.extensions.insert(value);
+        self
+    }
+
+    /// Run an async task on a schedule while the framework is running, see
+    /// [`crate::BackgroundTask`]
+    ///
+    /// ```rust
+    /// # type Error = Box<dyn std::error::Error + Send + Sync>;
+    /// async fn post_stats(ctx: poise::TaskContext<'_, (), Error>) -> Result<(), Error> {
+    ///     let bot = ctx.http.get_current_user().await?;
+    ///     println!("{} is still alive", bot.name);
+    ///     Ok(())
+    /// }
+    ///
+    /// poise::Framework::<(), Error>::build().background_task(
+    ///     "post_stats",
+    ///     poise::TaskSchedule::Every(std::time::Duration::from_secs(3600)),
+    ///     |ctx| Box::pin(post_stats(ctx)),
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the schedule is a [`crate::TaskSchedule::DailyAt`] with an hour of 24 or more or
+    /// a minute of 60 or more.
+    pub fn background_task(
+        mut self,
+        name: &'static str,
+        schedule: crate::TaskSchedule,
+        action: for<'a> fn(crate::TaskContext<'a, U, E>) -> BoxFuture<'a, Result<(), E>>,
+    ) -> Self {
+        if let crate::TaskSchedule::DailyAt { hour, minute } = schedule {
+            assert!(
+                hour < 24 && minute < 60,
+                "background task {:?} is scheduled at the invalid time {}:{:02}",
+                name,
+                hour,
+                minute,
+            );
+        }
+        self.background_tasks.push(crate::BackgroundTask {
+            name,
+            schedule,
+            action,
+        });
+        self
+    }
+
+    /// Run code once the bot is ready and the user data is set up, see [`crate::ReadyHook`]. Can be
+    /// called multiple times; the hooks run in the order they were added
+    ///
+    /// ```rust
+    /// # type Error = Box<dyn std::error::Error + Send + Sync>;
+    /// async fn log_guilds(ctx: poise::ReadyContext<'_, (), Error>) -> Result<(), Error> {
+    ///     println!("Ready in {} guilds", ctx.ready.guilds.len());
+    ///     Ok(())
+    /// }
+    ///
+    /// poise::Framework::<(), Error>::build().on_ready(|ctx| Box::pin(log_guilds(ctx)));
+    /// ```
+    pub fn on_ready(
+        mut self,
+        action: for<'a> fn(crate::ReadyContext<'a, U, E>) -> BoxFuture<'a, Result<(), E>>,
+    ) -> Self {
+        self.ready_hooks.push(crate::ReadyHook { action });
+        self
+    }
+
     /// Build the framework with the specified configuration.
     ///
     /// For more information, see [`FrameworkBuilder`]
@@ -131,6 +220,9 @@ impl<U, E> FrameworkBuilder<U, E> {
         for (command, meta_builder) in self.commands {
             options.command(command, meta_builder);
         }
+        for group in self.command_groups {
+            options.mount(group);
+        }
         options.owners.insert(application_info.owner.id);
 
         // Create framework with specified settings
@@ -140,9 +232,24 @@ impl<U, E> FrameworkBuilder<U, E> {
             bot_id: serenity::parse_token(&token)
                 .expect("Invalid bot token")
                 .bot_user_id,
+            command_index: super::command_index::CommandIndex::new(&options),
             options,
             application_id: serenity::ApplicationId(application_info.id.0),
             shard_manager: arc_swap::ArcSwapOption::from(None),
+            autocomplete_tracker: Default::default(),
+            started_at: std::time::Instant::now(),
+            metrics: Default::default(),
+            runtime_commands: Default::default(),
+            extensions: self.extensions,
+            background_tasks: self.background_tasks,
+            ready_hooks: self.ready_hooks,
+            readiness: Default::default(),
+            check_cache: Default::default(),
+            ready_shards: Default::default(),
+            shard_ready_notify: Default::default(),
+            in_flight: Default::default(),
+            delayed_typing: Default::default(),
+            pending_cooldowns: Default::default(),
         };
 
         // Create serenity client
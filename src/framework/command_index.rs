@@ -0,0 +1,183 @@
+//! Name lookup tables for the commands in [`crate::FrameworkOptions`], so that dispatch doesn't
+//! have to compare every command name and alias
+
+use std::collections::HashMap;
+
+/// Indices into [`crate::PrefixFrameworkOptions::commands`] and then into the subcommands of each
+/// level, leading to a single prefix command
+type PrefixCommandPath = Box<[usize]>;
+
+/// Maps command names to the commands from [`crate::FrameworkOptions`], built once when the
+/// framework is created. Commands added with [`super::Framework::add_command`] aren't indexed.
+pub(super) struct CommandIndex {
+    /// Name paths like `"ban"` or `"settings prefix"` to the prefix commands of that name, in order.
+    /// Aliases get their own entries. The names are normalized with [`prefix_key`]
+    prefix_commands: HashMap<Box<str>, Vec<PrefixCommandPath>>,
+    /// Top-level application command names to their indices in
+    /// [`crate::ApplicationFrameworkOptions::commands`], in order
+    application_commands: HashMap<Box<str>, Vec<usize>>,
+}
+
+/// The key of a prefix command name in the index: normalized according to
+/// [`crate::PrefixFrameworkOptions::case_insensitive_commands`] and
+/// [`crate::PrefixFrameworkOptions::normalize_unicode`], and preceded by the key of the parent
+/// command, if any
+pub(super) fn prefix_key<U, E>(
+    options: &crate::PrefixFrameworkOptions<U, E>,
+    parent_key: Option<&str>,
+    name: &str,
+) -> String {
+    #[cfg(feature = "unicode-normalization")]
+    let normalized_name = if options.normalize_unicode {
+        super::prefix::normalize(name)
+    } else if options.case_insensitive_commands {
+        name.to_ascii_lowercase()
+    } else {
+        name.to_owned()
+    };
+    #[cfg(not(feature = "unicode-normalization"))]
+    let normalized_name = if options.case_insensitive_commands {
+        name.to_ascii_lowercase()
+    } else {
+        name.to_owned()
+    };
+
+    match parent_key {
+        Some(parent_key) => format!("{} {}", parent_key, normalized_name),
+        None => normalized_name,
+    }
+}
+
+impl CommandIndex {
+    pub(super) fn new<U, E>(options: &crate::FrameworkOptions<U, E>) -> Self {
+        fn index_prefix_commands<U, E>(
+            index: &mut HashMap<Box<str>, Vec<PrefixCommandPath>>,
+            options: &crate::PrefixFrameworkOptions<U, E>,
+            commands: &[crate::PrefixCommandMeta<U, E>],
+            parent_key: Option<&str>,
+            parent_path: &[usize],
+        ) {
+            for (i, meta) in commands.iter().enumerate() {
+                let path = parent_path
+                    .iter()
+                    .copied()
+                    .chain(std::iter::once(i))
+                    .collect::<PrefixCommandPath>();
+
+                let names = std::iter::once(meta.command.name)
+                    .chain(meta.command.options.aliases.iter().copied());
+                for name in names {
+                    let entry = index
+                        .entry(prefix_key(options, parent_key, name).into_boxed_str())
+                        .or_default();
+                    // An alias may normalize to the command name
+                    if !entry.contains(&path) {
+                        entry.push(path.clone());
+                    }
+                }
+
+                let key = prefix_key(options, parent_key, meta.command.name);
+                index_prefix_commands(index, options, &meta.subcommands, Some(&key), &path);
+            }
+        }
+
+        let mut prefix_commands = HashMap::new();
+        index_prefix_commands(
+            &mut prefix_commands,
+            &options.prefix_options,
+            &options.prefix_options.commands,
+            None,
+            &[],
+        );
+
+        let mut application_commands = HashMap::<_, Vec<_>>::new();
+        for (i, command) in options.application_options.commands.iter().enumerate() {
+            application_commands
+                .entry(command.name().into())
+                .or_default()
+                .push(i);
+        }
+
+        Self {
+            prefix_commands,
+            application_commands,
+        }
+    }
+
+    /// The indexed prefix commands with the given key, see [`prefix_key`], in order
+    pub(super) fn prefix_commands<'a, U, E>(
+        &'a self,
+        options: &'a crate::PrefixFrameworkOptions<U, E>,
+        key: &str,
+    ) -> impl Iterator<Item = &'a crate::PrefixCommandMeta<U, E>> {
+        self.prefix_commands
+            .get(key)
+            .into_iter()
+            .flatten()
+            .filter_map(move |path| {
+                let (&first, rest) = path.split_first()?;
+                let mut meta = options.commands.get(first)?;
+                for &i in rest {
+                    meta = meta.subcommands.get(i)?;
+                }
+                Some(meta)
+            })
+    }
+
+    /// The indexed top-level application commands with the given name, in order
+    pub(super) fn application_commands<'a, U, E>(
+        &'a self,
+        options: &'a crate::ApplicationFrameworkOptions<U, E>,
+        name: &str,
+    ) -> impl Iterator<Item = &'a crate::ApplicationCommandTree<U, E>> {
+        self.application_commands
+            .get(name)
+            .into_iter()
+            .flatten()
+            .filter_map(move |&i| options.commands.get(i))
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_command_index() {
+    fn definition(
+        name: &'static str,
+        aliases: &'static [&'static str],
+    ) -> crate::CommandDefinition<(), String> {
+        crate::CommandDefinition {
+            prefix: Some(crate::PrefixCommand {
+                name,
+                action: |_, _| Box::pin(async { Ok(()) }),
+                options: crate::PrefixCommandOptions {
+                    aliases,
+                    ..Default::default()
+                },
+            }),
+            slash: None,
+            context_menu: None,
+        }
+    }
+
+    let mut options = crate::FrameworkOptions::<(), String>::default();
+    options.command(definition("ping", &["p"]), |f| f);
+    options.command(definition("settings", &[]), |f| {
+        f.subcommand(definition("prefix", &["pre"]), |f| f)
+    });
+    let index = CommandIndex::new(&options);
+
+    let names = |key: &str| {
+        index
+            .prefix_commands(&options.prefix_options, key)
+            .map(|meta| meta.command.name)
+            .collect::<Vec<_>>()
+    };
+    let key = |parent_key: Option<&str>, name: &str| {
+        prefix_key(&options.prefix_options, parent_key, name)
+    };
+    assert_eq!(names(&key(None, "PING")), ["ping"]);
+    assert_eq!(names(&key(None, "p")), ["ping"]);
+    assert_eq!(names(&key(Some("settings"), "pre")), ["prefix"]);
+    assert!(names(&key(None, "prefix")).is_empty());
+    assert!(names(&key(None, "pong")).is_empty());
+}
@@ -0,0 +1,171 @@
+//! Navigating the command tree by qualified names like `"mod ban"`, see
+//! [`super::Framework::find_command`] and [`super::Framework::command_paths`]
+
+/// All prefix commands, slash commands and context menu commands with their qualified names
+pub(super) fn command_paths<U, E>(
+    framework: &super::Framework<U, E>,
+) -> Vec<(String, crate::CommandRef<'_, U, E>)> {
+    fn prefix_paths<'a, U, E>(
+        framework: &super::Framework<U, E>,
+        paths: &mut Vec<(String, crate::CommandRef<'a, U, E>)>,
+        meta: &'a crate::PrefixCommandMeta<U, E>,
+        parent_path: Option<&str>,
+    ) {
+        let path = join(parent_path, meta.command.name);
+        paths.push((path.clone(), crate::CommandRef::Prefix(&meta.command)));
+        for subcommand in &meta.subcommands {
+            if !framework
+                .runtime_commands
+                .is_subcommand_removed(&subcommand.command)
+            {
+                prefix_paths(framework, paths, subcommand, Some(&path));
+            }
+        }
+    }
+
+    fn slash_paths<'a, U, E>(
+        paths: &mut Vec<(String, crate::CommandRef<'a, U, E>)>,
+        meta: &'a crate::SlashCommandMeta<U, E>,
+        parent_path: Option<&str>,
+    ) {
+        match meta {
+            crate::SlashCommandMeta::Command(command) => paths.push((
+                join(parent_path, command.name),
+                crate::CommandRef::Application(crate::ApplicationCommand::Slash(command)),
+            )),
+            crate::SlashCommandMeta::CommandGroup {
+                name, subcommands, ..
+            } => {
+                let path = join(parent_path, name);
+                for subcommand in subcommands {
+                    slash_paths(paths, subcommand, Some(&path));
+                }
+            }
+        }
+    }
+
+    let mut paths = Vec::new();
+    for meta in framework.prefix_commands() {
+        prefix_paths(framework, &mut paths, meta, None);
+    }
+    for command in framework.application_commands() {
+        match command {
+            crate::ApplicationCommandTree::Slash(meta) => slash_paths(&mut paths, meta, None),
+            crate::ApplicationCommandTree::ContextMenu(command) => paths.push((
+                command.name.to_owned(),
+                crate::CommandRef::Application(crate::ApplicationCommand::ContextMenu(command)),
+            )),
+        }
+    }
+    paths
+}
+
+/// The qualified name of the given command, i.e. the names of its parent commands and its own name
+/// separated by spaces. Aliases don't affect the qualified name.
+///
+/// Falls back to the plain command name for commands which aren't registered in the framework
+pub(crate) fn qualified_command_name<U, E>(
+    framework: &super::Framework<U, E>,
+    command: crate::CommandRef<'_, U, E>,
+) -> String {
+    let is_same_command = |other: crate::CommandRef<'_, U, E>| match (command, other) {
+        (crate::CommandRef::Prefix(a), crate::CommandRef::Prefix(b)) => std::ptr::eq(a, b),
+        (
+            crate::CommandRef::Application(crate::ApplicationCommand::Slash(a)),
+            crate::CommandRef::Application(crate::ApplicationCommand::Slash(b)),
+        ) => std::ptr::eq(a, b),
+        (
+            crate::CommandRef::Application(crate::ApplicationCommand::ContextMenu(a)),
+            crate::CommandRef::Application(crate::ApplicationCommand::ContextMenu(b)),
+        ) => std::ptr::eq(a, b),
+        _ => false,
+    };
+
+    command_paths(framework)
+        .into_iter()
+        .find(|&(_, other)| is_same_command(other))
+        .map_or_else(|| command.name().to_owned(), |(path, _)| path)
+}
+
+/// The qualified name of a command with the given parent
+fn join(parent_path: Option<&str>, name: &str) -> String {
+    match parent_path {
+        Some(parent_path) => format!("{} {}", parent_path, name),
+        None => name.to_owned(),
+    }
+}
+
+/// The prefix command with the given qualified name, matching aliases and
+/// [`crate::PrefixFrameworkOptions::case_insensitive_commands`]. If there is none, the slash
+/// command or context menu command of that name
+pub(super) fn find_command<'a, U, E>(
+    framework: &'a super::Framework<U, E>,
+    qualified_name: &str,
+) -> Option<crate::CommandRef<'a, U, E>> {
+    let case_insensitive = framework.options().prefix_options.case_insensitive_commands;
+    let mut names = qualified_name.split_whitespace();
+    let first_name = names.next()?;
+
+    let prefix_command = (|| {
+        let mut meta = framework
+            .prefix_commands()
+            .into_iter()
+            .find(|meta| meta.command.is_named(first_name, case_insensitive))?;
+        for name in names.clone() {
+            meta = meta.subcommands.iter().find(|meta| {
+                meta.command.is_named(name, case_insensitive)
+                    && !framework
+                        .runtime_commands
+                        .is_subcommand_removed(&meta.command)
+            })?;
+        }
+        Some(crate::CommandRef::Prefix(&meta.command))
+    })();
+    if prefix_command.is_some() {
+        return prefix_command;
+    }
+
+    let application_commands = framework.application_commands();
+    let slash_command = (|| {
+        let mut meta = application_commands
+            .iter()
+            .copied()
+            .find_map(|command| match command {
+                crate::ApplicationCommandTree::Slash(meta) if meta.name() == first_name => {
+                    Some(meta)
+                }
+                _ => None,
+            })?;
+        for name in names {
+            meta = match meta {
+                crate::SlashCommandMeta::CommandGroup { subcommands, .. } => subcommands
+                    .iter()
+                    .find(|subcommand| subcommand.name() == name)?,
+                crate::SlashCommandMeta::Command(_) => return None,
+            };
+        }
+        match meta {
+            crate::SlashCommandMeta::Command(command) => Some(crate::CommandRef::Application(
+                crate::ApplicationCommand::Slash(command),
+            )),
+            crate::SlashCommandMeta::CommandGroup { .. } => None,
+        }
+    })();
+    if slash_command.is_some() {
+        return slash_command;
+    }
+
+    // Context menu command names may contain spaces
+    application_commands
+        .into_iter()
+        .find_map(|command| match command {
+            crate::ApplicationCommandTree::ContextMenu(command)
+                if command.name == qualified_name.trim() =>
+            {
+                Some(crate::CommandRef::Application(
+                    crate::ApplicationCommand::ContextMenu(command),
+                ))
+            }
+            _ => None,
+        })
+}
@@ -0,0 +1,37 @@
+//! Typing indicators which only start if a command hasn't responded in time, see
+//! [`crate::PrefixCommandOptions::broadcast_typing_after`]
+
+use crate::serenity_prelude as serenity;
+
+/// The invocations with a pending or running delayed typing indicator, keyed by
+/// [`crate::Context::id`]
+#[derive(Default)]
+pub(super) struct DelayedTyping {
+    invocations: parking_lot::Mutex<std::collections::HashMap<u64, Option<serenity::Typing>>>,
+}
+
+impl DelayedTyping {
+    /// Registers an invocation whose typing indicator starts at [`Self::start`], unless it
+    /// responds before
+    pub(super) fn register(&self, invocation_id: u64) {
+        self.invocations.lock().insert(invocation_id, None);
+    }
+
+    /// Starts the typing indicator of the invocation, if it's registered and hasn't responded yet
+    pub(super) fn start(
+        &self,
+        invocation_id: u64,
+        start_typing: impl FnOnce() -> Option<serenity::Typing>,
+    ) {
+        if let Some(typing @ None) = self.invocations.lock().get_mut(&invocation_id) {
+            *typing = start_typing();
+        }
+    }
+
+    /// Stops the typing indicator of the invocation, if any, and prevents it from starting.
+    /// Called when the invocation responds or finishes
+    pub(super) fn stop(&self, invocation_id: u64) {
+        // Dropping the Typing stops the indicator
+        self.invocations.lock().remove(&invocation_id);
+    }
+}
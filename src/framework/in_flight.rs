@@ -0,0 +1,97 @@
+//! Keeps track of the running command invocations, see [`super::Framework::shutdown_graceful`]
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+tokio::task_local! {
+    /// Set while a tracked invocation runs, so that [`InFlightTracker::wait_idle`] called from
+    /// within a command doesn't wait for that command itself
+    static IN_INVOCATION: ();
+}
+
+/// Counts the running command invocations, and rejects new ones once shutdown began
+#[derive(Default)]
+pub(super) struct InFlightTracker {
+    shutting_down: AtomicBool,
+    count: AtomicUsize,
+    /// Notified whenever the count drops
+    finished: tokio::sync::Notify,
+}
+
+/// Counts an invocation as running while alive, see [`InFlightTracker::track`]
+struct InFlightGuard<'a>(&'a InFlightTracker);
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.0.count.fetch_sub(1, Ordering::SeqCst);
+        self.0.finished.notify_waiters();
+    }
+}
+
+impl InFlightTracker {
+    /// Runs the given invocation and counts it as running meanwhile. Returns None without running
+    /// it if shutdown began
+    pub(super) async fn track<F: std::future::Future>(&self, invocation: F) -> Option<F::Output> {
+        // Increment first, so that shutdown can't miss an invocation which passed the check
+        self.count.fetch_add(1, Ordering::SeqCst);
+        let _guard = InFlightGuard(self);
+        if self.shutting_down.load(Ordering::SeqCst) {
+            return None;
+        }
+        Some(IN_INVOCATION.scope((), invocation).await)
+    }
+
+    pub(super) fn begin_shutdown(&self) {
+        self.shutting_down.store(true, Ordering::SeqCst);
+    }
+
+    pub(super) fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::SeqCst)
+    }
+
+    /// Number of running invocations
+    pub(super) fn count(&self) -> usize {
+        self.count.load(Ordering::SeqCst)
+    }
+
+    /// Waits until no invocations are running. When called from within a tracked invocation, that
+    /// invocation itself isn't waited for, since it can't finish while waiting
+    pub(super) async fn wait_idle(&self) {
+        let own_invocations = if IN_INVOCATION.try_with(|_| ()).is_ok() {
+            1
+        } else {
+            0
+        };
+        loop {
+            // Created before checking, so that a notification in between isn't missed
+            let notified = self.finished.notified();
+            if self.count() <= own_invocations {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn test_in_flight_tracker() {
+    let tracker = InFlightTracker::default();
+    let (finish_first, first_finished) = tokio::sync::oneshot::channel::<()>();
+    let first = tracker.track(async {
+        let _: Result<_, _> = first_finished.await;
+    });
+    let second = tracker.track(async {
+        assert_eq!(tracker.count(), 2);
+        tracker.begin_shutdown();
+        assert!(tracker.track(async {}).await.is_none());
+
+        // Waits for the first invocation, but not for itself
+        let _ = finish_first.send(());
+        tracker.wait_idle().await;
+        assert_eq!(tracker.count(), 1);
+    });
+    let (first, second) = futures::future::join(first, second).await;
+    assert!(first.is_some() && second.is_some());
+    assert_eq!(tracker.count(), 0);
+    tracker.wait_idle().await;
+}
@@ -1,69 +1,165 @@
 //! The central Framework struct that ties everything together.
 
 // Prefix and slash specific implementation details
+mod command_index;
+mod command_paths;
+mod delayed_typing;
+mod in_flight;
 mod prefix;
+mod runtime_commands;
 mod slash;
+mod validate;
 
 mod builder;
 
 pub use builder::*;
+pub use validate::{CommandValidationError, CommandViolation};
 
 use crate::serenity::client::{bridge::gateway::ShardManager, Client};
 use crate::serenity_prelude as serenity;
 use crate::*;
 
-pub use prefix::dispatch_message;
+pub use prefix::{dispatch_message, dispatch_message_chain};
 
-async fn check_permissions<U, E>(
-    ctx: crate::Context<'_, U, E>,
-    required_permissions: serenity::Permissions,
-) -> bool {
-    if required_permissions.is_empty() {
-        return true;
-    }
+pub(crate) use command_paths::qualified_command_name;
 
-    let guild_id = match ctx.guild_id() {
-        Some(x) => x,
-        None => return true, // no permission checks in DMs
-    };
-
-    let guild = match ctx.discord().cache.guild(guild_id) {
-        Some(x) => x,
-        None => return false, // Guild not in cache
-    };
-
-    let channel = match guild.channels.get(&ctx.channel_id()) {
-        Some(serenity::Channel::Guild(channel)) => channel,
+/// Looks up the guild channel in the guild's channels and threads, or via HTTP if it's not cached
+async fn guild_channel(
+    ctx: &serenity::Context,
+    guild: &serenity::Guild,
+    channel_id: serenity::ChannelId,
+) -> Option<serenity::GuildChannel> {
+    match guild.channels.get(&channel_id) {
+        Some(serenity::Channel::Guild(channel)) => return Some(channel.clone()),
         Some(_other_channel) => {
             println!(
                 "Warning: guild message was supposedly sent in a non-guild channel. Denying invocation"
             );
-            return false;
+            return None;
+        }
+        None => {}
+    }
+
+    // Threads aren't part of the channel list
+    if let Some(thread) = guild.threads.iter().find(|thread| thread.id == channel_id) {
+        return Some(thread.clone());
+    }
+
+    match channel_id.to_channel(ctx).await {
+        Ok(serenity::Channel::Guild(channel)) => Some(channel),
+        _ => None,
+    }
+}
+
+/// Whether channels of the given type are threads, including forum posts
+pub(crate) fn is_thread(kind: serenity::ChannelType) -> bool {
+    matches!(
+        kind,
+        serenity::ChannelType::NewsThread
+            | serenity::ChannelType::PublicThread
+            | serenity::ChannelType::PrivateThread
+    )
+}
+
+/// The permissions in a thread, given the permissions in its parent channel. Sending messages in
+/// a thread requires SEND_MESSAGES_IN_THREADS instead of SEND_MESSAGES, and private threads are
+/// only visible to their members and to users who can manage threads
+fn thread_permissions(
+    parent_permissions: serenity::Permissions,
+    kind: serenity::ChannelType,
+    is_member: bool,
+) -> serenity::Permissions {
+    let mut permissions = parent_permissions;
+    if kind == serenity::ChannelType::PrivateThread
+        && !is_member
+        && !permissions.contains(serenity::Permissions::MANAGE_THREADS)
+        && !permissions.contains(serenity::Permissions::ADMINISTRATOR)
+    {
+        return serenity::Permissions::empty();
+    }
+    permissions.set(
+        serenity::Permissions::SEND_MESSAGES,
+        permissions.contains(serenity::Permissions::SEND_MESSAGES_IN_THREADS),
+    );
+    permissions
+}
+
+/// The permissions of the invoking user in the channel of the invocation, or None if they can't be
+/// determined.
+///
+/// Threads, including forum posts, don't have permission overwrites of their own, so the
+/// permissions are computed in their parent channel and adjusted with [`thread_permissions`].
+async fn user_permissions<U, E>(ctx: crate::Context<'_, U, E>) -> Option<serenity::Permissions> {
+    // Discord includes the invoker's permissions in the channel, thread-aware, in interactions
+    if let crate::Context::Application(ctx) = ctx {
+        if let Some(permissions) = ctx.interaction.member().and_then(|m| m.permissions) {
+            return Some(permissions);
         }
-        None => return false,
+    }
+
+    let guild_id = ctx.guild_id()?;
+    // Guild not in cache
+    let guild = ctx.discord().cache.guild(guild_id)?;
+
+    let channel = guild_channel(ctx.discord(), &guild, ctx.channel_id()).await?;
+    let (thread, channel) = if is_thread(channel.kind) {
+        let parent = guild_channel(ctx.discord(), &guild, channel.parent_id?).await?;
+        (Some(channel), parent)
+    } else {
+        (None, channel)
     };
 
     // If member not in cache (probably because presences intent is not enabled), retrieve via HTTP
     let member = match guild.members.get(&ctx.author().id) {
         Some(x) => x.clone(),
-        None => match ctx
+        None => ctx
             .discord()
             .http
             .get_member(guild_id.0, ctx.author().id.0)
             .await
-        {
-            Ok(member) => member,
-            Err(_) => return false,
-        },
+            .ok()?,
     };
 
-    match guild.user_permissions_in(channel, &member) {
-        Ok(perms) => perms.contains(required_permissions),
-        Err(_) => false,
+    let permissions = guild.user_permissions_in(&channel, &member).ok()?;
+    let thread = match thread {
+        Some(x) => x,
+        None => return Some(permissions),
+    };
+
+    // Thread members are only fetched where they matter
+    let is_member = thread.kind != serenity::ChannelType::PrivateThread
+        || permissions.contains(serenity::Permissions::MANAGE_THREADS)
+        || thread
+            .id
+            .get_thread_members(&ctx.discord().http)
+            .await
+            .map_or(false, |members| {
+                members
+                    .iter()
+                    .any(|member| member.user_id == Some(ctx.author().id))
+            });
+    Some(thread_permissions(permissions, thread.kind, is_member))
+}
+
+async fn check_permissions<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    required_permissions: serenity::Permissions,
+) -> bool {
+    if required_permissions.is_empty() {
+        return true;
+    }
+
+    if ctx.guild_id().is_none() {
+        return true; // no permission checks in DMs
+    }
+
+    match user_permissions(ctx).await {
+        Some(permissions) => permissions.contains(required_permissions),
+        None => false,
     }
 }
 
-async fn check_required_permissions_and_owners_only<U, E>(
+pub(crate) async fn check_required_permissions_and_owners_only<U, E>(
     ctx: crate::Context<'_, U, E>,
     required_permissions: serenity::Permissions,
     owners_only: bool,
@@ -79,6 +175,18 @@ async fn check_required_permissions_and_owners_only<U, E>(
     true
 }
 
+/// Status of a single shard, see [`Framework::shard_statuses`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShardStatus {
+    /// ID of the shard
+    pub shard_id: u64,
+    /// Whether the shard is connected to the gateway
+    pub stage: crate::serenity::gateway::ConnectionStage,
+    /// Time between the last heartbeat and its acknowledgement, or None if no heartbeat was
+    /// acknowledged yet
+    pub latency: Option<std::time::Duration>,
+}
+
 /// The main framework struct which stores all data and handles message and interaction dispatch.
 pub struct Framework<U, E> {
     user_data: once_cell::sync::OnceCell<U>,
@@ -101,6 +209,58 @@ pub struct Framework<U, E> {
     options: FrameworkOptions<U, E>,
     application_id: serenity::ApplicationId,
     shard_manager: arc_swap::ArcSwapOption<tokio::sync::Mutex<ShardManager>>,
+    /// Keeps track of running autocomplete callbacks to cancel them when superseded
+    autocomplete_tracker: slash::AutocompleteTracker,
+    started_at: std::time::Instant,
+    metrics: crate::CommandMetrics,
+    runtime_commands: runtime_commands::RuntimeCommands<U, E>,
+    command_index: command_index::CommandIndex,
+    extensions: crate::Extensions,
+    /// The shards which received a Ready event, see [`Self::wait_until_shards_ready`]
+    ready_shards: parking_lot::Mutex<ReadyShards>,
+    /// Notified whenever a shard becomes ready
+    shard_ready_notify: tokio::sync::Notify,
+    in_flight: in_flight::InFlightTracker,
+    delayed_typing: delayed_typing::DelayedTyping,
+    pending_cooldowns: crate::PendingCooldowns,
+    background_tasks: Vec<crate::BackgroundTask<U, E>>,
+    ready_hooks: Vec<crate::ReadyHook<U, E>>,
+    /// Set once the user data is set up and the ready hooks ran, see [`Self::wait_until_ready`]
+    readiness: crate::Readiness,
+    check_cache: crate::CheckCache,
+}
+
+/// See [`Framework::wait_until_shards_ready`]
+#[derive(Debug, Default)]
+struct ReadyShards {
+    /// The shards which received a Ready event
+    shard_ids: std::collections::HashSet<u64>,
+    /// The shards started by this process, set in [`Framework::start`]. With multiple processes
+    /// sharing the shards of a bot, this is only a subset of all shards
+    started_shard_ids: std::collections::HashSet<u64>,
+}
+
+impl ReadyShards {
+    fn all_ready(&self) -> bool {
+        !self.started_shard_ids.is_empty() && self.started_shard_ids.is_subset(&self.shard_ids)
+    }
+}
+
+/// Passes the events which serenity doesn't model to
+/// [`FrameworkOptions::unknown_event_listener`]
+struct UnknownEventForwarder<U, E>(std::sync::Arc<Framework<U, E>>);
+
+#[serenity::async_trait]
+impl<U: Send + Sync, E: Send> serenity::RawEventHandler for UnknownEventForwarder<U, E> {
+    async fn raw_event(&self, ctx: serenity::Context, event: serenity::model::event::Event) {
+        let unknown_event_listener = match self.0.options.unknown_event_listener {
+            Some(x) => x,
+            None => return,
+        };
+        if let serenity::model::event::Event::Unknown(event) = &event {
+            unknown_event_listener(&ctx, &event.kind, &event.value, &self.0).await;
+        }
+    }
 }
 
 impl<U, E> Framework<U, E> {
@@ -144,9 +304,24 @@ impl<U, E> Framework<U, E> {
             user_data: once_cell::sync::OnceCell::new(),
             user_data_setup: std::sync::Mutex::new(Some(Box::new(user_data_setup))),
             bot_id,
+            command_index: command_index::CommandIndex::new(&options),
             options,
             application_id,
             shard_manager: arc_swap::ArcSwapOption::from(None),
+            autocomplete_tracker: slash::AutocompleteTracker::default(),
+            started_at: std::time::Instant::now(),
+            metrics: Default::default(),
+            runtime_commands: Default::default(),
+            extensions: Default::default(),
+            ready_shards: Default::default(),
+            shard_ready_notify: Default::default(),
+            in_flight: Default::default(),
+            delayed_typing: Default::default(),
+            pending_cooldowns: Default::default(),
+            background_tasks: Vec::new(),
+            ready_hooks: Vec::new(),
+            readiness: Default::default(),
+            check_cache: Default::default(),
         }
     }
 
@@ -154,6 +329,9 @@ impl<U, E> Framework<U, E> {
     ///
     /// Takes a `serenity::ClientBuilder`, in which you need to supply the bot token, as well as
     /// any gateway intents.
+    ///
+    /// Fails without connecting if a command uses a rate limit bucket which isn't registered in
+    /// [`FrameworkOptions::rate_limit_buckets`].
     pub async fn start(self, builder: serenity::ClientBuilder<'_>) -> Result<(), serenity::Error>
     where
         U: Send + Sync + 'static,
@@ -161,6 +339,21 @@ impl<U, E> Framework<U, E> {
     {
         let application_id = self.application_id;
 
+        if let Err(errors) = self.validate() {
+            for error in errors {
+                println!("Warning: invalid application command {}", error);
+            }
+        }
+        let bucket_errors = validate::validate_rate_limit_buckets(&self);
+        if !bucket_errors.is_empty() {
+            for error in bucket_errors {
+                println!("Error: command {}", error);
+            }
+            return Err(serenity::Error::Other(
+                "a command uses an unknown rate limit bucket",
+            ));
+        }
+
         let self_1 = std::sync::Arc::new(self);
         let self_2 = self_1.clone();
         let self_3 = self_1.clone();
@@ -168,8 +361,10 @@ impl<U, E> Framework<U, E> {
         let edit_track_cache_purge_task = tokio::spawn(async move {
             loop {
                 if let Some(edit_tracker) = &self_1.options.prefix_options.edit_tracker {
-                    edit_tracker.write().purge();
+                    crate::prefix::purge_tracked_messages(edit_tracker).await;
                 }
+                self_1.check_cache.purge();
+                crate::purge_cooldowns(&self_1);
                 // not sure if the purging interval should be configurable
                 tokio::time::sleep(std::time::Duration::from_secs(60)).await;
             }
@@ -182,27 +377,205 @@ impl<U, E> Framework<U, E> {
             }) as _
         });
 
-        let mut client: Client = builder
+        let mut builder = builder
             .application_id(application_id.0)
-            .event_handler(event_handler)
-            .await?;
+            .event_handler(event_handler);
+        if self_3.options.unknown_event_listener.is_some() {
+            builder = builder.raw_event_handler(UnknownEventForwarder(self_3.clone()));
+        }
+        let mut client: Client = builder.await?;
 
         self_3
             .shard_manager
             .store(Some(client.shard_manager.clone()));
 
-        client.start().await?;
+        let background_tasks = (0..self_3.background_tasks.len())
+            .map(|i| {
+                let framework = self_3.clone();
+                let cache_and_http = client.cache_and_http.clone();
+                tokio::spawn(async move {
+                    let task = &framework.background_tasks[i];
+                    framework.run_background_task(task, &cache_and_http).await;
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // `Client::start` runs a single shard with ID 0
+        self_3.ready_shards.lock().started_shard_ids = std::iter::once(0).collect();
+        let result = client.start().await;
 
         edit_track_cache_purge_task.abort();
+        for background_task in background_tasks {
+            background_task.abort();
+        }
+        result?;
 
         Ok(())
     }
 
-    /// Return the stored framework options, including commands.
+    /// The values added with [`FrameworkBuilder::extension`]. See [`crate::Extensions`]
+    pub fn extensions(&self) -> &crate::Extensions {
+        &self.extensions
+    }
+
+    /// Checks the current application commands against Discord's constraints, like name and
+    /// description lengths, option counts and order, and duplicate names. Commands which violate
+    /// them would be rejected as a whole with an opaque HTTP 400 error when registering.
+    ///
+    /// This runs automatically when the framework is started, where the violations are logged,
+    /// and in the command registration builtins, which refuse to register invalid commands.
+    pub fn validate(&self) -> Result<(), Vec<CommandValidationError>> {
+        let commands = crate::builtins::application_commands_json(self.application_commands());
+        let errors = validate::validate_commands_json(&commands);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Return the stored framework options, including the commands the framework was created
+    /// with. For the commands currently in use, see [`Self::prefix_commands`] and
+    /// [`Self::application_commands`].
     pub fn options(&self) -> &FrameworkOptions<U, E> {
         &self.options
     }
 
+    /// Add a command while the framework is running, for example from a plugin system. Works like
+    /// [`FrameworkOptions::command`]; the command is dispatched and listed in help from now on.
+    ///
+    /// New application commands still need to be registered on Discord's side:
+    /// ```rust,no_run
+    /// # type Error = Box<dyn std::error::Error + Send + Sync>;
+    /// # #[poise::command(slash_command)]
+    /// # async fn ping(ctx: poise::Context<'_, (), Error>) -> Result<(), Error> { Ok(()) }
+    /// # async fn _f(ctx: poise::Context<'_, (), Error>) -> Result<(), Error> {
+    /// let framework = ctx.framework();
+    /// framework.add_command(ping(), |f| f);
+    /// let commands = framework.application_commands();
+    /// poise::builtins::sync_commands(&ctx.discord().http, None, commands).await?;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// Commands added or removed at runtime stay allocated until the framework is dropped, so this
+    /// isn't meant for adding and removing commands in a loop.
+    pub fn add_command(
+        &self,
+        definition: crate::CommandDefinition<U, E>,
+        meta_builder: impl FnOnce(&mut crate::CommandBuilder<U, E>) -> &mut crate::CommandBuilder<U, E>,
+    ) {
+        let (prefix_command, application_commands) =
+            crate::CommandBuilder::build(definition, meta_builder);
+        self.runtime_commands
+            .add(prefix_command, application_commands);
+    }
+
+    /// Remove the command with the given qualified name, resolved like in [`Self::find_command`],
+    /// no matter if it was passed in [`FrameworkOptions`] or added with [`Self::add_command`].
+    /// Returns false if there was no such command.
+    ///
+    /// For a top-level name, the prefix, slash and context menu commands of that name are all
+    /// removed, and so are slash command groups. Prefix subcommands like `"mod ban"` are removed
+    /// on their own. Slash subcommands can't be removed on their own, since Discord registers a
+    /// slash command with all its subcommands; remove the top-level command instead.
+    ///
+    /// Removed application commands stay registered on Discord's side until you sync, see
+    /// [`Self::add_command`].
+    pub fn remove_command(&self, qualified_name: &str) -> bool {
+        let mut names = qualified_name.split_whitespace();
+        let is_top_level = names.next().is_some() && names.next().is_none();
+        let command = self.find_command(qualified_name);
+
+        let name = match command {
+            Some(crate::CommandRef::Prefix(command)) if !is_top_level => {
+                return self.runtime_commands.remove_subcommand(command);
+            }
+            Some(crate::CommandRef::Application(crate::ApplicationCommand::Slash(_)))
+                if !is_top_level =>
+            {
+                return false;
+            }
+            // Resolves aliases and case-insensitive names to the command name
+            Some(command) => command.name(),
+            // Slash command groups aren't commands by themselves
+            None if is_top_level => qualified_name.trim(),
+            None => return false,
+        };
+        self.remove_top_level_command(name)
+    }
+
+    /// [`Self::remove_command`] for a top-level command name
+    fn remove_top_level_command(&self, name: &str) -> bool {
+        let is_initial = self
+            .options
+            .prefix_options
+            .commands
+            .iter()
+            .any(|meta| meta.command.name == name)
+            || self
+                .options
+                .application_options
+                .commands
+                .iter()
+                .any(|command| command.name() == name);
+        self.runtime_commands.remove(name, is_initial)
+    }
+
+    /// The top-level prefix commands currently in use: the ones from [`FrameworkOptions`] which
+    /// weren't removed with [`Self::remove_command`], followed by the ones added with
+    /// [`Self::add_command`]
+    pub fn prefix_commands(&self) -> Vec<&crate::PrefixCommandMeta<U, E>> {
+        self.options
+            .prefix_options
+            .commands
+            .iter()
+            .filter(|meta| !self.runtime_commands.is_initial_removed(meta.command.name))
+            .chain(self.runtime_commands.prefix_commands())
+            .collect()
+    }
+
+    /// The application commands currently in use, like [`Self::prefix_commands`]
+    pub fn application_commands(&self) -> Vec<&crate::ApplicationCommandTree<U, E>> {
+        self.options
+            .application_options
+            .commands
+            .iter()
+            .filter(|command| !self.runtime_commands.is_initial_removed(command.name()))
+            .chain(self.runtime_commands.application_commands())
+            .collect()
+    }
+
+    /// Looks up a command by its qualified name, i.e. the names of its parent commands and its own
+    /// name separated by spaces, like `"mod ban"`.
+    ///
+    /// Prefix commands are searched first, matching aliases and
+    /// [`crate::PrefixFrameworkOptions::case_insensitive_commands`]. If there's no such prefix
+    /// command, the slash command or context menu command of that name is returned.
+    pub fn find_command(&self, qualified_name: &str) -> Option<crate::CommandRef<'_, U, E>> {
+        command_paths::find_command(self, qualified_name)
+    }
+
+    /// All commands currently in use with their qualified names, see [`Self::find_command`].
+    ///
+    /// Yields the prefix commands with their subcommands, each parent before its subcommands,
+    /// followed by the slash commands and context menu commands. Slash command groups aren't
+    /// commands by themselves and only appear as part of the paths of their subcommands. Commands
+    /// which exist both as prefix and as slash command are yielded once for each version.
+    ///
+    /// ```rust,no_run
+    /// # type Error = Box<dyn std::error::Error + Send + Sync>;
+    /// # fn _f(framework: &poise::Framework<(), Error>) {
+    /// for (path, command) in framework.command_paths() {
+    ///     if let poise::CommandRef::Prefix(command) = command {
+    ///         println!("{}: {}", path, command.options.inline_help.unwrap_or(""));
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn command_paths(&self) -> impl Iterator<Item = (String, crate::CommandRef<'_, U, E>)> {
+        command_paths::command_paths(self).into_iter()
+    }
+
     /// Returns the application ID given to the framework on its creation.
     pub fn application_id(&self) -> serenity::ApplicationId {
         self.application_id
@@ -213,6 +586,133 @@ impl<U, E> Framework<U, E> {
         self.shard_manager.load().as_ref().unwrap().clone()
     }
 
+    /// The connection status and heartbeat latency of the given shard, or None if this process
+    /// doesn't run it
+    pub async fn shard_status(&self, shard_id: u64) -> Option<ShardStatus> {
+        self.shard_statuses()
+            .await
+            .into_iter()
+            .find(|status| status.shard_id == shard_id)
+    }
+
+    /// Restarts the given shard, reconnecting it to the gateway. Commands running on it continue,
+    /// but their responses can only be sent once it's connected again.
+    pub async fn restart_shard(&self, shard_id: u64) {
+        self.shard_manager()
+            .lock()
+            .await
+            .restart(serenity::ShardId(shard_id))
+            .await;
+    }
+
+    /// Waits until every shard started by this process received its Ready event. Shards run by
+    /// other processes aren't waited for. Returns immediately if that already happened, even if
+    /// shards disconnected since then.
+    ///
+    /// Wrap in [`tokio::time::timeout`] to wait for a limited time.
+    pub async fn wait_until_shards_ready(&self) {
+        loop {
+            // Created before checking, so that a notification in between isn't missed
+            let notified = self.shard_ready_notify.notified();
+            if self.ready_shards.lock().all_ready() {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// Waits until the bot is ready: the first Ready event arrived, the user data was set up and
+    /// the [ready hooks](crate::ReadyHook) ran. Returns immediately if that already happened.
+    ///
+    /// If the user data setup fails, this never returns. Wrap in [`tokio::time::timeout`] to wait
+    /// for a limited time.
+    pub async fn wait_until_ready(&self) {
+        self.readiness.wait().await
+    }
+
+    /// Whether the bot is ready, see [`Self::wait_until_ready`]
+    pub fn is_ready(&self) -> bool {
+        self.readiness.is_ready()
+    }
+
+    /// The cached check results, see [`crate::CheckCache`]
+    pub fn check_cache(&self) -> &crate::CheckCache {
+        &self.check_cache
+    }
+
+    /// How long ago the framework was created
+    pub fn uptime(&self) -> std::time::Duration {
+        self.started_at.elapsed()
+    }
+
+    /// Invocation statistics of the framework's commands
+    pub fn metrics(&self) -> &crate::CommandMetrics {
+        &self.metrics
+    }
+
+    /// Gracefully disconnects all shards, which makes [`Self::start`] return
+    pub async fn shutdown(&self) {
+        self.shard_manager().lock().await.shutdown_all().await;
+    }
+
+    /// Shuts down gracefully: stops accepting new command invocations, waits up to `timeout` for
+    /// the running ones to finish, runs [`FrameworkOptions::on_shutdown`] and then disconnects all
+    /// shards, like [`Self::shutdown`].
+    ///
+    /// Returns false if some invocations were still running when the timeout expired. They're
+    /// abandoned when the shards disconnect.
+    ///
+    /// When called from within a command, that command itself isn't waited for.
+    pub async fn shutdown_graceful(&self, timeout: std::time::Duration) -> bool {
+        self.in_flight.begin_shutdown();
+        let drained = tokio::time::timeout(timeout, self.in_flight.wait_idle())
+            .await
+            .is_ok();
+        (self.options.on_shutdown)(self).await;
+        self.shutdown().await;
+        drained
+    }
+
+    /// Whether [`Self::shutdown_graceful`] was called. New command invocations are ignored from
+    /// then on
+    pub fn is_shutting_down(&self) -> bool {
+        self.in_flight.is_shutting_down()
+    }
+
+    /// Number of command invocations which are currently running
+    pub fn running_invocations(&self) -> usize {
+        self.in_flight.count()
+    }
+
+    /// The invocations whose cooldowns start once they run, see [`crate::PendingCooldowns`]
+    pub(crate) fn pending_cooldowns(&self) -> &crate::PendingCooldowns {
+        &self.pending_cooldowns
+    }
+
+    /// Called when a prefix command invocation responds, so that its delayed typing indicator
+    /// stops or doesn't start at all, see [`crate::PrefixCommandOptions::broadcast_typing_after`]
+    pub(crate) fn stop_delayed_typing(&self, invocation_id: u64) {
+        self.delayed_typing.stop(invocation_id);
+    }
+
+    /// The connection status and heartbeat latency of every shard run by this process, sorted by
+    /// shard ID
+    pub async fn shard_statuses(&self) -> Vec<ShardStatus> {
+        let shard_manager = self.shard_manager();
+        let shard_manager = shard_manager.lock().await;
+        let runners = shard_manager.runners.lock().await;
+        let mut statuses = runners
+            .iter()
+            .map(|(shard_id, runner)| ShardStatus {
+                shard_id: shard_id.0,
+                stage: runner.stage,
+                latency: runner.latency,
+            })
+            .collect::<Vec<_>>();
+        statuses.sort_by_key(|status| status.shard_id);
+        statuses
+    }
+
     async fn get_user_data(&self) -> &U {
         // We shouldn't get a Message event before a Ready event. But if we do, wait until
         // the Ready event does come and the resulting data has arrived.
@@ -224,17 +724,101 @@ impl<U, E> Framework<U, E> {
         }
     }
 
+    /// Runs the task on its schedule until the framework shuts down
+    async fn run_background_task(
+        &self,
+        task: &crate::BackgroundTask<U, E>,
+        cache_and_http: &serenity::CacheAndHttp,
+    ) {
+        self.wait_until_ready().await;
+        let data = self.get_user_data().await;
+        let mut last_run = None;
+        loop {
+            tokio::time::sleep_until(task.schedule.next_run(last_run)).await;
+            if self.is_shutting_down() {
+                return;
+            }
+
+            last_run = Some(tokio::time::Instant::now());
+            let ctx = crate::TaskContext {
+                cache: &cache_and_http.cache,
+                http: &cache_and_http.http,
+                data,
+                framework: self,
+            };
+            if let Err(e) = (task.action)(ctx).await {
+                (self.options.on_error)(e, ErrorContext::BackgroundTask(task.name)).await;
+            }
+        }
+    }
+
+    /// Runs the ready hooks in order, see [`crate::ReadyHook`]
+    async fn run_ready_hooks(&self, ctx: &serenity::Context, ready: &serenity::Ready) {
+        let data = self.get_user_data().await;
+        for hook in &self.ready_hooks {
+            let ready_ctx = crate::ReadyContext {
+                discord: ctx,
+                ready,
+                data,
+                framework: self,
+            };
+            if let Err(e) = (hook.action)(ready_ctx).await {
+                (self.options.on_error)(e, ErrorContext::Setup).await;
+            }
+        }
+    }
+
+    /// Stops tracking the given deleted messages in the edit tracker and applies
+    /// [`crate::PrefixFrameworkOptions::invocation_deleted_behavior`] to their bot responses
+    async fn handle_invocation_deleted(
+        &self,
+        ctx: &serenity::Context,
+        deleted_message_ids: &[serenity::MessageId],
+    ) {
+        let edit_tracker = match &self.options.prefix_options.edit_tracker {
+            Some(x) => x,
+            None => return,
+        };
+        for &deleted_message_id in deleted_message_ids {
+            let bot_responses =
+                crate::prefix::forget_tracked_message(edit_tracker, deleted_message_id).await;
+            for bot_response in bot_responses {
+                if let Err(e) = crate::handle_invocation_deleted(
+                    ctx,
+                    bot_response,
+                    self.options.prefix_options.invocation_deleted_behavior,
+                )
+                .await
+                {
+                    println!(
+                        "Warning: couldn't update bot response when user deleted message: {}",
+                        e
+                    );
+                }
+            }
+        }
+    }
+
     async fn event(&self, ctx: serenity::Context, event: Event<'_>)
     where
         U: Send + Sync,
     {
         match &event {
             Event::Ready { data_about_bot } => {
+                {
+                    let mut ready_shards = self.ready_shards.lock();
+                    let [shard_id, _] = data_about_bot.shard.unwrap_or([0, 1]);
+                    ready_shards.shard_ids.insert(shard_id);
+                }
+                self.shard_ready_notify.notify_waiters();
+
                 let user_data_setup = Option::take(&mut *self.user_data_setup.lock().unwrap());
                 if let Some(user_data_setup) = user_data_setup {
                     match user_data_setup(&ctx, data_about_bot, self).await {
                         Ok(user_data) => {
                             let _: Result<_, _> = self.user_data.set(user_data);
+                            self.run_ready_hooks(&ctx, data_about_bot).await;
+                            self.readiness.set_ready();
                         }
                         Err(e) => (self.options.on_error)(e, ErrorContext::Setup).await,
                     }
@@ -244,11 +828,14 @@ impl<U, E> Framework<U, E> {
                 }
             }
             Event::Message { new_message } => {
-                if let Err(Some((err, ctx))) =
-                    prefix::dispatch_message(self, &ctx, new_message, false).await
+                for (err, ctx) in
+                    prefix::dispatch_message_chain(self, &ctx, new_message, false).await
                 {
+                    let group = crate::command_group(self, crate::CommandRef::Prefix(ctx.command));
                     if let Some(on_error) = ctx.command.options.on_error {
                         (on_error)(err, ctx).await;
+                    } else if let Some(on_error) = group.and_then(|group| group.on_error) {
+                        on_error(err, crate::CommandErrorContext::Prefix(ctx)).await;
                     } else {
                         (self.options.on_error)(
                             err,
@@ -260,14 +847,16 @@ impl<U, E> Framework<U, E> {
             }
             Event::MessageUpdate { event, .. } => {
                 if let Some(edit_tracker) = &self.options.prefix_options.edit_tracker {
+                    crate::prefix::load_tracked_message(edit_tracker, event.id).await;
                     let msg = edit_tracker.write().process_message_update(
                         event,
                         self.options().prefix_options.ignore_edit_tracker_cache,
                     );
+                    crate::prefix::persist_tracked_message(edit_tracker, event.id).await;
 
                     if let Some(msg) = msg {
-                        if let Err(Some((err, ctx))) =
-                            prefix::dispatch_message(self, &ctx, &msg, true).await
+                        for (err, ctx) in
+                            prefix::dispatch_message_chain(self, &ctx, &msg, true).await
                         {
                             (self.options.on_error)(
                                 err,
@@ -283,20 +872,15 @@ impl<U, E> Framework<U, E> {
             Event::MessageDelete {
                 deleted_message_id, ..
             } => {
-                if let Some(edit_tracker) = &self.options.prefix_options.edit_tracker {
-                    let bot_response = edit_tracker
-                        .write()
-                        .find_bot_response(*deleted_message_id)
-                        .cloned();
-                    if let Some(bot_response) = bot_response {
-                        if let Err(e) = bot_response.delete(&ctx).await {
-                            println!(
-                                "Warning: couldn't delete bot response when user deleted message: {}",
-                                e
-                            );
-                        }
-                    }
-                }
+                self.handle_invocation_deleted(&ctx, std::slice::from_ref(deleted_message_id))
+                    .await;
+            }
+            Event::MessageDeleteBulk {
+                multiple_deleted_messages_ids,
+                ..
+            } => {
+                self.handle_invocation_deleted(&ctx, multiple_deleted_messages_ids)
+                    .await;
             }
             Event::InteractionCreate {
                 interaction: serenity::Interaction::ApplicationCommand(interaction),
@@ -309,8 +893,14 @@ impl<U, E> Framework<U, E> {
                 )
                 .await
                 {
+                    let group = crate::command_group(
+                        self,
+                        crate::CommandRef::Application(error_ctx.ctx.command),
+                    );
                     if let Some(on_error) = error_ctx.ctx.command.options().on_error {
                         on_error(e, error_ctx).await;
+                    } else if let Some(on_error) = group.and_then(|group| group.on_error) {
+                        on_error(e, CommandErrorContext::Application(error_ctx)).await;
                     } else {
                         (self.options.on_error)(
                             e,
@@ -343,10 +933,49 @@ impl<U, E> Framework<U, E> {
 
         // Do this after the framework's Ready handling, so that self.get_user_data() doesnt
         // potentially block infinitely
-        if let Err(e) =
-            (self.options.listener)(&ctx, &event, self, self.get_user_data().await).await
-        {
+        let data = self.get_user_data().await;
+        if let Err(e) = (self.options.listener)(&ctx, &event, self, data).await {
             (self.options.on_error)(e, ErrorContext::Listener(&event));
         }
+        for subscriber in &self.options.event_subscribers {
+            if let Err(e) =
+                crate::event::dispatch_to_subscriber(&**subscriber, &ctx, &event, self, data).await
+            {
+                (self.options.on_error)(e, ErrorContext::Listener(&event)).await;
+            }
+        }
     }
 }
+
+#[cfg(test)]
+#[test]
+fn test_thread_permissions() {
+    use serenity::{ChannelType, Permissions};
+
+    let permissions = Permissions::READ_MESSAGES | Permissions::SEND_MESSAGES;
+    // Sending in threads needs its own permission
+    assert_eq!(
+        thread_permissions(permissions, ChannelType::PublicThread, false),
+        Permissions::READ_MESSAGES
+    );
+    let permissions = permissions | Permissions::SEND_MESSAGES_IN_THREADS;
+    assert_eq!(
+        thread_permissions(permissions, ChannelType::PublicThread, false),
+        permissions
+    );
+
+    // Private threads are invisible to non-members, unless they can manage threads
+    assert_eq!(
+        thread_permissions(permissions, ChannelType::PrivateThread, false),
+        Permissions::empty()
+    );
+    assert_eq!(
+        thread_permissions(permissions, ChannelType::PrivateThread, true),
+        permissions
+    );
+    let manager = permissions | Permissions::MANAGE_THREADS;
+    assert_eq!(
+        thread_permissions(manager, ChannelType::PrivateThread, false),
+        manager
+    );
+}
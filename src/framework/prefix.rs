@@ -1,43 +1,131 @@
 use crate::serenity_prelude as serenity;
 
+/// Unicode-aware comparison key for [`crate::PrefixFrameworkOptions::normalize_unicode`]: NFKC
+/// normalized and lowercased
+#[cfg(feature = "unicode-normalization")]
+pub(super) fn normalize(string: &str) -> String {
+    use unicode_normalization::UnicodeNormalization as _;
+    string.nfkc().flat_map(char::to_lowercase).nfkc().collect()
+}
+
+/// Like [`str::strip_prefix`], but comparing [`normalize`]d forms. Returns the rest of the
+/// original string after the shortest start whose normalized form equals the normalized prefix
+#[cfg(feature = "unicode-normalization")]
+fn strip_normalized_prefix<'a>(string: &'a str, prefix: &str) -> Option<&'a str> {
+    let prefix = normalize(prefix);
+    // Normalization can compose and decompose characters, but never by this much
+    let max_chars = prefix.chars().count() * 4 + 4;
+    string
+        .char_indices()
+        .map(|(i, _)| i)
+        .skip(1)
+        .chain(std::iter::once(string.len()))
+        .take(max_chars)
+        .find(|&end| normalize(&string[..end]) == prefix)
+        .map(|end| &string[end..])
+}
+
+/// Strips a literal prefix off the message content, respecting
+/// [`crate::PrefixFrameworkOptions::normalize_unicode`]
+fn strip_literal_prefix<'a, U, E>(
+    options: &crate::PrefixFrameworkOptions<U, E>,
+    content: &'a str,
+    prefix: &str,
+) -> Option<&'a str> {
+    #[cfg(feature = "unicode-normalization")]
+    if options.normalize_unicode {
+        return strip_normalized_prefix(content, prefix);
+    }
+    #[cfg(not(feature = "unicode-normalization"))]
+    let _ = options;
+
+    content.strip_prefix(prefix)
+}
+
+/// Whether the user-provided command name refers to the given command, respecting
+/// [`crate::PrefixFrameworkOptions::case_insensitive_commands`] and
+/// [`crate::PrefixFrameworkOptions::normalize_unicode`]
+fn command_name_matches<U, E>(
+    options: &crate::PrefixFrameworkOptions<U, E>,
+    command: &crate::PrefixCommand<U, E>,
+    command_name: &str,
+) -> bool {
+    #[cfg(feature = "unicode-normalization")]
+    if options.normalize_unicode {
+        let command_name = normalize(command_name);
+        return std::iter::once(command.name)
+            .chain(command.options.aliases.iter().copied())
+            .any(|name| normalize(name) == command_name);
+    }
+
+    command.is_named(command_name, options.case_insensitive_commands)
+}
+
 // Returns message with (only) bot prefix removed, if it matches
 async fn strip_prefix<'a, U, E>(
     this: &'a super::Framework<U, E>,
     ctx: &'a serenity::Context,
     msg: &'a serenity::Message,
 ) -> Option<&'a str> {
+    if this.options.prefix_options.mention_only {
+        return strip_mention_prefix(this, msg).or_else(|| strip_reply_prefix(this, msg));
+    }
+
     if let Some(dynamic_prefix) = this.options.prefix_options.dynamic_prefix {
         if let Some(prefix) = dynamic_prefix(ctx, msg, this.get_user_data().await).await {
-            if let Some(content) = msg.content.strip_prefix(&prefix) {
+            if let Some(content) =
+                strip_literal_prefix(&this.options.prefix_options, &msg.content, &prefix)
+            {
                 return Some(content);
             }
         }
     }
 
-    if let Some(prefix) = &this.options.prefix_options.prefix {
-        if let Some(content) = msg.content.strip_prefix(prefix) {
+    let mut stored_prefix = match (&this.options.prefix_options.prefix_store, msg.guild_id) {
+        (Some(store), Some(guild_id)) => store.get(guild_id).await,
+        _ => None,
+    };
+    if stored_prefix.is_none() {
+        stored_prefix = crate::settings_of(this, msg.guild_id)
+            .await
+            .and_then(|settings| settings.prefix);
+    }
+    if let Some(prefix) = &stored_prefix {
+        if let Some(content) =
+            strip_literal_prefix(&this.options.prefix_options, &msg.content, prefix)
+        {
             return Some(content);
         }
-    }
+    } else {
+        if let Some(prefix) = &this.options.prefix_options.prefix {
+            if let Some(content) =
+                strip_literal_prefix(&this.options.prefix_options, &msg.content, prefix)
+            {
+                return Some(content);
+            }
+        }
 
-    if let Some(content) = this
-        .options
-        .prefix_options
-        .additional_prefixes
-        .iter()
-        .find_map(|prefix| match prefix {
-            crate::Prefix::Literal(prefix) => msg.content.strip_prefix(prefix),
-            crate::Prefix::Regex(prefix) => {
-                let regex_match = prefix.find(&msg.content)?;
-                if regex_match.start() == 0 {
-                    Some(&msg.content[regex_match.end()..])
-                } else {
-                    None
+        if let Some(content) = this
+            .options
+            .prefix_options
+            .additional_prefixes
+            .iter()
+            .find_map(|prefix| match prefix {
+                crate::Prefix::Literal(prefix) => {
+                    strip_literal_prefix(&this.options.prefix_options, &msg.content, prefix)
                 }
-            }
-        })
-    {
-        return Some(content);
+                crate::Prefix::Regex(prefix) => {
+                    let regex_match = prefix.find(&msg.content)?;
+                    if regex_match.start() == 0 {
+                        Some(&msg.content[regex_match.end()..])
+                    } else {
+                        None
+                    }
+                }
+            })
+        {
+            return Some(content);
+        }
     }
 
     if let Some(dynamic_prefix) = this.options.prefix_options.stripped_dynamic_prefix {
@@ -47,31 +135,165 @@ async fn strip_prefix<'a, U, E>(
     }
 
     if this.options.prefix_options.mention_as_prefix {
-        // Mentions are either <@USER_ID> or <@!USER_ID>
-        let stripped_mention_prefix = || {
-            msg.content
-                .strip_prefix("<@")?
-                .trim_start_matches('!')
-                .strip_prefix(&this.bot_id.0.to_string())?
-                .strip_prefix('>')
-        };
-        if let Some(content) = stripped_mention_prefix() {
+        if let Some(content) = strip_mention_prefix(this, msg) {
             return Some(content);
         }
     }
 
-    None
+    strip_reply_prefix(this, msg)
+}
+
+/// Strips the prefix of a [`crate::CommandGroup`] or, if none matches, the framework prefix off the
+/// message. Also returns the name of the group whose prefix matched, if any, see [`route_of`]
+async fn strip_routed_prefix<'a, U, E>(
+    this: &'a super::Framework<U, E>,
+    ctx: &'a serenity::Context,
+    msg: &'a serenity::Message,
+) -> Option<(Option<&'static str>, &'a str)> {
+    if !this.options.prefix_options.mention_only {
+        for group in &this.options.command_groups {
+            if let Some(prefix) = &group.prefix {
+                if let Some(content) =
+                    strip_literal_prefix(&this.options.prefix_options, &msg.content, prefix)
+                {
+                    return Some((Some(group.name()), content));
+                }
+            }
+        }
+    }
+
+    let content = strip_prefix(this, ctx, msg).await?;
+    Some((None, content))
+}
+
+/// The name of the command's group if the group has its own prefix, so that the command is only
+/// invoked with that prefix. None if the command is invoked with the framework prefix
+fn route_of<U, E>(
+    this: &super::Framework<U, E>,
+    command: &crate::PrefixCommand<U, E>,
+) -> Option<&'static str> {
+    let group = crate::command_group(this, crate::CommandRef::Prefix(command))?;
+    group.prefix.as_ref()?;
+    Some(group.name())
+}
+
+/// Returns the message with the bot mention at the start removed, if there is one
+fn strip_mention_prefix<'a, U, E>(
+    this: &'a super::Framework<U, E>,
+    msg: &'a serenity::Message,
+) -> Option<&'a str> {
+    // Mentions are either <@USER_ID> or <@!USER_ID>
+    let content = msg
+        .content
+        .strip_prefix("<@")?
+        .trim_start_matches('!')
+        .strip_prefix(&this.bot_id.0.to_string())?
+        .strip_prefix('>')?;
+
+    Some(match this.options.prefix_options.strip_after_mention {
+        Some(strip_after_mention) => strip_after_mention(content),
+        None => content,
+    })
+}
+
+/// Returns the entire message if it's a reply to a message by the bot and
+/// [`crate::PrefixFrameworkOptions::reply_as_prefix`] is enabled
+fn strip_reply_prefix<'a, U, E>(
+    this: &'a super::Framework<U, E>,
+    msg: &'a serenity::Message,
+) -> Option<&'a str> {
+    if !this.options.prefix_options.reply_as_prefix {
+        return None;
+    }
+    match &msg.referenced_message {
+        Some(referenced_message) if referenced_message.author.id == this.bot_id => {
+            Some(&msg.content)
+        }
+        _ => None,
+    }
+}
+
+/// A command found by name in [`commands_named`]
+struct CommandCandidate<'a, U, E> {
+    meta: &'a crate::PrefixCommandMeta<U, E>,
+    /// Key of the command in the [`super::command_index::CommandIndex`], or None if it was added
+    /// at runtime and isn't indexed
+    index_key: Option<String>,
+}
+
+/// The top-level commands, or the subcommands of `parent`, which are named `command_name`, in
+/// order. Looked up in the command index, except for commands added at runtime
+fn commands_named<'a, U, E>(
+    this: &'a super::Framework<U, E>,
+    parent: Option<&CommandCandidate<'a, U, E>>,
+    command_name: &str,
+) -> Vec<CommandCandidate<'a, U, E>> {
+    let options = &this.options.prefix_options;
+    let parent_key = match parent {
+        Some(CommandCandidate {
+            index_key: Some(key),
+            ..
+        }) => Some(key.as_str()),
+        Some(CommandCandidate {
+            meta,
+            index_key: None,
+        }) => {
+            return meta
+                .subcommands
+                .iter()
+                .filter(|meta| command_name_matches(options, &meta.command, command_name))
+                .filter(|meta| !this.runtime_commands.is_subcommand_removed(&meta.command))
+                .map(|meta| CommandCandidate {
+                    meta,
+                    index_key: None,
+                })
+                .collect();
+        }
+        None => None,
+    };
+
+    let key = super::command_index::prefix_key(options, parent_key, command_name);
+    let mut candidates = this
+        .command_index
+        .prefix_commands(options, &key)
+        .filter(|meta| match parent {
+            Some(_) => !this.runtime_commands.is_subcommand_removed(&meta.command),
+            None => !this.runtime_commands.is_initial_removed(meta.command.name),
+        })
+        .map(|meta| CommandCandidate {
+            meta,
+            index_key: Some(super::command_index::prefix_key(
+                options,
+                parent_key,
+                meta.command.name,
+            )),
+        })
+        .collect::<Vec<_>>();
+    if parent.is_none() {
+        candidates.extend(
+            this.runtime_commands
+                .prefix_commands()
+                .filter(|meta| command_name_matches(options, &meta.command, command_name))
+                .map(|meta| CommandCandidate {
+                    meta,
+                    index_key: None,
+                }),
+        );
+    }
+    candidates
 }
 
 /// Find a command within nested PrefixCommandMeta's by the user message string. Also returns
-/// the arguments, i.e. the remaining string.
+/// the arguments, i.e. the remaining string. Searches the top-level commands whose [`route_of`]
+/// is `route`, or the subcommands of `parent` if given.
 ///
 /// May throw an error if a command check fails
 fn find_command<'a, U, E>(
     this: &'a super::Framework<U, E>,
     ctx: &'a serenity::Context,
     msg: &'a serenity::Message,
-    commands: &'a [crate::PrefixCommandMeta<U, E>],
+    route: Option<&'static str>,
+    parent: Option<CommandCandidate<'a, U, E>>,
     remaining_message: &'a str,
 ) -> crate::BoxFuture<
     'a,
@@ -83,14 +305,22 @@ fn find_command<'a, U, E>(
 where
     U: Send + Sync,
 {
-    Box::pin(_find_command(this, ctx, msg, commands, remaining_message))
+    Box::pin(_find_command(
+        this,
+        ctx,
+        msg,
+        route,
+        parent,
+        remaining_message,
+    ))
 }
 
 async fn _find_command<'a, U, E>(
     this: &'a super::Framework<U, E>,
     ctx: &'a serenity::Context,
     msg: &'a serenity::Message,
-    commands: &'a [crate::PrefixCommandMeta<U, E>],
+    route: Option<&'static str>,
+    parent: Option<CommandCandidate<'a, U, E>>,
     remaining_message: &'a str,
 ) -> Result<
     Option<(&'a crate::PrefixCommandMeta<U, E>, &'a str)>,
@@ -99,28 +329,16 @@ async fn _find_command<'a, U, E>(
 where
     U: Send + Sync,
 {
-    let considered_equal = if this.options.prefix_options.case_insensitive_commands {
-        |a: &str, b: &str| a.eq_ignore_ascii_case(b)
-    } else {
-        |a: &str, b: &str| a == b
-    };
-
     let (command_name, remaining_message) = {
         let mut iter = remaining_message.splitn(2, char::is_whitespace);
         (iter.next().unwrap(), iter.next().unwrap_or("").trim_start())
     };
 
     let mut first_matching_command = None;
-    for command_meta in commands {
+    for candidate in commands_named(this, parent.as_ref(), command_name) {
+        let command_meta = candidate.meta;
         let command = &command_meta.command;
-
-        let primary_name_matches = considered_equal(command.name, command_name);
-        let alias_matches = command
-            .options
-            .aliases
-            .iter()
-            .any(|alias| considered_equal(alias, command_name));
-        if !primary_name_matches && !alias_matches {
+        if parent.is_none() && route_of(this, command) != route {
             continue;
         }
 
@@ -150,12 +368,15 @@ where
                 None => true,
             };
 
+            let group_check_passes =
+                crate::check_command_group(crate::Context::Prefix(prefix_ctx)).await?;
+
             let command_specific_check_passes = match &command.options.check {
                 Some(check) => check(prefix_ctx).await?,
                 None => true,
             };
 
-            Ok(global_check_passes && command_specific_check_passes)
+            Ok(global_check_passes && group_check_passes && command_specific_check_passes)
         })()
         .await
         .map_err(|e| {
@@ -169,12 +390,14 @@ where
             )
         })?;
         if !checks_passing {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(command = command.name, "command check failed");
             continue;
         }
 
         first_matching_command = Some(
             if let Some((subcommand_meta, remaining_message)) =
-                find_command(this, ctx, msg, &command_meta.subcommands, remaining_message).await?
+                find_command(this, ctx, msg, route, Some(candidate), remaining_message).await?
             {
                 (subcommand_meta, remaining_message)
             } else {
@@ -187,12 +410,77 @@ where
     Ok(first_matching_command)
 }
 
+/// Names of the given commands whose name or one of whose aliases is similar to the given name,
+/// most similar first, according to [`crate::builtins::filter_by_name`]. Commands hidden from help
+/// are never suggested.
+fn similar_command_names<U, E>(
+    commands: Vec<&crate::PrefixCommandMeta<U, E>>,
+    command_name: &str,
+) -> Vec<&'static str> {
+    /// A command name or alias, which resolves to the command name
+    struct Candidate {
+        name: &'static str,
+        command_name: &'static str,
+    }
+
+    impl AsRef<str> for Candidate {
+        fn as_ref(&self) -> &str {
+            self.name
+        }
+    }
+
+    let candidates = commands
+        .into_iter()
+        .map(|meta| &meta.command)
+        .filter(|command| !command.options.hide_in_help)
+        .flat_map(|command| {
+            std::iter::once(command.name)
+                .chain(command.options.aliases.iter().copied())
+                .map(move |name| Candidate {
+                    name,
+                    command_name: command.name,
+                })
+        });
+
+    let mut suggestions = Vec::new();
+    for candidate in crate::builtins::filter_by_name(command_name, candidates) {
+        if !suggestions.contains(&candidate.command_name) {
+            suggestions.push(candidate.command_name);
+        }
+    }
+    suggestions
+}
+
+/// Whether the message shouldn't be processed as a command because of who sent it. See
+/// [`crate::PrefixFrameworkOptions::ignore_bots`] and related options
+fn is_ignored_author<U, E>(this: &super::Framework<U, E>, msg: &serenity::Message) -> bool {
+    let options = &this.options.prefix_options;
+
+    // The bot's own messages are governed by execute_self_messages alone
+    if this.bot_id == msg.author.id {
+        return !options.execute_self_messages;
+    }
+
+    let ignored = if msg.webhook_id.is_some() {
+        options.ignore_webhooks
+    } else {
+        options.ignore_bots && msg.author.bot
+    };
+    ignored
+        && !options
+            .allow_ignored_author
+            .map_or(false, |allow| allow(msg))
+}
+
 /// Manually dispatches a message with the prefix framework.
 ///
 /// Returns:
 /// - Ok(()) if a command was successfully dispatched and run
 /// - Err(None) if no command was run but no error happened
 /// - Err(Some(error: UserError)) if any user code yielded an error
+///
+/// [`crate::PrefixFrameworkOptions::command_separator`] is not applied; the entire message is
+/// treated as one invocation. See [`dispatch_message_chain`] for that.
 pub async fn dispatch_message<'a, U, E>(
     this: &'a super::Framework<U, E>,
     ctx: &'a serenity::Context,
@@ -203,36 +491,174 @@ where
     U: Send + Sync,
 {
     // Strip prefix and whitespace between prefix and command
-    let msg_content = strip_prefix(this, ctx, msg).await.ok_or(None)?.trim_start();
+    let (route, msg_content) = strip_routed_prefix(this, ctx, msg).await.ok_or(None)?;
+    let msg_content = msg_content.trim_start();
 
-    // If we know our own ID, and the message author ID is our own, and we aren't supposed to
-    // execute our own messages, THEN stop execution.
-    if !this.options.prefix_options.execute_self_messages && this.bot_id == msg.author.id {
+    if is_ignored_author(this, msg) {
         return Err(None);
     }
 
-    let (command_meta, args) = find_command(
-        this,
-        ctx,
-        msg,
-        &this.options.prefix_options.commands,
+    dispatch_invocation(this, ctx, msg, route, msg_content, triggered_by_edit).await
+}
+
+/// Manually dispatches a message with the prefix framework, running each of the commands
+/// separated by [`crate::PrefixFrameworkOptions::command_separator`] in order. This is what the
+/// framework does for incoming messages.
+///
+/// Only the first command needs the prefix. A failing command doesn't stop the following ones;
+/// the errors of all failed commands are returned in order.
+///
+/// Edit tracking doesn't apply to messages with more than one command: their responses aren't
+/// tracked, and edits to them are ignored.
+pub async fn dispatch_message_chain<'a, U, E>(
+    this: &'a super::Framework<U, E>,
+    ctx: &'a serenity::Context,
+    msg: &'a serenity::Message,
+    triggered_by_edit: bool,
+) -> Vec<(E, crate::PrefixCommandErrorContext<'a, U, E>)>
+where
+    U: Send + Sync,
+{
+    let separator = match &this.options.prefix_options.command_separator {
+        Some(x) => x,
+        None => {
+            return match dispatch_message(this, ctx, msg, triggered_by_edit).await {
+                Err(Some(error)) => vec![error],
+                Ok(()) | Err(None) => Vec::new(),
+            }
+        }
+    };
+
+    // Strip prefix and whitespace between prefix and command
+    let (route, msg_content) = match strip_routed_prefix(this, ctx, msg).await {
+        Some((route, content)) => (route, content.trim_start()),
+        None => return Vec::new(),
+    };
+
+    if is_ignored_author(this, msg) {
+        return Vec::new();
+    }
+
+    let segments = crate::split_top_level(
         msg_content,
+        separator,
+        &this.options.prefix_options.parsing_options,
     )
-    .await
-    .map_err(Some)?
-    .ok_or(None)?;
+    .into_iter()
+    .map(str::trim)
+    .filter(|segment| !segment.is_empty())
+    .collect::<Vec<_>>();
+    let is_chain = segments.len() > 1;
+    if is_chain && triggered_by_edit {
+        return Vec::new();
+    }
+
+    let mut errors = Vec::new();
+    for segment in segments {
+        if let Err(Some(error)) =
+            dispatch_invocation(this, ctx, msg, route, segment, triggered_by_edit).await
+        {
+            errors.push(error);
+        }
+
+        // Forget the response, so that the next command sends a new message instead of editing
+        // this command's response
+        if is_chain {
+            if let Some(edit_tracker) = &this.options.prefix_options.edit_tracker {
+                crate::prefix::forget_tracked_message(edit_tracker, msg.id).await;
+            }
+        }
+    }
+    errors
+}
+
+/// Find and run the command invoked by the given message content, which has the prefix already
+/// stripped. Only the commands whose [`route_of`] is `route` are considered
+async fn dispatch_invocation<'a, U, E>(
+    this: &'a super::Framework<U, E>,
+    ctx: &'a serenity::Context,
+    msg: &'a serenity::Message,
+    route: Option<&'static str>,
+    msg_content: &'a str,
+    triggered_by_edit: bool,
+) -> Result<(), Option<(E, crate::PrefixCommandErrorContext<'a, U, E>)>>
+where
+    U: Send + Sync,
+{
+    let invocation = _dispatch_invocation(this, ctx, msg, route, msg_content, triggered_by_edit);
+    #[cfg(feature = "tracing")]
+    let invocation = tracing::Instrument::instrument(
+        invocation,
+        crate::instrumentation::invocation_span(msg.guild_id, msg.author.id, ctx.shard_id),
+    );
+    // Invocations arriving after shutdown began are ignored
+    this.in_flight.track(invocation).await.unwrap_or(Err(None))
+}
+
+async fn _dispatch_invocation<'a, U, E>(
+    this: &'a super::Framework<U, E>,
+    ctx: &'a serenity::Context,
+    msg: &'a serenity::Message,
+    route: Option<&'static str>,
+    msg_content: &'a str,
+    triggered_by_edit: bool,
+) -> Result<(), Option<(E, crate::PrefixCommandErrorContext<'a, U, E>)>>
+where
+    U: Send + Sync,
+{
+    if crate::is_abusive_invocation(this, msg.author.id, msg.guild_id, msg.channel_id) {
+        return Err(None);
+    }
+
+    let (command_meta, args) = match find_command(this, ctx, msg, route, None, msg_content)
+        .await
+        .map_err(Some)?
+    {
+        Some(x) => x,
+        None => {
+            let command_name = msg_content.split_whitespace().next().unwrap_or("");
+            if let Some(unknown_command) = this.options.prefix_options.unknown_command {
+                if !triggered_by_edit && !command_name.is_empty() {
+                    let ctx = crate::PrefixContext {
+                        discord: ctx,
+                        msg,
+                        framework: this,
+                        data: this.get_user_data().await,
+                        command: None,
+                    };
+                    unknown_command(crate::UnknownCommand {
+                        ctx,
+                        command_name,
+                        suggestions: similar_command_names(this.prefix_commands(), command_name),
+                    })
+                    .await;
+                }
+            }
+            return Err(None);
+        }
+    };
     let command = &command_meta.command;
 
     if triggered_by_edit && !command.options.track_edits {
         return Err(None);
     }
 
-    // Typing is broadcasted as long as this object is alive
-    let _typing_broadcaster = if command.options.broadcast_typing {
-        msg.channel_id.start_typing(&ctx.http).ok()
-    } else {
-        None
-    };
+    // Edits which only swap an alias for the command name (or the other way around) don't change
+    // the invocation, so the command isn't run again
+    if let Some(edit_tracker) = &this.options.prefix_options.edit_tracker {
+        if command.options.track_edits {
+            let invocation = format!(
+                "{}\n{}\n{:?}",
+                crate::qualified_command_name(this, crate::CommandRef::Prefix(command)),
+                args,
+                msg.attachments.iter().map(|a| a.id).collect::<Vec<_>>(),
+            );
+            let changed = edit_tracker.write().track_invocation(msg.id, invocation);
+            if triggered_by_edit && !changed {
+                return Err(None);
+            }
+        }
+    }
 
     let ctx = crate::PrefixContext {
         discord: ctx,
@@ -241,22 +667,157 @@ where
         data: this.get_user_data().await,
         command: Some(command),
     };
+    #[cfg(feature = "tracing")]
+    crate::instrumentation::record_command(crate::Context::Prefix(ctx));
+
+    if crate::check_feature_gate(
+        crate::Context::Prefix(ctx),
+        crate::CommandRef::Prefix(command),
+    )
+    .await
+    {
+        return Err(None);
+    }
+
+    if crate::check_command_disabled(
+        crate::Context::Prefix(ctx),
+        crate::CommandRef::Prefix(command),
+    )
+    .await
+    {
+        return Err(None);
+    }
+
+    if !crate::check_channel_type(
+        crate::Context::Prefix(ctx),
+        crate::CommandRef::Prefix(command),
+    )
+    .await
+    {
+        return Err(None);
+    }
+
+    if !crate::check_nsfw_channel(
+        crate::Context::Prefix(ctx),
+        crate::CommandRef::Prefix(command),
+    )
+    .await
+    {
+        return Err(None);
+    }
+
+    if !crate::check_required_roles(
+        crate::Context::Prefix(ctx),
+        crate::CommandRef::Prefix(command),
+    )
+    .await
+    {
+        return Err(None);
+    }
+
+    if !crate::check_cooldowns(
+        crate::Context::Prefix(ctx),
+        crate::CommandRef::Prefix(command),
+        &command.options.cooldowns,
+        command.options.cooldown_key,
+        command.options.bucket,
+        command.options.on_cooldown,
+    )
+    .await
+    {
+        return Err(None);
+    }
+
+    // Held until the command finished
+    let _concurrency_permit = crate::acquire_concurrency_permit(
+        crate::Context::Prefix(ctx),
+        crate::CommandRef::Prefix(command),
+    )
+    .await
+    .map_err(|()| None)?;
+
+    // Typing is broadcasted as long as this object is alive
+    let _typing_broadcaster = if command.options.broadcast_typing {
+        msg.channel_id.start_typing(&ctx.discord.http).ok()
+    } else {
+        None
+    };
 
     (this.options.pre_command)(crate::Context::Prefix(ctx)).await;
 
-    // Execute command
-    let res = (command.action)(ctx, args).await.map_err(|e| {
-        Some((
-            e,
-            crate::PrefixCommandErrorContext {
-                ctx,
-                command,
-                while_checking: false,
-            },
-        ))
+    crate::emit_command_event(
+        crate::Context::Prefix(ctx),
+        crate::CommandEventKind::Started,
+    );
+    let start = std::time::Instant::now();
+    let action = Box::pin(crate::run_with_timeout(
+        crate::Context::Prefix(ctx),
+        crate::catch_command_panic(
+            crate::Context::Prefix(ctx),
+            crate::Next::new(
+                crate::Context::Prefix(ctx),
+                &this.options.middleware,
+                (command.action)(ctx, args),
+            )
+            .run(),
+        ),
+    ));
+    // Execute command. The delayed typing timer runs alongside it from the start, and the
+    // typing indicator only starts if the command hasn't responded by then
+    let res = match command.options.broadcast_typing_after {
+        Some(delay) => {
+            let invocation_id = crate::Context::Prefix(ctx).id();
+            this.delayed_typing.register(invocation_id);
+            let typing_timer = Box::pin(async {
+                tokio::time::sleep(delay).await;
+                this.delayed_typing.start(invocation_id, || {
+                    msg.channel_id.start_typing(&ctx.discord.http).ok()
+                });
+                futures::future::pending::<()>().await
+            });
+            let res = match futures::future::select(action, typing_timer).await {
+                futures::future::Either::Left((res, _)) => res,
+                futures::future::Either::Right(((), _)) => unreachable!(),
+            };
+            this.delayed_typing.stop(invocation_id);
+            res
+        }
+        None => action.await,
+    };
+    // A panic or timeout was already passed to on_command_panic or on_command_timeout
+    let res = res.map_or(Err(None), |result| {
+        result.map_err(|e| {
+            Some((
+                e,
+                crate::PrefixCommandErrorContext {
+                    ctx,
+                    command,
+                    while_checking: false,
+                },
+            ))
+        })
     });
+    crate::finish_cooldowns(crate::Context::Prefix(ctx), res.is_ok()).await;
+    #[cfg(feature = "tracing")]
+    if res.is_err() {
+        tracing::warn!("command returned an error");
+    }
+    crate::emit_command_event(
+        crate::Context::Prefix(ctx),
+        crate::finished_event_kind(res.is_ok(), start.elapsed()),
+    );
 
     (this.options.post_command)(crate::Context::Prefix(ctx)).await;
 
     res
 }
+
+#[cfg(all(test, feature = "unicode-normalization"))]
+#[test]
+fn test_strip_normalized_prefix() {
+    assert_eq!(strip_normalized_prefix("！ban user", "!"), Some("ban user"));
+    assert_eq!(strip_normalized_prefix("ＢＯＴ ping", "bot "), Some("ping"));
+    assert_eq!(strip_normalized_prefix("e\u{301}x", "\u{e9}"), Some("x"));
+    assert_eq!(strip_normalized_prefix("?ban", "!"), None);
+    assert_eq!(normalize("ＢＡＮ"), normalize("ban"));
+}
@@ -0,0 +1,181 @@
+//! Commands added and removed while the framework is running, see
+//! [`super::Framework::add_command`]
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// A command added with [`super::Framework::add_command`]
+struct RuntimeCommand<U, E> {
+    prefix_command: Option<crate::PrefixCommandMeta<U, E>>,
+    application_commands: Vec<crate::ApplicationCommandTree<U, E>>,
+    removed: AtomicBool,
+    next: once_cell::sync::OnceCell<Box<RuntimeCommand<U, E>>>,
+}
+
+impl<U, E> RuntimeCommand<U, E> {
+    /// Whether this command has the given name, as prefix, slash or context menu command
+    fn is_named(&self, name: &str) -> bool {
+        self.prefix_command
+            .iter()
+            .any(|meta| meta.command.name == name)
+            || self
+                .application_commands
+                .iter()
+                .any(|command| command.name() == name)
+    }
+}
+
+/// The commands added and removed at runtime, on top of the ones in [`crate::FrameworkOptions`].
+///
+/// Added commands are kept in an append-only linked list. That way, dispatch can hand out
+/// references to them for as long as the framework lives, without holding a lock. The flip side
+/// is that removed commands are only marked as such, and stay allocated until the framework is
+/// dropped.
+pub(super) struct RuntimeCommands<U, E> {
+    head: once_cell::sync::OnceCell<Box<RuntimeCommand<U, E>>>,
+    /// Names of the removed commands from [`crate::FrameworkOptions`]
+    removed_initial: parking_lot::RwLock<std::collections::HashSet<String>>,
+    /// Addresses of the removed prefix subcommands. Commands never move, since the ones from
+    /// [`crate::FrameworkOptions`] live as long as the framework and added ones are never freed
+    removed_subcommands: parking_lot::RwLock<std::collections::HashSet<usize>>,
+}
+
+impl<U, E> Default for RuntimeCommands<U, E> {
+    fn default() -> Self {
+        Self {
+            head: Default::default(),
+            removed_initial: Default::default(),
+            removed_subcommands: Default::default(),
+        }
+    }
+}
+
+fn command_address<U, E>(command: &crate::PrefixCommand<U, E>) -> usize {
+    command as *const crate::PrefixCommand<U, E> as usize
+}
+
+impl<U, E> RuntimeCommands<U, E> {
+    fn iter(&self) -> impl Iterator<Item = &RuntimeCommand<U, E>> {
+        std::iter::successors(self.head.get(), |command| command.next.get())
+            .map(|command| &**command)
+            .filter(|command| !command.removed.load(Ordering::SeqCst))
+    }
+
+    /// Whether the command from [`crate::FrameworkOptions`] with this name was removed
+    pub(super) fn is_initial_removed(&self, name: &str) -> bool {
+        self.removed_initial.read().contains(name)
+    }
+
+    pub(super) fn add(
+        &self,
+        prefix_command: Option<crate::PrefixCommandMeta<U, E>>,
+        application_commands: Vec<crate::ApplicationCommandTree<U, E>>,
+    ) {
+        let mut new_command = Box::new(RuntimeCommand {
+            prefix_command,
+            application_commands,
+            removed: AtomicBool::new(false),
+            next: once_cell::sync::OnceCell::new(),
+        });
+
+        // Walk to the end of the list. If another command is appended concurrently, keep walking
+        let mut slot = &self.head;
+        loop {
+            match slot.set(new_command) {
+                Ok(()) => return,
+                Err(rejected) => {
+                    new_command = rejected;
+                    // Set failed, so the slot is occupied
+                    if let Some(occupant) = slot.get() {
+                        slot = &occupant.next;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns whether any command was removed. `is_initial` tells if there's a command of that
+    /// name in [`crate::FrameworkOptions`]
+    pub(super) fn remove(&self, name: &str, is_initial: bool) -> bool {
+        let mut removed_any = false;
+        if is_initial {
+            removed_any |= self.removed_initial.write().insert(name.to_owned());
+        }
+        for command in self.iter() {
+            if command.is_named(name) {
+                command.removed.store(true, Ordering::SeqCst);
+                removed_any = true;
+            }
+        }
+        removed_any
+    }
+
+    /// Marks the given prefix subcommand as removed. Returns false if it was removed already
+    pub(super) fn remove_subcommand(&self, command: &crate::PrefixCommand<U, E>) -> bool {
+        self.removed_subcommands
+            .write()
+            .insert(command_address(command))
+    }
+
+    /// Whether the given prefix subcommand was removed with [`Self::remove_subcommand`]
+    pub(super) fn is_subcommand_removed(&self, command: &crate::PrefixCommand<U, E>) -> bool {
+        self.removed_subcommands
+            .read()
+            .contains(&command_address(command))
+    }
+
+    pub(super) fn prefix_commands(&self) -> impl Iterator<Item = &crate::PrefixCommandMeta<U, E>> {
+        self.iter()
+            .filter_map(|command| command.prefix_command.as_ref())
+    }
+
+    pub(super) fn application_commands(
+        &self,
+    ) -> impl Iterator<Item = &crate::ApplicationCommandTree<U, E>> {
+        self.iter()
+            .flat_map(|command| &command.application_commands)
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_runtime_commands() {
+    fn prefix_command(name: &'static str) -> Option<crate::PrefixCommandMeta<(), ()>> {
+        Some(crate::PrefixCommandMeta {
+            command: crate::PrefixCommand {
+                name,
+                action: |_, _| Box::pin(async { Ok(()) }),
+                options: Default::default(),
+            },
+            category: None,
+            subcommands: Vec::new(),
+        })
+    }
+    let names = |commands: &RuntimeCommands<(), ()>| {
+        commands
+            .prefix_commands()
+            .map(|meta| meta.command.name)
+            .collect::<Vec<_>>()
+    };
+
+    let commands = RuntimeCommands::default();
+    commands.add(prefix_command("ping"), Vec::new());
+    commands.add(prefix_command("echo"), Vec::new());
+    assert_eq!(names(&commands), ["ping", "echo"]);
+
+    assert!(commands.remove("ping", false));
+    assert!(!commands.remove("ping", false));
+    assert_eq!(names(&commands), ["echo"]);
+
+    commands.add(prefix_command("ping"), Vec::new());
+    assert_eq!(names(&commands), ["echo", "ping"]);
+
+    assert!(!commands.is_initial_removed("help"));
+    assert!(commands.remove("help", true));
+    assert!(commands.is_initial_removed("help"));
+
+    let subcommand = prefix_command("sub").unwrap().command;
+    assert!(!commands.is_subcommand_removed(&subcommand));
+    assert!(commands.remove_subcommand(&subcommand));
+    assert!(!commands.remove_subcommand(&subcommand));
+    assert!(commands.is_subcommand_removed(&subcommand));
+}
@@ -1,5 +1,54 @@
 use crate::serenity_prelude as serenity;
 
+/// Identifies the parameter a user is currently typing into: user, channel, qualified command name,
+/// parameter name. The channel is part of the key because a user may have the same command open in
+/// several channels at once, for example on two devices
+type AutocompleteKey = (serenity::UserId, serenity::ChannelId, String, &'static str);
+
+/// Tracks the autocomplete callback currently running for each user, channel and parameter.
+///
+/// When a user types quickly, Discord sends a new autocomplete interaction for every keystroke.
+/// Only the response to the latest partial input is relevant, so older callbacks which are still
+/// running are aborted when a new autocomplete interaction for the same parameter arrives.
+#[derive(Default)]
+pub(super) struct AutocompleteTracker {
+    next_generation: std::sync::atomic::AtomicU64,
+    in_flight: parking_lot::Mutex<
+        std::collections::HashMap<AutocompleteKey, (u64, futures::future::AbortHandle)>,
+    >,
+}
+
+impl AutocompleteTracker {
+    /// Register a new autocomplete callback, aborting the previous one for the same key if it's
+    /// still running. Returns the generation of the new callback, to be passed to [`Self::finish`]
+    fn start(&self, key: &AutocompleteKey, abort_handle: futures::future::AbortHandle) -> u64 {
+        let generation = self
+            .next_generation
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        if let Some((_, superseded)) = self
+            .in_flight
+            .lock()
+            .insert(key.clone(), (generation, abort_handle))
+        {
+            superseded.abort();
+        }
+        generation
+    }
+
+    /// Unregister a finished autocomplete callback. Returns false if the callback was superseded
+    /// in the meantime, in which case its result should be discarded
+    fn finish(&self, key: &AutocompleteKey, generation: u64) -> bool {
+        let mut in_flight = self.in_flight.lock();
+        match in_flight.get(key) {
+            Some(&(latest_generation, _)) if latest_generation == generation => {
+                in_flight.remove(key);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
 fn find_matching_application_command<'a, 'b, U, E>(
     framework: &'a crate::Framework<U, E>,
     interaction: &'b serenity::ApplicationCommandInteractionData,
@@ -7,8 +56,12 @@ fn find_matching_application_command<'a, 'b, U, E>(
     crate::ApplicationCommand<'a, U, E>,
     &'b [serenity::ApplicationCommandInteractionDataOption],
 )> {
-    let commands = &framework.options.application_options.commands;
-    commands.iter().find_map(|cmd| match cmd {
+    let mut commands = framework
+        .command_index
+        .application_commands(&framework.options.application_options, &interaction.name)
+        .filter(|cmd| !framework.runtime_commands.is_initial_removed(cmd.name()))
+        .chain(framework.runtime_commands.application_commands());
+    commands.find_map(|cmd| match cmd {
         crate::ApplicationCommandTree::ContextMenu(cmd) => {
             let application_command_type = match &cmd.action {
                 crate::ContextMenuCommandAction::User(_) => serenity::ApplicationCommandType::User,
@@ -128,9 +181,12 @@ pub async fn extract_command_and_run_checks<'a, U, E>(
         discord: ctx,
         framework,
         interaction,
+        args: leaf_interaction_options,
         command,
         has_sent_initial_response,
     };
+    #[cfg(feature = "tracing")]
+    crate::instrumentation::record_command(crate::Context::Application(ctx));
 
     // Make sure that user has required permissions
     if !super::check_required_permissions_and_owners_only(
@@ -155,12 +211,15 @@ pub async fn extract_command_and_run_checks<'a, U, E>(
             None => true,
         };
 
+        let group_check_passes =
+            crate::check_command_group(crate::Context::Application(ctx)).await?;
+
         let command_specific_check_passes = match &command.options().check {
             Some(check) => check(ctx).await?,
             None => true,
         };
 
-        Ok(global_check_passes && command_specific_check_passes)
+        Ok(global_check_passes && group_check_passes && command_specific_check_passes)
     })()
     .await
     .map_err(|e| {
@@ -173,6 +232,8 @@ pub async fn extract_command_and_run_checks<'a, U, E>(
         )
     })?;
     if !checks_passing {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("command check failed");
         return Err(None);
     }
 
@@ -186,6 +247,39 @@ pub async fn dispatch_interaction<'a, U, E>(
     // Need to pass this in from outside because of lifetime issues
     has_sent_initial_response: &'a std::sync::atomic::AtomicBool,
 ) -> Result<(), Option<(E, crate::ApplicationCommandErrorContext<'a, U, E>)>> {
+    let invocation = _dispatch_interaction(framework, ctx, interaction, has_sent_initial_response);
+    #[cfg(feature = "tracing")]
+    let invocation = tracing::Instrument::instrument(
+        invocation,
+        crate::instrumentation::invocation_span(
+            interaction.guild_id,
+            interaction.user.id,
+            ctx.shard_id,
+        ),
+    );
+    // Invocations arriving after shutdown began are ignored
+    framework
+        .in_flight
+        .track(invocation)
+        .await
+        .unwrap_or(Err(None))
+}
+
+async fn _dispatch_interaction<'a, U, E>(
+    framework: &'a super::Framework<U, E>,
+    ctx: &'a serenity::Context,
+    interaction: &'a serenity::ApplicationCommandInteraction,
+    has_sent_initial_response: &'a std::sync::atomic::AtomicBool,
+) -> Result<(), Option<(E, crate::ApplicationCommandErrorContext<'a, U, E>)>> {
+    if crate::is_abusive_invocation(
+        framework,
+        interaction.user.id,
+        interaction.guild_id,
+        interaction.channel_id,
+    ) {
+        return Err(None);
+    }
+
     let (ctx, options) = extract_command_and_run_checks(
         framework,
         ctx,
@@ -194,20 +288,86 @@ pub async fn dispatch_interaction<'a, U, E>(
     )
     .await?;
 
+    if crate::check_feature_gate(
+        crate::Context::Application(ctx),
+        crate::CommandRef::Application(ctx.command),
+    )
+    .await
+    {
+        return Err(None);
+    }
+
+    if crate::check_command_disabled(
+        crate::Context::Application(ctx),
+        crate::CommandRef::Application(ctx.command),
+    )
+    .await
+    {
+        return Err(None);
+    }
+
+    if !crate::check_channel_type(
+        crate::Context::Application(ctx),
+        crate::CommandRef::Application(ctx.command),
+    )
+    .await
+    {
+        return Err(None);
+    }
+
+    if !crate::check_nsfw_channel(
+        crate::Context::Application(ctx),
+        crate::CommandRef::Application(ctx.command),
+    )
+    .await
+    {
+        return Err(None);
+    }
+
+    if !crate::check_required_roles(
+        crate::Context::Application(ctx),
+        crate::CommandRef::Application(ctx.command),
+    )
+    .await
+    {
+        return Err(None);
+    }
+
+    if !crate::check_cooldowns(
+        crate::Context::Application(ctx),
+        crate::CommandRef::Application(ctx.command),
+        &ctx.command.options().cooldowns,
+        ctx.command.options().cooldown_key,
+        ctx.command.options().bucket,
+        ctx.command.options().on_cooldown,
+    )
+    .await
+    {
+        return Err(None);
+    }
+
+    // Held until the command finished
+    let _concurrency_permit = crate::acquire_concurrency_permit(
+        crate::Context::Application(ctx),
+        crate::CommandRef::Application(ctx.command),
+    )
+    .await
+    .map_err(|()| None)?;
+
     (framework.options.pre_command)(crate::Context::Application(ctx)).await;
 
-    let action_result = match ctx.command {
-        crate::ApplicationCommand::Slash(cmd) => (cmd.action)(ctx, options).await,
+    let action = match ctx.command {
+        crate::ApplicationCommand::Slash(cmd) => (cmd.action)(ctx, options),
         crate::ApplicationCommand::ContextMenu(cmd) => match cmd.action {
             crate::ContextMenuCommandAction::User(action) => match &interaction.data.target {
-                Some(serenity::ResolvedTarget::User(user, _)) => (action)(ctx, user.clone()).await,
+                Some(serenity::ResolvedTarget::User(user, _)) => (action)(ctx, user.clone()),
                 _ => {
                     println!("Warning: no user object sent in user context menu interaction");
                     return Ok(());
                 }
             },
             crate::ContextMenuCommandAction::Message(action) => match &interaction.data.target {
-                Some(serenity::ResolvedTarget::Message(msg)) => (action)(ctx, msg.clone()).await,
+                Some(serenity::ResolvedTarget::Message(msg)) => (action)(ctx, msg.clone()),
                 _ => {
                     println!("Warning: no message object sent in message context menu interaction");
                     return Ok(());
@@ -215,20 +375,94 @@ pub async fn dispatch_interaction<'a, U, E>(
             },
         },
     };
+    crate::emit_command_event(
+        crate::Context::Application(ctx),
+        crate::CommandEventKind::Started,
+    );
+    let start = std::time::Instant::now();
+    let action_result = crate::run_with_timeout(
+        crate::Context::Application(ctx),
+        crate::catch_command_panic(
+            crate::Context::Application(ctx),
+            crate::Next::new(
+                crate::Context::Application(ctx),
+                &framework.options.middleware,
+                action,
+            )
+            .run(),
+        ),
+    )
+    .await
+    // A panic or timeout was already passed to on_command_panic or on_command_timeout
+    .map_or(Err(None), |result| result.map_err(Some));
+    crate::finish_cooldowns(crate::Context::Application(ctx), action_result.is_ok()).await;
+
+    #[cfg(feature = "tracing")]
+    if action_result.is_err() {
+        tracing::warn!("command returned an error");
+    }
+    crate::emit_command_event(
+        crate::Context::Application(ctx),
+        crate::finished_event_kind(action_result.is_ok(), start.elapsed()),
+    );
 
     (framework.options.post_command)(crate::Context::Application(ctx)).await;
 
     action_result.map_err(|e| {
-        Some((
-            e,
-            crate::ApplicationCommandErrorContext {
-                ctx,
-                while_checking: false,
-            },
-        ))
+        e.map(|e| {
+            (
+                e,
+                crate::ApplicationCommandErrorContext {
+                    ctx,
+                    while_checking: false,
+                },
+            )
+        })
     })
 }
 
+/// The qualified name of the invoked slash command, i.e. the command name followed by the
+/// subcommand group and subcommand names, if any
+fn invoked_command_path(interaction: &serenity::ApplicationCommandInteractionData) -> String {
+    let mut command_path = vec![interaction.name.as_str()];
+    let mut level = &*interaction.options;
+    while let Some(subcommand) = level.iter().find(|option| {
+        option.kind == serenity::ApplicationCommandOptionType::SubCommand
+            || option.kind == serenity::ApplicationCommandOptionType::SubCommandGroup
+    }) {
+        command_path.push(&subcommand.name);
+        level = &subcommand.options;
+    }
+    command_path.join(" ")
+}
+
+/// Key of an autocomplete response in the parameter's [`crate::AutocompleteCache`]. Besides the
+/// focused option, callbacks may depend on the command and on the other options filled in so far,
+/// so all of them are part of the key
+fn autocomplete_cache_key(
+    interaction: &serenity::ApplicationCommandInteractionData,
+    focused_option: &str,
+    options: &[serenity::ApplicationCommandInteractionDataOption],
+) -> String {
+    let values = options
+        .iter()
+        .map(|option| (option.name.as_str(), &option.value))
+        .collect::<Vec<_>>();
+    serde_json::json!([invoked_command_path(interaction), focused_option, values]).to_string()
+}
+
+/// Serialize an autocomplete choice from a runtime autocomplete callback
+fn choice_to_json(choice: crate::AutocompleteChoice<serde_json::Value>) -> serde_json::Value {
+    let mut choice_json = serde_json::json!({
+        "name": choice.name,
+        "value": choice.value,
+    });
+    if !choice.name_localizations.is_empty() {
+        choice_json["name_localizations"] = serde_json::json!(choice.name_localizations);
+    }
+    choice_json
+}
+
 pub async fn dispatch_autocomplete<'a, U, E>(
     framework: &'a super::Framework<U, E>,
     ctx: &'a serenity::Context,
@@ -249,24 +483,120 @@ pub async fn dispatch_autocomplete<'a, U, E>(
         crate::ApplicationCommand::ContextMenu(_) => return Err(None),
     };
 
-    for param in &command.parameters {
-        let autocomplete_callback = match param.autocomplete_callback {
-            Some(x) => x,
-            None => continue,
-        };
+    let focused_option = match options.iter().find(|option| option.focused) {
+        Some(x) => x,
+        None => return Ok(()),
+    };
+    let param = match command
+        .parameters
+        .iter()
+        .find(|param| param.name == focused_option.name)
+    {
+        Some(x) => x,
+        None => {
+            println!(
+                "Warning: received autocomplete interaction for unknown parameter \"{}\"",
+                focused_option.name
+            );
+            return Ok(());
+        }
+    };
+    if !param.has_autocomplete() {
+        return Ok(());
+    }
 
-        if let Err(e) = autocomplete_callback(ctx, interaction, options).await {
-            let error_ctx = crate::ApplicationCommandErrorContext {
-                ctx,
-                while_checking: false,
+    let null = serde_json::Value::Null;
+    let partial_input = focused_option.value.as_ref().unwrap_or(&null);
+    let cache_key = autocomplete_cache_key(&interaction.data, &focused_option.name, options);
+
+    let cached_choices = param
+        .autocomplete_cache
+        .as_ref()
+        .and_then(|cache| cache.get(interaction.guild_id, &cache_key));
+    let choices = match cached_choices {
+        Some(x) => x,
+        None => {
+            // Run the callback abortably, so that it can be cancelled once the user has typed
+            // something new
+            let tracker_key = (
+                interaction.user.id,
+                interaction.channel_id,
+                invoked_command_path(&interaction.data),
+                param.name,
+            );
+            let callback_future = match (&param.autocomplete_closure, param.autocomplete_callback) {
+                (Some(autocomplete_closure), _) => {
+                    let choices_future = autocomplete_closure(ctx, partial_input);
+                    Box::pin(async move {
+                        let choices = choices_future.await?;
+                        Ok(serde_json::Value::Array(
+                            choices.into_iter().map(choice_to_json).collect(),
+                        ))
+                    }) as crate::BoxFuture<'_, _>
+                }
+                (None, Some(autocomplete_callback)) => autocomplete_callback(ctx, partial_input),
+                (None, None) => return Ok(()),
+            };
+            let (callback_future, abort_handle) = futures::future::abortable(callback_future);
+            let generation = framework
+                .autocomplete_tracker
+                .start(&tracker_key, abort_handle);
+            let callback_result = callback_future.await;
+            if !framework
+                .autocomplete_tracker
+                .finish(&tracker_key, generation)
+            {
+                return Ok(());
+            }
+            let callback_result = match callback_result {
+                Ok(x) => x,
+                Err(futures::future::Aborted) => return Ok(()),
             };
 
-            if let Some(on_error) = error_ctx.ctx.command.options().on_error {
-                on_error(e, error_ctx).await;
-            } else {
-                (framework.options.on_error)(e, crate::ErrorContext::Autocomplete(error_ctx)).await;
+            match callback_result {
+                Ok(choices) => {
+                    if let Some(cache) = &param.autocomplete_cache {
+                        cache.insert(interaction.guild_id, cache_key, choices.clone());
+                    }
+                    choices
+                }
+                Err(e) => {
+                    let error_ctx = crate::ApplicationCommandErrorContext {
+                        ctx,
+                        while_checking: false,
+                    };
+
+                    if let Some(on_error) = error_ctx.ctx.command.options().on_error {
+                        on_error(e, error_ctx).await;
+                    } else {
+                        (framework.options.on_error)(
+                            e,
+                            crate::ErrorContext::Autocomplete(error_ctx),
+                        )
+                        .await;
+                    }
+
+                    match param.autocomplete_fallback {
+                        Some(autocomplete_fallback) => autocomplete_fallback(),
+                        None => return Ok(()),
+                    }
+                }
             }
         }
+    };
+
+    let choices = crate::sanitize_autocomplete_choices(
+        choices,
+        framework
+            .options
+            .application_options
+            .autocomplete_truncation,
+    );
+    if let Err(e) = interaction
+        .create_autocomplete_response(&ctx.discord.http, |b| b.set_choices(choices))
+        .await
+    {
+        println!("Warning: couldn't send autocomplete response: {}", e);
     }
 
     Ok(())
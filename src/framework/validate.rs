@@ -0,0 +1,445 @@
+//! Checks the application commands against Discord's constraints before they're registered, see
+//! [`super::Framework::validate`]
+
+/// A way in which an application command violates Discord's constraints, see
+/// [`CommandValidationError`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandViolation {
+    /// Slash command, subcommand and option names must be 1-32 lowercase letters, digits, `-` or
+    /// `_`
+    InvalidName {
+        /// The offending name
+        name: String,
+    },
+    /// Context menu command names must be 1-32 characters long
+    InvalidContextMenuName {
+        /// The offending name
+        name: String,
+    },
+    /// Descriptions must be 1-100 characters long
+    InvalidDescriptionLength {
+        /// Length of the description in characters
+        length: usize,
+    },
+    /// A command or subcommand can have at most 25 options, and a subcommand group at most 25
+    /// subcommands
+    TooManyOptions {
+        /// Number of options
+        count: usize,
+    },
+    /// An option can have at most 25 choices
+    TooManyChoices {
+        /// Number of choices
+        count: usize,
+    },
+    /// Required options must come before all optional options
+    RequiredAfterOptional,
+    /// Command names must be unique per command type, and option names per command
+    DuplicateName {
+        /// The name which occurs more than once
+        name: String,
+    },
+    /// Subcommands can only be nested in a subcommand group, and subcommand groups only directly
+    /// in a top-level command
+    TooDeep,
+    /// A command can either have subcommands and subcommand groups or other options, not both
+    MixedOptions,
+    /// Subcommand groups can only contain subcommands, not other options
+    OptionInSubcommandGroup,
+    /// There can be at most 100 slash commands, 5 user context menu commands and 5 message context
+    /// menu commands
+    TooManyCommands {
+        /// `"slash"`, `"user context menu"` or `"message context menu"`
+        command_type: &'static str,
+        /// Number of commands of this type
+        count: usize,
+        /// Maximum number of commands of this type
+        max: usize,
+    },
+    /// Commands can only use rate limit buckets which are registered in
+    /// [`crate::FrameworkOptions::rate_limit_buckets`]
+    UnknownBucket {
+        /// The bucket name declared by the command
+        bucket: String,
+    },
+}
+
+impl std::fmt::Display for CommandViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidName { name } => write!(
+                f,
+                "invalid name `{}`, must be 1-32 lowercase letters, digits, `-` or `_`",
+                name
+            ),
+            Self::InvalidContextMenuName { name } => {
+                write!(f, "invalid name `{}`, must be 1-32 characters long", name)
+            }
+            Self::InvalidDescriptionLength { length } => write!(
+                f,
+                "description is {} characters long, must be 1-100",
+                length
+            ),
+            Self::TooManyOptions { count } => {
+                write!(f, "has {} options, at most 25 are allowed", count)
+            }
+            Self::TooManyChoices { count } => {
+                write!(f, "has {} choices, at most 25 are allowed", count)
+            }
+            Self::RequiredAfterOptional => write!(f, "required option after an optional option"),
+            Self::DuplicateName { name } => write!(f, "name `{}` is used more than once", name),
+            Self::TooDeep => write!(
+                f,
+                "nested too deeply, subcommands can only be nested in a subcommand group"
+            ),
+            Self::MixedOptions => write!(
+                f,
+                "has both subcommands and other options, which can't be mixed"
+            ),
+            Self::OptionInSubcommandGroup => write!(
+                f,
+                "is in a subcommand group, which can only contain subcommands"
+            ),
+            Self::TooManyCommands {
+                command_type,
+                count,
+                max,
+            } => write!(
+                f,
+                "{} {} commands, at most {} are allowed",
+                count, command_type, max
+            ),
+            Self::UnknownBucket { bucket } => {
+                write!(f, "uses the unknown rate limit bucket `{}`", bucket)
+            }
+        }
+    }
+}
+
+/// An application command which would be rejected by Discord, returned by
+/// [`super::Framework::validate`], or a command using an unknown rate limit bucket
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandValidationError {
+    /// Names leading to the offending command or subcommand, separated by spaces, like
+    /// `settings prefix`. Empty if the violation concerns all commands
+    pub command_path: String,
+    /// Name of the offending option of that command, if the violation concerns an option
+    pub option: Option<String>,
+    /// What's wrong
+    pub violation: CommandViolation,
+}
+
+impl std::fmt::Display for CommandValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.command_path.is_empty() {
+            write!(f, "`{}`", self.command_path)?;
+            if let Some(option) = &self.option {
+                write!(f, " option `{}`", option)?;
+            }
+            write!(f, ": ")?;
+        }
+        write!(f, "{}", self.violation)
+    }
+}
+
+impl std::error::Error for CommandValidationError {}
+
+/// `type` values of the Discord API
+const CHAT_INPUT: u64 = 1;
+const USER: u64 = 2;
+const MESSAGE: u64 = 3;
+const SUB_COMMAND: u64 = 1;
+const SUB_COMMAND_GROUP: u64 = 2;
+
+const MAX_OPTIONS: usize = 25;
+const MAX_CHOICES: usize = 25;
+
+fn str_field<'a>(value: &'a serde_json::Value, field: &str) -> &'a str {
+    value.get(field).and_then(|x| x.as_str()).unwrap_or("")
+}
+
+fn array_field<'a>(value: &'a serde_json::Value, field: &str) -> &'a [serde_json::Value] {
+    value
+        .get(field)
+        .and_then(|x| x.as_array())
+        .map_or(&[][..], |x| x.as_slice())
+}
+
+struct Validator {
+    errors: Vec<CommandValidationError>,
+}
+
+impl Validator {
+    fn push(&mut self, command_path: &str, option: Option<&str>, violation: CommandViolation) {
+        self.errors.push(CommandValidationError {
+            command_path: command_path.to_owned(),
+            option: option.map(|x| x.to_owned()),
+            violation,
+        });
+    }
+
+    /// Checks the name and description of a slash command, subcommand or option
+    fn check_name_and_description(
+        &mut self,
+        command_path: &str,
+        option: Option<&str>,
+        value: &serde_json::Value,
+    ) {
+        let name = str_field(value, "name");
+        let name_length = name.chars().count();
+        let is_valid_char =
+            |c: char| (c.is_alphanumeric() && !c.is_uppercase()) || c == '-' || c == '_';
+        if name_length < 1 || name_length > 32 || !name.chars().all(is_valid_char) {
+            let name = name.to_owned();
+            self.push(command_path, option, CommandViolation::InvalidName { name });
+        }
+
+        let length = str_field(value, "description").chars().count();
+        if length < 1 || length > 100 {
+            let violation = CommandViolation::InvalidDescriptionLength { length };
+            self.push(command_path, option, violation);
+        }
+    }
+
+    /// Reports the names occurring more than once. Duplicate top-level commands are reported with
+    /// their own name as command path
+    fn check_duplicates<'a>(
+        &mut self,
+        command_path: Option<&str>,
+        names: impl Iterator<Item = &'a str>,
+    ) {
+        let mut seen = std::collections::HashSet::new();
+        let mut reported = std::collections::HashSet::new();
+        for name in names {
+            if !seen.insert(name) && reported.insert(name) {
+                let violation = CommandViolation::DuplicateName {
+                    name: name.to_owned(),
+                };
+                self.push(command_path.unwrap_or(name), None, violation);
+            }
+        }
+    }
+
+    /// Checks the options of a command, subcommand group or subcommand. `parent_kind` is the
+    /// `type` of the subcommand group or subcommand owning the options, or None for top-level
+    /// commands
+    fn check_options(
+        &mut self,
+        command_path: &str,
+        options: &[serde_json::Value],
+        parent_kind: Option<u64>,
+    ) {
+        if options.len() > MAX_OPTIONS {
+            let violation = CommandViolation::TooManyOptions {
+                count: options.len(),
+            };
+            self.push(command_path, None, violation);
+        }
+        self.check_duplicates(
+            Some(command_path),
+            options.iter().map(|o| str_field(o, "name")),
+        );
+
+        let kind_of = |option: &serde_json::Value| option.get("type").and_then(|x| x.as_u64());
+        let is_subcommand =
+            |kind: Option<u64>| kind == Some(SUB_COMMAND) || kind == Some(SUB_COMMAND_GROUP);
+        let has_subcommands = options.iter().any(|o| is_subcommand(kind_of(o)));
+        let has_other_options = options.iter().any(|o| !is_subcommand(kind_of(o)));
+        if has_subcommands && has_other_options && parent_kind != Some(SUB_COMMAND_GROUP) {
+            self.push(command_path, None, CommandViolation::MixedOptions);
+        }
+
+        let mut seen_optional = false;
+        for option in options {
+            let name = str_field(option, "name");
+            let kind = kind_of(option);
+            if is_subcommand(kind) {
+                let subcommand_path = format!("{} {}", command_path, name);
+                self.check_name_and_description(&subcommand_path, None, option);
+
+                let allowed = match parent_kind {
+                    None => true,
+                    Some(SUB_COMMAND_GROUP) => kind == Some(SUB_COMMAND),
+                    Some(_) => false,
+                };
+                if !allowed {
+                    self.push(&subcommand_path, None, CommandViolation::TooDeep);
+                }
+                self.check_options(&subcommand_path, array_field(option, "options"), kind);
+                continue;
+            }
+
+            self.check_name_and_description(command_path, Some(name), option);
+            if parent_kind == Some(SUB_COMMAND_GROUP) {
+                let violation = CommandViolation::OptionInSubcommandGroup;
+                self.push(command_path, Some(name), violation);
+            }
+
+            let required = option
+                .get("required")
+                .and_then(|x| x.as_bool())
+                .unwrap_or(false);
+            if !required {
+                seen_optional = true;
+            } else if seen_optional {
+                let violation = CommandViolation::RequiredAfterOptional;
+                self.push(command_path, Some(name), violation);
+            }
+
+            let count = array_field(option, "choices").len();
+            if count > MAX_CHOICES {
+                let violation = CommandViolation::TooManyChoices { count };
+                self.push(command_path, Some(name), violation);
+            }
+        }
+    }
+}
+
+/// Checks that all commands of the framework only use registered rate limit buckets
+pub(super) fn validate_rate_limit_buckets<U, E>(
+    framework: &super::Framework<U, E>,
+) -> Vec<CommandValidationError> {
+    let mut errors = Vec::new();
+    for (path, command) in super::command_paths::command_paths(framework) {
+        let bucket = match command {
+            crate::CommandRef::Prefix(command) => command.options.bucket,
+            crate::CommandRef::Application(command) => command.options().bucket,
+        };
+        let bucket = match bucket {
+            Some(x) => x,
+            None => continue,
+        };
+        if framework.options().rate_limit_buckets.contains_key(bucket) {
+            continue;
+        }
+
+        let error = CommandValidationError {
+            command_path: path,
+            option: None,
+            violation: CommandViolation::UnknownBucket {
+                bucket: bucket.to_owned(),
+            },
+        };
+        // Prefix and application variants of a command have the same path
+        if !errors.contains(&error) {
+            errors.push(error);
+        }
+    }
+    errors
+}
+
+/// Checks application commands in the JSON format of the Discord API
+pub(super) fn validate_commands_json(
+    commands: &[serde_json::Value],
+) -> Vec<CommandValidationError> {
+    let mut validator = Validator { errors: Vec::new() };
+    let command_type = |command: &serde_json::Value| {
+        command
+            .get("type")
+            .and_then(|x| x.as_u64())
+            .unwrap_or(CHAT_INPUT)
+    };
+
+    for &(kind, type_name, max) in &[
+        (CHAT_INPUT, "slash", 100),
+        (USER, "user context menu", 5),
+        (MESSAGE, "message context menu", 5),
+    ] {
+        let commands = commands.iter().filter(|&c| command_type(c) == kind);
+        validator.check_duplicates(None, commands.clone().map(|c| str_field(c, "name")));
+
+        let count = commands.count();
+        if count > max {
+            let violation = CommandViolation::TooManyCommands {
+                command_type: type_name,
+                count,
+                max,
+            };
+            validator.push("", None, violation);
+        }
+    }
+
+    for command in commands {
+        let name = str_field(command, "name");
+        if command_type(command) == CHAT_INPUT {
+            validator.check_name_and_description(name, None, command);
+            validator.check_options(name, array_field(command, "options"), None);
+        } else {
+            let length = name.chars().count();
+            if length < 1 || length > 32 {
+                let violation = CommandViolation::InvalidContextMenuName {
+                    name: name.to_owned(),
+                };
+                validator.push(name, None, violation);
+            }
+        }
+    }
+
+    validator.errors
+}
+
+#[cfg(test)]
+#[test]
+fn test_validate_commands_json() {
+    use serde_json::json;
+
+    let violations = |commands: serde_json::Value| {
+        let commands = commands.as_array().unwrap().clone();
+        validate_commands_json(&commands)
+            .into_iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>()
+    };
+
+    let valid = json!([
+        { "name": "ping", "description": "Pong", "options": [
+            { "type": 3, "name": "text", "description": "Text", "required": true },
+            { "type": 4, "name": "count", "description": "Count" },
+        ] },
+        { "name": "settings", "description": "Settings", "options": [
+            { "type": 2, "name": "prefix", "description": "Prefix", "options": [
+                { "type": 1, "name": "set", "description": "Set the prefix" },
+            ] },
+        ] },
+        { "type": 2, "name": "User Info" },
+    ]);
+    assert!(violations(valid).is_empty());
+
+    let invalid = json!([
+        { "name": "Ping", "description": "", "options": [
+            { "type": 4, "name": "count", "description": "Count" },
+            { "type": 3, "name": "text", "description": "Text", "required": true },
+        ] },
+        { "name": "ping", "description": "Pong" },
+        { "name": "ping", "description": "Pong again" },
+        { "name": "settings", "description": "Settings", "options": [
+            { "type": 2, "name": "a", "description": "A", "options": [
+                { "type": 2, "name": "b", "description": "B" },
+                { "type": 3, "name": "c", "description": "C" },
+            ] },
+            { "type": 1, "name": "d", "description": "D", "options": [
+                { "type": 1, "name": "e", "description": "E" },
+            ] },
+        ] },
+        { "name": "mixed", "description": "Mixed", "options": [
+            { "type": 1, "name": "sub", "description": "Sub" },
+            { "type": 3, "name": "text", "description": "Text" },
+        ] },
+    ]);
+    assert_eq!(
+        violations(invalid),
+        [
+            "`ping`: name `ping` is used more than once",
+            "`Ping`: invalid name `Ping`, must be 1-32 lowercase letters, digits, `-` or `_`",
+            "`Ping`: description is 0 characters long, must be 1-100",
+            "`Ping` option `text`: required option after an optional option",
+            "`settings a b`: nested too deeply, subcommands can only be nested in a subcommand \
+                group",
+            "`settings a` option `c`: is in a subcommand group, which can only contain \
+                subcommands",
+            "`settings d e`: nested too deeply, subcommands can only be nested in a subcommand \
+                group",
+            "`mixed`: has both subcommands and other options, which can't be mixed",
+        ]
+    );
+}
@@ -0,0 +1,111 @@
+//! Common per-guild configuration, see [`GuildSettingsProvider`]
+
+use crate::serenity_prelude as serenity;
+
+/// Error type returned by [`GuildSettingsProvider`] implementations
+pub type GuildSettingsError = Box<dyn std::error::Error + Send + Sync>;
+
+/// The configuration of a single guild, see [`GuildSettingsProvider`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GuildSettings {
+    /// Prefix of the guild. Replaces [`crate::PrefixFrameworkOptions::prefix`] and
+    /// [`crate::PrefixFrameworkOptions::additional_prefixes`] in the guild, unless the
+    /// [`crate::PrefixFrameworkOptions::prefix_store`] has a prefix for it
+    pub prefix: Option<String>,
+    /// Discord locale code of the guild, like `en-US` or `de`. Durations in
+    /// [`crate::builtins::cooldown_feedback`] and in the cooldowns listed by the help menus are
+    /// formatted in this locale, see [`crate::builtins::humanize_duration`]
+    pub locale: Option<String>,
+    /// Categories whose commands can't be invoked in the guild and are hidden in the help menus
+    pub disabled_categories: Vec<String>,
+    /// Channel into which the error builtins post command errors which occur in the guild
+    pub mod_log_channel: Option<serenity::ChannelId>,
+}
+
+/// Storage backend for per-guild configuration, used by
+/// [`crate::FrameworkOptions::guild_settings`]
+///
+/// Prefix dispatch, disabled command checks, the help builtins and the error builtins consult it.
+/// Commands can read the settings of the current guild with [`crate::Context::guild_settings`].
+///
+/// Implement this trait to persist the settings in a database. [`InMemoryGuildSettings`] is
+/// provided for bots which don't need persistence.
+#[async_trait::async_trait]
+pub trait GuildSettingsProvider: Send + Sync {
+    /// Returns the settings of the given guild, or the default settings if there are none.
+    ///
+    /// This is called for every message in a guild, so it should be fast. Errors can't be
+    /// reported from here; implementations should log them and return the default settings.
+    async fn get(&self, guild_id: serenity::GuildId) -> GuildSettings;
+
+    /// Replaces the settings of the given guild
+    async fn set(
+        &self,
+        guild_id: serenity::GuildId,
+        settings: GuildSettings,
+    ) -> Result<(), GuildSettingsError>;
+}
+
+/// A [`GuildSettingsProvider`] which keeps the settings in memory. They are lost when the bot
+/// restarts.
+#[derive(Debug, Default)]
+pub struct InMemoryGuildSettings {
+    settings: parking_lot::RwLock<std::collections::HashMap<serenity::GuildId, GuildSettings>>,
+}
+
+impl InMemoryGuildSettings {
+    /// Create a provider in which all guilds have the default settings
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait::async_trait]
+impl GuildSettingsProvider for InMemoryGuildSettings {
+    async fn get(&self, guild_id: serenity::GuildId) -> GuildSettings {
+        self.settings
+            .read()
+            .get(&guild_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    async fn set(
+        &self,
+        guild_id: serenity::GuildId,
+        settings: GuildSettings,
+    ) -> Result<(), GuildSettingsError> {
+        self.settings.write().insert(guild_id, settings);
+        Ok(())
+    }
+}
+
+/// The settings of the given guild, or None if there's no guild or no
+/// [`crate::FrameworkOptions::guild_settings`]
+pub(crate) async fn settings_of<U, E>(
+    framework: &crate::Framework<U, E>,
+    guild_id: Option<serenity::GuildId>,
+) -> Option<GuildSettings> {
+    let provider = framework.options().guild_settings.as_ref()?;
+    Some(provider.get(guild_id?).await)
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn test_in_memory_guild_settings() {
+    let provider = InMemoryGuildSettings::new();
+    let guild = serenity::GuildId(1);
+    assert_eq!(provider.get(guild).await, GuildSettings::default());
+
+    let settings = GuildSettings {
+        prefix: Some("$".into()),
+        disabled_categories: vec!["Fun".into()],
+        ..Default::default()
+    };
+    provider.set(guild, settings.clone()).await.unwrap();
+    assert_eq!(provider.get(guild).await, settings);
+    assert_eq!(
+        provider.get(serenity::GuildId(2)).await,
+        GuildSettings::default()
+    );
+}
@@ -0,0 +1,70 @@
+//! Spans and events for the `tracing` feature. Each command invocation runs in a
+//! `command_invocation` span, and checks failures, cooldown hits and command errors are emitted as
+//! events within it.
+
+use crate::serenity_prelude as serenity;
+
+/// Creates the span of an invocation. The command and invocation kind aren't known yet at this
+/// point, they're filled in by [`record_command`]
+pub(crate) fn invocation_span(
+    guild_id: Option<serenity::GuildId>,
+    user_id: serenity::UserId,
+    shard_id: u64,
+) -> tracing::Span {
+    let span = tracing::info_span!(
+        "command_invocation",
+        command = tracing::field::Empty,
+        kind = tracing::field::Empty,
+        guild_id = tracing::field::Empty,
+        user_id = user_id.0,
+        shard_id = shard_id,
+    );
+    if let Some(guild_id) = guild_id {
+        span.record("guild_id", &guild_id.0);
+    }
+    span
+}
+
+/// Fills in the command of the current invocation span
+pub(crate) fn record_command<U, E>(ctx: crate::Context<'_, U, E>) {
+    let (command, kind) = match ctx {
+        crate::Context::Prefix(ctx) => match ctx.command {
+            Some(command) => (
+                crate::qualified_command_name(ctx.framework, crate::CommandRef::Prefix(command)),
+                "prefix",
+            ),
+            None => return,
+        },
+        crate::Context::Application(ctx) => {
+            let kind = match ctx.command {
+                crate::ApplicationCommand::Slash(_) => "slash",
+                crate::ApplicationCommand::ContextMenu(_) => "context_menu",
+            };
+            (
+                qualified_application_command_name(ctx.interaction.data()),
+                kind,
+            )
+        }
+    };
+
+    let span = tracing::Span::current();
+    span.record("command", &command.as_str());
+    span.record("kind", &kind);
+}
+
+/// The invoked command name followed by the subcommand group and subcommand names, if any
+fn qualified_application_command_name(
+    interaction: &serenity::ApplicationCommandInteractionData,
+) -> String {
+    let mut name = interaction.name.clone();
+    let mut options = &interaction.options;
+    while let Some(subcommand) = options.iter().find(|option| {
+        option.kind == serenity::ApplicationCommandOptionType::SubCommand
+            || option.kind == serenity::ApplicationCommandOptionType::SubCommandGroup
+    }) {
+        name += " ";
+        name += &subcommand.name;
+        options = &subcommand.options;
+    }
+    name
+}
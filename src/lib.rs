@@ -14,7 +14,7 @@ mod slash;
 pub use slash::*;
 
 mod event;
-pub use event::{Event, EventWrapper};
+pub use event::{Event, EventSubscriber, EventWrapper};
 
 mod structs;
 pub use structs::*;
@@ -25,7 +25,68 @@ pub use framework::*;
 mod reply;
 pub use reply::*;
 
+mod validation;
+pub use validation::*;
+
+mod cooldown;
+pub use cooldown::*;
+
+mod command_filter;
+pub use command_filter::*;
+
+mod channel_type;
+pub use channel_type::*;
+
+mod throttle;
+pub use throttle::*;
+
+mod metrics;
+pub use metrics::*;
+
+mod middleware;
+pub use middleware::*;
+
+mod rate_limit;
+pub use rate_limit::*;
+
+mod extensions;
+pub use extensions::*;
+
+mod command_panic;
+pub use command_panic::*;
+
+mod command_timeout;
+pub use command_timeout::*;
+
+mod concurrency;
+pub use concurrency::*;
+
+mod background_task;
+pub use background_task::*;
+
+mod ready_hook;
+pub use ready_hook::*;
+
+mod command_group;
+pub use command_group::*;
+
+mod guild_settings;
+pub use guild_settings::*;
+
+mod feature_gate;
+pub use feature_gate::*;
+
+mod check_cache;
+pub use check_cache::*;
+
+mod required_roles;
+pub use required_roles::*;
+
+#[cfg(feature = "tracing")]
+mod instrumentation;
+
 pub mod builtins;
+pub mod checks;
 /// See [`builtins`]
 #[deprecated = "`samples` module was renamed to `builtins`"]
 pub mod samples {
@@ -0,0 +1,455 @@
+//! Command invocation statistics collected by the framework, see [`CommandMetrics`], and
+//! structured invocation events for exporting them, see [`MetricsSink`]
+
+use crate::serenity_prelude as serenity;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// How often a command was invoked, and how many of these invocations returned an error
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CommandInvocationCounts {
+    /// Number of times the command ran
+    pub invocations: u64,
+    /// Number of times the command returned an error
+    pub errors: u64,
+}
+
+/// A single recorded invocation
+#[derive(Debug, Clone)]
+struct Invocation {
+    command_name: String,
+    user_id: serenity::UserId,
+    succeeded: bool,
+    time: Instant,
+}
+
+/// Command and user statistics of the invocations within a time window, see
+/// [`CommandMetrics::window_stats`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WindowStats {
+    /// Invocation counts of each command that ran in the window, most invoked first
+    pub commands: Vec<(String, CommandInvocationCounts)>,
+    /// Invocation counts of each user who invoked commands in the window, most active first
+    pub users: Vec<(serenity::UserId, CommandInvocationCounts)>,
+}
+
+/// Invocation counts of each command since the framework was created, and of commands and users
+/// within recent time windows. See [`crate::Framework::metrics`].
+///
+/// The framework feeds these metrics as a [`MetricsSink`], alongside
+/// [`crate::FrameworkOptions::metrics_sink`], so both see the same invocations under the same
+/// qualified command names, like `mod ban`. An invocation is recorded once the command has run.
+/// Invocations which were rejected before, for example by a check or a cooldown, are not counted.
+#[derive(Debug, Default)]
+pub struct CommandMetrics {
+    counts: parking_lot::Mutex<HashMap<String, CommandInvocationCounts>>,
+    /// The most recent invocations, oldest first, for [`Self::window_stats`]
+    recent: parking_lot::Mutex<VecDeque<Invocation>>,
+}
+
+/// Sorts by invocations descendingly, then by key
+fn sort_counts<K: Ord>(counts: &mut [(K, CommandInvocationCounts)]) {
+    counts.sort_by(|(a_key, a), (b_key, b)| {
+        b.invocations
+            .cmp(&a.invocations)
+            .then_with(|| a_key.cmp(b_key))
+    });
+}
+
+impl CommandMetrics {
+    /// How many of the most recent invocations are kept for [`Self::window_stats`]
+    pub const MAX_RECENT_INVOCATIONS: usize = 10_000;
+
+    fn record_at(
+        &self,
+        command_name: &str,
+        user_id: serenity::UserId,
+        succeeded: bool,
+        time: Instant,
+    ) {
+        {
+            let mut counts = self.counts.lock();
+            let counts = match counts.get_mut(command_name) {
+                Some(x) => x,
+                None => counts.entry(command_name.to_owned()).or_default(),
+            };
+            counts.invocations += 1;
+            if !succeeded {
+                counts.errors += 1;
+            }
+        }
+
+        let mut recent = self.recent.lock();
+        if recent.len() >= Self::MAX_RECENT_INVOCATIONS {
+            recent.pop_front();
+        }
+        recent.push_back(Invocation {
+            command_name: command_name.to_owned(),
+            user_id,
+            succeeded,
+            time,
+        });
+    }
+
+    /// Statistics of the invocations within the last `window`. Only the most recent
+    /// [`Self::MAX_RECENT_INVOCATIONS`] invocations are kept, so for busy bots the statistics may
+    /// cover a shorter time.
+    pub fn window_stats(&self, window: Duration) -> WindowStats {
+        self.window_stats_at(window, Instant::now())
+    }
+
+    fn window_stats_at(&self, window: Duration, now: Instant) -> WindowStats {
+        let mut commands = HashMap::<&str, CommandInvocationCounts>::new();
+        let mut users = HashMap::<serenity::UserId, CommandInvocationCounts>::new();
+
+        let recent = self.recent.lock();
+        let in_window = recent
+            .iter()
+            .rev()
+            .take_while(|invocation| now.saturating_duration_since(invocation.time) <= window);
+        let add = |counts: &mut CommandInvocationCounts, succeeded: bool| {
+            counts.invocations += 1;
+            if !succeeded {
+                counts.errors += 1;
+            }
+        };
+        for invocation in in_window {
+            add(
+                commands
+                    .entry(invocation.command_name.as_str())
+                    .or_default(),
+                invocation.succeeded,
+            );
+            add(
+                users.entry(invocation.user_id).or_default(),
+                invocation.succeeded,
+            );
+        }
+
+        let mut commands = commands
+            .into_iter()
+            .map(|(name, counts)| (name.to_owned(), counts))
+            .collect::<Vec<_>>();
+        sort_counts(&mut commands);
+        let mut users = users.into_iter().collect::<Vec<_>>();
+        sort_counts(&mut users);
+        WindowStats { commands, users }
+    }
+
+    /// The invocation counts of every command that ran at least once, most invoked first
+    pub fn invocation_counts(&self) -> Vec<(String, CommandInvocationCounts)> {
+        let mut counts = self
+            .counts
+            .lock()
+            .iter()
+            .map(|(name, &counts)| (name.clone(), counts))
+            .collect::<Vec<_>>();
+        sort_counts(&mut counts);
+        counts
+    }
+
+    /// The invocation counts of all commands combined
+    pub fn total(&self) -> CommandInvocationCounts {
+        self.counts
+            .lock()
+            .values()
+            .fold(CommandInvocationCounts::default(), |total, counts| {
+                CommandInvocationCounts {
+                    invocations: total.invocations + counts.invocations,
+                    errors: total.errors + counts.errors,
+                }
+            })
+    }
+}
+
+impl MetricsSink for CommandMetrics {
+    fn on_event(&self, event: &CommandEvent<'_>) {
+        let succeeded = match event.kind {
+            CommandEventKind::Started => return,
+            CommandEventKind::Succeeded { .. } => true,
+            CommandEventKind::Failed { .. } => false,
+        };
+        self.record_at(event.command_name, event.user_id, succeeded, Instant::now());
+    }
+}
+
+/// How a command was invoked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum InvocationKind {
+    /// As prefix command, i.e. with a message
+    Prefix,
+    /// As slash command
+    Slash,
+    /// As context menu command
+    ContextMenu,
+}
+
+/// What happened to a command invocation, see [`CommandEvent`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CommandEventKind {
+    /// The command passed all checks and cooldowns and is about to run
+    Started,
+    /// The command returned successfully
+    Succeeded {
+        /// How long the command ran
+        latency: Duration,
+    },
+    /// The command returned an error
+    Failed {
+        /// How long the command ran
+        latency: Duration,
+    },
+}
+
+/// A command invocation event passed to [`MetricsSink::on_event`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandEvent<'a> {
+    /// What happened
+    pub kind: CommandEventKind,
+    /// Qualified name of the invoked command, i.e. including the names of its parent commands,
+    /// like `mod ban`
+    pub command_name: &'a str,
+    /// How the command was invoked
+    pub invocation_kind: InvocationKind,
+    /// The invoking user
+    pub user_id: serenity::UserId,
+    /// The guild the command was invoked in, if any
+    pub guild_id: Option<serenity::GuildId>,
+    /// The shard which received the invocation
+    pub shard_id: u64,
+}
+
+/// Receives an event whenever a command starts and finishes, set in
+/// [`crate::FrameworkOptions::metrics_sink`]. Can be used to export usage data, for example to
+/// Prometheus or StatsD, without wrapping every command.
+///
+/// Events are delivered synchronously from dispatch, so implementations should be quick and
+/// hand off slow work, like network requests, to a background task.
+///
+/// See [`MetricsAggregator`] for a simple in-memory implementation.
+pub trait MetricsSink: Send + Sync {
+    /// Called with each command invocation event
+    fn on_event(&self, event: &CommandEvent<'_>);
+}
+
+/// Statistics of a single command, collected by [`MetricsAggregator`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct AggregatedCommandStats {
+    /// Number of times the command started
+    pub started: u64,
+    /// Number of times the command returned successfully
+    pub succeeded: u64,
+    /// Number of times the command returned an error
+    pub failed: u64,
+    /// Combined latency of all finished invocations
+    pub total_latency: Duration,
+    /// The longest latency of a finished invocation
+    pub max_latency: Duration,
+}
+
+impl AggregatedCommandStats {
+    /// The average latency of the finished invocations, or None if none finished yet
+    pub fn average_latency(&self) -> Option<Duration> {
+        let finished = self.succeeded + self.failed;
+        if finished == 0 {
+            return None;
+        }
+        Some(Duration::from_secs_f64(
+            self.total_latency.as_secs_f64() / finished as f64,
+        ))
+    }
+}
+
+/// A [`MetricsSink`] which sums up the events in memory, per command and invocation kind
+#[derive(Debug, Default)]
+pub struct MetricsAggregator {
+    stats: parking_lot::Mutex<HashMap<(String, InvocationKind), AggregatedCommandStats>>,
+}
+
+impl MetricsAggregator {
+    /// Create an aggregator without any events yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The statistics of each command and invocation kind which had any events, sorted by name
+    pub fn stats(&self) -> Vec<(String, InvocationKind, AggregatedCommandStats)> {
+        let mut stats = self
+            .stats
+            .lock()
+            .iter()
+            .map(|((name, kind), &stats)| (name.clone(), *kind, stats))
+            .collect::<Vec<_>>();
+        stats.sort_by(|(a_name, a_kind, _), (b_name, b_kind, _)| {
+            (a_name, a_kind).cmp(&(b_name, b_kind))
+        });
+        stats
+    }
+
+    /// Forgets all statistics
+    pub fn reset(&self) {
+        self.stats.lock().clear();
+    }
+}
+
+impl MetricsSink for MetricsAggregator {
+    fn on_event(&self, event: &CommandEvent<'_>) {
+        let mut stats = self.stats.lock();
+        let stats = stats
+            .entry((event.command_name.to_owned(), event.invocation_kind))
+            .or_default();
+        let latency = match event.kind {
+            CommandEventKind::Started => {
+                stats.started += 1;
+                return;
+            }
+            CommandEventKind::Succeeded { latency } => {
+                stats.succeeded += 1;
+                latency
+            }
+            CommandEventKind::Failed { latency } => {
+                stats.failed += 1;
+                latency
+            }
+        };
+        stats.total_latency += latency;
+        stats.max_latency = stats.max_latency.max(latency);
+    }
+}
+
+/// The event of a command which finished after the given latency
+pub(crate) fn finished_event_kind(succeeded: bool, latency: Duration) -> CommandEventKind {
+    if succeeded {
+        CommandEventKind::Succeeded { latency }
+    } else {
+        CommandEventKind::Failed { latency }
+    }
+}
+
+/// Passes an event about the invoked command to [`crate::Framework::metrics`] and
+/// [`crate::FrameworkOptions::metrics_sink`], if any
+pub(crate) fn emit_command_event<U, E>(ctx: crate::Context<'_, U, E>, kind: CommandEventKind) {
+    let command = match ctx.command() {
+        Some(x) => x,
+        None => return,
+    };
+    let invocation_kind = match command {
+        crate::CommandRef::Prefix(_) => InvocationKind::Prefix,
+        crate::CommandRef::Application(crate::ApplicationCommand::Slash(_)) => {
+            InvocationKind::Slash
+        }
+        crate::CommandRef::Application(crate::ApplicationCommand::ContextMenu(_)) => {
+            InvocationKind::ContextMenu
+        }
+    };
+    let framework = ctx.framework();
+    let command_name = crate::qualified_command_name(framework, command);
+    let event = CommandEvent {
+        kind,
+        command_name: &command_name,
+        invocation_kind,
+        user_id: ctx.author().id,
+        guild_id: ctx.guild_id(),
+        shard_id: ctx.discord().shard_id,
+    };
+
+    framework.metrics().on_event(&event);
+    if let Some(sink) = &framework.options().metrics_sink {
+        sink.on_event(&event);
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_command_metrics() {
+    let metrics = CommandMetrics::default();
+    let event = |command_name, kind| CommandEvent {
+        kind,
+        command_name,
+        invocation_kind: InvocationKind::Prefix,
+        user_id: serenity::UserId(1),
+        guild_id: None,
+        shard_id: 0,
+    };
+    let latency = Duration::from_secs(1);
+    metrics.on_event(&event("ping", CommandEventKind::Started));
+    metrics.on_event(&event("ping", CommandEventKind::Succeeded { latency }));
+    metrics.on_event(&event("ban", CommandEventKind::Succeeded { latency }));
+    metrics.on_event(&event("ban", CommandEventKind::Failed { latency }));
+
+    let counts = |invocations, errors| CommandInvocationCounts {
+        invocations,
+        errors,
+    };
+    assert_eq!(
+        metrics.invocation_counts(),
+        vec![
+            ("ban".to_owned(), counts(2, 1)),
+            ("ping".to_owned(), counts(1, 0))
+        ],
+    );
+    assert_eq!(metrics.total(), counts(3, 1));
+}
+
+#[cfg(test)]
+#[test]
+fn test_window_stats() {
+    let metrics = CommandMetrics::default();
+    let (alice, bob) = (serenity::UserId(1), serenity::UserId(2));
+    let start = Instant::now();
+    metrics.record_at("ping", alice, true, start);
+    metrics.record_at("ping", bob, true, start + Duration::from_secs(100));
+    metrics.record_at("ban", bob, false, start + Duration::from_secs(110));
+
+    let counts = |invocations, errors| CommandInvocationCounts {
+        invocations,
+        errors,
+    };
+    let now = start + Duration::from_secs(120);
+    assert_eq!(
+        metrics.window_stats_at(Duration::from_secs(60), now),
+        WindowStats {
+            commands: vec![
+                ("ban".to_owned(), counts(1, 1)),
+                ("ping".to_owned(), counts(1, 0))
+            ],
+            users: vec![(bob, counts(2, 1))],
+        }
+    );
+    assert_eq!(
+        metrics.window_stats_at(Duration::from_secs(600), now).users,
+        vec![(bob, counts(2, 1)), (alice, counts(1, 0))]
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_metrics_aggregator() {
+    let aggregator = MetricsAggregator::new();
+    let event = |kind| CommandEvent {
+        kind,
+        command_name: "ping",
+        invocation_kind: InvocationKind::Slash,
+        user_id: serenity::UserId(1),
+        guild_id: None,
+        shard_id: 0,
+    };
+    let secs = Duration::from_secs;
+    aggregator.on_event(&event(CommandEventKind::Started));
+    aggregator.on_event(&event(CommandEventKind::Succeeded { latency: secs(1) }));
+    aggregator.on_event(&event(CommandEventKind::Started));
+    aggregator.on_event(&event(CommandEventKind::Failed { latency: secs(3) }));
+
+    let stats = AggregatedCommandStats {
+        started: 2,
+        succeeded: 1,
+        failed: 1,
+        total_latency: secs(4),
+        max_latency: secs(3),
+    };
+    assert_eq!(
+        aggregator.stats(),
+        [("ping".to_owned(), InvocationKind::Slash, stats)]
+    );
+    assert_eq!(stats.average_latency(), Some(secs(2)));
+}
@@ -0,0 +1,71 @@
+//! Layers wrapped around every command invocation, see [`Middleware`]
+
+use crate::BoxFuture;
+
+/// A layer around command execution, used by [`crate::FrameworkOptions::middleware`].
+///
+/// Receives the invocation context and the rest of the chain. Call [`Next::run`] to continue
+/// with the next layer and finally the command itself. A middleware can return early without
+/// calling it to skip the command, measure how long it takes, or inspect and replace the
+/// returned error. It runs for prefix, slash and context menu commands alike, after checks,
+/// cooldowns and [`crate::FrameworkOptions::pre_command`].
+///
+/// ```rust
+/// # type Error = Box<dyn std::error::Error + Send + Sync>;
+/// fn log_duration<'a>(
+///     ctx: poise::Context<'a, (), Error>,
+///     next: poise::Next<'a, (), Error>,
+/// ) -> poise::BoxFuture<'a, Result<(), Error>> {
+///     Box::pin(async move {
+///         let start = std::time::Instant::now();
+///         let result = next.run().await;
+///         if let Some(command) = ctx.command() {
+///             println!("{} took {:?}", command.name(), start.elapsed());
+///         }
+///         result
+///     })
+/// }
+///
+/// let mut options = poise::FrameworkOptions::<(), Error>::default();
+/// options.middleware.push(log_duration);
+/// ```
+pub type Middleware<U, E> =
+    for<'a> fn(crate::Context<'a, U, E>, Next<'a, U, E>) -> BoxFuture<'a, Result<(), E>>;
+
+/// The remaining middleware layers and the command, see [`Middleware`]
+pub struct Next<'a, U, E> {
+    ctx: crate::Context<'a, U, E>,
+    middleware: &'a [Middleware<U, E>],
+    /// The command action. Futures don't do anything until polled, so this is only executed if
+    /// all layers call [`Next::run`]
+    action: BoxFuture<'a, Result<(), E>>,
+}
+
+impl<'a, U, E> Next<'a, U, E> {
+    pub(crate) fn new(
+        ctx: crate::Context<'a, U, E>,
+        middleware: &'a [Middleware<U, E>],
+        action: BoxFuture<'a, Result<(), E>>,
+    ) -> Self {
+        Self {
+            ctx,
+            middleware,
+            action,
+        }
+    }
+
+    /// Runs the next middleware layer, or the command if this was the last layer
+    pub fn run(self) -> BoxFuture<'a, Result<(), E>> {
+        match self.middleware.split_first() {
+            Some((layer, rest)) => layer(
+                self.ctx,
+                Next {
+                    ctx: self.ctx,
+                    middleware: rest,
+                    action: self.action,
+                },
+            ),
+            None => self.action,
+        }
+    }
+}
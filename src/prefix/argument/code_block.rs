@@ -54,6 +54,18 @@ impl std::fmt::Display for CodeBlock {
     }
 }
 
+impl CodeBlock {
+    /// Set the language to the given one if none was specified
+    ///
+    /// Used by the `#[language = "..."]` parameter attribute.
+    pub fn with_default_language(mut self, language: &str) -> Self {
+        if self.language.is_none() {
+            self.language = Some(language.to_owned());
+        }
+        self
+    }
+}
+
 impl<'a> PopArgument<'a> for CodeBlock {
     type Err = CodeBlockError;
 
@@ -122,6 +134,38 @@ impl<'a> PopArgument<'a> for CodeBlock {
     }
 }
 
+/// In slash commands, the code is entered into a string option. The input may be a code block
+/// like in prefix commands; any other input is taken as the code verbatim, without language.
+#[async_trait::async_trait]
+impl crate::SlashArgument for CodeBlock {
+    async fn extract(
+        _: &serenity::Context,
+        _: Option<serenity::GuildId>,
+        _: Option<serenity::ChannelId>,
+        value: &serde_json::Value,
+    ) -> Result<Self, crate::SlashArgError> {
+        let string = value
+            .as_str()
+            .ok_or(crate::SlashArgError::CommandStructureMismatch(
+                "expected string",
+            ))?;
+
+        match Self::pop_from(&ArgString(string.trim())) {
+            Ok((rest, code_block)) if rest.0.trim().is_empty() => Ok(code_block),
+            _ => Ok(Self {
+                code: string.to_owned(),
+                language: None,
+            }),
+        }
+    }
+
+    fn create(
+        builder: &mut serenity::CreateApplicationCommandOption,
+    ) -> &mut serenity::CreateApplicationCommandOption {
+        builder.kind(serenity::ApplicationCommandOptionType::String)
+    }
+}
+
 #[cfg(test)]
 #[test]
 fn test_pop_code_block() {
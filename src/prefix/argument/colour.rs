@@ -0,0 +1,30 @@
+use super::*;
+
+impl<'a> PopArgument<'a> for serenity::Colour {
+    type Err = MaybeEmptyError<crate::ColourParseError>;
+
+    /// Pop a color like `#FF8800` or `orange` from the front of the arguments. See
+    /// [`crate::parse_colour`] for the accepted formats.
+    ///
+    /// ```rust
+    /// # use poise::{ArgString, PopArgument as _};
+    /// use poise::serenity_prelude::Colour;
+    /// assert_eq!(
+    ///     Colour::pop_from(&ArgString("#FF8800 rest")).unwrap(),
+    ///     (ArgString(" rest"), Colour::new(0xFF8800)),
+    /// );
+    /// ```
+    fn pop_from(args: &ArgString<'a>) -> Result<(ArgString<'a>, Self), Self::Err> {
+        Self::pop_from_with_options(args, &PrefixParsingOptions::default())
+    }
+
+    fn pop_from_with_options(
+        args: &ArgString<'a>,
+        options: &PrefixParsingOptions,
+    ) -> Result<(ArgString<'a>, Self), Self::Err> {
+        let (args, string) =
+            String::pop_from_with_options(args, options).map_err(MaybeEmptyError::EmptyArgs)?;
+        let colour = crate::parse_colour(&string).map_err(MaybeEmptyError::ParseError)?;
+        Ok((args, colour))
+    }
+}
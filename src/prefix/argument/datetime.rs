@@ -0,0 +1,60 @@
+use super::*;
+
+impl<'a> PopArgument<'a> for std::time::Duration {
+    type Err = MaybeEmptyError<crate::DurationParseError>;
+
+    /// Pop a duration like `1h30m` or `"1 day 4 hours"` (with quotes, if it contains spaces) from
+    /// the front of the arguments. See [`crate::parse_duration`] for the accepted format.
+    ///
+    /// ```rust
+    /// # use poise::{ArgString, PopArgument as _};
+    /// # use std::time::Duration;
+    /// assert_eq!(
+    ///     Duration::pop_from(&ArgString("1h30m rest")).unwrap(),
+    ///     (ArgString(" rest"), Duration::from_secs(5400)),
+    /// );
+    /// assert!(Duration::pop_from(&ArgString("tomorrow")).is_err());
+    /// ```
+    fn pop_from(args: &ArgString<'a>) -> Result<(ArgString<'a>, Self), Self::Err> {
+        Self::pop_from_with_options(args, &PrefixParsingOptions::default())
+    }
+
+    fn pop_from_with_options(
+        args: &ArgString<'a>,
+        options: &PrefixParsingOptions,
+    ) -> Result<(ArgString<'a>, Self), Self::Err> {
+        let (args, string) =
+            String::pop_from_with_options(args, options).map_err(MaybeEmptyError::EmptyArgs)?;
+        let duration = crate::parse_duration(&string).map_err(MaybeEmptyError::ParseError)?;
+        Ok((args, duration))
+    }
+}
+
+impl<'a> PopArgument<'a> for chrono::DateTime<chrono::Utc> {
+    type Err = MaybeEmptyError<crate::DateTimeParseError>;
+
+    /// Pop a point in time like `2021-11-24`, `<t:1637773200:R>` or `"2021-11-24 17:00"` (with
+    /// quotes, if it contains spaces) from the front of the arguments. See
+    /// [`crate::parse_datetime`] for the accepted formats.
+    ///
+    /// ```rust
+    /// # use poise::{ArgString, PopArgument as _};
+    /// let (rest, datetime) =
+    ///     chrono::DateTime::<chrono::Utc>::pop_from(&ArgString("<t:1637773200> rest")).unwrap();
+    /// assert_eq!(rest, ArgString(" rest"));
+    /// assert_eq!(datetime.timestamp(), 1637773200);
+    /// ```
+    fn pop_from(args: &ArgString<'a>) -> Result<(ArgString<'a>, Self), Self::Err> {
+        Self::pop_from_with_options(args, &PrefixParsingOptions::default())
+    }
+
+    fn pop_from_with_options(
+        args: &ArgString<'a>,
+        options: &PrefixParsingOptions,
+    ) -> Result<(ArgString<'a>, Self), Self::Err> {
+        let (args, string) =
+            String::pop_from_with_options(args, options).map_err(MaybeEmptyError::EmptyArgs)?;
+        let datetime = crate::parse_datetime(&string).map_err(MaybeEmptyError::ParseError)?;
+        Ok((args, datetime))
+    }
+}
@@ -0,0 +1,59 @@
+use super::*;
+
+#[async_trait::async_trait]
+impl<'a> PopArgumentAsync<'a> for serenity::ReactionType {
+    type Err = MaybeEmptyError<crate::EmojiParseError>;
+
+    /// Pop a unicode emoji, custom emoji (`<:name:id>`) or name of a custom emoji of the guild
+    /// from the front of the arguments. See [`crate::parse_reaction_type`]
+    async fn async_pop_from(
+        ctx: &serenity::Context,
+        msg: &serenity::Message,
+        args: &ArgString<'a>,
+    ) -> Result<(ArgString<'a>, Self), Self::Err> {
+        Self::async_pop_from_with_options(ctx, msg, args, &PrefixParsingOptions::default()).await
+    }
+
+    async fn async_pop_from_with_options(
+        ctx: &serenity::Context,
+        msg: &serenity::Message,
+        args: &ArgString<'a>,
+        options: &PrefixParsingOptions,
+    ) -> Result<(ArgString<'a>, Self), Self::Err> {
+        let (args, string) =
+            String::pop_from_with_options(args, options).map_err(MaybeEmptyError::EmptyArgs)?;
+        let emoji = crate::parse_reaction_type(ctx, msg.guild_id, &string)
+            .await
+            .map_err(MaybeEmptyError::ParseError)?;
+        Ok((args, emoji))
+    }
+}
+
+#[async_trait::async_trait]
+impl<'a> PopArgumentAsync<'a> for serenity::Emoji {
+    type Err = MaybeEmptyError<crate::EmojiParseError>;
+
+    /// Pop a custom emoji (`<:name:id>`) or name of a custom emoji of the guild from the front of
+    /// the arguments. See [`crate::parse_guild_emoji`]
+    async fn async_pop_from(
+        ctx: &serenity::Context,
+        msg: &serenity::Message,
+        args: &ArgString<'a>,
+    ) -> Result<(ArgString<'a>, Self), Self::Err> {
+        Self::async_pop_from_with_options(ctx, msg, args, &PrefixParsingOptions::default()).await
+    }
+
+    async fn async_pop_from_with_options(
+        ctx: &serenity::Context,
+        msg: &serenity::Message,
+        args: &ArgString<'a>,
+        options: &PrefixParsingOptions,
+    ) -> Result<(ArgString<'a>, Self), Self::Err> {
+        let (args, string) =
+            String::pop_from_with_options(args, options).map_err(MaybeEmptyError::EmptyArgs)?;
+        let emoji = crate::parse_guild_emoji(ctx, msg.guild_id, &string)
+            .await
+            .map_err(MaybeEmptyError::ParseError)?;
+        Ok((args, emoji))
+    }
+}
@@ -2,7 +2,8 @@ use super::*;
 
 /// A command parameter type for key-value args
 ///
-/// For example `key1=value1 key2="value2 with spaces"`.
+/// For example `key1=value1 key2="value2 with spaces"`. Can be used in both prefix and slash
+/// commands; in slash commands, the pairs are entered into a single string option.
 ///
 /// ```rust
 /// use poise::PopArgument;
@@ -26,41 +27,24 @@ impl KeyValueArgs {
         self.0.get(key).map(|x| x.as_str())
     }
 
-    fn pop_single_key_value_pair<'a>(
+    /// Pops a single `key=value` pair off the front of the input. Used by the `PrefixArgs` derive
+    /// macro to find named arguments
+    #[doc(hidden)]
+    pub fn pop_single_key_value_pair<'a>(
         args: &ArgString<'a>,
+        options: &PrefixParsingOptions,
     ) -> Option<(ArgString<'a>, (String, String))> {
-        // TODO: share quote parsing machinery with PopArgumentAsync impl for String
-
         if args.0.is_empty() {
             return None;
         }
 
-        let mut key = String::new();
-        let mut inside_string = false;
-        let mut escaping = false;
-
-        let mut chars = args.0.trim_start().chars();
-        loop {
-            let c = chars.next()?;
-            if escaping {
-                key.push(c);
-                escaping = false;
-            } else if !inside_string && c.is_whitespace() {
-                return None;
-            } else if c == '"' {
-                inside_string = !inside_string;
-            } else if c == '\\' {
-                escaping = true;
-            } else if !inside_string && c == '=' {
-                break;
-            } else {
-                key.push(c);
-            }
-        }
-
-        let args = ArgString(chars.as_str());
+        // Keys end at the first `=` outside of quotes. Whitespace before it means this isn't a
+        // key-value pair
+        let (rest, key) = pop_quoted_word(args.0.trim_start(), options, Some('='));
+        let args = ArgString(rest.strip_prefix('=')?);
         // `args` used to contain "key=value ...", now it contains "value ...", so pop the value off
-        let (args, value) = String::pop_from(&args).unwrap_or((args, String::new()));
+        let (args, value) =
+            String::pop_from_with_options(&args, options).unwrap_or((args, String::new()));
 
         Some((args, (key, value)))
     }
@@ -70,10 +54,17 @@ impl<'a> PopArgument<'a> for KeyValueArgs {
     type Err = std::convert::Infallible;
 
     fn pop_from(args: &ArgString<'a>) -> Result<(ArgString<'a>, Self), Self::Err> {
+        Self::pop_from_with_options(args, &PrefixParsingOptions::default())
+    }
+
+    fn pop_from_with_options(
+        args: &ArgString<'a>,
+        options: &PrefixParsingOptions,
+    ) -> Result<(ArgString<'a>, Self), Self::Err> {
         let mut pairs = std::collections::HashMap::new();
 
         let mut args = args.clone();
-        while let Some((new_args, (key, value))) = Self::pop_single_key_value_pair(&args) {
+        while let Some((new_args, (key, value))) = Self::pop_single_key_value_pair(&args, options) {
             args = new_args;
             pairs.insert(key, value);
         }
@@ -82,6 +73,60 @@ impl<'a> PopArgument<'a> for KeyValueArgs {
     }
 }
 
+/// Error returned from the slash command version of [`KeyValueArgs`] if the input contains
+/// something other than key-value pairs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidKeyValueArgs {
+    /// The part of the input which couldn't be parsed as key-value pairs
+    pub unparsed: String,
+}
+
+impl std::fmt::Display for InvalidKeyValueArgs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "expected key=value pairs, found `{}`", self.unparsed)
+    }
+}
+
+impl std::error::Error for InvalidKeyValueArgs {}
+
+/// In slash commands, key-value args are entered into a single string option. In contrast to
+/// prefix commands, the entire string must consist of key-value pairs.
+#[async_trait::async_trait]
+impl crate::SlashArgument for KeyValueArgs {
+    async fn extract(
+        _: &serenity::Context,
+        _: Option<serenity::GuildId>,
+        _: Option<serenity::ChannelId>,
+        value: &serde_json::Value,
+    ) -> Result<Self, crate::SlashArgError> {
+        let string = value
+            .as_str()
+            .ok_or(crate::SlashArgError::CommandStructureMismatch(
+                "expected string",
+            ))?;
+
+        let (rest, key_value_args) = match Self::pop_from(&ArgString(string.trim())) {
+            Ok(x) => x,
+            Err(infallible) => match infallible {},
+        };
+        if !rest.0.trim().is_empty() {
+            return Err(crate::SlashArgError::Parse(
+                InvalidKeyValueArgs {
+                    unparsed: rest.0.trim().to_owned(),
+                }
+                .into(),
+            ));
+        }
+        Ok(key_value_args)
+    }
+
+    fn create(
+        builder: &mut serenity::CreateApplicationCommandOption,
+    ) -> &mut serenity::CreateApplicationCommandOption {
+        builder.kind(serenity::ApplicationCommandOptionType::String)
+    }
+}
+
 #[cfg(test)]
 #[test]
 fn test_key_value_args() {
@@ -0,0 +1,41 @@
+use super::*;
+
+#[async_trait::async_trait]
+impl<'a> PopArgumentAsync<'a> for crate::MessageRef {
+    type Err = MaybeEmptyError<crate::MessageRefError>;
+
+    /// Pop a message link, channel and message ID pair or message ID from the front of the
+    /// arguments. See [`crate::parse_message_ref`]
+    ///
+    /// If the invoking message is a reply and the arguments don't start with a message reference,
+    /// the replied-to message is used without consuming any arguments.
+    async fn async_pop_from(
+        ctx: &serenity::Context,
+        msg: &serenity::Message,
+        args: &ArgString<'a>,
+    ) -> Result<(ArgString<'a>, Self), Self::Err> {
+        Self::async_pop_from_with_options(ctx, msg, args, &PrefixParsingOptions::default()).await
+    }
+
+    async fn async_pop_from_with_options(
+        ctx: &serenity::Context,
+        msg: &serenity::Message,
+        args: &ArgString<'a>,
+        options: &PrefixParsingOptions,
+    ) -> Result<(ArgString<'a>, Self), Self::Err> {
+        let error = match String::pop_from_with_options(args, options) {
+            Ok((rest, string)) => {
+                match crate::parse_message_ref(ctx, Some(msg.channel_id), &string).await {
+                    Ok(message) => return Ok((rest, Self(message))),
+                    Err(e) => MaybeEmptyError::ParseError(e),
+                }
+            }
+            Err(e) => MaybeEmptyError::EmptyArgs(e),
+        };
+
+        match &msg.referenced_message {
+            Some(referenced_message) => Ok((args.clone(), Self((**referenced_message).clone()))),
+            None => Err(error),
+        }
+    }
+}
@@ -12,6 +12,17 @@ pub use string::*;
 mod parse;
 pub use parse::*;
 
+mod unix_options;
+pub use unix_options::*;
+
+// Only contain trait impls
+mod colour;
+mod datetime;
+mod emoji;
+mod message_ref;
+#[cfg(feature = "url")]
+mod url;
+
 use crate::serenity_prelude as serenity;
 
 /// Type used throughout the prefix parameter parsing code in this code to store the raw string input.
@@ -30,6 +41,18 @@ pub trait PopArgument<'a>: Sized {
     /// Parse [`Self`] from the front of the given string and return a tuple of the remaining string
     /// and [`Self`].
     fn pop_from(args: &ArgString<'a>) -> Result<(ArgString<'a>, Self), Self::Err>;
+
+    /// Like [`Self::pop_from`], but respecting the given [`PrefixParsingOptions`]. The framework
+    /// calls this method with [`crate::PrefixFrameworkOptions::parsing_options`].
+    ///
+    /// Override this method if your type pops quoted strings. The default implementation ignores
+    /// the options and calls [`Self::pop_from`].
+    fn pop_from_with_options(
+        args: &ArgString<'a>,
+        _options: &PrefixParsingOptions,
+    ) -> Result<(ArgString<'a>, Self), Self::Err> {
+        Self::pop_from(args)
+    }
 }
 
 /// Parse a value out of a string by popping off the front of the string. Discord message context
@@ -50,6 +73,17 @@ pub trait PopArgumentAsync<'a>: Sized {
         msg: &serenity::Message,
         args: &ArgString<'a>,
     ) -> Result<(ArgString<'a>, Self), Self::Err>;
+
+    /// Like [`Self::async_pop_from`], but respecting the given [`PrefixParsingOptions`]. See
+    /// [`PopArgument::pop_from_with_options`]
+    async fn async_pop_from_with_options(
+        ctx: &serenity::Context,
+        msg: &serenity::Message,
+        args: &ArgString<'a>,
+        _options: &PrefixParsingOptions,
+    ) -> Result<(ArgString<'a>, Self), Self::Err> {
+        Self::async_pop_from(ctx, msg, args).await
+    }
 }
 
 #[async_trait::async_trait]
@@ -66,6 +100,15 @@ where
     ) -> Result<(ArgString<'a>, Self), Self::Err> {
         <Self as PopArgument>::pop_from(args)
     }
+
+    async fn async_pop_from_with_options(
+        _: &serenity::Context,
+        _: &serenity::Message,
+        args: &ArgString<'a>,
+        options: &PrefixParsingOptions,
+    ) -> Result<(ArgString<'a>, Self), Self::Err> {
+        <Self as PopArgument>::pop_from_with_options(args, options)
+    }
 }
 
 /// Error thrown if user passes too many arguments to a command
@@ -80,19 +123,150 @@ impl std::fmt::Display for TooManyArguments {
 
 impl std::error::Error for TooManyArguments {}
 
-/// The error type returned from [parse_prefix_args!]. It contains a `Box<dyn Error>`
+/// The error type returned from [parse_prefix_args!]. It contains a `Box<dyn Error>` of the
+/// argument which failed to parse, and where in the input that argument was.
 #[derive(Debug)]
-pub struct ArgumentParseError(pub Box<dyn std::error::Error + Send + Sync>);
+pub struct ArgumentParseError {
+    /// The underlying parse error
+    pub error: Box<dyn std::error::Error + Send + Sync>,
+    /// The content of the invocation message, which [`Self::span`] points into. If the parsed
+    /// arguments can't be located in the message, the argument string which was parsed instead
+    pub input: String,
+    /// Zero-based index of the parameter which failed to parse, if known. If too many arguments
+    /// were passed, this is the number of parameters.
+    pub parameter_index: Option<usize>,
+    /// Name of the parameter which failed to parse, if known. Filled in by the command macro
+    pub parameter_name: Option<&'static str>,
+    /// Byte range of the offending token in [`Self::input`], if known. If an argument is missing,
+    /// this is the empty range at the end of the arguments.
+    pub span: Option<std::ops::Range<usize>>,
+}
+
+impl ArgumentParseError {
+    /// Constructs the error from the location recorded by [parse_prefix_args!]: the parameter
+    /// index and the remaining input at the point of failure, which must be a suffix of `input`.
+    /// The span is translated into an offset in `message_content`, see [`Self::input`]
+    #[doc(hidden)]
+    pub fn _new(
+        error: Box<dyn std::error::Error + Send + Sync>,
+        input: &str,
+        location: Option<(usize, &str)>,
+        message_content: &str,
+    ) -> Self {
+        let (parameter_index, span) = match location {
+            Some((parameter_index, remaining)) => {
+                let remaining = remaining.trim_start();
+                let start = input.len() - remaining.len();
+                let token_len = remaining
+                    .find(char::is_whitespace)
+                    .unwrap_or(remaining.len());
+                (Some(parameter_index), Some(start..start + token_len))
+            }
+            None => (None, None),
+        };
+        let (input, span) = match &span {
+            Some(span) => match locate_in_message(message_content, input, span.clone()) {
+                Some(message_span) => (message_content, Some(message_span)),
+                None => (input, Some(span.clone())),
+            },
+            None => (message_content, None),
+        };
+        Self {
+            error,
+            input: input.to_owned(),
+            parameter_index,
+            parameter_name: None,
+            span,
+        }
+    }
+
+    /// Fill in [`Self::parameter_name`] by looking up [`Self::parameter_index`] in the given list
+    /// of parameter names
+    pub fn with_parameter_names(mut self, parameter_names: &[&'static str]) -> Self {
+        if let Some(parameter_index) = self.parameter_index {
+            self.parameter_name = parameter_names.get(parameter_index).copied();
+        }
+        self
+    }
+
+    /// Renders the input with a caret line underneath pointing at the offending token, for
+    /// example
+    /// ```text
+    /// 5 five
+    ///   ^^^^
+    /// ```
+    ///
+    /// Returns None if the location of the error is unknown
+    pub fn caret_diagram(&self) -> Option<String> {
+        let span = self.span.as_ref()?;
+        let input = self.input.replace(|c: char| c == '\n' || c == '\r', " ");
+        let offset = input[..span.start].chars().count();
+        let width = std::cmp::max(1, input[span.clone()].chars().count());
+        Some(format!(
+            "{}\n{}{}",
+            input,
+            " ".repeat(offset),
+            "^".repeat(width)
+        ))
+    }
+}
+
+/// Translates a byte range in the parsed argument string into a byte range in the message content.
+///
+/// The argument string is usually a slice of the message content. If it isn't, for example because
+/// Unix-style options were cut out of it, the offending token is searched in the message instead,
+/// assuming that the text before the arguments (prefix and command name) comes first.
+fn locate_in_message(
+    message_content: &str,
+    input: &str,
+    span: std::ops::Range<usize>,
+) -> Option<std::ops::Range<usize>> {
+    let message_start = message_content.as_ptr() as usize;
+    let input_start = input.as_ptr() as usize;
+    if input_start >= message_start
+        && input_start + input.len() <= message_start + message_content.len()
+    {
+        let offset = input_start - message_start;
+        return Some(span.start + offset..span.end + offset);
+    }
+
+    let token = &input[span.clone()];
+    if token.is_empty() {
+        // A missing argument is pointed out at the end of the arguments
+        let end = message_content.len();
+        return if message_content.ends_with(input.trim_end()) {
+            Some(end..end)
+        } else {
+            None
+        };
+    }
+    let occurrences_before = input[..span.start].matches(token).count();
+    let occurrences_outside_input = message_content
+        .matches(token)
+        .count()
+        .checked_sub(input.matches(token).count())?;
+    let (start, _) = message_content
+        .match_indices(token)
+        .nth(occurrences_outside_input + occurrences_before)?;
+    Some(start..start + token.len())
+}
 
 impl std::fmt::Display for ArgumentParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Failed to parse argument: {}", self.0)
+        match self.parameter_name {
+            Some(parameter_name) => write!(
+                f,
+                "Failed to parse argument `{}`: {}",
+                parameter_name, self.error
+            ),
+            None => write!(f, "Failed to parse argument: {}", self.error),
+        }
     }
 }
 
 impl std::error::Error for ArgumentParseError {
     fn cause(&self) -> Option<&dyn std::error::Error> {
-        Some(&*self.0)
+        Some(&*self.error)
     }
 }
 
@@ -119,6 +293,7 @@ pub trait PrefixArgumentHack<'a, T> {
         args: &ArgString<'a>,
         ctx: &serenity::Context,
         msg: &serenity::Message,
+        options: &PrefixParsingOptions,
     ) -> Result<(ArgString<'a>, T), Self::Err>;
 }
 
@@ -161,8 +336,10 @@ impl<'a, T: serenity::ArgumentConvert + Send> PrefixArgumentHack<'a, T>
         args: &ArgString<'a>,
         ctx: &serenity::Context,
         msg: &serenity::Message,
+        options: &PrefixParsingOptions,
     ) -> Result<(ArgString<'a>, T), Self::Err> {
-        let (args, string) = String::pop_from(args).map_err(MaybeEmptyError::EmptyArgs)?;
+        let (args, string) =
+            String::pop_from_with_options(args, options).map_err(MaybeEmptyError::EmptyArgs)?;
         let object = T::convert(ctx, msg.guild_id, Some(msg.channel_id), &string)
             .await
             .map_err(MaybeEmptyError::ParseError)?;
@@ -171,19 +348,21 @@ impl<'a, T: serenity::ArgumentConvert + Send> PrefixArgumentHack<'a, T>
     }
 }
 
+// Also handles PopArgument types via the blanket impl of PopArgumentAsync
 #[async_trait::async_trait]
-impl<'a, T: crate::PopArgument<'a> + Sync> PrefixArgumentHack<'a, T>
+impl<'a, T: crate::PopArgumentAsync<'a> + Sync> PrefixArgumentHack<'a, T>
     for &std::marker::PhantomData<T>
 {
-    type Err = <T as crate::PopArgument<'a>>::Err;
+    type Err = <T as crate::PopArgumentAsync<'a>>::Err;
 
     async fn pop(
         self,
         args: &ArgString<'a>,
-        _: &serenity::Context,
-        _: &serenity::Message,
+        ctx: &serenity::Context,
+        msg: &serenity::Message,
+        options: &PrefixParsingOptions,
     ) -> Result<(ArgString<'a>, T), Self::Err> {
-        let (args, object) = T::pop_from(args)?;
+        let (args, object) = T::async_pop_from_with_options(ctx, msg, args, options).await?;
 
         Ok((ArgString(args.0.trim_start()), object))
     }
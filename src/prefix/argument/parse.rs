@@ -1,53 +1,65 @@
+/// Counts the given token trees. Used by [`_parse_prefix`] to determine the index of the parameter
+/// being parsed
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _tt_count {
+    () => { 0usize };
+    ( $head:tt $( $tail:tt )* ) => { 1usize + $crate::_tt_count!( $( $tail )* ) };
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! _parse_prefix {
     // All arguments have been consumed
-    ( $ctx:ident $msg:ident $args:ident => [ $error:ident $( $name:ident )* ] ) => {
+    ( $ctx:ident $msg:ident $options:ident $args:ident => [ $error:ident $( $name:ident )* ] ) => {
         if $args.0.is_empty() {
             return Ok(( $( $name, )* ));
+        } else if $error.1.is_none() {
+            // Too many arguments; point at the first spare one
+            $error.1 = Some(($crate::_tt_count!($( $name )*), $args.0));
         }
     };
 
     // Consume Option<T> greedy-first
-    ( $ctx:ident $msg:ident $args:ident => [ $error:ident $($preamble:tt)* ]
+    ( $ctx:ident $msg:ident $options:ident $args:ident => [ $error:ident $($preamble:tt)* ]
         (Option<$type:ty $(,)?>)
         $( $rest:tt )*
     ) => {
-        match (&PhantomData::<$type>).pop(&$args, $ctx, $msg).await {
+        match (&PhantomData::<$type>).pop(&$args, $ctx, $msg, $options).await {
             Ok(($args, token)) => {
                 let token: Option<$type> = Some(token);
-                $crate::_parse_prefix!($ctx $msg $args => [ $error $($preamble)* token ] $($rest)* );
+                $crate::_parse_prefix!($ctx $msg $options $args => [ $error $($preamble)* token ] $($rest)* );
             },
-            Err(e) => $error = Box::new(e),
+            Err(e) => $error = (Box::new(e), Some(($crate::_tt_count!($($preamble)*), $args.0))),
         }
         let token: Option<$type> = None;
-        $crate::_parse_prefix!($ctx $msg $args => [ $error $($preamble)* token ] $($rest)* );
+        $crate::_parse_prefix!($ctx $msg $options $args => [ $error $($preamble)* token ] $($rest)* );
     };
 
     // Consume Option<T> lazy-first
-    ( $ctx:ident $msg:ident $args:ident => [ $error:ident $($preamble:tt)* ]
+    ( $ctx:ident $msg:ident $options:ident $args:ident => [ $error:ident $($preamble:tt)* ]
         (#[lazy] Option<$type:ty $(,)?>)
         $( $rest:tt )*
     ) => {
         let token: Option<$type> = None;
-        $crate::_parse_prefix!($ctx $msg $args => [ $error $($preamble)* token ] $($rest)* );
-        match (&PhantomData::<$type>).pop(&$args, $ctx, $msg).await {
+        $crate::_parse_prefix!($ctx $msg $options $args => [ $error $($preamble)* token ] $($rest)* );
+        match (&PhantomData::<$type>).pop(&$args, $ctx, $msg, $options).await {
             Ok(($args, token)) => {
                 let token: Option<$type> = Some(token);
-                $crate::_parse_prefix!($ctx $msg $args => [ $error $($preamble)* token ] $($rest)* );
+                $crate::_parse_prefix!($ctx $msg $options $args => [ $error $($preamble)* token ] $($rest)* );
             },
-            Err(e) => $error = Box::new(e),
+            Err(e) => $error = (Box::new(e), Some(($crate::_tt_count!($($preamble)*), $args.0))),
         }
     };
 
     // Consume #[rest] Option<T> until the end of the input
-    ( $ctx:ident $msg:ident $args:ident => [ $error:ident $($preamble:tt)* ]
+    ( $ctx:ident $msg:ident $options:ident $args:ident => [ $error:ident $($preamble:tt)* ]
         (#[rest] Option<$type:ty $(,)?>)
         $( $rest:tt )*
     ) => {
         if $args.0.trim_start().is_empty() {
             let token: Option<$type> = None;
-            $crate::_parse_prefix!($ctx $msg $args => [ $error $($preamble)* token ]);
+            $crate::_parse_prefix!($ctx $msg $options $args => [ $error $($preamble)* token ]);
         } else {
             match <$type as $crate::serenity_prelude::ArgumentConvert>::convert(
                 $ctx, $msg.guild_id, Some($msg.channel_id), $args.0.trim_start()
@@ -55,15 +67,15 @@ macro_rules! _parse_prefix {
                 Ok(token) => {
                     let $args = $crate::ArgString("");
                     let token = Some(token);
-                    $crate::_parse_prefix!($ctx $msg $args => [ $error $($preamble)* token ]);
+                    $crate::_parse_prefix!($ctx $msg $options $args => [ $error $($preamble)* token ]);
                 },
-                Err(e) => $error = Box::new(e),
+                Err(e) => $error = (Box::new(e), Some(($crate::_tt_count!($($preamble)*), $args.0))),
             }
         }
     };
 
     // Consume Vec<T> greedy-first
-    ( $ctx:ident $msg:ident $args:ident => [ $error:ident $($preamble:tt)* ]
+    ( $ctx:ident $msg:ident $options:ident $args:ident => [ $error:ident $($preamble:tt)* ]
         (Vec<$type:ty $(,)?>)
         $( $rest:tt )*
     ) => {
@@ -72,14 +84,20 @@ macro_rules! _parse_prefix {
 
         let mut running_args = $args.clone();
         loop {
-            match (&PhantomData::<$type>).pop(&running_args, $ctx, $msg).await {
+            match (&PhantomData::<$type>).pop(&running_args, $ctx, $msg, $options).await {
                 Ok((popped_args, token)) => {
+                    // Stop if the argument didn't consume any input (e.g. a MessageRef falling
+                    // back to the replied-to message), which would otherwise repeat forever
+                    let made_progress = popped_args.0.len() < running_args.0.len();
                     tokens.push(token);
                     token_rest_args.push(popped_args.clone());
                     running_args = popped_args;
+                    if !made_progress {
+                        break;
+                    }
                 },
                 Err(e) => {
-                    $error = Box::new(e);
+                    $error = (Box::new(e), Some(($crate::_tt_count!($($preamble)*), running_args.0)));
                     break;
                 }
 
@@ -88,7 +106,7 @@ macro_rules! _parse_prefix {
 
         // This will run at least once
         while let Some(token_rest_args) = token_rest_args.pop() {
-            $crate::_parse_prefix!($ctx $msg token_rest_args => [ $error $($preamble)* tokens ] $($rest)* );
+            $crate::_parse_prefix!($ctx $msg $options token_rest_args => [ $error $($preamble)* tokens ] $($rest)* );
             tokens.pop();
         }
     };
@@ -97,7 +115,7 @@ macro_rules! _parse_prefix {
     // inconsistency and also the further implementation work makes it not worth it.
 
     // Consume #[rest] T as the last argument
-    ( $ctx:ident $msg:ident $args:ident => [ $error:ident $($preamble:tt)* ]
+    ( $ctx:ident $msg:ident $options:ident $args:ident => [ $error:ident $($preamble:tt)* ]
         // question to my former self: why the $(poise::)* ?
         (#[rest] $(poise::)* $type:ty)
     ) => {
@@ -106,36 +124,48 @@ macro_rules! _parse_prefix {
         ).await {
             Ok(token) => {
                 let $args = $crate::ArgString("");
-                $crate::_parse_prefix!($ctx $msg $args => [ $error $($preamble)* token ]);
+                $crate::_parse_prefix!($ctx $msg $options $args => [ $error $($preamble)* token ]);
             },
-            Err(e) => $error = Box::new(e),
+            Err(e) => $error = (Box::new(e), Some(($crate::_tt_count!($($preamble)*), $args.0))),
         }
     };
 
     // Consume #[flag] FLAGNAME
-    ( $ctx:ident $msg:ident $args:ident => [ $error:ident $($preamble:tt)* ]
+    ( $ctx:ident $msg:ident $options:ident $args:ident => [ $error:ident $($preamble:tt)* ]
         (#[flag] $name:literal)
         $( $rest:tt )*
     ) => {
-        if let Ok(($args, token)) = (&PhantomData::<String>).pop(&$args, $ctx, $msg).await {
+        if let Ok(($args, token)) = (&PhantomData::<String>).pop(&$args, $ctx, $msg, $options).await {
             if token.eq_ignore_ascii_case($name) {
-                $crate::_parse_prefix!($ctx $msg $args => [ $error $($preamble)* true ] $($rest)* );
+                $crate::_parse_prefix!($ctx $msg $options $args => [ $error $($preamble)* true ] $($rest)* );
             }
         }
-        $error = concat!("Must use either `", $name, "` or nothing as a modifier").into();
-        $crate::_parse_prefix!($ctx $msg $args => [ $error $($preamble)* false ] $($rest)* );
+        $error = (
+            concat!("Must use either `", $name, "` or nothing as a modifier").into(),
+            Some(($crate::_tt_count!($($preamble)*), $args.0)),
+        );
+        $crate::_parse_prefix!($ctx $msg $options $args => [ $error $($preamble)* false ] $($rest)* );
+    };
+
+    // Collect all message attachments into Vec<Attachment>, without consuming any input
+    ( $ctx:ident $msg:ident $options:ident $args:ident => [ $error:ident $($preamble:tt)* ]
+        (#[attachments] $type:ty)
+        $( $rest:tt )*
+    ) => {
+        let token: $type = $msg.attachments.clone();
+        $crate::_parse_prefix!($ctx $msg $options $args => [ $error $($preamble)* token ] $($rest)* );
     };
 
     // Consume T
-    ( $ctx:ident $msg:ident $args:ident => [ $error:ident $($preamble:tt)* ]
+    ( $ctx:ident $msg:ident $options:ident $args:ident => [ $error:ident $($preamble:tt)* ]
         ($type:ty)
         $( $rest:tt )*
     ) => {
-        match (&PhantomData::<$type>).pop(&$args, $ctx, $msg).await {
+        match (&PhantomData::<$type>).pop(&$args, $ctx, $msg, $options).await {
             Ok(($args, token)) => {
-                $crate::_parse_prefix!($ctx $msg $args => [ $error $($preamble)* token ] $($rest)* );
+                $crate::_parse_prefix!($ctx $msg $options $args => [ $error $($preamble)* token ] $($rest)* );
             },
-            Err(e) => $error = Box::new(e),
+            Err(e) => $error = (Box::new(e), Some(($crate::_tt_count!($($preamble)*), $args.0))),
         }
     };
 
@@ -188,12 +218,34 @@ assert_eq!(
     ),
 );
 
+assert_eq!(
+    poise::parse_prefix_args!(
+        &ctx, &msg,
+        "'one two' three", &poise::PrefixParsingOptions {
+            quote_chars: vec!['\''],
+            ..Default::default()
+        } => (String), (String)
+    ).await?,
+    (
+        String::from("one two"),
+        String::from("three"),
+    ),
+);
+
 # Ok(()) }
 ```
+
+Quote and escape characters can be configured by passing [`crate::PrefixParsingOptions`] after the
+arguments, as shown in the last example. Otherwise, the default options are used.
 */
 #[macro_export]
 macro_rules! parse_prefix_args {
-    ($ctx:expr, $msg:expr, $args:expr => $(
+    ($ctx:expr, $msg:expr, $args:expr => $( $spec:tt )* ) => {
+        $crate::parse_prefix_args!(
+            $ctx, $msg, $args, &$crate::PrefixParsingOptions::default() => $( $spec )*
+        )
+    };
+    ($ctx:expr, $msg:expr, $args:expr, $options:expr => $(
         $( #[$attr:ident] )?
         ( $($type:tt)* )
     ),* $(,)? ) => {
@@ -203,17 +255,23 @@ macro_rules! parse_prefix_args {
 
             let ctx = $ctx;
             let msg = $msg;
-            let args = $crate::ArgString($args);
+            let options: &$crate::PrefixParsingOptions = $options;
+            let input: &str = $args;
+            let args = $crate::ArgString(input);
 
-            let mut error = Box::new($crate::TooManyArguments) as Box<dyn std::error::Error + Send + Sync>;
+            // The last error, along with the index of the parameter and the remaining input at
+            // the point of failure
+            let mut error: (Box<dyn std::error::Error + Send + Sync>, Option<(usize, &str)>) =
+                (Box::new($crate::TooManyArguments), None);
 
             $crate::_parse_prefix!(
-                ctx msg args => [error]
+                ctx msg options args => [error]
                 $(
                     ($( #[$attr] )? $($type)*)
                 )*
             );
-            Err($crate::ArgumentParseError(error))
+            let (error, location) = error;
+            Err($crate::ArgumentParseError::_new(error, input, location, &msg.content))
         }
     };
 }
@@ -305,5 +363,37 @@ mod test {
                 .unwrap(),
             (false, "helloo".into())
         );
+
+        let error = parse_prefix_args!(&ctx, &msg, "5 five" => (u32), (u32))
+            .await
+            .unwrap_err()
+            .with_parameter_names(&["a", "b"]);
+        assert_eq!(error.parameter_index, Some(1));
+        assert_eq!(error.parameter_name, Some("b"));
+        assert_eq!(error.span, Some(2..6));
+        assert_eq!(error.caret_diagram().unwrap(), "5 five\n  ^^^^");
+
+        // Spans point into the message content
+        let mut msg_with_content = serenity::CustomMessage::new().build();
+        msg_with_content.content = "~add 5 five".into();
+        let args = &msg_with_content.content[5..];
+        let error = parse_prefix_args!(&ctx, &msg_with_content, args => (u32), (u32))
+            .await
+            .unwrap_err();
+        assert_eq!(error.span, Some(7..11));
+        assert_eq!(error.caret_diagram().unwrap(), "~add 5 five\n       ^^^^");
+        let error = parse_prefix_args!(&ctx, &msg_with_content, "five" => (u32))
+            .await
+            .unwrap_err();
+        assert_eq!(error.span, Some(7..11));
+        let error = parse_prefix_args!(&ctx, &msg, "1 2 3" => (u32))
+            .await
+            .unwrap_err();
+        assert_eq!(error.parameter_index, Some(1));
+        assert_eq!(error.span, Some(2..3));
+        let error = parse_prefix_args!(&ctx, &msg, "1" => (u32), (u32))
+            .await
+            .unwrap_err();
+        assert_eq!(error.span, Some(1..1));
     }
 }
@@ -11,6 +11,140 @@ impl std::fmt::Display for EmptyArgs {
 
 impl std::error::Error for EmptyArgs {}
 
+/// Configures how quoted and escaped arguments are parsed in prefix commands, see
+/// [`crate::PrefixFrameworkOptions::parsing_options`]
+///
+/// Respected by the [`PopArgument`] implementations in this crate, in particular by
+/// [`String::pop_from_with_options`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PrefixParsingOptions {
+    /// Characters which start and end a quoted argument, inside of which whitespace doesn't
+    /// separate arguments. By default only `"`
+    pub quote_chars: Vec<char>,
+    /// Whether typographic quotes (`“...”`, `‘...’` and `„...“`) count as quotes too, as inserted
+    /// by some mobile keyboards. Off by default
+    pub smart_quotes: bool,
+    /// Character which makes the following character literal, e.g. to put a quote inside a quoted
+    /// argument. None disables escaping. By default a backslash
+    pub escape_char: Option<char>,
+}
+
+impl Default for PrefixParsingOptions {
+    fn default() -> Self {
+        Self {
+            quote_chars: vec!['"'],
+            smart_quotes: false,
+            escape_char: Some('\\'),
+        }
+    }
+}
+
+impl PrefixParsingOptions {
+    /// If the given character opens a quoted section, returns the character which closes it
+    fn closing_quote(&self, c: char) -> Option<char> {
+        if self.quote_chars.contains(&c) {
+            return Some(c);
+        }
+        if self.smart_quotes {
+            match c {
+                '“' => return Some('”'),
+                '‘' => return Some('’'),
+                '„' => return Some('“'),
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+/// Pops a possibly quoted word off the front of the input. The word ends at the first whitespace
+/// or `delimiter` outside of quotes, which is not consumed.
+///
+/// Shared by the string and key-value argument parsers
+pub(crate) fn pop_quoted_word<'a>(
+    input: &'a str,
+    options: &PrefixParsingOptions,
+    delimiter: Option<char>,
+) -> (&'a str, String) {
+    let mut output = String::new();
+    let mut closing_quote = None;
+    let mut escaping = false;
+
+    let mut chars = input.chars();
+    // .clone().next() is poor man's .peek(), but we can't do peekable because then we can't
+    // call as_str on the Chars iterator
+    while let Some(c) = chars.clone().next() {
+        if escaping {
+            output.push(c);
+            escaping = false;
+        } else if closing_quote.is_none() && (c.is_whitespace() || Some(c) == delimiter) {
+            break;
+        } else if closing_quote == Some(c) {
+            closing_quote = None;
+        } else if let (None, Some(quote)) = (closing_quote, options.closing_quote(c)) {
+            closing_quote = Some(quote);
+        } else if Some(c) == options.escape_char {
+            escaping = true;
+        } else {
+            output.push(c);
+        }
+
+        chars.next();
+    }
+
+    (chars.as_str(), output)
+}
+
+/// Splits the input at every occurrence of `separator` which is outside of quotes and code blocks,
+/// following the same quoting and escaping rules as [`pop_quoted_word`].
+///
+/// Used to split messages with multiple chained commands, see
+/// [`crate::PrefixFrameworkOptions::command_separator`]
+pub(crate) fn split_top_level<'a>(
+    input: &'a str,
+    separator: &str,
+    options: &PrefixParsingOptions,
+) -> Vec<&'a str> {
+    let mut segments = Vec::new();
+    let mut segment_start = 0;
+    let mut closing_quote = None;
+    // Number of backticks which opened the current code block
+    let mut code_block_fence = None;
+    let mut escaping = false;
+
+    let mut chars = input.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if escaping {
+            escaping = false;
+        } else if c == '`' && closing_quote.is_none() {
+            let mut fence = 1;
+            while chars.next_if(|&(_, c)| c == '`').is_some() {
+                fence += 1;
+            }
+            match code_block_fence {
+                Some(opening_fence) if opening_fence == fence => code_block_fence = None,
+                Some(_) => {}
+                None => code_block_fence = Some(fence),
+            }
+        } else if code_block_fence.is_some() {
+            // Quotes, escapes and separators are literal inside code blocks
+        } else if closing_quote == Some(c) {
+            closing_quote = None;
+        } else if let (None, Some(quote)) = (closing_quote, options.closing_quote(c)) {
+            closing_quote = Some(quote);
+        } else if Some(c) == options.escape_char {
+            escaping = true;
+        } else if closing_quote.is_none() && input[i..].starts_with(separator) {
+            segments.push(&input[segment_start..i]);
+            segment_start = i + separator.len();
+            // Skip the rest of the separator
+            while chars.next_if(|&(j, _)| j < segment_start).is_some() {}
+        }
+    }
+    segments.push(&input[segment_start..]);
+    segments
+}
+
 impl<'a> PopArgument<'a> for String {
     type Err = EmptyArgs;
 
@@ -31,6 +165,27 @@ impl<'a> PopArgument<'a> for String {
     /// );
     /// ```
     fn pop_from(args: &ArgString<'a>) -> Result<(ArgString<'a>, Self), Self::Err> {
+        Self::pop_from_with_options(args, &PrefixParsingOptions::default())
+    }
+
+    /// Like [`Self::pop_from`], but with configurable quote and escape characters
+    ///
+    /// ```rust
+    /// # use poise::{ArgString, PopArgument as _, PrefixParsingOptions};
+    /// let options = PrefixParsingOptions {
+    ///     quote_chars: vec!['"', '\''],
+    ///     smart_quotes: true,
+    ///     escape_char: None,
+    /// };
+    /// let pop = |input| String::pop_from_with_options(&ArgString(input), &options).unwrap().1;
+    /// assert_eq!(pop("'first arg' secondarg"), "first arg");
+    /// assert_eq!(pop("“first arg” secondarg"), "first arg");
+    /// assert_eq!(pop(r"C:\Users\ferris"), r"C:\Users\ferris");
+    /// ```
+    fn pop_from_with_options(
+        args: &ArgString<'a>,
+        options: &PrefixParsingOptions,
+    ) -> Result<(ArgString<'a>, Self), Self::Err> {
         // TODO: consider changing the behavior to parse quotes literally if they're in the middle
         // of the string:
         // - `"hello world"` => `hello world`
@@ -41,31 +196,8 @@ impl<'a> PopArgument<'a> for String {
             return Err(EmptyArgs);
         }
 
-        let mut output = String::new();
-        let mut inside_string = false;
-        let mut escaping = false;
-
-        let mut chars = args.0.chars();
-        // .clone().next() is poor man's .peek(), but we can't do peekable because then we can't
-        // call as_str on the Chars iterator
-        while let Some(c) = chars.clone().next() {
-            if escaping {
-                output.push(c);
-                escaping = false;
-            } else if !inside_string && c.is_whitespace() {
-                break;
-            } else if c == '"' {
-                inside_string = !inside_string;
-            } else if c == '\\' {
-                escaping = true;
-            } else {
-                output.push(c);
-            }
-
-            chars.next();
-        }
-
-        Ok((ArgString(chars.as_str()), output))
+        let (rest, output) = pop_quoted_word(args.0, options, None);
+        Ok((ArgString(rest), output))
     }
 }
 
@@ -91,3 +223,55 @@ fn test_pop_string() {
         assert_eq!(String::pop_from(&ArgString(string)).unwrap().1, arg);
     }
 }
+
+#[cfg(test)]
+#[test]
+fn test_split_top_level() {
+    let options = PrefixParsingOptions::default();
+    for &(string, segments) in &[
+        ("a && b", &["a ", " b"][..]),
+        ("a && b && c", &["a ", " b ", " c"]),
+        (r#"say "a && b" && ping"#, &[r#"say "a && b" "#, " ping"]),
+        (r#"say \"a && b"#, &[r#"say \"a "#, " b"]),
+        ("run `a && b` && ping", &["run `a && b` ", " ping"]),
+        (
+            "run ```\na && b\n``` && ping",
+            &["run ```\na && b\n``` ", " ping"],
+        ),
+        (
+            "run ```a ` && b``` && ping",
+            &["run ```a ` && b``` ", " ping"],
+        ),
+        ("a &&&& b", &["a ", "", " b"]),
+    ] {
+        assert_eq!(split_top_level(string, "&&", &options), segments);
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_pop_string_with_options() {
+    let options = PrefixParsingOptions {
+        quote_chars: vec!['\''],
+        smart_quotes: true,
+        escape_char: Some('^'),
+    };
+    for &(string, arg) in &[
+        (r#""AA BB""#, r#""AA"#),
+        (r#"'AA BB'"#, r#"AA BB"#),
+        (r#"'AA "BB' CC"#, r#"AA "BB"#),
+        (r#"“AA BB” CC"#, r#"AA BB"#),
+        (r#"‘AA BB’ CC"#, r#"AA BB"#),
+        (r#"„AA BB“ CC"#, r#"AA BB"#),
+        (r#"“AA ’BB” CC"#, r#"AA ’BB"#),
+        (r#"AA^ BB"#, r#"AA BB"#),
+        (r#"\AA BB"#, r#"\AA"#),
+    ] {
+        assert_eq!(
+            String::pop_from_with_options(&ArgString(string), &options)
+                .unwrap()
+                .1,
+            arg
+        );
+    }
+}
@@ -0,0 +1,143 @@
+use super::*;
+
+/// Unix-style options picked out of prefix command arguments by [`_extract_unix_options`]
+#[doc(hidden)]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct _UnixOptions {
+    /// The arguments with all recognized options removed. Everything else is kept verbatim,
+    /// including whitespace and newlines
+    pub args: String,
+    /// For each flag name, whether `--name` was present
+    pub flags: Vec<bool>,
+    /// For each option name, the value of the last `--name value` or `--name=value`
+    pub named: Vec<Option<String>>,
+}
+
+/// Removes `--flag`, `--key value` and `--key=value` tokens with the given flag and option names
+/// from anywhere in the arguments. Names are matched case-insensitively. Everything after a `--`
+/// token is left untouched.
+///
+/// Used by the command macro for `#[flag]` and `#[named]` parameters.
+#[doc(hidden)]
+pub fn _extract_unix_options(
+    args: &str,
+    flags: &[&str],
+    named: &[&str],
+    parsing_options: &PrefixParsingOptions,
+) -> _UnixOptions {
+    let mut options = _UnixOptions {
+        args: String::new(),
+        flags: vec![false; flags.len()],
+        named: vec![None; named.len()],
+    };
+
+    // Recognized options are cut out of the original string by byte range instead of re-joining
+    // the other tokens, so that the whitespace in #[rest] arguments and code blocks is preserved
+    let original = args.trim_start();
+    let offset = |rest: &str| original.len() - rest.len();
+    let mut removed_ranges = Vec::new();
+    // Removes the option from the start of `args` up to the start of `rest`
+    let mut remove = |args: &ArgString<'_>, rest: &ArgString<'_>| {
+        let (mut start, end) = (offset(args.0), offset(rest.0));
+        // Don't leave trailing whitespace behind when removing the last token
+        if end == original.len() {
+            start = original[..start].trim_end().len();
+        }
+        removed_ranges.push(start..end);
+    };
+
+    let mut args = ArgString(original);
+    while let Ok((rest, token)) = String::pop_from_with_options(&args, parsing_options) {
+        let rest = ArgString(rest.0.trim_start());
+        let raw_token = args.0[..args.0.len() - rest.0.len()].trim_end();
+
+        if raw_token == "--" {
+            remove(&args, &rest);
+            break;
+        }
+        // Quoted tokens like "--foo" are never interpreted as options
+        if raw_token.starts_with("--") {
+            let mut name_and_value = token[2..].splitn(2, '=');
+            let name = name_and_value.next().unwrap_or("");
+            let inline_value = name_and_value.next();
+
+            let flag_index = flags.iter().position(|x| x.eq_ignore_ascii_case(name));
+            let named_index = named.iter().position(|x| x.eq_ignore_ascii_case(name));
+            match (flag_index, named_index, inline_value) {
+                (Some(i), _, None) => {
+                    options.flags[i] = true;
+                    remove(&args, &rest);
+                    args = rest;
+                    continue;
+                }
+                (_, Some(i), Some(value)) => {
+                    options.named[i] = Some(value.to_owned());
+                    remove(&args, &rest);
+                    args = rest;
+                    continue;
+                }
+                (_, Some(i), None) => {
+                    if let Ok((rest, value)) = String::pop_from_with_options(&rest, parsing_options)
+                    {
+                        options.named[i] = Some(value);
+                        let rest = ArgString(rest.0.trim_start());
+                        remove(&args, &rest);
+                        args = rest;
+                        continue;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        args = rest;
+    }
+
+    let mut kept_start = 0;
+    for range in removed_ranges {
+        options.args.push_str(&original[kept_start..range.start]);
+        kept_start = range.end;
+    }
+    options.args.push_str(&original[kept_start..]);
+    options
+}
+
+#[cfg(test)]
+#[test]
+fn test_extract_unix_options() {
+    let options = _extract_unix_options(
+        r#"a --verbose b --limit 5 --REASON="spam bot" c"#,
+        &["verbose", "dry-run"],
+        &["limit", "reason"],
+        &PrefixParsingOptions::default(),
+    );
+    assert_eq!(options.args, "a b c");
+    assert_eq!(options.flags, [true, false]);
+    assert_eq!(
+        options.named,
+        [Some("5".to_owned()), Some("spam bot".to_owned())]
+    );
+
+    let options = _extract_unix_options(
+        r#""--verbose" --unknown --limit=3 -- --verbose"#,
+        &["verbose"],
+        &["limit"],
+        &PrefixParsingOptions::default(),
+    );
+    assert_eq!(options.args, r#""--verbose" --unknown --verbose"#);
+    assert_eq!(options.flags, [false]);
+    assert_eq!(options.named, [Some("3".to_owned())]);
+
+    // Whitespace and newlines outside of the options are preserved
+    let options = _extract_unix_options(
+        "first  line\n--verbose ```\nfn main() {\n    42\n}\n```\n--verbose",
+        &["verbose"],
+        &[],
+        &PrefixParsingOptions::default(),
+    );
+    assert_eq!(
+        options.args,
+        "first  line\n```\nfn main() {\n    42\n}\n```"
+    );
+    assert_eq!(options.flags, [true]);
+}
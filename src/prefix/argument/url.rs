@@ -0,0 +1,27 @@
+use super::*;
+
+impl<'a> PopArgument<'a> for crate::HttpUrl {
+    type Err = MaybeEmptyError<crate::UrlParseError>;
+
+    /// Pop an http(s) URL from the front of the arguments. See [`crate::HttpUrl`]
+    ///
+    /// ```rust
+    /// # use poise::{ArgString, PopArgument as _};
+    /// let (rest, url) = poise::HttpUrl::pop_from(&ArgString("<https://example.com> rest")).unwrap();
+    /// assert_eq!(rest, ArgString(" rest"));
+    /// assert_eq!(url.as_str(), "https://example.com/");
+    /// ```
+    fn pop_from(args: &ArgString<'a>) -> Result<(ArgString<'a>, Self), Self::Err> {
+        Self::pop_from_with_options(args, &PrefixParsingOptions::default())
+    }
+
+    fn pop_from_with_options(
+        args: &ArgString<'a>,
+        options: &PrefixParsingOptions,
+    ) -> Result<(ArgString<'a>, Self), Self::Err> {
+        let (args, string) =
+            String::pop_from_with_options(args, options).map_err(MaybeEmptyError::EmptyArgs)?;
+        let url = string.parse().map_err(MaybeEmptyError::ParseError)?;
+        Ok((args, url))
+    }
+}
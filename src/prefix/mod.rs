@@ -6,5 +6,8 @@ pub use argument::*;
 mod track_edits;
 pub use track_edits::*;
 
+mod prefix_store;
+pub use prefix_store::*;
+
 mod structs;
 pub use structs::*;
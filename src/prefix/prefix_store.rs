@@ -0,0 +1,71 @@
+//! Storage for per-guild prefixes, see [`PrefixStore`]
+
+use crate::serenity_prelude as serenity;
+
+/// Error type returned by [`PrefixStore`] implementations
+pub type PrefixStoreError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Storage backend for guild-specific prefixes, used by
+/// [`PrefixFrameworkOptions::prefix_store`](crate::PrefixFrameworkOptions::prefix_store).
+///
+/// If a guild has a prefix stored, it replaces [`PrefixFrameworkOptions::prefix`] and
+/// [`PrefixFrameworkOptions::additional_prefixes`](crate::PrefixFrameworkOptions::additional_prefixes)
+/// in that guild.
+///
+/// Implement this trait to persist prefixes in a database. [`InMemoryPrefixStore`] is provided
+/// for bots which don't need persistence.
+///
+/// [`PrefixFrameworkOptions::prefix`]: crate::PrefixFrameworkOptions::prefix
+#[async_trait::async_trait]
+pub trait PrefixStore: Send + Sync {
+    /// Returns the prefix configured for the given guild, if any.
+    ///
+    /// This is called for every message in a guild, so it should be fast. Errors can't be
+    /// reported from here; implementations should log them and return None.
+    async fn get(&self, guild_id: serenity::GuildId) -> Option<String>;
+
+    /// Sets the prefix for the given guild, replacing any previous one
+    async fn set(
+        &self,
+        guild_id: serenity::GuildId,
+        prefix: String,
+    ) -> Result<(), PrefixStoreError>;
+
+    /// Removes the prefix configured for the given guild, so that the default prefixes apply
+    /// again
+    async fn remove(&self, guild_id: serenity::GuildId) -> Result<(), PrefixStoreError>;
+}
+
+/// A [`PrefixStore`] which keeps prefixes in memory. Prefixes are lost when the bot restarts.
+#[derive(Debug, Default)]
+pub struct InMemoryPrefixStore {
+    prefixes: parking_lot::RwLock<std::collections::HashMap<serenity::GuildId, String>>,
+}
+
+impl InMemoryPrefixStore {
+    /// Create an empty prefix store
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait::async_trait]
+impl PrefixStore for InMemoryPrefixStore {
+    async fn get(&self, guild_id: serenity::GuildId) -> Option<String> {
+        self.prefixes.read().get(&guild_id).cloned()
+    }
+
+    async fn set(
+        &self,
+        guild_id: serenity::GuildId,
+        prefix: String,
+    ) -> Result<(), PrefixStoreError> {
+        self.prefixes.write().insert(guild_id, prefix);
+        Ok(())
+    }
+
+    async fn remove(&self, guild_id: serenity::GuildId) -> Result<(), PrefixStoreError> {
+        self.prefixes.write().remove(&guild_id);
+        Ok(())
+    }
+}
@@ -34,6 +34,19 @@ impl<U, E> crate::_GetGenerics for PrefixContext<'_, U, E> {
     type E = E;
 }
 
+/// Name and description of a prefix command parameter, for help menus
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ParameterHelp {
+    /// Name of the parameter
+    pub name: &'static str,
+    /// Description of the parameter, as given with `#[description = "..."]`
+    pub description: Option<&'static str>,
+    /// False for parameters which can be left out, like `Option`, `Vec` and `#[flag]` parameters
+    pub required: bool,
+    /// True for `#[named]` parameters, which are given as `--name value`
+    pub named: bool,
+}
+
 /// Optional settings for a [`PrefixCommand`].
 pub struct PrefixCommandOptions<U, E> {
     /// Short description of the command. Displayed inline in help menus and similar.
@@ -42,27 +55,84 @@ pub struct PrefixCommandOptions<U, E> {
     /// help: `~help command_name`
     // TODO: fix the inconsistency that this is String and everywhere else it's &'static str
     pub multiline_help: Option<fn() -> String>,
-    /// Alternative triggers for the command
+    /// Alternative triggers for the command, for example short forms like `b` for `ban`.
+    ///
+    /// Aliases only apply to prefix command matching. They don't change the name of the slash
+    /// command, and help menus list the command under its main name. Invocations via an alias
+    /// share the cooldowns of the main name, and editing an invocation to swap an alias for the
+    /// main name (or the other way around) doesn't run the command again.
     pub aliases: &'static [&'static str],
+    /// Usage syntax shown in the command specific help, like `ban <user> [reason]`. If None, it's
+    /// generated from [`Self::parameters`]
+    pub usage: Option<&'static str>,
+    /// Example invocations shown in the command specific help
+    pub examples: &'static [&'static str],
+    /// The parameters of the command, shown in the command specific help
+    pub parameters: &'static [ParameterHelp],
     /// Falls back to the framework-specified value on None. See there for documentation.
     pub on_error: Option<fn(E, PrefixCommandErrorContext<'_, U, E>) -> BoxFuture<'_, ()>>,
     /// If this function returns false, this command will not be executed.
     pub check: Option<fn(PrefixContext<'_, U, E>) -> BoxFuture<'_, Result<bool, E>>>,
+    /// How often this command may be invoked. Checked after [`Self::check`]
+    pub cooldowns: crate::Cooldowns,
+    /// Computes the key of the [`crate::CooldownConfig::custom`] cooldown for an invocation. If
+    /// None, the custom cooldown doesn't apply. Called before the arguments are parsed, so the key
+    /// can only depend on the invocation context, not on the arguments
+    pub cooldown_key: Option<fn(crate::Context<'_, U, E>) -> crate::CooldownKey>,
+    /// Name of the [`crate::RateLimitBucket`] in [`crate::FrameworkOptions::rate_limit_buckets`]
+    /// which this command shares with other commands, if any
+    pub bucket: Option<&'static str>,
+    /// If the command runs for longer than this, it's cancelled and
+    /// [`crate::FrameworkOptions::on_command_timeout`] is called.
+    ///
+    /// Falls back to the framework-specified value on None. See there for documentation.
+    pub timeout: Option<std::time::Duration>,
+    /// Limits how many invocations of this command may run at once. Excess invocations wait
+    /// before the command runs, or are passed to
+    /// [`crate::FrameworkOptions::on_concurrency_limit_reached`] if the queue is full.
+    pub concurrency: Option<&'static crate::ConcurrencyLimiter>,
+    /// Name of the [`crate::CommandGroup`] this command was mounted with, if any. Set by
+    /// [`crate::FrameworkOptions::mount`]
+    pub group: Option<&'static str>,
+    /// Falls back to the framework-specified value on None. See there for documentation.
+    pub on_cooldown:
+        Option<for<'a> fn(crate::CooldownHit<'a, U, E>) -> BoxFuture<'a, crate::CooldownFeedback>>,
     /// Whether to enable edit tracking for commands by default.
     ///
     /// Note: this won't do anything if `Framework::edit_tracker` isn't set.
     pub track_edits: bool,
     /// Whether to broadcast a typing indicator while executing this commmand.
     pub broadcast_typing: bool,
+    /// If set, a typing indicator is broadcasted if the command hasn't responded after this long,
+    /// until it responds. Unlike [`Self::broadcast_typing`], commands which respond quickly don't
+    /// show a typing indicator at all.
+    pub broadcast_typing_after: Option<std::time::Duration>,
+    /// Whether to post responses into a thread attached to the invocation message. Edits of
+    /// tracked responses stay in the thread.
+    ///
+    /// Falls back to the framework-specified value on None. See there for documentation.
+    pub respond_in_thread: Option<bool>,
     /// Whether to hide this command in help menus.
     pub hide_in_help: bool,
-    /// Permissions which users must have to invoke this command.
+    /// Permissions which users must have to invoke this command. Inside threads and forum posts,
+    /// the permissions in the parent channel are required.
     ///
     /// Set to [`serenity::Permissions::empty()`] by default
     pub required_permissions: serenity::Permissions,
+    /// Roles which users must all have to invoke this command, see [`crate::RequiredRole`].
+    /// Commands with required roles can't be invoked outside of guilds
+    pub required_roles: &'static [crate::RequiredRole],
     /// If true, only users from the [owners list](crate::FrameworkOptions::owners) may use this
     /// command.
     pub owners_only: bool,
+    /// If Some, this command may only be invoked in channels of these types. Checked before the
+    /// command runs, other invocations are passed to
+    /// [`crate::FrameworkOptions::on_channel_type_not_allowed`].
+    pub allowed_channel_types: Option<&'static [serenity::ChannelType]>,
+    /// If true, this command may only be invoked in NSFW channels and threads of NSFW channels.
+    /// Checked before the command runs, other invocations are passed to
+    /// [`crate::FrameworkOptions::on_nsfw_channel_required`].
+    pub nsfw_only: bool,
 }
 
 impl<U, E> Default for PrefixCommandOptions<U, E> {
@@ -71,13 +141,28 @@ impl<U, E> Default for PrefixCommandOptions<U, E> {
             inline_help: None,
             multiline_help: None,
             check: None,
+            cooldowns: crate::Cooldowns::default(),
+            cooldown_key: None,
+            bucket: None,
+            timeout: None,
+            concurrency: None,
+            group: None,
+            on_cooldown: None,
             on_error: None,
             aliases: &[],
+            usage: None,
+            examples: &[],
+            parameters: &[],
             track_edits: false,
             broadcast_typing: false,
+            broadcast_typing_after: None,
+            respond_in_thread: None,
             hide_in_help: false,
             required_permissions: serenity::Permissions::empty(),
+            required_roles: &[],
             owners_only: false,
+            allowed_channel_types: None,
+            nsfw_only: false,
         }
     }
 }
@@ -93,6 +178,20 @@ pub struct PrefixCommand<U, E> {
     pub options: PrefixCommandOptions<U, E>,
 }
 
+impl<U, E> PrefixCommand<U, E> {
+    /// Whether the given name is the main name or one of the aliases of this command
+    pub fn is_named(&self, name: &str, case_insensitive: bool) -> bool {
+        let considered_equal = |command_name: &str| {
+            if case_insensitive {
+                command_name.eq_ignore_ascii_case(name)
+            } else {
+                command_name == name
+            }
+        };
+        considered_equal(self.name) || self.options.aliases.iter().any(|x| considered_equal(x))
+    }
+}
+
 /// Includes a command, plus metadata like associated sub-commands or category.
 pub struct PrefixCommandMeta<U, E> {
     /// Core command data
@@ -123,6 +222,18 @@ impl<U, E> Clone for PrefixCommandErrorContext<'_, U, E> {
     }
 }
 
+/// Passed to [`PrefixFrameworkOptions::unknown_command`] when a message starts with the prefix but
+/// doesn't match any command
+pub struct UnknownCommand<'a, U, E> {
+    /// Context of the message. [`PrefixContext::command`] is None
+    pub ctx: PrefixContext<'a, U, E>,
+    /// The command name the user typed
+    pub command_name: &'a str,
+    /// Names of existing commands which are similar to [`Self::command_name`], most similar first.
+    /// May be empty
+    pub suggestions: Vec<&'static str>,
+}
+
 /// Possible ways to define a command prefix
 pub enum Prefix {
     /// A case-sensitive string literal prefix (passed to [`str::strip_prefix`])
@@ -171,8 +282,37 @@ pub struct PrefixFrameworkOptions<U, E> {
             &'a U,
         ) -> BoxFuture<'a, Option<&'a str>>,
     >,
+    /// If Some, guild-specific prefixes are looked up in this store. A stored prefix replaces
+    /// [`Self::prefix`] and [`Self::additional_prefixes`] in its guild.
+    ///
+    /// See [`crate::builtins::set_prefix`] and [`crate::builtins::reset_prefix`] for commands
+    /// which modify the store.
+    pub prefix_store: Option<Box<dyn super::PrefixStore>>,
     /// Treat a bot mention (a ping) like a prefix
     pub mention_as_prefix: bool,
+    /// Whether prefix commands post their responses into a thread attached to the invocation
+    /// message, instead of into the channel directly. Can be overridden per command with
+    /// [`PrefixCommandOptions::respond_in_thread`].
+    ///
+    /// Only applies in guild channels which aren't threads themselves; otherwise, responses are
+    /// posted normally. Application command responses are unaffected.
+    ///
+    /// The thread is created by the first response. If [`Self::edit_tracker`] is set, it
+    /// remembers the thread for all further responses of the invocation, including re-runs after
+    /// edits; otherwise, each further response has to look the thread up again.
+    pub respond_in_thread: bool,
+    /// If true, only bot mentions (and replies, if [`Self::reply_as_prefix`] is set) are accepted as
+    /// prefix. All other prefixes are ignored, even if [`Self::mention_as_prefix`] is false.
+    pub mention_only: bool,
+    /// Treat replying to a message by the bot like a prefix, so that the reply content is
+    /// interpreted as a command invocation without any further prefix
+    pub reply_as_prefix: bool,
+    /// Callback applied to the message content after a bot mention prefix has been stripped, for
+    /// example to also strip a following comma or colon:
+    /// ```rust,ignore
+    /// strip_after_mention: Some(|content| content.trim_start().trim_start_matches(&[',', ':'][..])),
+    /// ```
+    pub strip_after_mention: Option<for<'a> fn(&'a str) -> &'a str>,
     /// If Some, the framework will react to message edits by editing the corresponding bot response
     /// with the new result.
     pub edit_tracker: Option<parking_lot::RwLock<super::EditTracker>>,
@@ -180,11 +320,47 @@ pub struct PrefixFrameworkOptions<U, E> {
     /// This can happen if the message edit happens while the command is being invoked, or the
     /// original message wasn't a command.
     pub ignore_edit_tracker_cache: bool,
+    /// What to do with a tracked bot response when the user deletes the message which invoked the
+    /// command. Only has an effect if [`Self::edit_tracker`] is set.
+    pub invocation_deleted_behavior: super::InvocationDeletedBehavior,
 
     /// Whether commands in messages emitted by the bot itself should be executed as well.
     pub execute_self_messages: bool,
+    /// Whether to ignore commands in messages from other bots. Messages sent by webhooks are
+    /// governed by [`Self::ignore_webhooks`] instead. False by default
+    pub ignore_bots: bool,
+    /// Whether to ignore commands in messages sent by webhooks. False by default
+    pub ignore_webhooks: bool,
+    /// Called for messages which would be ignored because of [`Self::ignore_bots`] or
+    /// [`Self::ignore_webhooks`]. If it returns true, the message is processed anyways, for
+    /// example to let a specific bridge bot invoke commands:
+    /// ```rust,ignore
+    /// allow_ignored_author: Some(|msg| msg.author.id == BRIDGE_BOT_ID),
+    /// ```
+    ///
+    /// Doesn't apply to the bot's own messages; see [`Self::execute_self_messages`] for those.
+    pub allow_ignored_author: Option<fn(&serenity::Message) -> bool>,
     /// Whether command names should be compared case-insensitively.
     pub case_insensitive_commands: bool,
+    /// Whether to compare literal prefixes and command names after Unicode NFKC normalization and
+    /// lowercasing, so that for example full-width `！ＢＡＮ` matches `!ban`. Implies
+    /// case-insensitive command names. Regex prefixes are unaffected.
+    #[cfg(feature = "unicode-normalization")]
+    pub normalize_unicode: bool,
+    /// If Some, a single message can invoke multiple commands separated by this string, for example
+    /// `&&` or `\n`. The commands are run in order; only the first one needs the prefix.
+    /// Separators inside quotes (see [`Self::parsing_options`]) and code blocks don't split.
+    ///
+    /// Messages with more than one command aren't edit tracked. See
+    /// [`crate::dispatch_message_chain`] for details.
+    pub command_separator: Option<String>,
+    /// How quotes and escapes in command arguments are parsed
+    pub parsing_options: super::PrefixParsingOptions,
+    /// Callback invoked when a message starts with the prefix but no command matches. Command names
+    /// and aliases similar to the typed name are passed along as suggestions.
+    ///
+    /// See [`crate::builtins::suggest_similar_commands`] for a ready-made implementation.
+    pub unknown_command: Option<for<'a> fn(UnknownCommand<'a, U, E>) -> BoxFuture<'a, ()>>,
     /* // TODO: implement
     /// Whether to invoke help command when someone sends a message with just a bot mention
     pub help_when_mentioned: bool,
@@ -204,11 +380,25 @@ impl<U, E> Default for PrefixFrameworkOptions<U, E> {
             additional_prefixes: Vec::new(),
             dynamic_prefix: None,
             stripped_dynamic_prefix: None,
+            prefix_store: None,
             mention_as_prefix: true,
+            respond_in_thread: false,
+            mention_only: false,
+            reply_as_prefix: false,
+            strip_after_mention: None,
             edit_tracker: None,
             ignore_edit_tracker_cache: false,
+            invocation_deleted_behavior: super::InvocationDeletedBehavior::Delete,
             execute_self_messages: false,
+            ignore_bots: false,
+            ignore_webhooks: false,
+            allow_ignored_author: None,
             case_insensitive_commands: true,
+            #[cfg(feature = "unicode-normalization")]
+            normalize_unicode: false,
+            command_separator: None,
+            parsing_options: super::PrefixParsingOptions::default(),
+            unknown_command: None,
             // help_when_mentioned: true,
             // help_commmand: None,
             // command_specific_help_commmand: None,
@@ -46,11 +46,77 @@ fn update_message(message: &mut serenity::Message, update: serenity::MessageUpda
     // }
 }
 
+/// Identifies a set of message attachments, to detect when attachments are added or removed in an
+/// edit
+fn attachment_fingerprint(attachments: &[serenity::Attachment]) -> Vec<serenity::AttachmentId> {
+    attachments.iter().map(|attachment| attachment.id).collect()
+}
+
+/// Persistent storage backend of an [`EditTracker`], mapping user messages which invoked a command
+/// to the associated bot responses.
+///
+/// The [`EditTracker`] always keeps the tracked messages in memory, which are lost when the bot
+/// restarts. If a store is configured via [`EditTracker::for_timespan_with_store`], every change is
+/// additionally written to the store, and invocations which aren't in memory are looked up in the
+/// store. Implement this trait to persist the mapping, for example in a database, so that edits to
+/// invocations from before a restart still update the response. [`serenity::Message`] implements
+/// `Serialize` and `Deserialize` for that purpose.
+///
+/// The store is never called while the edit tracker is locked.
+#[async_trait::async_trait]
+pub trait EditTrackerStore: Send + Sync {
+    /// Returns the user message with the given ID and the associated bot response, if tracked
+    async fn get(
+        &self,
+        user_msg_id: serenity::MessageId,
+    ) -> Option<(serenity::Message, serenity::Message)>;
+
+    /// Starts tracking the given user message and bot response. Replaces the existing entry if
+    /// the user message is already tracked.
+    async fn insert(&self, user_msg: serenity::Message, bot_response: serenity::Message);
+
+    /// Stops tracking the user message with the given ID
+    async fn remove(&self, user_msg_id: serenity::MessageId);
+
+    /// Forgets all entries whose user message was last edited (or sent, if never edited) before
+    /// the given point in time
+    async fn remove_older_than(&self, cutoff: chrono::DateTime<chrono::Utc>);
+}
+
+/// What to do with the bot response when a tracked command invocation message is deleted, see
+/// [`crate::PrefixFrameworkOptions::invocation_deleted_behavior`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InvocationDeletedBehavior {
+    /// Leave the bot response as is
+    Ignore,
+    /// Delete the bot response
+    Delete,
+    /// Strike through the text content of the bot response
+    StrikeThrough,
+}
+
+impl Default for InvocationDeletedBehavior {
+    fn default() -> Self {
+        Self::Delete
+    }
+}
+
 /// Stores messages and the associated bot responses in order to implement poise's edit tracking
 /// feature.
 pub struct EditTracker {
     max_duration: std::time::Duration,
     cache: Vec<(serenity::Message, serenity::Message)>,
+    /// The last invocation of each user message in canonical form, i.e. with aliases resolved,
+    /// see [`Self::track_invocation`]
+    invocations: std::collections::HashMap<serenity::MessageId, String>,
+    /// The thread which responses to each user message are sent into, see
+    /// [`crate::PrefixCommandOptions::respond_in_thread`]
+    response_threads: std::collections::HashMap<serenity::MessageId, serenity::ChannelId>,
+    /// The leading messages of the chunked response to each user message, in order. The last
+    /// chunk is the tracked bot response itself, see [`crate::CreateReply::chunk_content`]. Only
+    /// kept in memory
+    response_chunks: std::collections::HashMap<serenity::MessageId, Vec<serenity::Message>>,
+    store: Option<std::sync::Arc<dyn EditTrackerStore>>,
 }
 
 impl EditTracker {
@@ -63,14 +129,34 @@ impl EditTracker {
         parking_lot::RwLock::new(Self {
             max_duration: duration,
             cache: Vec::new(),
+            invocations: std::collections::HashMap::new(),
+            response_threads: std::collections::HashMap::new(),
+            response_chunks: std::collections::HashMap::new(),
+            store: None,
+        })
+    }
+
+    /// Like [`Self::for_timespan`], but additionally persists the tracked messages in the given
+    /// [`EditTrackerStore`].
+    pub fn for_timespan_with_store(
+        duration: std::time::Duration,
+        store: impl EditTrackerStore + 'static,
+    ) -> parking_lot::RwLock<Self> {
+        parking_lot::RwLock::new(Self {
+            max_duration: duration,
+            cache: Vec::new(),
+            invocations: std::collections::HashMap::new(),
+            response_threads: std::collections::HashMap::new(),
+            response_chunks: std::collections::HashMap::new(),
+            store: Some(std::sync::Arc::new(store)),
         })
     }
 
     /// Returns a copy of a newly up-to-date cached message, or a brand new generated message when
     /// not in cache
     ///
-    /// Returns None if the command shouldn't be re-run, e.g. if the message content stayed the
-    /// same
+    /// Returns None if the command shouldn't be re-run, i.e. if neither the message content nor
+    /// the attachments changed
     pub fn process_message_update(
         &mut self,
         user_msg_update: &serenity::MessageUpdateEvent,
@@ -82,11 +168,20 @@ impl EditTracker {
             .find(|(user_msg, _)| user_msg.id == user_msg_update.id)
         {
             Some((user_msg, _)) => {
-                // If message content didn't change, don't re-run command
-                match &user_msg_update.content {
-                    Some(content) if content == &user_msg.content => return None,
-                    None => return None,
-                    _ => {}
+                // If neither message content nor attachments changed, don't re-run command
+                let content_changed = match &user_msg_update.content {
+                    Some(content) => content != &user_msg.content,
+                    None => false,
+                };
+                let attachments_changed = match &user_msg_update.attachments {
+                    Some(attachments) => {
+                        attachment_fingerprint(attachments)
+                            != attachment_fingerprint(&user_msg.attachments)
+                    }
+                    None => false,
+                };
+                if !content_changed && !attachments_changed {
+                    return None;
                 }
 
                 update_message(user_msg, user_msg_update.clone());
@@ -105,19 +200,36 @@ impl EditTracker {
     }
 
     /// Forget all of the messages that are older than the specified duration.
+    ///
+    /// Entries in the [`EditTrackerStore`] are purged separately by the framework, since the store
+    /// can't be called while the edit tracker is locked.
     pub fn purge(&mut self) {
-        let max_duration = self.max_duration;
+        let cutoff = match self.purge_cutoff() {
+            Some(x) => x,
+            None => return,
+        };
         self.cache.retain(|(user_msg, _)| {
-            let last_update = user_msg.edited_timestamp.unwrap_or(user_msg.timestamp);
-            if let Ok(age) = (chrono::Utc::now() - last_update).to_std() {
-                age < max_duration
-            } else {
-                false
-            }
+            user_msg.edited_timestamp.unwrap_or(user_msg.timestamp) > cutoff
         });
+        let cache = &self.cache;
+        let is_tracked =
+            |id: &serenity::MessageId| cache.iter().any(|(user_msg, _)| user_msg.id == *id);
+        self.invocations.retain(|id, _| is_tracked(id));
+        self.response_chunks.retain(|id, _| is_tracked(id));
+        // Responses without edit tracking aren't cached, so threads are also kept by age
+        self.response_threads
+            .retain(|id, _| is_tracked(id) || id.created_at() > cutoff);
+    }
+
+    /// Point in time before which tracked messages are forgotten. None if the tracking duration is
+    /// too long for any message to have expired yet
+    fn purge_cutoff(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        let max_duration = chrono::Duration::from_std(self.max_duration).ok()?;
+        Some(chrono::Utc::now() - max_duration)
     }
 
-    /// Given a message by a user, find the corresponding bot response, if one exists and is cached.
+    /// Given a message by a user, find the corresponding bot response, if one exists and is
+    /// cached.
     pub fn find_bot_response(
         &mut self,
         user_msg_id: serenity::MessageId,
@@ -129,13 +241,333 @@ impl EditTracker {
         Some(bot_response)
     }
 
+    /// Notify the [`EditTracker`] that the given user message was deleted. Stops tracking the
+    /// message and returns the associated bot response, if one was tracked.
+    pub fn process_message_delete(
+        &mut self,
+        deleted_message_id: serenity::MessageId,
+    ) -> Option<serenity::Message> {
+        self.invocations.remove(&deleted_message_id);
+        self.response_threads.remove(&deleted_message_id);
+        self.response_chunks.remove(&deleted_message_id);
+        self.remove_response(deleted_message_id)
+    }
+
+    /// Stops tracking the bot response of the given user message, but keeps the other state of
+    /// the invocation, like its response thread
+    fn remove_response(&mut self, user_msg_id: serenity::MessageId) -> Option<serenity::Message> {
+        let index = self
+            .cache
+            .iter()
+            .position(|(user_msg, _)| user_msg.id == user_msg_id)?;
+        let (_, bot_response) = self.cache.remove(index);
+        Some(bot_response)
+    }
+
     /// Notify the [`EditTracker`] that the given user message should be associated with the given
-    /// bot response.
+    /// bot response. Replaces the existing entry if the user message is already tracked.
     fn register_response(&mut self, user_msg: serenity::Message, bot_response: serenity::Message) {
-        self.cache.push((user_msg, bot_response));
+        match self
+            .cache
+            .iter_mut()
+            .find(|(existing_user_msg, _)| existing_user_msg.id == user_msg.id)
+        {
+            Some(entry) => *entry = (user_msg, bot_response),
+            None => self.cache.push((user_msg, bot_response)),
+        }
+    }
+
+    /// Remembers the canonical form of the command invocation in the given user message. Returns
+    /// whether it differs from the previous invocation of that message, i.e. whether the command
+    /// needs to run again after an edit.
+    pub(crate) fn track_invocation(
+        &mut self,
+        user_msg_id: serenity::MessageId,
+        invocation: String,
+    ) -> bool {
+        self.invocations.insert(user_msg_id, invocation.clone()) != Some(invocation)
+    }
+
+    /// Replace the bot response associated with the given user message, if the user message is
+    /// still tracked
+    fn update_response(
+        &mut self,
+        user_msg_id: serenity::MessageId,
+        bot_response: serenity::Message,
+    ) {
+        if let Some(existing_response) = self.find_bot_response(user_msg_id) {
+            *existing_response = bot_response;
+        }
+    }
+}
+
+/// If the given user message isn't tracked in memory, looks it up in the [`EditTrackerStore`] and
+/// caches the entry, if found
+pub(crate) async fn load_tracked_message(
+    edit_tracker: &parking_lot::RwLock<EditTracker>,
+    user_msg_id: serenity::MessageId,
+) {
+    let store = {
+        let edit_tracker = edit_tracker.read();
+        if edit_tracker
+            .cache
+            .iter()
+            .any(|(user_msg, _)| user_msg.id == user_msg_id)
+        {
+            return;
+        }
+        match &edit_tracker.store {
+            Some(store) => store.clone(),
+            None => return,
+        }
+    };
+
+    if let Some((user_msg, bot_response)) = store.get(user_msg_id).await {
+        edit_tracker
+            .write()
+            .register_response(user_msg, bot_response);
+    }
+}
+
+/// Writes the in-memory entry of the given user message to the [`EditTrackerStore`], or removes
+/// it from the store if it's not tracked anymore
+pub(crate) async fn persist_tracked_message(
+    edit_tracker: &parking_lot::RwLock<EditTracker>,
+    user_msg_id: serenity::MessageId,
+) {
+    let (store, entry) = {
+        let edit_tracker = edit_tracker.read();
+        let store = match &edit_tracker.store {
+            Some(store) => store.clone(),
+            None => return,
+        };
+        let entry = edit_tracker
+            .cache
+            .iter()
+            .find(|(user_msg, _)| user_msg.id == user_msg_id)
+            .cloned();
+        (store, entry)
+    };
+
+    match entry {
+        Some((user_msg, bot_response)) => store.insert(user_msg, bot_response).await,
+        None => store.remove(user_msg_id).await,
+    }
+}
+
+/// Stops tracking the given user message, in memory and in the [`EditTrackerStore`]. Returns the
+/// associated bot response messages, i.e. the leading chunks of a chunked response followed by
+/// the tracked response itself
+pub(crate) async fn forget_tracked_message(
+    edit_tracker: &parking_lot::RwLock<EditTracker>,
+    user_msg_id: serenity::MessageId,
+) -> Vec<serenity::Message> {
+    load_tracked_message(edit_tracker, user_msg_id).await;
+    let bot_responses = {
+        let mut edit_tracker = edit_tracker.write();
+        let chunks = edit_tracker
+            .response_chunks
+            .remove(&user_msg_id)
+            .unwrap_or_default();
+        let bot_response = edit_tracker.process_message_delete(user_msg_id);
+        chunks.into_iter().chain(bot_response).collect::<Vec<_>>()
+    };
+    persist_tracked_message(edit_tracker, user_msg_id).await;
+    bot_responses
+}
+
+/// Calls [`EditTracker::purge`] and purges the [`EditTrackerStore`] accordingly
+pub(crate) async fn purge_tracked_messages(edit_tracker: &parking_lot::RwLock<EditTracker>) {
+    let (store, cutoff) = {
+        let mut edit_tracker = edit_tracker.write();
+        edit_tracker.purge();
+        (edit_tracker.store.clone(), edit_tracker.purge_cutoff())
+    };
+
+    if let (Some(store), Some(cutoff)) = (store, cutoff) {
+        store.remove_older_than(cutoff).await;
+    }
+}
+
+/// Applies the configured [`InvocationDeletedBehavior`] to the bot response of a deleted command
+/// invocation
+pub(crate) async fn handle_invocation_deleted(
+    ctx: &serenity::Context,
+    mut bot_response: serenity::Message,
+    behavior: InvocationDeletedBehavior,
+) -> Result<(), serenity::Error> {
+    match behavior {
+        InvocationDeletedBehavior::Ignore => Ok(()),
+        InvocationDeletedBehavior::Delete => bot_response.delete(ctx).await,
+        InvocationDeletedBehavior::StrikeThrough => {
+            if bot_response.content.is_empty() {
+                return Ok(());
+            }
+            let content = format!("~~{}~~", bot_response.content);
+            bot_response.edit(ctx, |f| f.content(content)).await
+        }
+    }
+}
+
+/// The channel to send new responses into: the invocation channel, or a thread attached to the
+/// invocation message if [`crate::PrefixCommandOptions::respond_in_thread`] is enabled.
+///
+/// Falls back to the invocation channel if no thread can be created, for example in DMs or if
+/// the invocation is inside a thread already.
+async fn response_channel<U, E>(
+    ctx: crate::prefix::PrefixContext<'_, U, E>,
+) -> serenity::ChannelId {
+    let respond_in_thread = ctx
+        .command
+        .and_then(|command| command.options.respond_in_thread)
+        .unwrap_or(ctx.framework.options().prefix_options.respond_in_thread);
+    if !respond_in_thread || ctx.msg.guild_id.is_none() {
+        return ctx.msg.channel_id;
+    }
+
+    // Reuse the thread created by an earlier response of this invocation
+    let edit_tracker = ctx.framework.options().prefix_options.edit_tracker.as_ref();
+    let existing_thread = edit_tracker.and_then(|edit_tracker| {
+        edit_tracker
+            .read()
+            .response_threads
+            .get(&ctx.msg.id)
+            .copied()
+    });
+    if let Some(thread_id) = existing_thread {
+        return thread_id;
+    }
+    let thread_id = create_response_thread(ctx).await;
+    if thread_id != ctx.msg.channel_id {
+        if let Some(edit_tracker) = edit_tracker {
+            edit_tracker
+                .write()
+                .response_threads
+                .insert(ctx.msg.id, thread_id);
+        }
+    }
+    thread_id
+}
+
+/// Creates the thread for [`response_channel`], falling back to the invocation channel
+async fn create_response_thread<U, E>(
+    ctx: crate::prefix::PrefixContext<'_, U, E>,
+) -> serenity::ChannelId {
+    // Thread names are limited to 100 characters
+    let thread_name = match ctx.msg.content.char_indices().nth(100) {
+        Some((end, _)) => &ctx.msg.content[..end],
+        None => &ctx.msg.content,
+    };
+    match ctx
+        .msg
+        .channel_id
+        .create_public_thread(ctx.discord, ctx.msg.id, |f| f.name(thread_name))
+        .await
+    {
+        Ok(thread) => thread.id,
+        Err(e) => {
+            // Threads started from a message share its ID. If an earlier response of this
+            // invocation created the thread already, post into it
+            let existing_thread = serenity::ChannelId(ctx.msg.id.0);
+            if existing_thread.to_channel(ctx.discord).await.is_ok() {
+                existing_thread
+            } else {
+                println!("Warning: couldn't create thread for response: {}", e);
+                ctx.msg.channel_id
+            }
+        }
     }
 }
 
+/// The edit tracker which responses of this invocation are tracked in, if edit tracking is on
+fn active_edit_tracker<'a, U, E>(
+    ctx: crate::prefix::PrefixContext<'a, U, E>,
+) -> Option<&'a parking_lot::RwLock<EditTracker>> {
+    if let Some(command) = ctx.command {
+        if !command.options.track_edits {
+            return None;
+        }
+    }
+
+    ctx.framework.options().prefix_options.edit_tracker.as_ref()
+}
+
+/// Sends the leading chunks of a reply, i.e. all but the last, which is sent as the regular
+/// response. See [`crate::CreateReply::chunk_content`]; called with no chunks for other replies.
+///
+/// With edit tracking, the chunk messages of an earlier run of the invocation are edited in place
+/// and the ones which aren't needed anymore are deleted. If more chunks are needed than before,
+/// the earlier response is deleted, so that the new response is sent below the new chunks.
+pub(crate) async fn send_prefix_reply_chunks<U, E>(
+    ctx: crate::prefix::PrefixContext<'_, U, E>,
+    chunks: Vec<String>,
+) -> Result<(), serenity::Error> {
+    let edit_tracker = active_edit_tracker(ctx);
+    if chunks.is_empty() && edit_tracker.is_none() {
+        return Ok(());
+    }
+    ctx.framework
+        .stop_delayed_typing(crate::Context::Prefix(ctx).id());
+
+    if let Some(edit_tracker) = edit_tracker {
+        load_tracked_message(edit_tracker, ctx.msg.id).await;
+    }
+    let mut old_chunks = edit_tracker
+        .and_then(|t| t.write().response_chunks.remove(&ctx.msg.id))
+        .unwrap_or_default()
+        .into_iter();
+
+    let mut new_chunks = Vec::with_capacity(chunks.len());
+    let mut sent_new_chunk = false;
+    for content in chunks {
+        let chunk = match old_chunks.next() {
+            Some(mut chunk) => {
+                chunk.edit(ctx.discord, |f| f.content(content)).await?;
+                chunk
+            }
+            None => {
+                sent_new_chunk = true;
+                response_channel(ctx)
+                    .await
+                    .send_message(ctx.discord, |m| {
+                        m.content(content);
+                        if let Some(allowed_mentions) = &ctx.framework.options().allowed_mentions {
+                            m.allowed_mentions(|m| {
+                                *m = allowed_mentions.clone();
+                                m
+                            });
+                        }
+                        m
+                    })
+                    .await?
+            }
+        };
+        new_chunks.push(chunk);
+    }
+    for chunk in old_chunks {
+        chunk.delete(ctx.discord).await?;
+    }
+
+    let edit_tracker = match edit_tracker {
+        Some(x) => x,
+        None => return Ok(()),
+    };
+    if sent_new_chunk {
+        let old_response = edit_tracker.write().remove_response(ctx.msg.id);
+        if let Some(old_response) = old_response {
+            persist_tracked_message(edit_tracker, ctx.msg.id).await;
+            old_response.delete(ctx.discord).await?;
+        }
+    }
+    if !new_chunks.is_empty() {
+        edit_tracker
+            .write()
+            .response_chunks
+            .insert(ctx.msg.id, new_chunks);
+    }
+    Ok(())
+}
+
 /// Prefix-specific reply function. For more details, see [`crate::send_reply`].
 pub async fn send_prefix_reply<U, E>(
     ctx: crate::prefix::PrefixContext<'_, U, E>,
@@ -143,33 +575,32 @@ pub async fn send_prefix_reply<U, E>(
 ) -> Result<serenity::Message, serenity::Error> {
     let mut reply = crate::CreateReply::default();
     builder(&mut reply);
+    send_built_prefix_reply(ctx, reply).await
+}
+
+/// Like [`send_prefix_reply`], but with an already built reply
+pub(crate) async fn send_built_prefix_reply<U, E>(
+    ctx: crate::prefix::PrefixContext<'_, U, E>,
+    reply: crate::CreateReply<'_>,
+) -> Result<serenity::Message, serenity::Error> {
     let crate::CreateReply {
         content,
         embed,
         attachments,
         components,
         ephemeral: _,
+        chunk_content: _,
     } = reply;
+    ctx.framework
+        .stop_delayed_typing(crate::Context::Prefix(ctx).id());
 
-    let lock_edit_tracker = || {
-        if let Some(command) = ctx.command {
-            if !command.options.track_edits {
-                return None;
-            }
-        }
+    let edit_tracker = || active_edit_tracker(ctx);
 
-        ctx.framework
-            .options()
-            .prefix_options
-            .edit_tracker
-            .as_ref()
-            .map(|t| t.write())
-    };
-
-    let existing_response = lock_edit_tracker()
-        .as_mut()
-        .and_then(|t| t.find_bot_response(ctx.msg.id))
-        .cloned();
+    if let Some(edit_tracker) = edit_tracker() {
+        load_tracked_message(edit_tracker, ctx.msg.id).await;
+    }
+    let existing_response =
+        edit_tracker().and_then(|t| t.write().find_bot_response(ctx.msg.id).cloned());
 
     Ok(if let Some(mut response) = existing_response {
         response
@@ -200,18 +631,17 @@ pub async fn send_prefix_reply<U, E>(
             .await?;
 
         // If the entry still exists after the await, update it to the new contents
-        if let Some(response_entry) = lock_edit_tracker()
-            .as_mut()
-            .and_then(|t| t.find_bot_response(ctx.msg.id))
-        {
-            *response_entry = response.clone();
+        if let Some(edit_tracker) = edit_tracker() {
+            edit_tracker
+                .write()
+                .update_response(ctx.msg.id, response.clone());
+            persist_tracked_message(edit_tracker, ctx.msg.id).await;
         }
 
         response
     } else {
-        let new_response = ctx
-            .msg
-            .channel_id
+        let new_response = response_channel(ctx)
+            .await
             .send_message(ctx.discord, |m| {
                 if let Some(content) = content {
                     m.content(content);
@@ -238,8 +668,11 @@ pub async fn send_prefix_reply<U, E>(
                 m
             })
             .await?;
-        if let Some(track_edits) = &mut lock_edit_tracker() {
-            track_edits.register_response(ctx.msg.clone(), new_response.clone());
+        if let Some(edit_tracker) = edit_tracker() {
+            edit_tracker
+                .write()
+                .register_response(ctx.msg.clone(), new_response.clone());
+            persist_tracked_message(edit_tracker, ctx.msg.id).await;
         }
 
         new_response
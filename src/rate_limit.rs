@@ -0,0 +1,258 @@
+//! Rate limits shared by multiple commands, see [`RateLimitBucket`]
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// Who shares the uses of a [`RateLimitBucket`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RateLimitScope {
+    /// Everyone shares the same uses
+    Global,
+    /// Each user has their own uses, across all guilds
+    User,
+    /// Each guild has its own uses. In DMs, each user has their own uses
+    Guild,
+    /// Each channel has its own uses
+    Channel,
+    /// Each user has their own uses in each guild. In DMs, each user has their own uses
+    Member,
+}
+
+/// How often the commands of a [`RateLimitBucket`] may be used
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RateLimit {
+    /// How many invocations are allowed within [`Self::window`]
+    pub uses: u32,
+    /// The sliding window in which at most [`Self::uses`] invocations are allowed
+    pub window: Duration,
+    /// Who shares the uses
+    pub scope: RateLimitScope,
+}
+
+/// A rate limit shared by all commands which declare this bucket with `bucket = "name"`, for
+/// example to allow three moderation actions per minute per guild, no matter which moderation
+/// commands are used. Registered in [`crate::FrameworkOptions::rate_limit_buckets`].
+///
+/// Buckets are checked after the cooldowns of the command itself, and rejected invocations are
+/// passed to the `on_cooldown` callback like cooldown hits, with [`crate::CooldownHit::bucket`]
+/// set. Like cooldowns, a use is only recorded once the command runs, and only if it passed both
+/// its cooldowns and the bucket. Commands declaring a bucket which isn't registered fail
+/// [`crate::Framework::validate`], so the framework refuses to start.
+///
+/// ```rust
+/// # type Error = Box<dyn std::error::Error + Send + Sync>;
+/// use poise::{RateLimit, RateLimitBucket, RateLimitScope};
+///
+/// let mut options = poise::FrameworkOptions::<(), Error>::default();
+/// options.rate_limit_buckets.insert(
+///     "moderation".into(),
+///     RateLimitBucket::new(RateLimit {
+///         uses: 3,
+///         window: std::time::Duration::from_secs(60),
+///         scope: RateLimitScope::Guild,
+///     }),
+/// );
+/// ```
+#[derive(Debug)]
+pub struct RateLimitBucket {
+    limit: RateLimit,
+    /// The recent uses in each scope, oldest first
+    uses: parking_lot::Mutex<HashMap<crate::CooldownBucket, VecDeque<Instant>>>,
+}
+
+impl RateLimitBucket {
+    /// Create a new bucket with the given limit and no uses yet
+    pub fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            uses: Default::default(),
+        }
+    }
+
+    /// The limit of this bucket
+    pub fn limit(&self) -> &RateLimit {
+        &self.limit
+    }
+
+    /// The scope which the given invocation falls into
+    fn scope_of<U, E>(&self, ctx: crate::Context<'_, U, E>) -> crate::CooldownBucket {
+        let user_id = ctx.author().id;
+        match (self.limit.scope, ctx.guild_id()) {
+            (RateLimitScope::Global, _) => crate::CooldownBucket::Global,
+            (RateLimitScope::User, _) => crate::CooldownBucket::User(user_id),
+            (RateLimitScope::Guild, Some(guild_id)) => crate::CooldownBucket::Guild(guild_id),
+            (RateLimitScope::Channel, _) => crate::CooldownBucket::Channel(ctx.channel_id()),
+            (RateLimitScope::Member, Some(guild_id)) => {
+                crate::CooldownBucket::Member(guild_id, user_id)
+            }
+            (RateLimitScope::Guild, None) | (RateLimitScope::Member, None) => {
+                crate::CooldownBucket::User(user_id)
+            }
+        }
+    }
+
+    /// Forgets the uses which are outside the window, and returns how long until the next use is
+    /// allowed, if it isn't right now
+    fn refresh(&self, uses: &mut VecDeque<Instant>, now: Instant) -> Option<Duration> {
+        while let Some(&oldest) = uses.front() {
+            if now.saturating_duration_since(oldest) < self.limit.window {
+                break;
+            }
+            uses.pop_front();
+        }
+
+        if uses.len() < self.limit.uses as usize {
+            return None;
+        }
+        // The window must slide past this many uses to make room for another one
+        let excess = uses.len() + 1 - self.limit.uses as usize;
+        let blocking_use = match uses.get(excess - 1) {
+            Some(&x) => x,
+            // Zero uses allowed
+            None => return Some(self.limit.window),
+        };
+        Some(self.limit.window - now.saturating_duration_since(blocking_use))
+    }
+
+    fn scope_retry_after(&self, scope: &crate::CooldownBucket, now: Instant) -> Option<Duration> {
+        let mut all_uses = self.uses.lock();
+        self.refresh(all_uses.get_mut(scope)?, now)
+    }
+
+    fn scope_record_use(&self, scope: crate::CooldownBucket, now: Instant) {
+        self.uses.lock().entry(scope).or_default().push_back(now);
+    }
+
+    fn scope_remaining_uses(&self, scope: &crate::CooldownBucket, now: Instant) -> u32 {
+        let mut all_uses = self.uses.lock();
+        let uses = match all_uses.get_mut(scope) {
+            Some(x) => x,
+            None => return self.limit.uses,
+        };
+        self.refresh(uses, now);
+        self.limit.uses.saturating_sub(uses.len() as u32)
+    }
+
+    fn scope_try_use(&self, scope: crate::CooldownBucket, now: Instant) -> Result<(), Duration> {
+        let mut all_uses = self.uses.lock();
+        let uses = all_uses.entry(scope).or_default();
+        if let Some(retry_after) = self.refresh(uses, now) {
+            return Err(retry_after);
+        }
+        uses.push_back(now);
+        Ok(())
+    }
+
+    /// How many more times the commands of this bucket may be invoked in the scope of the given
+    /// invocation right now
+    pub fn remaining_uses<U, E>(&self, ctx: crate::Context<'_, U, E>) -> u32 {
+        self.scope_remaining_uses(&self.scope_of(ctx), Instant::now())
+    }
+
+    /// Records a use in the scope of the given invocation, if there are uses left. Otherwise,
+    /// returns how long until the next use is allowed
+    pub fn try_use<U, E>(&self, ctx: crate::Context<'_, U, E>) -> Result<(), Duration> {
+        self.scope_try_use(self.scope_of(ctx), Instant::now())
+    }
+
+    /// If there are no uses left in the scope of the given invocation, returns how long until the
+    /// next use is allowed. Unlike [`Self::try_use`], doesn't record a use
+    pub fn retry_after<U, E>(&self, ctx: crate::Context<'_, U, E>) -> Option<Duration> {
+        self.scope_retry_after(&self.scope_of(ctx), Instant::now())
+    }
+
+    /// Records a use in the scope of the given invocation, even if there are no uses left
+    pub fn record_use<U, E>(&self, ctx: crate::Context<'_, U, E>) {
+        self.scope_record_use(self.scope_of(ctx), Instant::now());
+    }
+
+    /// Forgets the uses which are outside the window. The framework calls this periodically for
+    /// all registered buckets.
+    pub fn purge(&self) {
+        self.purge_at(Instant::now());
+    }
+
+    fn purge_at(&self, now: Instant) {
+        self.uses.lock().retain(|_, uses| {
+            self.refresh(uses, now);
+            !uses.is_empty()
+        });
+    }
+
+    /// Forgets all uses in the scope of the given invocation
+    pub fn reset<U, E>(&self, ctx: crate::Context<'_, U, E>) {
+        self.uses.lock().remove(&self.scope_of(ctx));
+    }
+
+    /// Forgets all uses in all scopes
+    pub fn reset_all(&self) {
+        self.uses.lock().clear();
+    }
+}
+
+/// [`RateLimitBucket::retry_after`] of the named bucket for the given invocation. Unknown buckets
+/// are rejected by [`crate::Framework::validate`] on startup and don't limit anything
+pub(crate) fn bucket_retry_after<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    bucket: &str,
+) -> Option<Duration> {
+    let bucket = ctx.framework().options().rate_limit_buckets.get(bucket)?;
+    bucket.retry_after(ctx)
+}
+
+/// [`RateLimitBucket::record_use`] of the named bucket for the given invocation
+pub(crate) fn record_bucket_use<U, E>(ctx: crate::Context<'_, U, E>, bucket: &str) {
+    if let Some(bucket) = ctx.framework().options().rate_limit_buckets.get(bucket) {
+        bucket.record_use(ctx);
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_rate_limit_bucket() {
+    let bucket = RateLimitBucket::new(RateLimit {
+        uses: 2,
+        window: Duration::from_secs(60),
+        scope: RateLimitScope::User,
+    });
+    let user = crate::CooldownBucket::User(crate::serenity_prelude::UserId(1));
+    let other_user = crate::CooldownBucket::User(crate::serenity_prelude::UserId(2));
+    let start = Instant::now();
+
+    assert_eq!(bucket.scope_remaining_uses(&user, start), 2);
+    assert_eq!(bucket.scope_try_use(user.clone(), start), Ok(()));
+    let later = start + Duration::from_secs(10);
+    assert_eq!(bucket.scope_try_use(user.clone(), later), Ok(()));
+    assert_eq!(bucket.scope_remaining_uses(&user, later), 0);
+    assert_eq!(
+        bucket.scope_try_use(user.clone(), later),
+        Err(Duration::from_secs(50))
+    );
+
+    // Other scopes have their own uses
+    assert_eq!(bucket.scope_try_use(other_user, later), Ok(()));
+
+    // The first use leaves the window
+    let much_later = start + Duration::from_secs(60);
+    assert_eq!(bucket.scope_remaining_uses(&user, much_later), 1);
+    assert_eq!(bucket.scope_try_use(user.clone(), much_later), Ok(()));
+
+    // Checking doesn't record a use, recording does regardless of the limit
+    assert_eq!(
+        bucket.scope_retry_after(&user, much_later),
+        Some(Duration::from_secs(10))
+    );
+    let third_user = crate::CooldownBucket::User(crate::serenity_prelude::UserId(3));
+    assert_eq!(bucket.scope_retry_after(&third_user, much_later), None);
+    bucket.scope_record_use(third_user.clone(), much_later);
+    bucket.scope_record_use(third_user.clone(), much_later);
+    assert_eq!(
+        bucket.scope_retry_after(&third_user, much_later),
+        Some(Duration::from_secs(60))
+    );
+
+    // Scopes without uses in the window are forgotten
+    bucket.purge_at(start + Duration::from_secs(75));
+    assert!(bucket.uses.lock().contains_key(&third_user));
+    assert!(!bucket.uses.lock().contains_key(&other_user));
+}
@@ -0,0 +1,57 @@
+//! Startup code run once the bot is ready, see [`ReadyHook`]
+
+use crate::serenity_prelude as serenity;
+use crate::BoxFuture;
+
+/// Passed to a [`ReadyHook`]
+pub struct ReadyContext<'a, U, E> {
+    /// Serenity's context of the first Ready event
+    pub discord: &'a serenity::Context,
+    /// The payload of the first Ready event
+    pub ready: &'a serenity::Ready,
+    /// The user data, see [`crate::FrameworkBuilder::user_data_setup`]
+    pub data: &'a U,
+    /// The framework which became ready
+    pub framework: &'a crate::Framework<U, E>,
+}
+
+/// Code which runs once per process after the first Ready event and the user data setup,
+/// registered with [`crate::FrameworkBuilder::on_ready`]. Unlike code in an event listener, it
+/// doesn't run again when shards reconnect or further shards become ready.
+///
+/// Hooks run one after another in the order they were registered. Errors are passed to
+/// [`crate::FrameworkOptions::on_error`] with [`crate::ErrorContext::Setup`], and the remaining
+/// hooks still run. Once all hooks finished, [`crate::Framework::wait_until_ready`] returns.
+pub struct ReadyHook<U, E> {
+    /// The hook itself
+    pub action: for<'a> fn(ReadyContext<'a, U, E>) -> BoxFuture<'a, Result<(), E>>,
+}
+
+/// Whether the framework is ready, see [`crate::Framework::wait_until_ready`]
+#[derive(Default)]
+pub(crate) struct Readiness {
+    ready: std::sync::atomic::AtomicBool,
+    notify: tokio::sync::Notify,
+}
+
+impl Readiness {
+    pub(crate) fn is_ready(&self) -> bool {
+        self.ready.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    pub(crate) fn set_ready(&self) {
+        self.ready.store(true, std::sync::atomic::Ordering::SeqCst);
+        self.notify.notify_waiters();
+    }
+
+    pub(crate) async fn wait(&self) {
+        loop {
+            // Created before checking, so that a notification in between isn't missed
+            let notified = self.notify.notified();
+            if self.is_ready() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
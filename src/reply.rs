@@ -13,6 +13,9 @@ pub struct CreateReply<'a> {
     pub ephemeral: bool,
     /// Message components, that is, buttons
     pub components: Option<serenity::CreateComponents>,
+    /// Whether to split content that exceeds Discord's message length limit into multiple
+    /// messages, see [`Self::chunk_content`]
+    pub chunk_content: bool,
 }
 
 impl<'a> CreateReply<'a> {
@@ -68,6 +71,19 @@ impl<'a> CreateReply<'a> {
         self.ephemeral = ephemeral;
         self
     }
+
+    /// Toggles whether content longer than Discord's limit of 2000 characters is split into
+    /// multiple messages instead of failing to send.
+    ///
+    /// The content is split at line breaks where possible, and code blocks that span multiple
+    /// messages are closed and reopened, so that each message renders correctly. Embed,
+    /// attachments and components are attached to the last message, which is the one returned by
+    /// [`send_reply`]. With edit tracking, all messages are edited in place when the invocation is
+    /// edited, and surplus messages are deleted if the content got shorter.
+    pub fn chunk_content(&mut self, chunk_content: bool) -> &mut Self {
+        self.chunk_content = chunk_content;
+        self
+    }
 }
 
 /// Returned from [`send_reply`] to retrieve the sent message object.
@@ -77,6 +93,10 @@ impl<'a> CreateReply<'a> {
 pub enum ReplyHandle<'a> {
     /// When sending a normal message, Discord returns the message object directly
     Prefix(serenity::Message),
+    /// Like [`Self::Prefix`], but the message was posted into a thread attached to the invocation
+    /// message, see [`crate::PrefixCommandOptions::respond_in_thread`]. The message's channel ID
+    /// is the thread ID
+    PrefixThread(serenity::Message),
     /// When sending an application command response, you need to request the message object
     /// seperately
     Application {
@@ -87,20 +107,133 @@ pub enum ReplyHandle<'a> {
         /// message object
         interaction: &'a serenity::ApplicationCommandInteraction,
     },
+    /// A followup message to an application command response, i.e. any reply after the initial
+    /// response
+    ApplicationFollowup {
+        /// Serenity HTTP instance that can be used to edit the followup
+        http: &'a serenity::Http,
+        /// Interaction which the followup belongs to
+        interaction: &'a serenity::ApplicationCommandInteraction,
+        /// The followup message
+        message: serenity::Message,
+    },
 }
 
 impl ReplyHandle<'_> {
+    /// The thread the reply was posted into, if any. See
+    /// [`crate::PrefixCommandOptions::respond_in_thread`]
+    pub fn thread_id(&self) -> Option<serenity::ChannelId> {
+        match self {
+            Self::PrefixThread(msg) => Some(msg.channel_id),
+            Self::Prefix(_) | Self::Application { .. } | Self::ApplicationFollowup { .. } => None,
+        }
+    }
+
     /// Retrieve the message object of the sent reply.
     ///
     /// Only needs to do an HTTP request in the application command response case
     pub async fn message(self) -> Result<serenity::Message, serenity::Error> {
         match self {
-            Self::Prefix(msg) => Ok(msg),
+            Self::Prefix(msg) | Self::PrefixThread(msg) => Ok(msg),
+            Self::ApplicationFollowup { message, .. } => Ok(message),
             Self::Application { http, interaction } => {
                 interaction.get_interaction_response(http).await
             }
         }
     }
+
+    /// Edits the sent reply. Only the content, embed and components set in the builder are changed,
+    /// the rest of the message stays as it is. To remove the components, set empty ones.
+    ///
+    /// Attachments and ephemerality can't be changed by editing and are ignored.
+    ///
+    /// ```rust,no_run
+    /// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let ctx: poise::Context<'_, (), ()> = todo!();
+    /// if let Some(mut reply) = ctx.say("Working...").await? {
+    ///     reply.edit(ctx, |f| f.content("Done!").components(|c| c)).await?;
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub async fn edit<U, E>(
+        &mut self,
+        ctx: crate::Context<'_, U, E>,
+        builder: impl for<'a, 'b> FnOnce(&'a mut CreateReply<'b>) -> &'a mut CreateReply<'b>,
+    ) -> Result<(), serenity::Error> {
+        let mut reply = CreateReply::default();
+        builder(&mut reply);
+        let CreateReply {
+            content,
+            embed,
+            components,
+            ..
+        } = reply;
+
+        match self {
+            Self::Prefix(msg) | Self::PrefixThread(msg) => {
+                msg.edit(ctx.discord(), |m| {
+                    if let Some(content) = content {
+                        m.content(content);
+                    }
+                    if let Some(embed) = embed {
+                        m.set_embed(embed);
+                    }
+                    if let Some(components) = components {
+                        m.components(|c| {
+                            *c = components;
+                            c
+                        });
+                    }
+                    m
+                })
+                .await
+            }
+            Self::Application { http, interaction } => {
+                interaction
+                    .edit_original_interaction_response(*http, |r| {
+                        if let Some(content) = content {
+                            r.content(content);
+                        }
+                        if let Some(embed) = embed {
+                            r.add_embed(embed);
+                        }
+                        if let Some(components) = components {
+                            r.components(|c| {
+                                *c = components;
+                                c
+                            });
+                        }
+                        r
+                    })
+                    .await?;
+                Ok(())
+            }
+            Self::ApplicationFollowup {
+                http,
+                interaction,
+                message,
+            } => {
+                *message = interaction
+                    .edit_followup_message(*http, message.id, |f| {
+                        if let Some(content) = content {
+                            f.content(content);
+                        }
+                        if let Some(embed) = embed {
+                            f.add_embed(embed);
+                        }
+                        if let Some(components) = components {
+                            f.components(|c| {
+                                *c = components;
+                                c
+                            });
+                        }
+                        f
+                    })
+                    .await?;
+                Ok(())
+            }
+        }
+    }
 }
 
 /// Send a message in the given context: normal message if prefix command, interaction response
@@ -125,20 +258,71 @@ pub async fn send_reply<U, E>(
     ctx: crate::Context<'_, U, E>,
     builder: impl for<'a, 'b> FnOnce(&'a mut CreateReply<'b>) -> &'a mut CreateReply<'b>,
 ) -> Result<Option<ReplyHandle<'_>>, serenity::Error> {
+    let mut reply = CreateReply {
+        ephemeral: match ctx {
+            crate::Context::Application(ctx) => ctx.command.options().ephemeral,
+            crate::Context::Prefix(_) => false,
+        },
+        ..Default::default()
+    };
+    builder(&mut reply);
+
+    // All but the last chunk, which is sent with the rest of the reply
+    let mut leading_chunks = Vec::new();
+    if reply.chunk_content {
+        if let Some(content) = reply.content.take() {
+            leading_chunks = split_message(&content, MAX_CONTENT_LENGTH);
+            reply.content = leading_chunks.pop();
+        }
+    }
+
+    match ctx {
+        // Also called without chunks, to clean up the chunks of an earlier run of the invocation
+        crate::Context::Prefix(ctx) => crate::send_prefix_reply_chunks(ctx, leading_chunks).await?,
+        crate::Context::Application(ctx) => {
+            for chunk in leading_chunks {
+                let chunk = CreateReply {
+                    content: Some(chunk),
+                    ephemeral: reply.ephemeral,
+                    ..Default::default()
+                };
+                crate::send_built_application_reply(ctx, chunk).await?;
+            }
+        }
+    }
+
+    send_built_reply(ctx, reply).await
+}
+
+/// Like [`send_reply`], but with an already built reply
+async fn send_built_reply<'a, U, E>(
+    ctx: crate::Context<'a, U, E>,
+    reply: CreateReply<'_>,
+) -> Result<Option<ReplyHandle<'a>>, serenity::Error> {
     Ok(match ctx {
-        crate::Context::Prefix(ctx) => Some(ReplyHandle::Prefix(
-            crate::send_prefix_reply(ctx, builder).await?,
-        )),
+        crate::Context::Prefix(ctx) => {
+            let msg = crate::send_built_prefix_reply(ctx, reply).await?;
+            Some(if msg.channel_id == ctx.msg.channel_id {
+                ReplyHandle::Prefix(msg)
+            } else {
+                ReplyHandle::PrefixThread(msg)
+            })
+        }
         crate::Context::Application(ctx) => {
-            crate::send_application_reply(ctx, builder).await?;
+            let followup = crate::send_built_application_reply(ctx, reply).await?;
 
             if let crate::ApplicationCommandOrAutocompleteInteraction::ApplicationCommand(
                 interaction,
             ) = &ctx.interaction
             {
-                Some(ReplyHandle::Application {
-                    interaction,
-                    http: &ctx.discord.http,
+                let http = &ctx.discord.http;
+                Some(match followup {
+                    Some(message) => ReplyHandle::ApplicationFollowup {
+                        http,
+                        interaction,
+                        message,
+                    },
+                    None => ReplyHandle::Application { http, interaction },
                 })
             } else {
                 None
@@ -147,6 +331,91 @@ pub async fn send_reply<U, E>(
     })
 }
 
+/// Discord's length limit of message content, in characters
+const MAX_CONTENT_LENGTH: usize = 2000;
+
+/// Splits the line into pieces of at most `max_len` characters, preferably after whitespace
+fn split_long_line(mut line: &str, max_len: usize) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    while let Some((limit, _)) = line.char_indices().nth(max_len) {
+        let end = match line[..limit].rfind(char::is_whitespace) {
+            Some(whitespace) if whitespace > 0 => {
+                whitespace + line[whitespace..].chars().next().map_or(1, char::len_utf8)
+            }
+            _ => limit,
+        };
+        pieces.push(&line[..end]);
+        line = &line[end..];
+    }
+    pieces.push(line);
+    pieces
+}
+
+/// Splits the text into chunks of at most `max_len` characters, at line breaks where possible.
+/// Code blocks spanning multiple chunks are closed at the end of a chunk and reopened at the start
+/// of the next, with the same language.
+fn split_message(text: &str, max_len: usize) -> Vec<String> {
+    /// Length of the code block closing appended to chunks ending inside a code block
+    const CLOSING_LEN: usize = "\n```".len();
+
+    let mut chunks = Vec::new();
+    let mut chunk = String::new();
+    // The line which opened the current code block, if inside one
+    let mut open_code_block: Option<String> = None;
+
+    let close_chunk = |chunk: &mut String, open_code_block: &Option<String>| {
+        if open_code_block.is_some() {
+            if !chunk.ends_with('\n') {
+                chunk.push('\n');
+            }
+            chunk.push_str("```");
+        }
+        std::mem::take(chunk)
+    };
+
+    for line in text.split_inclusive('\n') {
+        // Leave room for reopening and closing the code block around each piece
+        let reopen_len = open_code_block
+            .as_ref()
+            .map_or(0, |opening| opening.chars().count() + 1);
+        let max_piece_len = max_len.saturating_sub(reopen_len + CLOSING_LEN).max(1);
+
+        for piece in split_long_line(line, max_piece_len) {
+            let closing_len = if open_code_block.is_some() {
+                CLOSING_LEN
+            } else {
+                0
+            };
+            if !chunk.is_empty()
+                && chunk.chars().count() + piece.chars().count() + closing_len > max_len
+            {
+                chunks.push(close_chunk(&mut chunk, &open_code_block));
+                if let Some(opening) = &open_code_block {
+                    chunk.push_str(opening);
+                    chunk.push('\n');
+                }
+            }
+            chunk.push_str(piece);
+        }
+
+        if line.matches("```").count() % 2 == 1 {
+            open_code_block = match open_code_block {
+                Some(_) => None,
+                None => Some(match line.split_whitespace().next() {
+                    Some(word) if word.starts_with("```") => word.to_owned(),
+                    _ => "```".to_owned(),
+                }),
+            };
+        }
+    }
+    if !chunk.is_empty() {
+        chunks.push(chunk);
+    }
+
+    chunks.retain(|chunk| !chunk.trim().is_empty());
+    chunks
+}
+
 /// Shorthand of [`send_reply`] for text-only messages
 pub async fn say_reply<U, E>(
     ctx: crate::Context<'_, U, E>,
@@ -154,3 +423,33 @@ pub async fn say_reply<U, E>(
 ) -> Result<Option<ReplyHandle<'_>>, serenity::Error> {
     send_reply(ctx, |m| m.content(text.into())).await
 }
+
+#[cfg(test)]
+#[test]
+fn test_split_message() {
+    assert_eq!(split_message("short", 2000), ["short"]);
+
+    let text = "line\n".repeat(10);
+    let chunks = split_message(&text, 12);
+    assert!(chunks.iter().all(|chunk| chunk.chars().count() <= 12));
+    assert_eq!(chunks.concat(), text);
+
+    let long_line = "word ".repeat(10);
+    let chunks = split_message(&long_line, 12);
+    assert!(chunks.iter().all(|chunk| chunk.chars().count() <= 12));
+    assert_eq!(chunks.concat(), long_line);
+
+    let code = format!("intro\n```rust\n{}```\noutro", "let x = 1;\n".repeat(5));
+    let chunks = split_message(&code, 40);
+    assert!(chunks.len() > 1);
+    for chunk in &chunks {
+        assert!(chunk.chars().count() <= 40);
+        assert_eq!(
+            chunk.matches("```").count() % 2,
+            0,
+            "unbalanced chunk {:?}",
+            chunk
+        );
+    }
+    assert!(chunks[1].starts_with("```rust\n"));
+}
@@ -0,0 +1,138 @@
+//! Runtime support for the `required_roles(...)` command attribute, see [`RequiredRole`]
+
+use crate::serenity_prelude as serenity;
+
+/// A role which users must have to invoke a command, configured with the
+/// `required_roles("Moderator", 123456789)` command attribute
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RequiredRole {
+    /// Any role of this name in the guild. Compared case-insensitively
+    Name(&'static str),
+    /// The role with this ID
+    Id(serenity::RoleId),
+}
+
+impl std::fmt::Display for RequiredRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Name(name) => f.write_str(name),
+            Self::Id(role_id) => write!(f, "<@&{}>", role_id.0),
+        }
+    }
+}
+
+/// Passed to [`crate::FrameworkOptions::on_missing_roles`] when a command is invoked by a user who
+/// lacks some of the command's required roles
+pub struct MissingRoles<'a, U, E> {
+    /// Context of the denied invocation
+    pub ctx: crate::Context<'a, U, E>,
+    /// The required roles which the user doesn't have, in the order they were declared
+    pub missing: Vec<RequiredRole>,
+}
+
+/// The required roles of the given command
+fn required_roles<U, E>(command: crate::CommandRef<'_, U, E>) -> &'static [RequiredRole] {
+    match command {
+        crate::CommandRef::Prefix(command) => command.options.required_roles,
+        crate::CommandRef::Application(command) => command.options().required_roles,
+    }
+}
+
+/// The role IDs of the invoking member. Taken from the invocation if possible, and otherwise from
+/// the cache or via HTTP
+async fn member_roles<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    guild_id: serenity::GuildId,
+) -> Option<Vec<serenity::RoleId>> {
+    let invocation_roles = match ctx {
+        crate::Context::Application(ctx) => ctx.interaction.member().map(|m| m.roles.clone()),
+        crate::Context::Prefix(ctx) => ctx.msg.member.as_ref().map(|m| m.roles.clone()),
+    };
+    if invocation_roles.is_some() {
+        return invocation_roles;
+    }
+
+    let user_id = ctx.author().id;
+    if let Some(member) = ctx.discord().cache.member(guild_id, user_id) {
+        return Some(member.roles);
+    }
+    let member = ctx.discord().http.get_member(guild_id.0, user_id.0).await;
+    member.ok().map(|member| member.roles)
+}
+
+/// The names of the given roles of the guild, from the cache or via HTTP
+async fn role_names<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    guild_id: serenity::GuildId,
+    role_ids: &[serenity::RoleId],
+) -> Vec<String> {
+    let roles = match ctx.discord().cache.guild_roles(guild_id) {
+        Some(roles) => roles.into_iter().map(|(_, role)| role).collect(),
+        None => ctx
+            .discord()
+            .http
+            .get_guild_roles(guild_id.0)
+            .await
+            .unwrap_or_default(),
+    };
+    roles
+        .into_iter()
+        .filter(|role| role_ids.contains(&role.id))
+        .map(|role| role.name)
+        .collect()
+}
+
+/// The required roles of the command which the invoking user doesn't have. Outside of guilds, all
+/// required roles are missing
+pub(crate) async fn missing_roles<U, E>(
+    ctx: crate::Context<'_, U, E>,
+    required: &'static [RequiredRole],
+) -> Vec<RequiredRole> {
+    if required.is_empty() {
+        return Vec::new();
+    }
+    let guild_id = match ctx.guild_id() {
+        Some(x) => x,
+        None => return required.to_vec(),
+    };
+    let member_roles = match member_roles(ctx, guild_id).await {
+        Some(x) => x,
+        None => return required.to_vec(),
+    };
+
+    let mut member_role_names = None;
+    let mut missing = Vec::new();
+    for &role in required {
+        let has_role = match role {
+            RequiredRole::Id(role_id) => member_roles.contains(&role_id),
+            RequiredRole::Name(name) => {
+                // Only look up role names if needed
+                if member_role_names.is_none() {
+                    member_role_names = Some(role_names(ctx, guild_id, &member_roles).await);
+                }
+                member_role_names
+                    .iter()
+                    .flatten()
+                    .any(|member_role| member_role.eq_ignore_ascii_case(name))
+            }
+        };
+        if !has_role {
+            missing.push(role);
+        }
+    }
+    missing
+}
+
+/// Whether the invoking user has all required roles of the invoked command. If not, calls
+/// [`crate::FrameworkOptions::on_missing_roles`]
+pub(crate) async fn check_required_roles<'a, U, E>(
+    ctx: crate::Context<'a, U, E>,
+    command: crate::CommandRef<'a, U, E>,
+) -> bool {
+    let missing = missing_roles(ctx, required_roles(command)).await;
+    if missing.is_empty() {
+        return true;
+    }
+    (ctx.framework().options().on_missing_roles)(MissingRoles { ctx, missing }).await;
+    false
+}
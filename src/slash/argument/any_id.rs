@@ -0,0 +1,228 @@
+//! Generic snowflake ID parameter type
+
+use crate::serenity_prelude as serenity;
+
+/// The kinds of Discord entities an [`AnyId`] may refer to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IdKind {
+    /// A user or member, mentioned as `<@id>` or `<@!id>`
+    User,
+    /// A role, mentioned as `<@&id>`
+    Role,
+    /// A channel, mentioned as `<#id>`
+    Channel,
+    /// A message in the channel the command was invoked in
+    Message,
+}
+
+impl std::fmt::Display for IdKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::User => "user",
+            Self::Role => "role",
+            Self::Channel => "channel",
+            Self::Message => "message",
+        })
+    }
+}
+
+/// Error returned when an [`AnyId`] argument is neither a snowflake ID nor a mention
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnyIdParseError {
+    /// The input which failed to parse
+    pub input: String,
+}
+impl std::fmt::Display for AnyIdParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "\"{}\" is not an ID or mention", self.input)
+    }
+}
+impl std::error::Error for AnyIdParseError {}
+
+/// A command parameter type for raw snowflake IDs and mentions of users, roles and channels
+///
+/// ```rust
+/// # use poise::{AnyId, IdKind};
+/// assert_eq!("<@&80351110224678912>".parse(), Ok(AnyId { id: 80351110224678912, kind: Some(IdKind::Role) }));
+/// assert_eq!("80351110224678912".parse(), Ok(AnyId { id: 80351110224678912, kind: None }));
+/// ```
+///
+/// To restrict which kinds of IDs are accepted, use the `#[id_kind("user", "role")]` parameter
+/// attribute. It checks that an entity of one of the given kinds exists under the ID, and fills
+/// in [`Self::kind`] accordingly. Otherwise, an [`UnexpectedIdKind`] error is returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AnyId {
+    /// The snowflake ID
+    pub id: u64,
+    /// The kind of entity this ID refers to, if known from the mention syntax or from the
+    /// `#[id_kind]` attribute
+    pub kind: Option<IdKind>,
+}
+
+impl std::str::FromStr for AnyId {
+    type Err = AnyIdParseError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let trimmed_input = input.trim();
+        let (kind, id) = match trimmed_input
+            .strip_prefix('<')
+            .and_then(|x| x.strip_suffix('>'))
+        {
+            Some(mention) => {
+                if let Some(id) = mention.strip_prefix("@&") {
+                    (Some(IdKind::Role), id)
+                } else if let Some(id) = mention.strip_prefix("@!") {
+                    (Some(IdKind::User), id)
+                } else if let Some(id) = mention.strip_prefix('@') {
+                    (Some(IdKind::User), id)
+                } else if let Some(id) = mention.strip_prefix('#') {
+                    (Some(IdKind::Channel), id)
+                } else {
+                    (None, "")
+                }
+            }
+            None => (None, trimmed_input),
+        };
+
+        match id.parse() {
+            Ok(id) => Ok(Self { id, kind }),
+            Err(_) => Err(AnyIdParseError {
+                input: input.to_owned(),
+            }),
+        }
+    }
+}
+
+impl std::fmt::Display for AnyId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            Some(IdKind::User) => write!(f, "<@{}>", self.id),
+            Some(IdKind::Role) => write!(f, "<@&{}>", self.id),
+            Some(IdKind::Channel) => write!(f, "<#{}>", self.id),
+            Some(IdKind::Message) | None => write!(f, "{}", self.id),
+        }
+    }
+}
+
+/// Returned when an [`AnyId`] argument doesn't refer to any of the kinds of entities allowed by
+/// the parameter's `#[id_kind(...)]` attribute.
+///
+/// Like argument parse errors, this error is converted into the user error type via `?`, so
+/// commands using the attribute require `E: From<UnexpectedIdKind>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnexpectedIdKind {
+    /// Name of the parameter whose argument was rejected
+    pub parameter_name: &'static str,
+    /// The rejected ID
+    pub id: AnyId,
+    /// The kinds of entities which would have been accepted
+    pub expected: Vec<IdKind>,
+}
+
+impl std::fmt::Display for UnexpectedIdKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Invalid value for `{}`: {} is not the ID of a",
+            self.parameter_name, self.id
+        )?;
+        for (i, kind) in self.expected.iter().enumerate() {
+            match i {
+                0 => write!(f, " {}", kind)?,
+                i if i == self.expected.len() - 1 => write!(f, " or {}", kind)?,
+                _ => write!(f, ", {}", kind)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for UnexpectedIdKind {}
+
+/// Whether an entity of the given kind exists under the ID, using the cache if possible. Roles
+/// are looked up in the given guild and messages in the given channel
+async fn id_exists(
+    ctx: &serenity::Context,
+    guild_id: Option<serenity::GuildId>,
+    channel_id: serenity::ChannelId,
+    kind: IdKind,
+    id: u64,
+) -> bool {
+    match kind {
+        IdKind::User => serenity::UserId(id).to_user(ctx).await.is_ok(),
+        IdKind::Channel => serenity::ChannelId(id).to_channel(ctx).await.is_ok(),
+        IdKind::Message => channel_id.message(ctx, id).await.is_ok(),
+        IdKind::Role => {
+            let guild_id = match guild_id {
+                Some(x) => x,
+                None => return false,
+            };
+            let role_id = serenity::RoleId(id);
+            match ctx.cache.guild(guild_id) {
+                Some(guild) => guild.roles.contains_key(&role_id),
+                None => guild_id
+                    .roles(&ctx.http)
+                    .await
+                    .map_or(false, |roles| roles.contains_key(&role_id)),
+            }
+        }
+    }
+}
+
+/// Runtime support for the `#[id_kind(...)]` parameter attribute. Finds the first of the given
+/// kinds for which an entity exists under the ID, and sets [`AnyId::kind`] to it. If the ID was
+/// given as a mention, only the mentioned kind is checked.
+#[doc(hidden)]
+pub async fn _resolve_id_kind(
+    ctx: &serenity::Context,
+    guild_id: Option<serenity::GuildId>,
+    channel_id: serenity::ChannelId,
+    parameter_name: &'static str,
+    mut id: AnyId,
+    expected: &[IdKind],
+) -> Result<AnyId, UnexpectedIdKind> {
+    for &kind in expected {
+        if id.kind.map_or(true, |x| x == kind)
+            && id_exists(ctx, guild_id, channel_id, kind, id.id).await
+        {
+            id.kind = Some(kind);
+            return Ok(id);
+        }
+    }
+    Err(UnexpectedIdKind {
+        parameter_name,
+        id,
+        expected: expected.to_vec(),
+    })
+}
+
+#[cfg(test)]
+#[test]
+fn test_parse_any_id() {
+    for &(input, id, kind) in &[
+        ("123", 123, None),
+        (" 123 ", 123, None),
+        ("<@123>", 123, Some(IdKind::User)),
+        ("<@!123>", 123, Some(IdKind::User)),
+        ("<@&123>", 123, Some(IdKind::Role)),
+        ("<#123>", 123, Some(IdKind::Channel)),
+    ] {
+        assert_eq!(input.parse(), Ok(AnyId { id, kind }));
+    }
+    for &input in &["", "abc", "<123>", "<@abc>", "<:emoji:123>", "-5"] {
+        assert!(input.parse::<AnyId>().is_err());
+    }
+
+    let error = UnexpectedIdKind {
+        parameter_name: "target",
+        id: AnyId {
+            id: 123,
+            kind: None,
+        },
+        expected: vec![IdKind::User, IdKind::Role, IdKind::Channel],
+    };
+    assert_eq!(
+        error.to_string(),
+        "Invalid value for `target`: 123 is not the ID of a user, role or channel"
+    );
+}
@@ -0,0 +1,56 @@
+//! Slash command support for `Vec<serenity::Attachment>` parameters
+
+use crate::{serenity_prelude as serenity, SlashArgError};
+
+/// Discord's option type for attachments. Set via the raw builder map, since the option type enum
+/// has no variant for it
+const ATTACHMENT_OPTION_TYPE: u64 = 11;
+
+/// Registers an attachment option. Used for the numbered options of `Vec<serenity::Attachment>`
+/// parameters
+#[doc(hidden)]
+pub fn _create_attachment_option(
+    builder: &mut serenity::CreateApplicationCommandOption,
+) -> &mut serenity::CreateApplicationCommandOption {
+    builder
+        .0
+        .insert("type", serde_json::Value::from(ATTACHMENT_OPTION_TYPE));
+    builder
+}
+
+/// Extracts the attachments of a `Vec<serenity::Attachment>` parameter which was expanded into the
+/// numbered attachment options `{name}1` to `{name}{count}`. Attachment options only carry the
+/// attachment ID, the attachment itself is looked up in the resolved data of the interaction.
+#[doc(hidden)]
+pub fn _extract_attachments(
+    interaction: &serenity::ApplicationCommandInteractionData,
+    args: &[serenity::ApplicationCommandInteractionDataOption],
+    name: &str,
+    count: usize,
+) -> Result<Vec<serenity::Attachment>, SlashArgError> {
+    let mut attachments = Vec::new();
+    for i in 1..=count {
+        let option_name = format!("{}{}", name, i);
+        let arg = match args.iter().find(|arg| arg.name == option_name) {
+            Some(x) => x,
+            None => continue,
+        };
+        let attachment_id = arg
+            .value
+            .as_ref()
+            .and_then(|value| value.as_str())
+            .and_then(|value| value.parse().ok())
+            .ok_or(SlashArgError::CommandStructureMismatch(
+                "expected attachment ID",
+            ))?;
+        let attachment = interaction
+            .resolved
+            .attachments
+            .get(&serenity::AttachmentId(attachment_id))
+            .ok_or(SlashArgError::CommandStructureMismatch(
+                "attachment missing from resolved data",
+            ))?;
+        attachments.push(attachment.clone());
+    }
+    Ok(attachments)
+}
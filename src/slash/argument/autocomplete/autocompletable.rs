@@ -4,8 +4,20 @@ use std::marker::PhantomData;
 
 /// Types that can be marked autocompletable in a slash command parameter.
 ///
-/// Includes almost all types that can be used as a slash command parameter in general,
-/// except some built-in model types (User, Member, Role...)
+/// Includes almost all types that can be used as a slash command parameter in general.
+///
+/// Discord doesn't support autocomplete on user, role or channel options, so autocompleted
+/// parameters of the corresponding model types ([`serenity::User`], [`serenity::Member`],
+/// [`serenity::Role`], [`serenity::Channel`], [`serenity::GuildChannel`]) are registered as
+/// string options instead. The chosen value is submitted as an ID and resolved into the model type
+/// when the command is invoked. ID types like [`serenity::UserId`] are transmitted as strings
+/// anyways and can be autocompleted too.
+///
+/// [`std::time::Duration`] parameters receive a [`crate::PartialDuration`], which tolerates
+/// incomplete input like `2h3` and can suggest possible interpretations of it.
+///
+/// Autocomplete functions can return either values of the parameter type directly, or wrap them in
+/// [`crate::AutocompleteChoice`] to display a custom label in place of the value.
 pub trait Autocompletable {
     /// Type of the partial input. This should be `Self` except in cases where a partial input
     /// cannot be parsed into `Self` (e.g. an IP address)
@@ -29,6 +41,15 @@ pub trait AutocompletableHack<T> {
     fn extract_partial(self, value: &serde_json::Value) -> Result<Self::Partial, SlashArgError>;
 
     fn into_json(self, value: T) -> serde_json::Value;
+
+    /// Whether this type needs to be registered as a string option when autocompleted, because
+    /// Discord doesn't support autocomplete on its native option type
+    fn autocomplete_as_string(self) -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
 }
 
 /// Handles arbitrary types that can be parsed from string.
@@ -60,8 +81,10 @@ impl<T: TryFrom<i64> + Into<serde_json::Number> + Send + Sync> AutocompletableHa
     type Partial = T;
 
     fn extract_partial(self, value: &serde_json::Value) -> Result<T, SlashArgError> {
+        // The focused option may be transmitted as a string while the user is still typing
         value
             .as_i64()
+            .or_else(|| value.as_str()?.trim().parse().ok())
             .ok_or(SlashArgError::CommandStructureMismatch("expected integer"))?
             .try_into()
             .map_err(|_| SlashArgError::IntegerOutOfBounds)
@@ -77,9 +100,7 @@ impl AutocompletableHack<f32> for &&PhantomData<f32> {
     type Partial = f32;
 
     fn extract_partial(self, value: &serde_json::Value) -> Result<f32, SlashArgError> {
-        Ok(value
-            .as_f64()
-            .ok_or(SlashArgError::CommandStructureMismatch("expected float"))? as f32)
+        Ok((&&PhantomData::<f64>).extract_partial(value)? as f32)
     }
 
     fn into_json(self, value: f32) -> serde_json::Value {
@@ -94,8 +115,10 @@ impl AutocompletableHack<f64> for &&PhantomData<f64> {
     type Partial = f64;
 
     fn extract_partial(self, value: &serde_json::Value) -> Result<f64, SlashArgError> {
+        // The focused option may be transmitted as a string while the user is still typing
         value
             .as_f64()
+            .or_else(|| value.as_str()?.trim().parse().ok())
             .ok_or(SlashArgError::CommandStructureMismatch("expected float"))
     }
 
@@ -118,3 +141,33 @@ impl<T: Autocompletable> AutocompletableHack<T> for &&PhantomData<T> {
         value.into_json()
     }
 }
+
+// Implement autocomplete for a model type that is represented in interactions via an ID. The
+// choices are serialized as IDs and parsed back via serenity::ArgumentConvert on invocation
+macro_rules! impl_autocompletable_model {
+    ($type:ty, |$value:ident| $id:expr) => {
+        impl AutocompletableHack<$type> for &&PhantomData<$type> {
+            type Partial = String;
+
+            fn extract_partial(self, value: &serde_json::Value) -> Result<String, SlashArgError> {
+                let string = value
+                    .as_str()
+                    .ok_or(SlashArgError::CommandStructureMismatch("expected string"))?;
+                Ok(string.to_owned())
+            }
+
+            fn into_json(self, $value: $type) -> serde_json::Value {
+                serde_json::Value::String($id.to_string())
+            }
+
+            fn autocomplete_as_string(self) -> bool {
+                true
+            }
+        }
+    };
+}
+impl_autocompletable_model!(serenity::User, |user| user.id);
+impl_autocompletable_model!(serenity::Member, |member| member.user.id);
+impl_autocompletable_model!(serenity::Role, |role| role.id);
+impl_autocompletable_model!(serenity::Channel, |channel| channel.id());
+impl_autocompletable_model!(serenity::GuildChannel, |channel| channel.id);
@@ -0,0 +1,84 @@
+//! In-memory cache for autocomplete responses
+
+use crate::serenity_prelude as serenity;
+
+/// Caches the choices returned from an autocomplete callback, keyed by guild, command path, focused
+/// option and the values of all options filled in so far, including the partial input.
+///
+/// Enable on a slash command parameter with
+/// `#[autocomplete_cache(ttl = 60, max_entries = 500)]` (TTL in seconds). Repeated partial inputs
+/// within the TTL are then answered from memory instead of invoking the autocomplete callback.
+pub struct AutocompleteCache {
+    ttl: std::time::Duration,
+    max_entries: usize,
+    entries: parking_lot::Mutex<
+        std::collections::HashMap<
+            (Option<serenity::GuildId>, String),
+            (std::time::Instant, serde_json::Value),
+        >,
+    >,
+}
+
+impl AutocompleteCache {
+    /// Create a cache which keeps entries for the given duration and stores at most `max_entries`
+    /// entries at once
+    pub fn new(ttl: std::time::Duration, max_entries: usize) -> Self {
+        Self {
+            ttl,
+            max_entries,
+            entries: parking_lot::Mutex::new(std::collections::HashMap::new()),
+        }
+    }
+
+    /// Retrieve the cached choices for the given guild and serialized invocation state, if
+    /// present and not yet expired
+    pub fn get(&self, guild_id: Option<serenity::GuildId>, key: &str) -> Option<serde_json::Value> {
+        let mut entries = self.entries.lock();
+        let key = (guild_id, key.to_owned());
+
+        let (inserted_at, choices) = entries.get(&key)?;
+        if inserted_at.elapsed() < self.ttl {
+            Some(choices.clone())
+        } else {
+            entries.remove(&key);
+            None
+        }
+    }
+
+    /// Store the choices for the given guild and serialized invocation state.
+    ///
+    /// If the cache is full, expired entries are dropped first, then the oldest entries.
+    pub fn insert(
+        &self,
+        guild_id: Option<serenity::GuildId>,
+        key: String,
+        choices: serde_json::Value,
+    ) {
+        if self.max_entries == 0 {
+            return;
+        }
+
+        let mut entries = self.entries.lock();
+        if entries.len() >= self.max_entries {
+            let ttl = self.ttl;
+            entries.retain(|_, (inserted_at, _)| inserted_at.elapsed() < ttl);
+        }
+        while entries.len() >= self.max_entries {
+            let oldest_key = match entries
+                .iter()
+                .min_by_key(|(_, (inserted_at, _))| *inserted_at)
+            {
+                Some((key, _)) => key.clone(),
+                None => break,
+            };
+            entries.remove(&oldest_key);
+        }
+
+        entries.insert((guild_id, key), (std::time::Instant::now(), choices));
+    }
+
+    /// Forget all cached choices, e.g. because the underlying data source changed
+    pub fn clear(&self) {
+        self.entries.lock().clear();
+    }
+}
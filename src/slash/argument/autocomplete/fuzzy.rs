@@ -0,0 +1,155 @@
+//! Simple fuzzy filtering and ranking for autocomplete callbacks
+
+/// Rate how well `partial` matches `candidate`, case-insensitively. Returns None if it doesn't
+/// match at all. Higher is better.
+///
+/// Exact matches beat prefix matches, which beat substring matches, which beat fuzzy matches
+/// (i.e. all characters of `partial` occur in `candidate` in order). Within each tier, shorter
+/// candidates and matches nearer to the start rank higher.
+pub fn fuzzy_score(partial: &str, candidate: &str) -> Option<u32> {
+    let partial = partial.to_lowercase();
+    let candidate = candidate.to_lowercase();
+
+    // Penalty within a tier is capped so that tiers never overlap
+    let penalty = |x: usize| std::cmp::min(x, 999) as u32;
+
+    if partial.is_empty() {
+        return Some(0);
+    }
+    if candidate == partial {
+        return Some(4000);
+    }
+    if candidate.starts_with(&partial) {
+        let num_extra_chars = candidate.chars().count() - partial.chars().count();
+        return Some(3999 - penalty(num_extra_chars));
+    }
+    if let Some(byte_pos) = candidate.find(&partial) {
+        let char_pos = candidate[..byte_pos].chars().count();
+        return Some(2999 - penalty(char_pos));
+    }
+
+    // Check if partial is a subsequence of candidate and count the skipped characters in between
+    let mut num_skipped = 0;
+    let mut candidate_chars = candidate.chars();
+    for partial_char in partial.chars() {
+        loop {
+            match candidate_chars.next() {
+                Some(c) if c == partial_char => break,
+                Some(_) => num_skipped += 1,
+                None => return None,
+            }
+        }
+    }
+    Some(1999 - penalty(num_skipped))
+}
+
+/// Filter and sort a list of autocomplete candidates by how well they match the user's partial
+/// input, using [`fuzzy_score`]. At most 25 candidates, the maximum Discord allows, are returned.
+///
+/// The partial input can be anything that implements [`std::fmt::Display`], so the
+/// [`crate::Autocompletable::Partial`] value of most parameter types can be passed in directly.
+/// Candidates which match equally well keep their original order.
+///
+/// ```rust
+/// # type Context<'a> = poise::Context<'a, (), ()>;
+/// async fn autocomplete_fruit(_ctx: Context<'_>, partial: String) -> Vec<&'static str> {
+///     let fruits = ["Apple", "Banana", "Pineapple", "Papaya"];
+///     poise::fuzzy_filter(partial, fruits.iter().copied())
+/// }
+/// ```
+pub fn fuzzy_filter<T: AsRef<str>>(
+    partial: impl std::fmt::Display,
+    candidates: impl IntoIterator<Item = T>,
+) -> Vec<T> {
+    rank_choices(partial, candidates, |candidate| candidate.as_ref(), |_| 0)
+}
+
+/// Like [`fuzzy_filter`], but for arbitrary candidate types and with a caller-supplied boost.
+///
+/// `name` returns the text the partial input is matched against, e.g. the name of an
+/// [`crate::AutocompleteChoice`]. `boost` returns a bonus score for a candidate, e.g. based on how
+/// recently the user picked it. The boost only reorders candidates _within_ a tier from
+/// [`fuzzy_score`]: a boosted substring match never outranks a prefix match. Boosts up to 999 are
+/// meaningful; a boost of 999 places a candidate at the top of its tier.
+///
+/// Returns the matching candidates in order, at most 25.
+///
+/// ```rust
+/// # type Context<'a> = poise::Context<'a, (), ()>;
+/// async fn autocomplete_tag(_ctx: Context<'_>, partial: String) -> Vec<poise::AutocompleteChoice<u32>> {
+///     let tags = vec![
+///         poise::AutocompleteChoice::new("rules", 1),
+///         poise::AutocompleteChoice::new("roles", 2),
+///     ];
+///     let recently_used = [2];
+///
+///     poise::rank_choices(
+///         partial,
+///         tags,
+///         |tag| tag.name.as_str(),
+///         |tag| if recently_used.contains(&tag.value) { 500 } else { 0 },
+///     )
+/// }
+/// ```
+pub fn rank_choices<T>(
+    partial: impl std::fmt::Display,
+    candidates: impl IntoIterator<Item = T>,
+    name: impl Fn(&T) -> &str,
+    boost: impl Fn(&T) -> u32,
+) -> Vec<T> {
+    let partial = partial.to_string();
+
+    let mut scored_candidates = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let score = fuzzy_score(&partial, name(&candidate))?;
+            // Split into tier and score within tier so that the boost can't cross tiers
+            let tier = score / 1000;
+            let score_within_tier = score % 1000 + boost(&candidate);
+            Some(((tier, score_within_tier), candidate))
+        })
+        .collect::<Vec<_>>();
+    // Stable sort, so equally scored candidates stay in order
+    scored_candidates.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+
+    scored_candidates
+        .into_iter()
+        .take(25)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+#[cfg(test)]
+#[test]
+fn test_fuzzy_filter() {
+    let candidates = ["ban", "unban", "banner", "about", "Ban", "bandana", "nab"];
+
+    assert_eq!(
+        fuzzy_filter("ban", &candidates),
+        [&"ban", &"Ban", &"banner", &"bandana", &"unban"],
+    );
+    assert_eq!(fuzzy_filter("bt", &candidates), [&"about"]);
+    assert_eq!(fuzzy_filter("", &candidates).len(), candidates.len());
+    assert_eq!(
+        fuzzy_filter(12, vec!["123", "312", "1x2", "21"]),
+        ["123", "312", "1x2"]
+    );
+
+    assert_eq!(fuzzy_filter("a", (0..100).map(|_| "a")).len(), 25);
+}
+
+#[cfg(test)]
+#[test]
+fn test_rank_choices() {
+    let candidates = ["unban", "banner", "ban", "bandana"];
+    let boost = |candidate: &&str| match *candidate {
+        "bandana" => 999,
+        "unban" => 999,
+        _ => 0,
+    };
+
+    assert_eq!(
+        rank_choices("ban", candidates.iter().copied(), |x| x, boost),
+        ["ban", "bandana", "banner", "unban"],
+    );
+}
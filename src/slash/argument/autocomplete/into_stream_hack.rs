@@ -34,3 +34,40 @@ macro_rules! into_stream {
         }
     };
 }
+
+#[doc(hidden)]
+pub struct IntoResultWrap<'a, T>(pub &'a T);
+
+#[doc(hidden)]
+pub trait ConvertResultFrom<T, E> {
+    type Output;
+    fn result_converter(self) -> fn(T) -> Result<Self::Output, E>;
+}
+
+impl<T, E> ConvertResultFrom<T, E> for &IntoResultWrap<'_, T> {
+    type Output = T;
+    fn result_converter(self) -> fn(T) -> Result<T, E> {
+        |value| Ok(value)
+    }
+}
+
+impl<T, E> ConvertResultFrom<Result<T, E>, E> for &&IntoResultWrap<'_, Result<T, E>> {
+    type Output = T;
+    fn result_converter(self) -> fn(Result<T, E>) -> Result<T, E> {
+        |result| result
+    }
+}
+
+// Takes an expression that is either a Result or any other value, and converts it to a Result
+#[doc(hidden)]
+#[macro_export]
+macro_rules! into_result {
+    ($e:expr) => {
+        match $e {
+            value => {
+                use $crate::ConvertResultFrom;
+                (&&$crate::IntoResultWrap(&value)).result_converter()(value)
+            }
+        }
+    };
+}
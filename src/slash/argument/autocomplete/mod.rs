@@ -4,22 +4,65 @@ pub use autocompletable::*;
 mod into_stream_hack;
 pub use into_stream_hack::*;
 
+mod fuzzy;
+pub use fuzzy::*;
+
+mod cache;
+pub use cache::*;
+
+mod truncation;
+pub use truncation::*;
+
+mod numeric;
+pub use numeric::*;
+
 /// A single autocomplete choice, displayed in Discord UI
 ///
 /// This type should be returned by functions set via the `#[autocomplete = ]` attribute on slash
-/// command parameters.
+/// command parameters. Autocomplete functions may also return plain values; those are converted
+/// into choices whose name is the value's string representation.
+///
+/// ```rust
+/// let choice = poise::AutocompleteChoice::new("Berlin — Germany", 2950159_u64)
+///     .name_localized("de", "Berlin — Deutschland");
+/// assert_eq!(choice.name, "Berlin — Germany");
+/// assert_eq!(choice.value, 2950159);
+/// ```
+///
+/// Constructing this struct with a struct literal requires the `name_localizations` field since it
+/// was added; prefer [`Self::new`], which doesn't break when fields are added.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct AutocompleteChoice<T> {
     /// Name of the choice, displayed in the Discord UI
     pub name: String,
+    /// Localized names of the choice, keyed by Discord locale code (e.g. `de` or `en-US`).
+    ///
+    /// Discord displays the localized name to users whose client uses the respective locale, and
+    /// falls back to [`Self::name`] otherwise.
+    pub name_localizations: std::collections::BTreeMap<String, String>,
     /// Value of the choice, sent to the bot
     pub value: T,
 }
 
-impl<T: ToString> From<T> for AutocompleteChoice<T> {
-    fn from(value: T) -> Self {
+impl<T> AutocompleteChoice<T> {
+    /// Create a choice with a display name that differs from the submitted value
+    pub fn new(name: impl Into<String>, value: T) -> Self {
         Self {
-            name: value.to_string(),
+            name: name.into(),
+            name_localizations: std::collections::BTreeMap::new(),
             value,
         }
     }
+
+    /// Add a localized name for the given Discord locale code
+    pub fn name_localized(mut self, locale: impl Into<String>, name: impl Into<String>) -> Self {
+        self.name_localizations.insert(locale.into(), name.into());
+        self
+    }
+}
+
+impl<T: ToString> From<T> for AutocompleteChoice<T> {
+    fn from(value: T) -> Self {
+        Self::new(value.to_string(), value)
+    }
 }
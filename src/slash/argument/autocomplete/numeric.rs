@@ -0,0 +1,70 @@
+//! Helpers for autocompleting numeric parameters
+
+use crate::AutocompleteChoice;
+
+/// Combine the number the user has typed so far with a list of commonly used values.
+///
+/// The typed number is always suggested first, so that the user can submit arbitrary values.
+/// It's followed by the presets whose digits start with the typed number (typing `1` suggests
+/// `10` and `100`), then the remaining presets. Presets can be plain numbers or
+/// [`AutocompleteChoice`]s with a descriptive label. At most 25 choices are returned.
+///
+/// Serializing the choices as JSON integers or floats is handled by the parameter type, as with
+/// all autocomplete choices.
+///
+/// ```rust
+/// # type Context<'a> = poise::Context<'a, (), ()>;
+/// async fn autocomplete_seconds(
+///     _ctx: Context<'_>,
+///     partial: u32,
+/// ) -> Vec<poise::AutocompleteChoice<u32>> {
+///     poise::numeric_choices(
+///         partial,
+///         vec![
+///             poise::AutocompleteChoice::new("10 (quick)", 10),
+///             poise::AutocompleteChoice::new("60 (one minute)", 60),
+///             poise::AutocompleteChoice::new("3600 (one hour)", 3600),
+///         ],
+///     )
+/// }
+/// ```
+pub fn numeric_choices<T, C>(
+    partial: T,
+    presets: impl IntoIterator<Item = C>,
+) -> Vec<AutocompleteChoice<T>>
+where
+    T: PartialEq + ToString,
+    C: Into<AutocompleteChoice<T>>,
+{
+    let partial_string = partial.to_string();
+
+    let (matching_presets, other_presets): (Vec<_>, Vec<_>) = presets
+        .into_iter()
+        .map(|preset| preset.into())
+        .filter(|preset| preset.value != partial)
+        .partition(|preset| preset.value.to_string().starts_with(&partial_string));
+
+    std::iter::once(AutocompleteChoice::new(partial_string, partial))
+        .chain(matching_presets)
+        .chain(other_presets)
+        .take(25)
+        .collect()
+}
+
+#[cfg(test)]
+#[test]
+fn test_numeric_choices() {
+    let values = |choices: Vec<AutocompleteChoice<i64>>| {
+        choices
+            .into_iter()
+            .map(|choice| choice.value)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        values(numeric_choices(1, vec![5, 10, 1, 100])),
+        [1, 10, 100, 5]
+    );
+    assert_eq!(values(numeric_choices(7, 0..100)).len(), 25);
+    assert_eq!(numeric_choices(2.5, vec![2.5_f64])[0].name, "2.5");
+}
@@ -0,0 +1,143 @@
+//! Makes sure autocomplete responses stay within Discord's limits
+
+/// Maximum number of autocomplete choices Discord accepts
+const MAX_CHOICES: usize = 25;
+/// Maximum length of an autocomplete choice name or string value, in characters
+const MAX_LENGTH: usize = 100;
+
+/// How to shorten autocomplete choice names which exceed Discord's 100 character limit.
+///
+/// Configured via [`crate::ApplicationFrameworkOptions::autocomplete_truncation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutocompleteTruncation {
+    /// Cut off the name after 100 characters
+    DropTail,
+    /// Cut off the name after 99 characters and append "…"
+    Ellipsis,
+}
+
+impl Default for AutocompleteTruncation {
+    fn default() -> Self {
+        Self::Ellipsis
+    }
+}
+
+impl AutocompleteTruncation {
+    /// Shorten the given string to at most 100 characters according to the strategy. Returns None
+    /// if the string was short enough already
+    fn truncate(self, name: &str) -> Option<String> {
+        if name.chars().count() <= MAX_LENGTH {
+            return None;
+        }
+        Some(match self {
+            Self::DropTail => name.chars().take(MAX_LENGTH).collect(),
+            Self::Ellipsis => {
+                let mut name = name.chars().take(MAX_LENGTH - 1).collect::<String>();
+                name.push('…');
+                name
+            }
+        })
+    }
+}
+
+/// Bring a JSON array of autocomplete choices into a shape Discord accepts.
+///
+/// Choices beyond the 25th are dropped, as are choices with an empty name or a string value over
+/// 100 characters (the value can't be shortened without changing its meaning). Over-long names
+/// and name localizations are shortened according to `truncation`. Prints a warning if anything
+/// had to be changed.
+pub(crate) fn sanitize_autocomplete_choices(
+    choices: serde_json::Value,
+    truncation: AutocompleteTruncation,
+) -> serde_json::Value {
+    let choices = match choices {
+        serde_json::Value::Array(choices) => choices,
+        other => {
+            println!(
+                "Warning: autocomplete choices must be a JSON array, got {}",
+                other
+            );
+            return serde_json::Value::Array(Vec::new());
+        }
+    };
+
+    let num_choices = choices.len();
+    let mut num_dropped = 0;
+    let mut num_truncated = 0;
+    let mut sanitized_choices = Vec::new();
+    for mut choice in choices {
+        let name_is_empty = choice["name"].as_str().map_or(true, |name| name.is_empty());
+        let value_is_too_long = choice["value"]
+            .as_str()
+            .map_or(false, |value| value.chars().count() > MAX_LENGTH);
+        if name_is_empty || value_is_too_long {
+            num_dropped += 1;
+            continue;
+        }
+        if sanitized_choices.len() == MAX_CHOICES {
+            break;
+        }
+
+        if let Some(name) = choice["name"].as_str().and_then(|x| truncation.truncate(x)) {
+            choice["name"] = name.into();
+            num_truncated += 1;
+        }
+        if let Some(localizations) = choice
+            .get_mut("name_localizations")
+            .and_then(|x| x.as_object_mut())
+        {
+            for localized_name in localizations.values_mut() {
+                if let Some(name) = localized_name.as_str().and_then(|x| truncation.truncate(x)) {
+                    *localized_name = name.into();
+                    num_truncated += 1;
+                }
+            }
+        }
+
+        sanitized_choices.push(choice);
+    }
+
+    let num_over_limit = num_choices - num_dropped - sanitized_choices.len();
+    if num_dropped > 0 || num_truncated > 0 || num_over_limit > 0 {
+        println!(
+            "Warning: autocomplete response exceeded Discord's limits. Dropped {} invalid and {} \
+            excess choices, shortened {} names",
+            num_dropped, num_over_limit, num_truncated,
+        );
+    }
+
+    serde_json::Value::Array(sanitized_choices)
+}
+
+#[cfg(test)]
+#[test]
+fn test_sanitize_autocomplete_choices() {
+    let long_name = "a".repeat(150);
+    let choices = (0..30)
+        .map(|i| serde_json::json!({ "name": i.to_string(), "value": i }))
+        .chain(std::iter::once(
+            serde_json::json!({ "name": long_name, "value": "x" }),
+        ))
+        .collect::<Vec<_>>();
+
+    let sanitized =
+        sanitize_autocomplete_choices(choices.clone().into(), AutocompleteTruncation::Ellipsis);
+    assert_eq!(sanitized.as_array().unwrap().len(), 25);
+
+    let mut choices = choices;
+    choices.drain(..29);
+    choices.push(serde_json::json!({ "name": "", "value": 1 }));
+    choices.push(serde_json::json!({ "name": "long value", "value": long_name }));
+
+    let sanitized =
+        sanitize_autocomplete_choices(choices.clone().into(), AutocompleteTruncation::Ellipsis);
+    assert_eq!(
+        sanitized,
+        serde_json::json!([
+            { "name": "29", "value": 29 },
+            { "name": format!("{}…", "a".repeat(99)), "value": "x" },
+        ])
+    );
+    let sanitized = sanitize_autocomplete_choices(choices.into(), AutocompleteTruncation::DropTail);
+    assert_eq!(sanitized[1]["name"], "a".repeat(100));
+}
@@ -0,0 +1,154 @@
+//! Slash command support for colors
+
+use crate::{serenity_prelude as serenity, Autocompletable, SlashArgError, SlashArgument};
+
+/// Common CSS color names accepted by [`parse_colour`], with their RGB values
+const COLOUR_NAMES: &[(&str, u32)] = &[
+    ("black", 0x000000),
+    ("white", 0xFFFFFF),
+    ("gray", 0x808080),
+    ("grey", 0x808080),
+    ("silver", 0xC0C0C0),
+    ("darkgray", 0xA9A9A9),
+    ("darkgrey", 0xA9A9A9),
+    ("lightgray", 0xD3D3D3),
+    ("lightgrey", 0xD3D3D3),
+    ("red", 0xFF0000),
+    ("darkred", 0x8B0000),
+    ("maroon", 0x800000),
+    ("crimson", 0xDC143C),
+    ("salmon", 0xFA8072),
+    ("coral", 0xFF7F50),
+    ("tomato", 0xFF6347),
+    ("orange", 0xFFA500),
+    ("darkorange", 0xFF8C00),
+    ("gold", 0xFFD700),
+    ("yellow", 0xFFFF00),
+    ("khaki", 0xF0E68C),
+    ("olive", 0x808000),
+    ("lime", 0x00FF00),
+    ("green", 0x008000),
+    ("darkgreen", 0x006400),
+    ("lightgreen", 0x90EE90),
+    ("teal", 0x008080),
+    ("cyan", 0x00FFFF),
+    ("aqua", 0x00FFFF),
+    ("turquoise", 0x40E0D0),
+    ("skyblue", 0x87CEEB),
+    ("lightblue", 0xADD8E6),
+    ("blue", 0x0000FF),
+    ("darkblue", 0x00008B),
+    ("navy", 0x000080),
+    ("royalblue", 0x4169E1),
+    ("indigo", 0x4B0082),
+    ("purple", 0x800080),
+    ("violet", 0xEE82EE),
+    ("magenta", 0xFF00FF),
+    ("fuchsia", 0xFF00FF),
+    ("pink", 0xFFC0CB),
+    ("hotpink", 0xFF69B4),
+    ("brown", 0xA52A2A),
+    ("chocolate", 0xD2691E),
+    ("tan", 0xD2B48C),
+    ("beige", 0xF5F5DC),
+];
+
+/// Error returned when a color string couldn't be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColourParseError {
+    /// The input which failed to parse
+    pub input: String,
+}
+impl std::fmt::Display for ColourParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "\"{}\" is not a color (try e.g. #FF8800, 0xFF8800 or orange)",
+            self.input
+        )
+    }
+}
+impl std::error::Error for ColourParseError {}
+
+/// Parse a color as `#RRGGBB`, `#RGB`, `0xRRGGBB` or a common CSS color name like `orange`
+/// (case-insensitive). Hex colors need the `#` or `0x` prefix, so that words like `add` aren't
+/// mistaken for colors.
+///
+/// ```rust
+/// use poise::serenity_prelude::Colour;
+/// assert_eq!(poise::parse_colour("#FF8800"), Ok(Colour::new(0xFF8800)));
+/// assert_eq!(poise::parse_colour("#f80"), Ok(Colour::new(0xFF8800)));
+/// assert_eq!(poise::parse_colour("0xff8800"), Ok(Colour::new(0xFF8800)));
+/// assert_eq!(poise::parse_colour("Orange"), Ok(Colour::new(0xFFA500)));
+/// assert!(poise::parse_colour("#FF880").is_err());
+/// assert!(poise::parse_colour("add").is_err());
+/// ```
+pub fn parse_colour(input: &str) -> Result<serenity::Colour, ColourParseError> {
+    let trimmed_input = input.trim();
+    let lowercase_input = trimmed_input.to_lowercase();
+
+    if let Some(&(_, rgb)) = COLOUR_NAMES
+        .iter()
+        .find(|(name, _)| *name == lowercase_input)
+    {
+        return Ok(serenity::Colour::new(rgb));
+    }
+
+    let hex = trimmed_input
+        .strip_prefix('#')
+        .or_else(|| trimmed_input.strip_prefix("0x"))
+        .or_else(|| trimmed_input.strip_prefix("0X"))
+        .unwrap_or("");
+    let rgb = match hex.len() {
+        _ if !hex.chars().all(|c| c.is_ascii_hexdigit()) => None,
+        6 => u32::from_str_radix(hex, 16).ok(),
+        // Short form: every digit is doubled, i.e. #F80 is #FF8800
+        3 => Some(
+            hex.chars()
+                .filter_map(|c| c.to_digit(16))
+                .fold(0, |rgb, digit| rgb << 8 | digit * 0x11),
+        ),
+        _ => None,
+    };
+    rgb.map(serenity::Colour::new)
+        .ok_or_else(|| ColourParseError {
+            input: input.to_owned(),
+        })
+}
+
+#[async_trait::async_trait]
+impl SlashArgument for serenity::Colour {
+    async fn extract(
+        _: &serenity::Context,
+        _: Option<serenity::GuildId>,
+        _: Option<serenity::ChannelId>,
+        value: &serde_json::Value,
+    ) -> Result<Self, SlashArgError> {
+        let string = value
+            .as_str()
+            .ok_or(SlashArgError::CommandStructureMismatch("expected string"))?;
+        parse_colour(string).map_err(|e| SlashArgError::Parse(e.into()))
+    }
+
+    fn create(
+        builder: &mut serenity::CreateApplicationCommandOption,
+    ) -> &mut serenity::CreateApplicationCommandOption {
+        builder.kind(serenity::ApplicationCommandOptionType::String)
+    }
+}
+
+/// The partial input is passed as a string because an incomplete color (`#FF8`) may not be valid
+impl Autocompletable for serenity::Colour {
+    type Partial = String;
+
+    fn extract_partial(value: &serde_json::Value) -> Result<String, SlashArgError> {
+        let string = value
+            .as_str()
+            .ok_or(SlashArgError::CommandStructureMismatch("expected string"))?;
+        Ok(string.to_owned())
+    }
+
+    fn into_json(self) -> serde_json::Value {
+        serde_json::Value::String(format!("#{}", self.hex()))
+    }
+}
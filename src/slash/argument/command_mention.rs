@@ -0,0 +1,182 @@
+//! Parameter type for mentions of the bot's own commands
+
+/// Error returned when a [`CommandMention`] argument couldn't be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandMentionParseError {
+    /// The input which failed to parse
+    pub input: String,
+}
+impl std::fmt::Display for CommandMentionParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "\"{}\" is not a command", self.input)
+    }
+}
+impl std::error::Error for CommandMentionParseError {}
+
+/// A command parameter type for references to commands of this bot, for meta commands like
+/// `/help command:</ban:123>` or `~disable ban`
+///
+/// Accepts slash command mentions like `</ban:123>` or `</config set:123>`, as inserted by the
+/// Discord client, and plain command names like `ban`, `/ban` or `config set`. Use [`Self::find`]
+/// to look up the referenced command.
+///
+/// ```rust
+/// # use poise::CommandMention;
+/// let mention = "</config set:80351110224678912>".parse::<CommandMention>().unwrap();
+/// assert_eq!(mention.name, "config set");
+/// assert_eq!(mention.id, Some(80351110224678912));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CommandMention {
+    /// Name of the command, including the names of parent commands separated by spaces
+    pub name: String,
+    /// ID of the application command, if the command was given as a slash command mention
+    pub id: Option<u64>,
+}
+
+impl std::str::FromStr for CommandMention {
+    type Err = CommandMentionParseError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let error = || CommandMentionParseError {
+            input: input.to_owned(),
+        };
+
+        let trimmed_input = input.trim();
+        let (name, id) = match trimmed_input
+            .strip_prefix("</")
+            .and_then(|x| x.strip_suffix('>'))
+        {
+            Some(mention) => {
+                let mut parts = mention.rsplitn(2, ':');
+                let id = parts
+                    .next()
+                    .and_then(|id| id.parse().ok())
+                    .ok_or_else(error)?;
+                (parts.next().ok_or_else(error)?, Some(id))
+            }
+            None => (trimmed_input.trim_start_matches('/'), None),
+        };
+
+        let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+        if name.is_empty() {
+            return Err(error());
+        }
+        Ok(Self { name, id })
+    }
+}
+
+impl std::fmt::Display for CommandMention {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.id {
+            Some(id) => write!(f, "</{}:{}>", self.name, id),
+            None => f.write_str(&self.name),
+        }
+    }
+}
+
+fn find_slash_command<'a, U, E>(
+    command: &'a crate::SlashCommandMeta<U, E>,
+    name: &str,
+    subcommand_names: &[&str],
+) -> Option<&'a crate::SlashCommand<U, E>> {
+    match command {
+        crate::SlashCommandMeta::Command(command) => {
+            if subcommand_names.is_empty() && command.name.eq_ignore_ascii_case(name) {
+                Some(command)
+            } else {
+                None
+            }
+        }
+        crate::SlashCommandMeta::CommandGroup {
+            name: group_name,
+            subcommands,
+            ..
+        } => {
+            if !group_name.eq_ignore_ascii_case(name) {
+                return None;
+            }
+            let (name, subcommand_names) = subcommand_names.split_first()?;
+            subcommands
+                .iter()
+                .find_map(|x| find_slash_command(x, name, subcommand_names))
+        }
+    }
+}
+
+fn find_prefix_subcommand<'a, U, E>(
+    command: &'a crate::PrefixCommandMeta<U, E>,
+    subcommand_names: &[&str],
+) -> Option<&'a crate::PrefixCommand<U, E>> {
+    match subcommand_names.split_first() {
+        Some((name, subcommand_names)) => {
+            let subcommand = command
+                .subcommands
+                .iter()
+                .find(|x| x.command.is_named(name, true))?;
+            find_prefix_subcommand(subcommand, subcommand_names)
+        }
+        None => Some(&command.command),
+    }
+}
+
+impl CommandMention {
+    /// Look up the mentioned command among the commands registered in the framework. Names are
+    /// compared case-insensitively.
+    ///
+    /// Application commands are searched first, then prefix commands including their aliases.
+    pub fn find<'a, U, E>(
+        &self,
+        framework: &'a crate::Framework<U, E>,
+    ) -> Option<crate::CommandRef<'a, U, E>> {
+        let names = self.name.split_whitespace().collect::<Vec<_>>();
+        let (name, subcommand_names) = names.split_first()?;
+        for command in framework.application_commands() {
+            match command {
+                crate::ApplicationCommandTree::Slash(command) => {
+                    if let Some(command) = find_slash_command(command, name, subcommand_names) {
+                        return Some(crate::CommandRef::Application(
+                            crate::ApplicationCommand::Slash(command),
+                        ));
+                    }
+                }
+                crate::ApplicationCommandTree::ContextMenu(command) => {
+                    if subcommand_names.is_empty() && command.name.eq_ignore_ascii_case(name) {
+                        return Some(crate::CommandRef::Application(
+                            crate::ApplicationCommand::ContextMenu(command),
+                        ));
+                    }
+                }
+            }
+        }
+
+        let command = framework
+            .prefix_commands()
+            .into_iter()
+            .find(|x| x.command.is_named(name, true))?;
+        find_prefix_subcommand(command, subcommand_names).map(crate::CommandRef::Prefix)
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_parse_command_mention() {
+    for &(input, name, id) in &[
+        ("ban", "ban", None),
+        ("/ban", "ban", None),
+        ("  config   set ", "config set", None),
+        ("</ban:123>", "ban", Some(123)),
+        ("</config set:123>", "config set", Some(123)),
+    ] {
+        assert_eq!(
+            input.parse(),
+            Ok(CommandMention {
+                name: name.into(),
+                id
+            })
+        );
+    }
+    for &input in &["", "/", "</ban>", "</ban:abc>", "</:123>"] {
+        assert!(input.parse::<CommandMention>().is_err());
+    }
+}
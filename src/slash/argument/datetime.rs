@@ -0,0 +1,533 @@
+//! Slash command and autocomplete support for durations and points in time
+
+use crate::{serenity_prelude as serenity, Autocompletable, SlashArgError, SlashArgument};
+
+/// Units recognized in durations, with their aliases and length in seconds. Order matters for
+/// resolving abbreviations in partial input: the first unit with a matching alias prefix wins
+const DURATION_UNITS: &[(&[&str], u64)] = &[
+    (&["s", "sec", "secs", "second", "seconds"], 1),
+    (&["m", "min", "mins", "minute", "minutes"], 60),
+    (&["h", "hr", "hrs", "hour", "hours"], 60 * 60),
+    (&["d", "day", "days"], 60 * 60 * 24),
+    (&["w", "week", "weeks"], 60 * 60 * 24 * 7),
+];
+
+/// Error returned when a duration string couldn't be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DurationParseError {
+    /// The input didn't contain any number-unit pair
+    Empty,
+    /// A number wasn't followed by a unit, e.g. `2h30`
+    MissingUnit,
+    /// A unit wasn't recognized, e.g. `5y`
+    UnknownUnit(String),
+    /// A unit wasn't preceded by a number, e.g. `h`
+    MissingNumber,
+    /// The duration is too large to be represented
+    Overflow,
+}
+impl std::fmt::Display for DurationParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "empty duration"),
+            Self::MissingUnit => write!(f, "expected a unit (s, m, h, d, w) after the number"),
+            Self::UnknownUnit(unit) => write!(f, "unknown duration unit \"{}\"", unit),
+            Self::MissingNumber => write!(f, "expected a number before the unit"),
+            Self::Overflow => write!(f, "duration too large"),
+        }
+    }
+}
+impl std::error::Error for DurationParseError {}
+
+/// A lexed component of a duration string
+enum DurationToken<'a> {
+    Number(u64),
+    Unit(&'a str),
+}
+
+/// Split a duration string like `2h 30min` into numbers and units. Returns None on numeric
+/// overflow
+fn lex_duration(input: &str) -> Option<Vec<DurationToken<'_>>> {
+    let mut tokens = Vec::new();
+    let mut rest = input.trim_start();
+    while let Some(c) = rest.chars().next() {
+        let token_len = if c.is_ascii_digit() {
+            let len = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            tokens.push(DurationToken::Number(rest[..len].parse().ok()?));
+            len
+        } else {
+            let len = rest
+                .find(|c: char| c.is_ascii_digit() || c.is_whitespace())
+                .unwrap_or(rest.len());
+            tokens.push(DurationToken::Unit(&rest[..len]));
+            len
+        };
+        rest = rest[token_len..].trim_start();
+    }
+    Some(tokens)
+}
+
+fn unit_seconds(unit: &str) -> Option<u64> {
+    let unit = unit.to_lowercase();
+    DURATION_UNITS
+        .iter()
+        .find(|(aliases, _)| aliases.contains(&&*unit))
+        .map(|&(_, seconds)| seconds)
+}
+
+/// Parse a duration like `90s`, `2h30m` or `1 day 4 hours`.
+///
+/// Supported units are seconds (`s`), minutes (`m`), hours (`h`), days (`d`) and weeks (`w`),
+/// along with their common spellings.
+///
+/// ```rust
+/// # use std::time::Duration;
+/// assert_eq!(poise::parse_duration("2h 30m"), Ok(Duration::from_secs(9000)));
+/// assert!(poise::parse_duration("2h30").is_err());
+/// ```
+pub fn parse_duration(input: &str) -> Result<std::time::Duration, DurationParseError> {
+    let tokens = lex_duration(input).ok_or(DurationParseError::Overflow)?;
+    if tokens.is_empty() {
+        return Err(DurationParseError::Empty);
+    }
+
+    let mut total_seconds: u64 = 0;
+    let mut tokens = tokens.into_iter();
+    while let Some(token) = tokens.next() {
+        let number = match token {
+            DurationToken::Number(number) => number,
+            DurationToken::Unit(_) => return Err(DurationParseError::MissingNumber),
+        };
+        let unit = match tokens.next() {
+            Some(DurationToken::Unit(unit)) => unit,
+            _ => return Err(DurationParseError::MissingUnit),
+        };
+        let seconds =
+            unit_seconds(unit).ok_or_else(|| DurationParseError::UnknownUnit(unit.to_owned()))?;
+        total_seconds = number
+            .checked_mul(seconds)
+            .and_then(|x| x.checked_add(total_seconds))
+            .ok_or(DurationParseError::Overflow)?;
+    }
+    Ok(std::time::Duration::from_secs(total_seconds))
+}
+
+/// Format a duration in the format understood by [`parse_duration`], e.g. `2h 30m`.
+///
+/// Sub-second precision is discarded.
+pub fn format_duration(duration: std::time::Duration) -> String {
+    let mut remaining = duration.as_secs();
+    if remaining == 0 {
+        return "0s".into();
+    }
+
+    let mut components = Vec::new();
+    for &(aliases, seconds) in DURATION_UNITS.iter().rev() {
+        if remaining >= seconds {
+            components.push(format!("{}{}", remaining / seconds, aliases[0]));
+            remaining %= seconds;
+        }
+    }
+    components.join(" ")
+}
+
+/// Partial input of a [`std::time::Duration`] parameter during autocomplete.
+///
+/// The user may still be typing, so the input is parsed as far as possible: `2h3` yields
+/// `parsed` = 2 hours and `pending_number` = 3.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialDuration {
+    /// The raw input string
+    pub input: String,
+    /// Sum of all complete number-unit pairs at the start of the input
+    pub parsed: std::time::Duration,
+    /// A trailing number for which no unit has been typed yet
+    pub pending_number: Option<u64>,
+}
+
+impl PartialDuration {
+    /// Parse a possibly incomplete duration string. Abbreviated units like `mi` are resolved to
+    /// the first unit they are a prefix of. Parsing stops at the first unrecognized component.
+    pub fn parse(input: &str) -> Self {
+        let mut parsed_seconds: u64 = 0;
+        let mut pending_number = None;
+
+        for token in lex_duration(input).unwrap_or_default() {
+            match (token, pending_number.take()) {
+                (DurationToken::Number(number), None) => pending_number = Some(number),
+                (DurationToken::Unit(unit), Some(number)) => {
+                    let unit = unit.to_lowercase();
+                    let seconds = DURATION_UNITS
+                        .iter()
+                        .find(|(aliases, _)| aliases.iter().any(|alias| alias.starts_with(&unit)))
+                        .map(|&(_, seconds)| seconds);
+                    let new_total = seconds
+                        .and_then(|seconds| number.checked_mul(seconds))
+                        .and_then(|x| x.checked_add(parsed_seconds));
+                    match new_total {
+                        Some(new_total) => parsed_seconds = new_total,
+                        None => break,
+                    }
+                }
+                _ => break,
+            }
+        }
+
+        Self {
+            input: input.to_owned(),
+            parsed: std::time::Duration::from_secs(parsed_seconds),
+            pending_number,
+        }
+    }
+
+    /// Durations the user may be in the process of typing, for use as autocomplete suggestions.
+    ///
+    /// If a number without unit is pending, one interpretation per unit is returned (`2h3` →
+    /// `2h 3s`, `2h 3m`, `2h 3h`, ...). Otherwise, the parsed duration is returned if non-zero.
+    pub fn interpretations(&self) -> Vec<std::time::Duration> {
+        match self.pending_number {
+            Some(number) => DURATION_UNITS
+                .iter()
+                .filter_map(|&(_, seconds)| {
+                    let seconds = number.checked_mul(seconds)?;
+                    self.parsed
+                        .checked_add(std::time::Duration::from_secs(seconds))
+                })
+                .collect(),
+            None if self.parsed.as_secs() > 0 => vec![self.parsed],
+            None => Vec::new(),
+        }
+    }
+}
+
+impl std::fmt::Display for PartialDuration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.input)
+    }
+}
+
+fn extract_str(value: &serde_json::Value) -> Result<&str, SlashArgError> {
+    value
+        .as_str()
+        .ok_or(SlashArgError::CommandStructureMismatch("expected string"))
+}
+
+#[async_trait::async_trait]
+impl SlashArgument for std::time::Duration {
+    async fn extract(
+        _: &serenity::Context,
+        _: Option<serenity::GuildId>,
+        _: Option<serenity::ChannelId>,
+        value: &serde_json::Value,
+    ) -> Result<Self, SlashArgError> {
+        parse_duration(extract_str(value)?).map_err(|e| SlashArgError::Parse(e.into()))
+    }
+
+    fn create(
+        builder: &mut serenity::CreateApplicationCommandOption,
+    ) -> &mut serenity::CreateApplicationCommandOption {
+        builder.kind(serenity::ApplicationCommandOptionType::String)
+    }
+}
+
+impl Autocompletable for std::time::Duration {
+    type Partial = PartialDuration;
+
+    fn extract_partial(value: &serde_json::Value) -> Result<PartialDuration, SlashArgError> {
+        Ok(PartialDuration::parse(extract_str(value)?))
+    }
+
+    fn into_json(self) -> serde_json::Value {
+        serde_json::Value::String(format_duration(self))
+    }
+}
+
+/// Formats in which points in time are accepted, tried in order. All are interpreted as UTC
+const NAIVE_DATETIME_FORMATS: &[&str] = &["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M"];
+
+/// Error returned when a date and time string couldn't be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DateTimeParseError {
+    /// The input which failed to parse
+    pub input: String,
+}
+impl std::fmt::Display for DateTimeParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "couldn't understand \"{}\" as a point in time (try e.g. 2021-11-24 17:00)",
+            self.input
+        )
+    }
+}
+impl std::error::Error for DateTimeParseError {}
+
+/// Parse a point in time. Accepted formats:
+/// - RFC 3339, e.g. `2021-11-24T18:00:00+01:00`
+/// - UTC date with optional time, e.g. `2021-11-24 17:00` or `2021-11-24`
+/// - Discord timestamps, e.g. `<t:1637773200>` or `<t:1637773200:R>`. Bare numbers aren't
+///   accepted as Unix timestamps, because numbers are easily passed by accident
+/// - With the `relative_datetime` feature: relative forms like `now`, `in 2h`, `3 days ago`,
+///   `5pm`, `tomorrow 5pm` or `yesterday 17:30`. These are interpreted in UTC
+///
+/// ```rust
+/// let datetime = poise::parse_datetime("<t:1637773200:R>").unwrap();
+/// assert_eq!(datetime, poise::parse_datetime("2021-11-24 17:00").unwrap());
+/// ```
+pub fn parse_datetime(input: &str) -> Result<chrono::DateTime<chrono::Utc>, DateTimeParseError> {
+    parse_datetime_at(input, chrono::Utc::now())
+}
+
+/// Like [`parse_datetime`], but with a custom reference point for relative forms
+fn parse_datetime_at(
+    input: &str,
+    #[allow(unused_variables)] now: chrono::DateTime<chrono::Utc>,
+) -> Result<chrono::DateTime<chrono::Utc>, DateTimeParseError> {
+    let trimmed_input = input.trim();
+
+    if let Ok(datetime) = chrono::DateTime::parse_from_rfc3339(trimmed_input) {
+        return Ok(datetime.with_timezone(&chrono::Utc));
+    }
+    for format in NAIVE_DATETIME_FORMATS {
+        if let Ok(datetime) = chrono::NaiveDateTime::parse_from_str(trimmed_input, format) {
+            return Ok(chrono::DateTime::from_utc(datetime, chrono::Utc));
+        }
+    }
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(trimmed_input, "%Y-%m-%d") {
+        return Ok(chrono::DateTime::from_utc(
+            date.and_hms(0, 0, 0),
+            chrono::Utc,
+        ));
+    }
+    if let Some(timestamp) = trimmed_input
+        .strip_prefix("<t:")
+        .and_then(|x| x.strip_suffix('>'))
+    {
+        // Strip the optional style suffix, e.g. `:R`
+        let timestamp = timestamp.split(':').next().unwrap_or(timestamp);
+        let datetime = timestamp
+            .parse()
+            .ok()
+            .and_then(|timestamp| chrono::NaiveDateTime::from_timestamp_opt(timestamp, 0));
+        if let Some(datetime) = datetime {
+            return Ok(chrono::DateTime::from_utc(datetime, chrono::Utc));
+        }
+    }
+    #[cfg(feature = "relative_datetime")]
+    if let Some(datetime) = parse_relative_datetime(trimmed_input, now) {
+        return Ok(datetime);
+    }
+
+    Err(DateTimeParseError {
+        input: input.to_owned(),
+    })
+}
+
+/// Parse a time of day like `5pm`, `5:30 pm`, `17:00` or `17:00:30`
+#[cfg(feature = "relative_datetime")]
+fn parse_time_of_day(input: &str) -> Option<chrono::NaiveTime> {
+    let input = input.trim();
+    let (input, pm) = if let Some(x) = input.strip_suffix("am") {
+        (x.trim_end(), Some(false))
+    } else if let Some(x) = input.strip_suffix("pm") {
+        (x.trim_end(), Some(true))
+    } else {
+        (input, None)
+    };
+
+    let mut components = input.split(':').map(|x| x.parse::<u32>().ok());
+    let mut hour = components.next()??;
+    let minute = components.next().unwrap_or(Some(0))?;
+    let second = components.next().unwrap_or(Some(0))?;
+    if components.next().is_some() {
+        return None;
+    }
+
+    if let Some(pm) = pm {
+        if hour == 0 || hour > 12 {
+            return None;
+        }
+        hour = hour % 12 + if pm { 12 } else { 0 };
+    }
+    chrono::NaiveTime::from_hms_opt(hour, minute, second)
+}
+
+/// Parse relative points in time like `in 2h`, `3 days ago`, `tomorrow 5pm` or `17:30`
+#[cfg(feature = "relative_datetime")]
+fn parse_relative_datetime(
+    input: &str,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Option<chrono::DateTime<chrono::Utc>> {
+    let input = input.to_lowercase();
+    let to_chrono = |duration| chrono::Duration::from_std(parse_duration(duration).ok()?).ok();
+
+    if input == "now" {
+        return Some(now);
+    }
+    if let Some(duration) = input.strip_prefix("in ") {
+        return now.checked_add_signed(to_chrono(duration)?);
+    }
+    if let Some(duration) = input.strip_suffix(" ago") {
+        return now.checked_sub_signed(to_chrono(duration)?);
+    }
+
+    let mut words = input.splitn(2, ' ');
+    let first_word = words.next()?;
+    let day_offset = match first_word {
+        "today" => Some(0),
+        "tomorrow" => Some(1),
+        "yesterday" => Some(-1),
+        _ => None,
+    };
+    let (day_offset, time) = match day_offset {
+        Some(day_offset) => (day_offset, words.next()),
+        None => (0, Some(&*input)),
+    };
+    let time = match time {
+        Some(time) => parse_time_of_day(time)?,
+        None => chrono::NaiveTime::from_hms_opt(0, 0, 0)?,
+    };
+
+    let date = now.naive_utc().date() + chrono::Duration::days(day_offset);
+    Some(chrono::DateTime::from_utc(date.and_time(time), chrono::Utc))
+}
+
+#[async_trait::async_trait]
+impl SlashArgument for chrono::DateTime<chrono::Utc> {
+    async fn extract(
+        _: &serenity::Context,
+        _: Option<serenity::GuildId>,
+        _: Option<serenity::ChannelId>,
+        value: &serde_json::Value,
+    ) -> Result<Self, SlashArgError> {
+        parse_datetime(extract_str(value)?).map_err(|e| SlashArgError::Parse(e.into()))
+    }
+
+    fn create(
+        builder: &mut serenity::CreateApplicationCommandOption,
+    ) -> &mut serenity::CreateApplicationCommandOption {
+        builder.kind(serenity::ApplicationCommandOptionType::String)
+    }
+}
+
+/// The partial input is passed as a string because an incomplete date (`2021-11-`) can't be
+/// represented as a point in time
+impl Autocompletable for chrono::DateTime<chrono::Utc> {
+    type Partial = String;
+
+    fn extract_partial(value: &serde_json::Value) -> Result<String, SlashArgError> {
+        Ok(extract_str(value)?.to_owned())
+    }
+
+    fn into_json(self) -> serde_json::Value {
+        serde_json::Value::String(self.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+    }
+}
+
+#[cfg(feature = "time")]
+#[async_trait::async_trait]
+impl SlashArgument for ::time::OffsetDateTime {
+    async fn extract(
+        _: &serenity::Context,
+        _: Option<serenity::GuildId>,
+        _: Option<serenity::ChannelId>,
+        value: &serde_json::Value,
+    ) -> Result<Self, SlashArgError> {
+        // Go through chrono to accept the same formats as for chrono::DateTime
+        let datetime =
+            parse_datetime(extract_str(value)?).map_err(|e| SlashArgError::Parse(e.into()))?;
+        ::time::OffsetDateTime::from_unix_timestamp(datetime.timestamp())
+            .map_err(|e| SlashArgError::Parse(e.into()))
+    }
+
+    fn create(
+        builder: &mut serenity::CreateApplicationCommandOption,
+    ) -> &mut serenity::CreateApplicationCommandOption {
+        builder.kind(serenity::ApplicationCommandOptionType::String)
+    }
+}
+
+/// The partial input is passed as a string because an incomplete date (`2021-11-`) can't be
+/// represented as a point in time
+#[cfg(feature = "time")]
+impl Autocompletable for ::time::OffsetDateTime {
+    type Partial = String;
+
+    fn extract_partial(value: &serde_json::Value) -> Result<String, SlashArgError> {
+        Ok(extract_str(value)?.to_owned())
+    }
+
+    fn into_json(self) -> serde_json::Value {
+        let datetime = chrono::DateTime::<chrono::Utc>::from_utc(
+            chrono::NaiveDateTime::from_timestamp(self.unix_timestamp(), 0),
+            chrono::Utc,
+        );
+        datetime.into_json()
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_duration_parsing() {
+    use std::time::Duration;
+
+    assert_eq!(parse_duration("90s"), Ok(Duration::from_secs(90)));
+    assert_eq!(
+        parse_duration("1 day 4 Hours"),
+        Ok(Duration::from_secs(28 * 3600))
+    );
+    assert_eq!(parse_duration(""), Err(DurationParseError::Empty));
+    assert_eq!(parse_duration("2h30"), Err(DurationParseError::MissingUnit));
+    assert_eq!(
+        parse_duration("5y"),
+        Err(DurationParseError::UnknownUnit("y".into()))
+    );
+    assert_eq!(format_duration(Duration::from_secs(9000)), "2h 30m");
+    assert_eq!(
+        parse_duration(&format_duration(Duration::from_secs(694_861))),
+        Ok(Duration::from_secs(694_861))
+    );
+
+    let partial = PartialDuration::parse("2h3");
+    assert_eq!(partial.parsed, Duration::from_secs(7200));
+    assert_eq!(partial.pending_number, Some(3));
+    assert_eq!(partial.interpretations()[1], Duration::from_secs(7380));
+    assert_eq!(
+        PartialDuration::parse("1h 5mi").interpretations(),
+        [Duration::from_secs(3900)]
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_datetime_parsing() {
+    let datetime = |s| {
+        chrono::DateTime::parse_from_rfc3339(s)
+            .unwrap()
+            .with_timezone(&chrono::Utc)
+    };
+    let now = datetime("2021-11-24T17:00:00Z");
+    let parse = |s| parse_datetime_at(s, now);
+
+    assert_eq!(parse("2021-11-24T18:00:00+01:00"), Ok(now));
+    assert_eq!(parse("2021-11-24 17:00"), Ok(now));
+    assert_eq!(parse("<t:1637773200:R>"), Ok(now));
+    assert!(parse("1637773200").is_err());
+    assert!(parse("<t:abc>").is_err());
+
+    #[cfg(feature = "relative_datetime")]
+    {
+        assert_eq!(parse("now"), Ok(now));
+        assert_eq!(parse("in 1h"), Ok(datetime("2021-11-24T18:00:00Z")));
+        assert_eq!(parse("2 days ago"), Ok(datetime("2021-11-22T17:00:00Z")));
+        assert_eq!(
+            parse("Tomorrow 5:30pm"),
+            Ok(datetime("2021-11-25T17:30:00Z"))
+        );
+        assert_eq!(parse("yesterday"), Ok(datetime("2021-11-23T00:00:00Z")));
+        assert_eq!(parse("12am"), Ok(datetime("2021-11-24T00:00:00Z")));
+        assert!(parse("13pm").is_err());
+    }
+}
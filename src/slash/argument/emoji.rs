@@ -0,0 +1,184 @@
+//! Slash command support for emojis
+
+use crate::{serenity_prelude as serenity, SlashArgError, SlashArgument};
+
+/// Error returned when an emoji argument couldn't be parsed or resolved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmojiParseError {
+    /// The input which failed to parse
+    pub input: String,
+}
+impl std::fmt::Display for EmojiParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "\"{}\" is not an emoji of this server", self.input)
+    }
+}
+impl std::error::Error for EmojiParseError {}
+
+/// Parse custom emoji syntax like `<:name:id>` or `<a:name:id>`. Returns whether the emoji is
+/// animated, its name and its ID
+fn parse_custom_emoji_syntax(input: &str) -> Option<(bool, &str, serenity::EmojiId)> {
+    let inner = input.strip_prefix('<')?.strip_suffix('>')?;
+    let (animated, inner) = match inner.strip_prefix("a:") {
+        Some(inner) => (true, inner),
+        None => (false, inner.strip_prefix(':')?),
+    };
+    let separator = inner.find(':')?;
+    let id = inner[(separator + 1)..].parse().ok()?;
+    Some((animated, &inner[..separator], serenity::EmojiId(id)))
+}
+
+/// Whether the string plausibly is a single unicode emoji, i.e. only consists of non-ASCII
+/// symbols, optionally with ASCII digits and symbols for keycap emojis like 1️⃣
+fn is_unicode_emoji(input: &str) -> bool {
+    !input.is_empty()
+        && !input
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_ascii_alphabetic())
+        && input.chars().any(|c| !c.is_ascii())
+}
+
+/// Find a custom emoji of the guild by ID or name, using the cache if possible
+async fn find_guild_emoji(
+    ctx: &serenity::Context,
+    guild_id: Option<serenity::GuildId>,
+    id: Option<serenity::EmojiId>,
+    name: &str,
+) -> Option<serenity::Emoji> {
+    let guild_id = guild_id?;
+    let emojis = match ctx.cache.guild(guild_id) {
+        Some(guild) => guild.emojis.into_iter().map(|(_, emoji)| emoji).collect(),
+        None => guild_id.emojis(&ctx.http).await.ok()?,
+    };
+
+    let mut emojis = emojis.into_iter();
+    match id {
+        Some(id) => emojis.find(|emoji| emoji.id == id),
+        None => {
+            let emojis = emojis.collect::<Vec<_>>();
+            // Prefer exact matches over case-insensitive matches
+            let exact_match = emojis.iter().position(|emoji| emoji.name == name);
+            let loose_match = || {
+                emojis
+                    .iter()
+                    .position(|emoji| emoji.name.eq_ignore_ascii_case(name))
+            };
+            let index = exact_match.or_else(loose_match)?;
+            emojis.into_iter().nth(index)
+        }
+    }
+}
+
+/// Parse an emoji for use as reaction. Accepts unicode emojis, custom emoji syntax (`<:name:id>`)
+/// and names of custom emojis of the given guild, optionally surrounded by colons (`:name:`).
+pub async fn parse_reaction_type(
+    ctx: &serenity::Context,
+    guild_id: Option<serenity::GuildId>,
+    input: &str,
+) -> Result<serenity::ReactionType, EmojiParseError> {
+    let trimmed_input = input.trim();
+    if let Some((animated, name, id)) = parse_custom_emoji_syntax(trimmed_input) {
+        return Ok(serenity::ReactionType::Custom {
+            animated,
+            id,
+            name: Some(name.to_owned()),
+        });
+    }
+    if is_unicode_emoji(trimmed_input) {
+        return Ok(serenity::ReactionType::Unicode(trimmed_input.to_owned()));
+    }
+
+    let name = trimmed_input.trim_matches(':');
+    match find_guild_emoji(ctx, guild_id, None, name).await {
+        Some(emoji) => Ok(serenity::ReactionType::Custom {
+            animated: emoji.animated,
+            id: emoji.id,
+            name: Some(emoji.name),
+        }),
+        None => Err(EmojiParseError {
+            input: input.to_owned(),
+        }),
+    }
+}
+
+/// Parse a custom emoji of the given guild, either in custom emoji syntax (`<:name:id>`) or by
+/// name, optionally surrounded by colons (`:name:`)
+pub async fn parse_guild_emoji(
+    ctx: &serenity::Context,
+    guild_id: Option<serenity::GuildId>,
+    input: &str,
+) -> Result<serenity::Emoji, EmojiParseError> {
+    let trimmed_input = input.trim();
+    let emoji = match parse_custom_emoji_syntax(trimmed_input) {
+        Some((_, name, id)) => find_guild_emoji(ctx, guild_id, Some(id), name).await,
+        None => find_guild_emoji(ctx, guild_id, None, trimmed_input.trim_matches(':')).await,
+    };
+    emoji.ok_or_else(|| EmojiParseError {
+        input: input.to_owned(),
+    })
+}
+
+#[async_trait::async_trait]
+impl SlashArgument for serenity::ReactionType {
+    async fn extract(
+        ctx: &serenity::Context,
+        guild: Option<serenity::GuildId>,
+        _: Option<serenity::ChannelId>,
+        value: &serde_json::Value,
+    ) -> Result<Self, SlashArgError> {
+        let string = value
+            .as_str()
+            .ok_or(SlashArgError::CommandStructureMismatch("expected string"))?;
+        parse_reaction_type(ctx, guild, string)
+            .await
+            .map_err(|e| SlashArgError::Parse(e.into()))
+    }
+
+    fn create(
+        builder: &mut serenity::CreateApplicationCommandOption,
+    ) -> &mut serenity::CreateApplicationCommandOption {
+        builder.kind(serenity::ApplicationCommandOptionType::String)
+    }
+}
+
+#[async_trait::async_trait]
+impl SlashArgument for serenity::Emoji {
+    async fn extract(
+        ctx: &serenity::Context,
+        guild: Option<serenity::GuildId>,
+        _: Option<serenity::ChannelId>,
+        value: &serde_json::Value,
+    ) -> Result<Self, SlashArgError> {
+        let string = value
+            .as_str()
+            .ok_or(SlashArgError::CommandStructureMismatch("expected string"))?;
+        parse_guild_emoji(ctx, guild, string)
+            .await
+            .map_err(|e| SlashArgError::Parse(e.into()))
+    }
+
+    fn create(
+        builder: &mut serenity::CreateApplicationCommandOption,
+    ) -> &mut serenity::CreateApplicationCommandOption {
+        builder.kind(serenity::ApplicationCommandOptionType::String)
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_emoji_syntax() {
+    assert_eq!(
+        parse_custom_emoji_syntax("<:ferris:123>"),
+        Some((false, "ferris", serenity::EmojiId(123)))
+    );
+    assert_eq!(
+        parse_custom_emoji_syntax("<a:ferris:123>"),
+        Some((true, "ferris", serenity::EmojiId(123)))
+    );
+    assert_eq!(parse_custom_emoji_syntax(":ferris:"), None);
+
+    assert!(is_unicode_emoji("👍"));
+    assert!(is_unicode_emoji("1️⃣"));
+    assert!(!is_unicode_emoji("ferris"));
+    assert!(!is_unicode_emoji("👍 👍"));
+}
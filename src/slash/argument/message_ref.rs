@@ -0,0 +1,158 @@
+//! Parameter type for references to existing messages
+
+use crate::{serenity_prelude as serenity, SlashArgError, SlashArgument};
+
+/// Error returned when a [`MessageRef`] argument couldn't be parsed or resolved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MessageRefError {
+    /// The input is neither a message link nor a message ID
+    Invalid(String),
+    /// The input refers to a message which doesn't exist or isn't accessible to the bot
+    NotFound(String),
+}
+impl std::fmt::Display for MessageRefError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Invalid(input) => write!(f, "\"{}\" is not a message link or ID", input),
+            Self::NotFound(input) => write!(f, "message \"{}\" not found", input),
+        }
+    }
+}
+impl std::error::Error for MessageRefError {}
+
+/// A command parameter type which resolves to an existing message
+///
+/// Accepts message links (`https://discord.com/channels/<guild>/<channel>/<message>`), channel and
+/// message ID pairs (`<channel>-<message>` as copied by shift-clicking "Copy ID", or
+/// `<channel>/<message>`) and plain IDs of messages in the channel the command was invoked in. In
+/// prefix commands, the argument may also be omitted when replying to the message.
+///
+/// The message is fetched from the cache if possible and via HTTP otherwise.
+#[derive(Debug, Clone)]
+pub struct MessageRef(pub serenity::Message);
+
+impl std::ops::Deref for MessageRef {
+    type Target = serenity::Message;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<MessageRef> for serenity::Message {
+    fn from(message: MessageRef) -> Self {
+        message.0
+    }
+}
+
+/// Parse a message link like `https://discord.com/channels/<guild>/<channel>/<message>`. The guild
+/// is `@me` for DM channels
+fn parse_message_link(input: &str) -> Option<(serenity::ChannelId, serenity::MessageId)> {
+    let input = input.trim_start_matches('<').trim_end_matches('>');
+    let input = input
+        .strip_prefix("https://")
+        .or_else(|| input.strip_prefix("http://"))?;
+    let mut parts = input.splitn(2, '/');
+    let host = parts.next()?;
+    let path = parts.next()?.strip_prefix("channels/")?;
+
+    // Also covers ptb.discord.com and canary.discord.com
+    let is_discord_host = ["discord.com", "discordapp.com"]
+        .iter()
+        .any(|domain| host == *domain || host.ends_with(&format!(".{}", domain)));
+    if !is_discord_host {
+        return None;
+    }
+
+    let parts = path.split('/').collect::<Vec<_>>();
+    match *parts.as_slice() {
+        [_guild, channel, message] => Some((
+            serenity::ChannelId(channel.parse().ok()?),
+            serenity::MessageId(message.parse().ok()?),
+        )),
+        _ => None,
+    }
+}
+
+/// Parse a channel and message ID pair like `<channel>-<message>` or `<channel>/<message>`
+fn parse_id_pair(input: &str) -> Option<(serenity::ChannelId, serenity::MessageId)> {
+    let mut parts = input.splitn(2, |c| c == '-' || c == '/');
+    let channel = parts.next()?.parse().ok()?;
+    let message = parts.next()?.parse().ok()?;
+    Some((serenity::ChannelId(channel), serenity::MessageId(message)))
+}
+
+/// Resolve a message link, channel and message ID pair, or ID of a message in the given channel
+/// into the message. See [`MessageRef`]
+pub async fn parse_message_ref(
+    ctx: &serenity::Context,
+    channel_id: Option<serenity::ChannelId>,
+    input: &str,
+) -> Result<serenity::Message, MessageRefError> {
+    let trimmed_input = input.trim();
+    let plain_id = || {
+        let message_id = trimmed_input.parse().ok()?;
+        Some((channel_id?, serenity::MessageId(message_id)))
+    };
+    let (channel_id, message_id) = parse_message_link(trimmed_input)
+        .or_else(|| parse_id_pair(trimmed_input))
+        .or_else(plain_id)
+        .ok_or_else(|| MessageRefError::Invalid(input.to_owned()))?;
+
+    channel_id
+        .message(ctx, message_id)
+        .await
+        .map_err(|_| MessageRefError::NotFound(input.to_owned()))
+}
+
+#[async_trait::async_trait]
+impl SlashArgument for MessageRef {
+    async fn extract(
+        ctx: &serenity::Context,
+        _: Option<serenity::GuildId>,
+        channel_id: Option<serenity::ChannelId>,
+        value: &serde_json::Value,
+    ) -> Result<Self, SlashArgError> {
+        let string = value
+            .as_str()
+            .ok_or(SlashArgError::CommandStructureMismatch("expected string"))?;
+        parse_message_ref(ctx, channel_id, string)
+            .await
+            .map(Self)
+            .map_err(|e| SlashArgError::Parse(e.into()))
+    }
+
+    fn create(
+        builder: &mut serenity::CreateApplicationCommandOption,
+    ) -> &mut serenity::CreateApplicationCommandOption {
+        builder.kind(serenity::ApplicationCommandOptionType::String)
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_parse_message_ref() {
+    let expected = Some((serenity::ChannelId(456), serenity::MessageId(789)));
+    for &input in &[
+        "https://discord.com/channels/123/456/789",
+        "<https://discord.com/channels/123/456/789>",
+        "https://canary.discord.com/channels/123/456/789",
+        "https://discordapp.com/channels/@me/456/789",
+    ] {
+        assert_eq!(parse_message_link(input), expected);
+    }
+    for &input in &[
+        "https://example.com/channels/123/456/789",
+        "https://notdiscord.com/channels/123/456/789",
+        "https://discord.com/channels/123/456",
+        "https://discord.com/channels/123/456/789/0",
+        "discord.com/channels/123/456/789",
+    ] {
+        assert_eq!(parse_message_link(input), None);
+    }
+
+    assert_eq!(parse_id_pair("456-789"), expected);
+    assert_eq!(parse_id_pair("456/789"), expected);
+    assert_eq!(parse_id_pair("456"), None);
+    assert_eq!(parse_id_pair("456-abc"), None);
+}
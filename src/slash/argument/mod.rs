@@ -6,3 +6,29 @@ pub use context_menu::*;
 
 mod autocomplete;
 pub use autocomplete::*;
+
+mod datetime;
+pub use datetime::*;
+
+mod colour;
+pub use colour::*;
+
+mod attachment;
+pub use attachment::*;
+
+mod emoji;
+pub use emoji::*;
+
+mod any_id;
+pub use any_id::*;
+
+mod command_mention;
+pub use command_mention::*;
+
+mod message_ref;
+pub use message_ref::*;
+
+#[cfg(feature = "url")]
+mod url;
+#[cfg(feature = "url")]
+pub use self::url::*;
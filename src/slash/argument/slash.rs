@@ -63,6 +63,18 @@ pub trait SlashArgument: Sized {
     fn create(
         builder: &mut serenity::CreateApplicationCommandOption,
     ) -> &mut serenity::CreateApplicationCommandOption;
+
+    /// Built-in autocomplete suggestions for the given partial input, for types which register
+    /// themselves as autocompleted string options in [`Self::create`]. The suggestions are
+    /// submitted as-is and must be accepted by [`Self::extract`].
+    ///
+    /// Returns None if the type has no built-in autocompletion, which is the default. Used by
+    /// `SlashChoiceParameter` enums with more than 25 variants, which exceed Discord's limit for
+    /// choices.
+    fn autocomplete(partial: &str) -> Option<Vec<String>> {
+        let _ = partial;
+        None
+    }
 }
 
 /// Implemented for all types that can be used as a function parameter in a slash command.
@@ -84,6 +96,14 @@ pub trait SlashArgumentHack<T> {
         self,
         builder: &mut serenity::CreateApplicationCommandOption,
     ) -> &mut serenity::CreateApplicationCommandOption;
+
+    fn autocomplete(self, partial: &str) -> Option<Vec<String>>
+    where
+        Self: Sized,
+    {
+        let _ = partial;
+        None
+    }
 }
 
 /// Handles arbitrary types that can be parsed from string.
@@ -204,6 +224,10 @@ impl<T: SlashArgument + Sync> SlashArgumentHack<T> for &&PhantomData<T> {
     ) -> &mut serenity::CreateApplicationCommandOption {
         <T as SlashArgument>::create(builder)
     }
+
+    fn autocomplete(self, partial: &str) -> Option<Vec<String>> {
+        <T as SlashArgument>::autocomplete(partial)
+    }
 }
 
 // Implement slash argument for a model type that is represented in interactions via an ID
@@ -267,6 +291,27 @@ macro_rules! _parse_slash {
         }
     };
 
+    // Extract Vec<T> which was expanded into multiple numbered options, e.g. `file1`, `file2`
+    ($ctx:ident, $guild_id:ident, $channel_id:ident, $args:ident => $name:ident: VARIADIC($count:literal) Vec<$type:ty $(,)*>) => {
+        {
+            let mut values = Vec::new();
+            for i in 1..=$count {
+                let option_name = format!("{}{}", stringify!($name), i);
+                if let Some(arg) = $args.iter().find(|arg| arg.name == option_name) {
+                    let arg = arg.value
+                        .as_ref()
+                        .ok_or($crate::SlashArgError::CommandStructureMismatch("expected argument value"))?;
+                    values.push(
+                        (&&&&&std::marker::PhantomData::<$type>)
+                        .extract($ctx, $guild_id, Some($channel_id), arg)
+                        .await?
+                    );
+                }
+            }
+            values
+        }
+    };
+
     // Extract #[flag]
     ($ctx:ident, $guild_id:ident, $channel_id:ident, $args:ident => $name:ident: FLAG) => {
         $crate::_parse_slash!($ctx, $guild_id, $channel_id, $args => $name: Option<bool>)
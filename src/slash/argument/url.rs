@@ -0,0 +1,137 @@
+//! Slash command support for validated http(s) URLs
+
+use crate::{serenity_prelude as serenity, ArgumentValidationError, SlashArgError, SlashArgument};
+
+/// Error returned when a URL argument couldn't be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UrlParseError {
+    /// The input is not a URL at all
+    Invalid(::url::ParseError),
+    /// The URL has a scheme other than `http` or `https`
+    NotHttp(String),
+}
+impl std::fmt::Display for UrlParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Invalid(e) => write!(f, "not a valid URL ({})", e),
+            Self::NotHttp(scheme) => write!(f, "expected an http(s) URL, not {}", scheme),
+        }
+    }
+}
+impl std::error::Error for UrlParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Invalid(e) => Some(e),
+            Self::NotHttp(_) => None,
+        }
+    }
+}
+
+/// A command parameter type for `http` and `https` URLs.
+///
+/// Angle brackets, which Discord users put around links to suppress embeds, are stripped. Use the
+/// `#[url_schemes("https")]` and `#[url_hosts("youtube.com", "youtu.be")]` parameter attributes
+/// to further restrict the accepted URLs. Host restrictions include subdomains.
+///
+/// ```rust
+/// let url: poise::HttpUrl = "<https://example.com/path>".parse().unwrap();
+/// assert_eq!(url.as_str(), "https://example.com/path");
+/// assert!("ftp://example.com".parse::<poise::HttpUrl>().is_err());
+/// assert!("example.com".parse::<poise::HttpUrl>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HttpUrl(pub ::url::Url);
+
+impl std::str::FromStr for HttpUrl {
+    type Err = UrlParseError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+        let input = input
+            .strip_prefix('<')
+            .and_then(|x| x.strip_suffix('>'))
+            .unwrap_or(input);
+
+        let url = ::url::Url::parse(input).map_err(UrlParseError::Invalid)?;
+        match url.scheme() {
+            "http" | "https" => Ok(Self(url)),
+            other => Err(UrlParseError::NotHttp(other.to_owned())),
+        }
+    }
+}
+
+impl std::ops::Deref for HttpUrl {
+    type Target = ::url::Url;
+
+    fn deref(&self) -> &::url::Url {
+        &self.0
+    }
+}
+
+impl std::borrow::Borrow<::url::Url> for HttpUrl {
+    fn borrow(&self) -> &::url::Url {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for HttpUrl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[async_trait::async_trait]
+impl SlashArgument for HttpUrl {
+    async fn extract(
+        _: &serenity::Context,
+        _: Option<serenity::GuildId>,
+        _: Option<serenity::ChannelId>,
+        value: &serde_json::Value,
+    ) -> Result<Self, SlashArgError> {
+        let string = value
+            .as_str()
+            .ok_or(SlashArgError::CommandStructureMismatch("expected string"))?;
+        string
+            .parse()
+            .map_err(|e: UrlParseError| SlashArgError::Parse(e.into()))
+    }
+
+    fn create(
+        builder: &mut serenity::CreateApplicationCommandOption,
+    ) -> &mut serenity::CreateApplicationCommandOption {
+        builder.kind(serenity::ApplicationCommandOptionType::String)
+    }
+}
+
+/// Checks the `#[url_schemes(...)]` and `#[url_hosts(...)]` parameter attributes. Empty lists
+/// mean no restriction
+#[doc(hidden)]
+pub fn _validate_url(
+    parameter_name: &'static str,
+    url: &impl std::borrow::Borrow<::url::Url>,
+    schemes: &[&str],
+    hosts: &[&str],
+) -> Result<(), ArgumentValidationError> {
+    let url = url.borrow();
+    let error = |reason| {
+        Err(ArgumentValidationError {
+            parameter_name,
+            reason,
+        })
+    };
+
+    if !schemes.is_empty() && !schemes.iter().any(|scheme| url.scheme() == *scheme) {
+        return error(format!("must start with {}://", schemes.join("://, ")));
+    }
+    if !hosts.is_empty() {
+        let host = url.host_str().unwrap_or("").to_lowercase();
+        let host_allowed = hosts.iter().any(|allowed_host| {
+            let allowed_host = allowed_host.to_lowercase();
+            host == allowed_host || host.ends_with(&format!(".{}", allowed_host))
+        });
+        if !host_allowed {
+            return error(format!("must be a link to {}", hosts.join(", ")));
+        }
+    }
+    Ok(())
+}
@@ -19,6 +19,7 @@ fn send_as_initial_response(
         attachments: _, // discord doesn't support attachments in initial response :(
         components,
         ephemeral,
+        chunk_content: _,
     } = data;
 
     if let Some(content) = content {
@@ -55,6 +56,7 @@ fn send_as_followup_response<'a>(
         attachments,
         components,
         ephemeral,
+        chunk_content: _,
     } = data;
 
     if let Some(content) = content {
@@ -91,29 +93,39 @@ pub async fn send_application_reply<U, E>(
     ctx: ApplicationContext<'_, U, E>,
     builder: impl for<'a, 'b> FnOnce(&'a mut crate::CreateReply<'b>) -> &'a mut crate::CreateReply<'b>,
 ) -> Result<(), serenity::Error> {
-    let interaction = match ctx.interaction {
-        crate::ApplicationCommandOrAutocompleteInteraction::ApplicationCommand(x) => x,
-        crate::ApplicationCommandOrAutocompleteInteraction::Autocomplete(_) => return Ok(()),
-    };
-
     let mut data = crate::CreateReply {
         ephemeral: ctx.command.options().ephemeral,
         ..Default::default()
     };
     builder(&mut data);
+    send_built_application_reply(ctx, data).await?;
+    Ok(())
+}
+
+/// Like [`send_application_reply`], but with an already built reply. Returns the followup
+/// message, if one was sent
+pub(crate) async fn send_built_application_reply<U, E>(
+    ctx: ApplicationContext<'_, U, E>,
+    data: crate::CreateReply<'_>,
+) -> Result<Option<serenity::Message>, serenity::Error> {
+    let interaction = match ctx.interaction {
+        crate::ApplicationCommandOrAutocompleteInteraction::ApplicationCommand(x) => x,
+        crate::ApplicationCommandOrAutocompleteInteraction::Autocomplete(_) => return Ok(None),
+    };
 
     let has_sent_initial_response = ctx
         .has_sent_initial_response
         .load(std::sync::atomic::Ordering::SeqCst);
 
     let allowed_mentions = ctx.framework.options().allowed_mentions.as_ref();
-    if has_sent_initial_response {
-        interaction
+    Ok(if has_sent_initial_response {
+        let followup = interaction
             .create_followup_message(ctx.discord, |f| {
                 send_as_followup_response(data, allowed_mentions, f);
                 f
             })
             .await?;
+        Some(followup)
     } else {
         interaction
             .create_interaction_response(ctx.discord, |r| {
@@ -126,7 +138,6 @@ pub async fn send_application_reply<U, E>(
             .await?;
         ctx.has_sent_initial_response
             .store(true, std::sync::atomic::Ordering::SeqCst);
-    }
-
-    Ok(())
+        None
+    })
 }
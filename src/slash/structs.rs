@@ -68,6 +68,11 @@ pub struct ApplicationContext<'a, U, E> {
     pub discord: &'a serenity::Context,
     /// The interaction which triggered this command execution.
     pub interaction: ApplicationCommandOrAutocompleteInteraction<'a>,
+    /// Slash command arguments
+    ///
+    /// In autocomplete callbacks, these are the options the user has filled in so far, which
+    /// allows suggestions that depend on other parameters. See [`Self::filled_option`].
+    pub args: &'a [serenity::ApplicationCommandInteractionDataOption],
     /// Keeps track of whether an initial response has been sent.
     ///
     /// Discord requires different HTTP endpoints for initial and additional responses.
@@ -92,7 +97,7 @@ impl<U, E> crate::_GetGenerics for ApplicationContext<'_, U, E> {
     type E = E;
 }
 
-impl<U, E> ApplicationContext<'_, U, E> {
+impl<'a, U, E> ApplicationContext<'a, U, E> {
     /// Defer the response, giving the bot multiple minutes to respond without the user seeing an
     /// "interaction failed error".
     ///
@@ -121,6 +126,81 @@ impl<U, E> ApplicationContext<'_, U, E> {
             .store(true, std::sync::atomic::Ordering::SeqCst);
         Ok(())
     }
+
+    /// Returns the raw value of the argument with the given name, if the user has filled it in.
+    ///
+    /// Mostly useful in autocomplete callbacks to narrow down suggestions based on other
+    /// arguments. Note that in autocomplete interactions, Discord doesn't validate arguments, so
+    /// the values may be incomplete or invalid.
+    ///
+    /// ```rust,no_run
+    /// # type Context<'a> = poise::Context<'a, (), ()>;
+    /// async fn autocomplete_channel(ctx: Context<'_>, partial: String) -> Vec<String> {
+    ///     let server = match ctx {
+    ///         poise::Context::Application(ctx) => ctx.filled_option("server"),
+    ///         poise::Context::Prefix(_) => None,
+    ///     };
+    ///     let server = server.and_then(|server| server.as_str()).unwrap_or("");
+    ///     // ... look up channels in the server which match `partial`
+    /// #   vec![]
+    /// }
+    /// ```
+    pub fn filled_option(&self, name: &str) -> Option<&'a serde_json::Value> {
+        self.args
+            .iter()
+            .find(|option| option.name == name)?
+            .value
+            .as_ref()
+    }
+
+    /// Returns the custom data attached to the focused parameter via `#[custom_data = "..."]`, if
+    /// it exists and has type `T`.
+    ///
+    /// This allows sharing a single autocomplete callback between multiple parameters which only
+    /// differ in some configuration.
+    ///
+    /// ```rust,no_run
+    /// # type Context<'a> = poise::Context<'a, (), ()>;
+    /// struct TableConfig {
+    ///     table: &'static str,
+    /// }
+    ///
+    /// async fn autocomplete_from_table(ctx: Context<'_>, partial: String) -> Vec<String> {
+    ///     let config = match ctx {
+    ///         poise::Context::Application(ctx) => ctx.parameter_custom_data::<TableConfig>(),
+    ///         poise::Context::Prefix(_) => None,
+    ///     };
+    ///     let table = config.map_or("default", |config| config.table);
+    ///     // ... query `table` for entries which match `partial`
+    /// #   vec![]
+    /// }
+    ///
+    /// /// Look up a user
+    /// #[poise::command(slash_command)]
+    /// async fn lookup(
+    ///     ctx: Context<'_>,
+    ///     #[description = "User name"]
+    ///     #[autocomplete = "autocomplete_from_table"]
+    ///     #[custom_data = "TableConfig { table: \"users\" }"]
+    ///     name: String,
+    /// ) -> Result<(), ()> {
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn parameter_custom_data<T: std::any::Any>(&self) -> Option<&'a T> {
+        let command = match self.command {
+            crate::ApplicationCommand::Slash(command) => command,
+            crate::ApplicationCommand::ContextMenu(_) => return None,
+        };
+        let focused_option = self.args.iter().find(|option| option.focused)?;
+        command
+            .parameters
+            .iter()
+            .find(|param| param.name == focused_option.name)?
+            .custom_data
+            .as_ref()?
+            .downcast_ref()
+    }
 }
 
 /// Application command specific context to an error in user code
@@ -147,13 +227,47 @@ pub struct ApplicationCommandOptions<U, E> {
     pub on_error: Option<fn(E, ApplicationCommandErrorContext<'_, U, E>) -> BoxFuture<'_, ()>>,
     /// If this function returns false, this command will not be executed.
     pub check: Option<fn(ApplicationContext<'_, U, E>) -> BoxFuture<'_, Result<bool, E>>>,
+    /// How often this command may be invoked. Checked after [`Self::check`]
+    pub cooldowns: crate::Cooldowns,
+    /// Computes the key of the [`crate::CooldownConfig::custom`] cooldown for an invocation. If
+    /// None, the custom cooldown doesn't apply. Called before the arguments are parsed, so the key
+    /// can only depend on the invocation context, not on the arguments
+    pub cooldown_key: Option<fn(crate::Context<'_, U, E>) -> crate::CooldownKey>,
+    /// Name of the [`crate::RateLimitBucket`] in [`crate::FrameworkOptions::rate_limit_buckets`]
+    /// which this command shares with other commands, if any
+    pub bucket: Option<&'static str>,
+    /// If the command runs for longer than this, it's cancelled and
+    /// [`crate::FrameworkOptions::on_command_timeout`] is called.
+    ///
+    /// Falls back to the framework-specified value on None. See there for documentation.
+    pub timeout: Option<std::time::Duration>,
+    /// Limits how many invocations of this command may run at once. Excess invocations wait
+    /// before the command runs, or are passed to
+    /// [`crate::FrameworkOptions::on_concurrency_limit_reached`] if the queue is full.
+    pub concurrency: Option<&'static crate::ConcurrencyLimiter>,
+    /// Name of the [`crate::CommandGroup`] this command was mounted with, if any. Set by
+    /// [`crate::FrameworkOptions::mount`]
+    pub group: Option<&'static str>,
+    /// Falls back to the framework-specified value on None. See there for documentation.
+    pub on_cooldown:
+        Option<for<'a> fn(crate::CooldownHit<'a, U, E>) -> BoxFuture<'a, crate::CooldownFeedback>>,
     /// Whether responses to this command should be ephemeral by default.
     pub ephemeral: bool,
     /// Permissions which a user needs to have so that the application command runs.
     pub required_permissions: serenity::Permissions,
+    /// Roles which a user needs to have so that the application command runs, see
+    /// [`crate::PrefixCommandOptions::required_roles`]
+    pub required_roles: &'static [crate::RequiredRole],
     /// If true, only users from the [owners list](crate::FrameworkOptions::owners) may use this
     /// command.
     pub owners_only: bool,
+    /// If Some, this command may only be invoked in channels of these types. Checked before the
+    /// command runs, other invocations are passed to
+    /// [`crate::FrameworkOptions::on_channel_type_not_allowed`].
+    pub allowed_channel_types: Option<&'static [serenity::ChannelType]>,
+    /// If true, this command may only be invoked in NSFW channels, see
+    /// [`crate::PrefixCommandOptions::nsfw_only`]
+    pub nsfw_only: bool,
 }
 
 impl<U, E> Default for ApplicationCommandOptions<U, E> {
@@ -161,28 +275,117 @@ impl<U, E> Default for ApplicationCommandOptions<U, E> {
         Self {
             on_error: None,
             check: None,
+            cooldowns: crate::Cooldowns::default(),
+            cooldown_key: None,
+            bucket: None,
+            timeout: None,
+            concurrency: None,
+            group: None,
+            on_cooldown: None,
             ephemeral: false,
             required_permissions: serenity::Permissions::empty(),
+            required_roles: &[],
             owners_only: false,
+            allowed_channel_types: None,
+            nsfw_only: false,
         }
     }
 }
 
 /// A single parameter of a slash command
 pub struct SlashCommandParameter<U, E> {
+    /// Name of this parameter, as registered in Discord
+    pub name: &'static str,
     /// Builder function for this parameters
     pub builder: fn(
         &mut serenity::CreateApplicationCommandOption,
     ) -> &mut serenity::CreateApplicationCommandOption,
-    /// Optionally, a callback on autocomplete interactions. If the focused option in the
-    /// autocomplete interaction matches this parameter, an autocomplete response should be sent
+    /// Optionally, a callback on autocomplete interactions. Invoked with the partial input when
+    /// this parameter is the focused option in an autocomplete interaction. Returns a JSON array
+    /// of the autocomplete choices which are sent to Discord.
     pub autocomplete_callback: Option<
         for<'a> fn(
             crate::ApplicationContext<'a, U, E>,
-            &'a serenity::AutocompleteInteraction,
-            &'a [serenity::ApplicationCommandInteractionDataOption],
-        ) -> BoxFuture<'a, Result<(), E>>,
+            &'a serde_json::Value,
+        ) -> BoxFuture<'a, Result<serde_json::Value, E>>,
     >,
+    /// Optionally, static autocomplete choices which are sent instead if the autocomplete
+    /// callback fails. Returns a JSON array of autocomplete choices.
+    pub autocomplete_fallback: Option<fn() -> serde_json::Value>,
+    /// If set, autocomplete choices are cached and reused for repeated partial inputs
+    pub autocomplete_cache: Option<crate::AutocompleteCache>,
+    /// Arbitrary user data attached to this parameter, accessible in autocomplete callbacks via
+    /// [`ApplicationContext::parameter_custom_data`]
+    pub custom_data: Option<Box<dyn std::any::Any + Send + Sync>>,
+    /// Optionally, an autocomplete callback set at runtime via [`Self::set_autocomplete`]. Takes
+    /// precedence over [`Self::autocomplete_callback`].
+    pub autocomplete_closure: Option<
+        Box<
+            dyn Send
+                + Sync
+                + for<'a> Fn(
+                    crate::ApplicationContext<'a, U, E>,
+                    &'a serde_json::Value,
+                ) -> BoxFuture<
+                    'a,
+                    Result<Vec<crate::AutocompleteChoice<serde_json::Value>>, E>,
+                >,
+        >,
+    >,
+}
+
+impl<U, E> SlashCommandParameter<U, E> {
+    /// Set an autocomplete callback for this parameter at runtime, e.g. a closure capturing data
+    /// which is only available once the bot has started.
+    ///
+    /// The callback receives the raw JSON partial input and returns the choices, whose values
+    /// must be JSON of the parameter's type (e.g. a string for `String` parameters).
+    ///
+    /// If the parameter wasn't marked `#[autocomplete]` in the command macro, this must be called
+    /// before the commands are registered in Discord.
+    ///
+    /// ```rust,no_run
+    /// # type Context<'a> = poise::Context<'a, (), ()>;
+    /// /// Load a plugin
+    /// #[poise::command(slash_command)]
+    /// async fn plugin(ctx: Context<'_>, #[description = "Plugin name"] name: String) -> Result<(), ()> {
+    ///     Ok(())
+    /// }
+    ///
+    /// let plugin_names = vec!["music".to_owned(), "moderation".to_owned()];
+    ///
+    /// let mut command = plugin();
+    /// let slash_command = command.slash.as_mut().unwrap();
+    /// slash_command.parameter_mut("name").unwrap().set_autocomplete(move |_ctx, partial| {
+    ///     let partial = partial.as_str().unwrap_or("").to_owned();
+    ///     let choices = poise::fuzzy_filter(partial, plugin_names.iter())
+    ///         .into_iter()
+    ///         .map(|name| poise::AutocompleteChoice::new(name.clone(), name.clone().into()))
+    ///         .collect();
+    ///     Box::pin(async move { Ok(choices) })
+    /// });
+    /// ```
+    pub fn set_autocomplete<F>(&mut self, callback: F)
+    where
+        F: Send
+            + Sync
+            + 'static
+            + for<'a> Fn(
+                crate::ApplicationContext<'a, U, E>,
+                &'a serde_json::Value,
+            ) -> BoxFuture<
+                'a,
+                Result<Vec<crate::AutocompleteChoice<serde_json::Value>>, E>,
+            >,
+    {
+        self.autocomplete_closure = Some(Box::new(callback));
+    }
+
+    /// Whether this parameter has an autocomplete callback, either from the command macro or set
+    /// at runtime
+    pub fn has_autocomplete(&self) -> bool {
+        self.autocomplete_callback.is_some() || self.autocomplete_closure.is_some()
+    }
 }
 
 /// Fully defines a single slash command in the framework
@@ -202,6 +405,13 @@ pub struct SlashCommand<U, E> {
     pub options: ApplicationCommandOptions<U, E>,
 }
 
+impl<U, E> SlashCommand<U, E> {
+    /// Returns the parameter with the given name, as registered in Discord
+    pub fn parameter_mut(&mut self, name: &str) -> Option<&mut SlashCommandParameter<U, E>> {
+        self.parameters.iter_mut().find(|param| param.name == name)
+    }
+}
+
 /// A single slash command or slash command group
 pub enum SlashCommandMeta<U, E> {
     /// Single slash command
@@ -258,6 +468,9 @@ impl<U, E> SlashCommandMeta<U, E> {
                 for param in &command.parameters {
                     let mut option = serenity::CreateApplicationCommandOption::default();
                     (param.builder)(&mut option);
+                    if param.autocomplete_closure.is_some() {
+                        option.set_autocomplete(true);
+                    }
                     builder.add_sub_option(option);
                 }
             }
@@ -289,6 +502,9 @@ impl<U, E> SlashCommandMeta<U, E> {
                 for param in &command.parameters {
                     let mut option = serenity::CreateApplicationCommandOption::default();
                     (param.builder)(&mut option);
+                    if param.autocomplete_closure.is_some() {
+                        option.set_autocomplete(true);
+                    }
                     interaction.add_option(option);
                 }
             }
@@ -325,6 +541,14 @@ pub enum ApplicationCommandTree<U, E> {
 }
 
 impl<U, E> ApplicationCommandTree<U, E> {
+    /// Returns the name of this slash command, command group or context menu command
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Slash(cmd) => cmd.name(),
+            Self::ContextMenu(cmd) => cmd.name,
+        }
+    }
+
     /// Instruct this application command to register itself in the given builder
     pub fn create<'b>(
         &self,
@@ -383,6 +607,8 @@ pub struct ApplicationFrameworkOptions<U, E> {
     /// This handler should be used to reply with some form of error message. If this handler does
     /// nothing, the user will be shown "Interaction failed" by their Discord client.
     pub missing_permissions_handler: fn(ApplicationContext<'_, U, E>) -> BoxFuture<'_, ()>,
+    /// How to shorten autocomplete choice names that exceed Discord's length limit
+    pub autocomplete_truncation: crate::AutocompleteTruncation,
 }
 
 impl<U: Send + Sync, E> Default for ApplicationFrameworkOptions<U, E> {
@@ -391,15 +617,14 @@ impl<U: Send + Sync, E> Default for ApplicationFrameworkOptions<U, E> {
             commands: Vec::new(),
             missing_permissions_handler: |ctx| {
                 Box::pin(async move {
-                    let response = format!(
-                        "You don't have the required permissions for `/{}`",
-                        ctx.command.slash_or_context_menu_name()
-                    );
-                    let _: Result<_, _> =
-                        crate::send_application_reply(ctx, |f| f.content(response).ephemeral(true))
-                            .await;
+                    crate::builtins::missing_permissions_reply(
+                        ctx,
+                        &crate::builtins::ErrorMessages::DEFAULT,
+                    )
+                    .await;
                 })
             },
+            autocomplete_truncation: crate::AutocompleteTruncation::default(),
         }
     }
 }
@@ -61,6 +61,22 @@ impl<'a, U, E> Context<'a, U, E> {
         })
     }
 
+    /// Starts a typing indicator in the channel the command was invoked in, which is broadcasted until
+    /// the return value is dropped.
+    ///
+    /// ```rust,ignore
+    /// let _typing = ctx.typing()?;
+    /// let answer = some_slow_computation().await;
+    /// ctx.say(answer).await?;
+    /// ```
+    ///
+    /// See also [`crate::PrefixCommandOptions::broadcast_typing_after`] to only start the typing
+    /// indicator if a command takes long.
+    #[must_use = "The typing broadcast will only persist if you store it"]
+    pub fn typing(&self) -> Result<serenity::Typing, serenity::Error> {
+        self.channel_id().start_typing(&self.discord().http)
+    }
+
     /// Shorthand of [`crate::say_reply`]
     pub async fn say(
         self,
@@ -116,6 +132,24 @@ impl<'a, U, E> Context<'a, U, E> {
         }
     }
 
+    /// Return a reference to a value added with [`crate::FrameworkBuilder::extension`], if there
+    /// is one of this type. See [`crate::Extensions`]
+    pub fn data_ext<T: std::any::Any + Send + Sync>(&self) -> Option<&'a T> {
+        self.framework().extensions().get()
+    }
+
+    /// The settings of the guild this command was invoked in, or None in DMs and if there's no
+    /// [`crate::FrameworkOptions::guild_settings`]
+    pub async fn guild_settings(&self) -> Option<crate::GuildSettings> {
+        crate::settings_of(self.framework(), self.guild_id()).await
+    }
+
+    /// The connection status and heartbeat latency of the shard which received this invocation.
+    /// See [`crate::Framework::shard_statuses`] for all shards
+    pub async fn shard_info(&self) -> Option<crate::ShardStatus> {
+        self.framework().shard_status(self.discord().shard_id).await
+    }
+
     /// Return the channel ID of this context
     pub fn channel_id(&self) -> serenity::ChannelId {
         match self {
@@ -276,6 +310,10 @@ impl<'a, U, E> CommandErrorContext<'a, U, E> {
 }
 
 /// Contains the location of the error with location-specific context
+///
+/// New error locations may be added in the future, so matches need a wildcard arm. Note that
+/// [`Self::BackgroundTask`] was added after the first release, which breaks exhaustive matches.
+#[non_exhaustive]
 pub enum ErrorContext<'a, U, E> {
     /// Error in user data setup
     Setup,
@@ -285,6 +323,8 @@ pub enum ErrorContext<'a, U, E> {
     Command(CommandErrorContext<'a, U, E>),
     /// Error in autocomplete callback
     Autocomplete(crate::ApplicationCommandErrorContext<'a, U, E>),
+    /// Error in the [`crate::BackgroundTask`] with this name
+    BackgroundTask(&'static str),
 }
 
 impl<U, E> Clone for ErrorContext<'_, U, E> {
@@ -294,6 +334,7 @@ impl<U, E> Clone for ErrorContext<'_, U, E> {
             Self::Listener(x) => Self::Listener(x),
             Self::Command(x) => Self::Command(x.clone()),
             Self::Autocomplete(x) => Self::Autocomplete(x.clone()),
+            Self::BackgroundTask(x) => Self::BackgroundTask(x),
         }
     }
 }
@@ -306,6 +347,50 @@ pub struct CommandBuilder<U, E> {
 }
 
 impl<U, E> CommandBuilder<U, E> {
+    /// Runs the meta builder on the command definition and returns the resulting top-level prefix
+    /// command and application commands
+    pub(crate) fn build(
+        definition: crate::CommandDefinition<U, E>,
+        meta_builder: impl FnOnce(&mut Self) -> &mut Self,
+    ) -> (
+        Option<crate::PrefixCommandMeta<U, E>>,
+        Vec<crate::ApplicationCommandTree<U, E>>,
+    ) {
+        // TODO: remove duplication with CommandBuilder::subcommand
+
+        let crate::CommandDefinition {
+            prefix: prefix_command,
+            slash: slash_command,
+            context_menu: context_menu_command,
+        } = definition;
+
+        let prefix_command = prefix_command.map(|prefix_command| crate::PrefixCommandMeta {
+            command: prefix_command,
+            category: None,
+            subcommands: Vec::new(),
+        });
+
+        let slash_command = slash_command.map(crate::SlashCommandMeta::Command);
+
+        let mut builder = CommandBuilder {
+            prefix_command,
+            slash_command,
+            context_menu_command,
+        };
+        meta_builder(&mut builder);
+
+        let mut application_commands = Vec::new();
+        if let Some(slash_command) = builder.slash_command {
+            application_commands.push(crate::ApplicationCommandTree::Slash(slash_command));
+        }
+        if let Some(context_menu_command) = builder.context_menu_command {
+            application_commands.push(crate::ApplicationCommandTree::ContextMenu(
+                context_menu_command,
+            ));
+        }
+        (builder.prefix_command, application_commands)
+    }
+
     /// Assign a category to this command, which can be used by help commands to group commands
     pub fn category(&mut self, category: &'static str) -> &mut Self {
         if let Some(prefix_command) = &mut self.prefix_command {
@@ -373,15 +458,104 @@ impl<U, E> CommandBuilder<U, E> {
 pub struct FrameworkOptions<U, E> {
     /// Provide a callback to be invoked when any user code yields an error.
     pub on_error: fn(E, ErrorContext<'_, U, E>) -> BoxFuture<'_, ()>,
+    /// Called when a command panics. The panic is caught, so the bot keeps running.
+    ///
+    /// By default, the panic message is printed.
+    pub on_command_panic: fn(crate::CommandPanic<'_, U, E>) -> BoxFuture<'_, ()>,
+    /// How long commands may run before they're cancelled, unless the command sets its own
+    /// timeout. None by default, so commands may run forever
+    pub command_timeout: Option<std::time::Duration>,
+    /// Called when a command was cancelled because it exceeded its timeout.
+    ///
+    /// By default, the user is told that the command timed out. For application commands, this
+    /// also resolves a deferred response which would otherwise be stuck at "thinking".
+    pub on_command_timeout: fn(crate::CommandTimeout<'_, U, E>) -> BoxFuture<'_, ()>,
     /// Called before every command
     pub pre_command: fn(Context<'_, U, E>) -> BoxFuture<'_, ()>,
     /// Called after every command
     pub post_command: fn(Context<'_, U, E>) -> BoxFuture<'_, ()>,
+    /// Called by [`crate::Framework::shutdown_graceful`] once the running commands finished, before
+    /// the shards are disconnected. Can be used to flush state to disk, for example
+    pub on_shutdown: fn(&crate::Framework<U, E>) -> BoxFuture<'_, ()>,
+    /// Layers wrapped around the execution of every command, outermost first. See
+    /// [`crate::Middleware`]
+    pub middleware: Vec<crate::Middleware<U, E>>,
+    /// If Some, receives an event whenever a command starts and finishes. See
+    /// [`crate::MetricsSink`]
+    pub metrics_sink: Option<Box<dyn crate::MetricsSink>>,
     /// Provide a callback to be invoked before every command. The command will only be executed
     /// if the callback returns true.
     ///
     /// If individual commands add their own check, both callbacks are run and must return true.
     pub command_check: Option<fn(Context<'_, U, E>) -> BoxFuture<'_, Result<bool, E>>>,
+    /// The groups mounted with [`Self::mount`], without their commands
+    pub command_groups: Vec<crate::CommandGroup<U, E>>,
+    /// If Some, every command invocation is first passed to this detector, and dropped silently if
+    /// it's deemed spam. This happens before any checks or cooldowns.
+    ///
+    /// See [`crate::TokenBucketThrottle`] for a ready-made implementation.
+    pub abuse_detector: Option<Box<dyn crate::AbuseDetector>>,
+    /// Called when a command is invoked while it's on cooldown, to decide how to respond. Can be
+    /// overridden per command.
+    ///
+    /// By default, replies with the remaining cooldown time (ephemerally for application
+    /// commands).
+    pub on_cooldown:
+        for<'a> fn(crate::CooldownHit<'a, U, E>) -> BoxFuture<'a, crate::CooldownFeedback>,
+    /// Called when a command is invoked by a user who lacks some of its
+    /// [required roles](crate::PrefixCommandOptions::required_roles).
+    ///
+    /// By default, replies with the missing roles (ephemerally for application commands), see
+    /// [`crate::builtins::missing_roles_reply`].
+    pub on_missing_roles: for<'a> fn(crate::MissingRoles<'a, U, E>) -> BoxFuture<'a, ()>,
+    /// Called when a command is invoked in a channel whose type isn't in its
+    /// [allowed channel types](crate::PrefixCommandOptions::allowed_channel_types).
+    ///
+    /// By default, replies with the error message (ephemerally for application commands).
+    pub on_channel_type_not_allowed:
+        for<'a> fn(Context<'a, U, E>, crate::ChannelTypeNotAllowed) -> BoxFuture<'a, ()>,
+    /// Called when an [`nsfw_only`](crate::PrefixCommandOptions::nsfw_only) command is invoked
+    /// outside of an NSFW channel.
+    ///
+    /// By default, replies with the error message (ephemerally for application commands).
+    pub on_nsfw_channel_required:
+        for<'a> fn(Context<'a, U, E>, crate::NsfwChannelRequired) -> BoxFuture<'a, ()>,
+    /// Called when a command is invoked while its
+    /// [concurrency limit](crate::PrefixCommandOptions::concurrency) is reached and its queue is
+    /// full.
+    ///
+    /// By default, replies with the error message (ephemerally for application commands).
+    pub on_concurrency_limit_reached:
+        for<'a> fn(Context<'a, U, E>, crate::ConcurrencyLimitReached) -> BoxFuture<'a, ()>,
+    /// If Some, command cooldowns are tracked in this store instead of in memory, for example to
+    /// keep them across restarts. See [`crate::CooldownStore`]
+    pub cooldown_store: Option<Box<dyn crate::CooldownStore>>,
+    /// Rate limits shared by the commands which declare them with `bucket = "name"`, by name. See
+    /// [`crate::RateLimitBucket`]
+    pub rate_limit_buckets: std::collections::HashMap<String, crate::RateLimitBucket>,
+    /// If Some, commands can be disabled per guild or channel through this store. Both prefix and
+    /// application commands are checked before they run.
+    ///
+    /// See [`crate::builtins::disable_command`] and [`crate::builtins::enable_command`] for
+    /// commands which modify the store.
+    pub command_filter_store: Option<Box<dyn crate::CommandFilterStore>>,
+    /// If Some, per-guild settings like prefix, locale and disabled categories are looked up in
+    /// this provider. See [`crate::GuildSettingsProvider`]
+    pub guild_settings: Option<Box<dyn crate::GuildSettingsProvider>>,
+    /// Called when a command is invoked where it's disabled by [`Self::command_filter_store`] or
+    /// by the [disabled categories](crate::GuildSettings::disabled_categories) of the guild.
+    ///
+    /// By default, application commands get an ephemeral reply and prefix commands are ignored.
+    pub on_command_disabled: fn(Context<'_, U, E>) -> BoxFuture<'_, ()>,
+    /// If Some, commands are only available to the guilds and users this gate enables them for,
+    /// for gradual rollouts and beta commands. See [`crate::FeatureGate`]
+    pub feature_gate: Option<Box<dyn crate::FeatureGate>>,
+    /// Called when a command is invoked by a user for whom it's gated off by
+    /// [`Self::feature_gate`]. The command doesn't run, and [`Self::on_error`] isn't called.
+    ///
+    /// By default, application commands get an ephemeral reply and prefix commands are ignored,
+    /// as if the command didn't exist.
+    pub on_command_gated: fn(Context<'_, U, E>) -> BoxFuture<'_, ()>,
     /// Default set of allowed mentions to use for all responses
     pub allowed_mentions: Option<serenity::CreateAllowedMentions>,
     /// Called on every Discord event. Can be used to react to non-command events, like messages
@@ -392,6 +566,26 @@ pub struct FrameworkOptions<U, E> {
         &'a crate::Framework<U, E>,
         &'a U,
     ) -> BoxFuture<'a, Result<(), E>>,
+    /// Receive every Discord event after [`Self::listener`], with one method per event type. See
+    /// [`crate::EventSubscriber`]
+    pub event_subscribers: Vec<Box<dyn crate::EventSubscriber<U, E>>>,
+    /// If Some, called with the gateway events whose type serenity doesn't model yet, with the
+    /// event name (like `GUILD_SOUNDBOARD_SOUNDS_UPDATE`) and the JSON payload. These events
+    /// never reach [`Self::listener`], so this is the place to log or handle them.
+    ///
+    /// Events which serenity models are only available deserialized, through [`Self::listener`];
+    /// their fields which serenity doesn't model are dropped.
+    ///
+    /// Runs on the same gateway connection as the framework, alongside the regular event
+    /// handling.
+    pub unknown_event_listener: Option<
+        for<'a> fn(
+            &'a serenity::Context,
+            &'a str,
+            &'a serde_json::Value,
+            &'a crate::Framework<U, E>,
+        ) -> BoxFuture<'a, ()>,
+    >,
     /// Application command specific options.
     pub application_options: crate::ApplicationFrameworkOptions<U, E>,
     /// Prefix command specific options.
@@ -424,44 +618,35 @@ impl<U, E> FrameworkOptions<U, E> {
         definition: crate::CommandDefinition<U, E>,
         meta_builder: impl FnOnce(&mut CommandBuilder<U, E>) -> &mut CommandBuilder<U, E>,
     ) {
-        // TODO: remove duplication with CommandBuilder::subcommand
-
-        let crate::CommandDefinition {
-            prefix: prefix_command,
-            slash: slash_command,
-            context_menu: context_menu_command,
-        } = definition;
-
-        let prefix_command = prefix_command.map(|prefix_command| crate::PrefixCommandMeta {
-            command: prefix_command,
-            category: None,
-            subcommands: Vec::new(),
-        });
-
-        let slash_command = slash_command.map(crate::SlashCommandMeta::Command);
-
-        let mut builder = CommandBuilder {
-            prefix_command,
-            slash_command,
-            context_menu_command,
-        };
-        meta_builder(&mut builder);
-
-        if let Some(prefix_command) = builder.prefix_command {
+        let (prefix_command, application_commands) =
+            CommandBuilder::build(definition, meta_builder);
+        if let Some(prefix_command) = prefix_command {
             self.prefix_options.commands.push(prefix_command);
         }
-        if let Some(slash_command) = builder.slash_command {
-            self.application_options
-                .commands
-                .push(crate::ApplicationCommandTree::Slash(slash_command));
-        }
-        if let Some(context_menu_command) = builder.context_menu_command {
-            self.application_options
-                .commands
-                .push(crate::ApplicationCommandTree::ContextMenu(
-                    context_menu_command,
-                ));
-        }
+        self.application_options
+            .commands
+            .extend(application_commands);
+    }
+
+    /// Add the commands of a group to the framework, with the group's prefix, check, error
+    /// handler and cooldowns. See [`crate::CommandGroup`]
+    ///
+    /// # Panics
+    ///
+    /// Panics if a group with the same name was already mounted, since commands refer to their
+    /// group by name.
+    pub fn mount(&mut self, mut group: crate::CommandGroup<U, E>) {
+        assert!(
+            self.command_groups.iter().all(|g| g.name() != group.name()),
+            "command group {:?} is mounted more than once",
+            group.name(),
+        );
+        let (prefix_commands, application_commands) = group.take_commands();
+        self.prefix_options.commands.extend(prefix_commands);
+        self.application_options
+            .commands
+            .extend(application_commands);
+        self.command_groups.push(group);
     }
 }
 
@@ -503,13 +688,113 @@ impl<U: Send + Sync, E: std::fmt::Display + Send> Default for FrameworkOptions<U
                                 cmd.name, error
                             ),
                         },
+                        ErrorContext::BackgroundTask(name) => {
+                            println!("Error in background task \"{}\": {}", name, error)
+                        }
+                    }
+                })
+            },
+            on_command_panic: |panic| {
+                Box::pin(async move {
+                    let command_name = match panic.ctx.command() {
+                        Some(command) => command.name(),
+                        None => "",
+                    };
+                    println!(
+                        "Command \"{}\" panicked: {}",
+                        command_name,
+                        panic.message().unwrap_or("<non-string panic payload>")
+                    );
+                })
+            },
+            command_timeout: None,
+            on_command_timeout: |timeout| {
+                Box::pin(async move {
+                    let text = format!(
+                        "The command took too long and was cancelled after {}",
+                        crate::builtins::humanize_duration(timeout.timeout, "en")
+                    );
+                    if let Err(e) = timeout.ctx.say(text).await {
+                        println!("Failed to send timeout message: {}", e);
                     }
                 })
             },
             listener: |_, _, _, _| Box::pin(async { Ok(()) }),
+            event_subscribers: Vec::new(),
+            unknown_event_listener: None,
             pre_command: |_| Box::pin(async {}),
             post_command: |_| Box::pin(async {}),
+            on_shutdown: |_| Box::pin(async {}),
+            middleware: Vec::new(),
+            metrics_sink: None,
             command_check: None,
+            command_groups: Vec::new(),
+            abuse_detector: None,
+            on_cooldown: |hit| {
+                Box::pin(async move {
+                    crate::builtins::cooldown_feedback(
+                        &hit,
+                        &crate::builtins::ErrorMessages::DEFAULT,
+                    )
+                    .await
+                })
+            },
+            on_missing_roles: |missing| {
+                Box::pin(async move {
+                    crate::builtins::missing_roles_reply(
+                        &missing,
+                        &crate::builtins::ErrorMessages::DEFAULT,
+                    )
+                    .await
+                })
+            },
+            on_channel_type_not_allowed: |ctx, error| {
+                Box::pin(async move {
+                    let _: Result<_, _> =
+                        crate::send_reply(ctx, |f| f.content(error.to_string()).ephemeral(true))
+                            .await;
+                })
+            },
+            on_nsfw_channel_required: |ctx, error| {
+                Box::pin(async move {
+                    let _: Result<_, _> =
+                        crate::send_reply(ctx, |f| f.content(error.to_string()).ephemeral(true))
+                            .await;
+                })
+            },
+            on_concurrency_limit_reached: |ctx, error| {
+                Box::pin(async move {
+                    let _: Result<_, _> =
+                        crate::send_reply(ctx, |f| f.content(error.to_string()).ephemeral(true))
+                            .await;
+                })
+            },
+            cooldown_store: None,
+            rate_limit_buckets: Default::default(),
+            command_filter_store: None,
+            guild_settings: None,
+            on_command_disabled: |ctx| {
+                Box::pin(async move {
+                    if let Context::Application(_) = ctx {
+                        let _: Result<_, _> = crate::send_reply(ctx, |f| {
+                            f.content("This command is disabled here").ephemeral(true)
+                        })
+                        .await;
+                    }
+                })
+            },
+            feature_gate: None,
+            on_command_gated: |ctx| {
+                Box::pin(async move {
+                    if let Context::Application(_) = ctx {
+                        let _: Result<_, _> = crate::send_reply(ctx, |f| {
+                            f.content("This command isn't available to you yet")
+                                .ephemeral(true)
+                        })
+                        .await;
+                    }
+                })
+            },
             allowed_mentions: Some({
                 let mut f = serenity::CreateAllowedMentions::default();
                 // Only support direct user pings by default
@@ -0,0 +1,215 @@
+//! Cheap framework-wide spam protection, see [`AbuseDetector`]
+
+use crate::serenity_prelude as serenity;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Decides whether a command invocation is spam that should be dropped, used by
+/// [`crate::FrameworkOptions::abuse_detector`].
+///
+/// Called for every prefix command invocation and application command interaction, before command
+/// checks and cooldowns. Dropped invocations are ignored silently, and don't count towards any
+/// cooldown. Since this runs so often, implementations should be fast and must not block.
+pub trait AbuseDetector: Send + Sync {
+    /// Returns true if the invocation should be dropped
+    fn is_abusive(
+        &self,
+        user_id: serenity::UserId,
+        guild_id: Option<serenity::GuildId>,
+        channel_id: serenity::ChannelId,
+    ) -> bool;
+}
+
+/// Size and refill rate of a token bucket, see [`TokenBucketThrottle`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenBucketConfig {
+    /// Maximum number of invocations in a burst
+    pub capacity: u32,
+    /// How long it takes to regain one invocation
+    pub refill_interval: Duration,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(config: TokenBucketConfig, now: Instant) -> Self {
+        Self {
+            tokens: config.capacity as f64,
+            last_refill: now,
+        }
+    }
+
+    fn refill(&mut self, config: TokenBucketConfig, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        let refilled = elapsed.as_secs_f64() / config.refill_interval.as_secs_f64();
+        self.tokens = (self.tokens + refilled).min(config.capacity as f64);
+        self.last_refill = now;
+    }
+
+    fn has_token(&self) -> bool {
+        self.tokens >= 1.0
+    }
+
+    /// Whether the bucket would be full by now, in which case it doesn't need to be stored anymore
+    fn is_full(&self, config: TokenBucketConfig, now: Instant) -> bool {
+        let mut bucket = *self;
+        bucket.refill(config, now);
+        bucket.tokens >= config.capacity as f64
+    }
+}
+
+/// Buckets of one kind, e.g. one per user
+#[derive(Debug)]
+struct TokenBuckets<K> {
+    config: TokenBucketConfig,
+    buckets: HashMap<K, TokenBucket>,
+}
+
+impl<K: std::hash::Hash + Eq> TokenBuckets<K> {
+    /// Above this many stored buckets, full buckets are removed to bound memory usage
+    const PRUNE_THRESHOLD: usize = 1000;
+
+    fn new(config: TokenBucketConfig) -> Self {
+        Self {
+            config,
+            buckets: HashMap::new(),
+        }
+    }
+
+    fn bucket(&mut self, key: K, now: Instant) -> &mut TokenBucket {
+        if self.buckets.len() > Self::PRUNE_THRESHOLD {
+            let config = self.config;
+            self.buckets
+                .retain(|_, bucket| !bucket.is_full(config, now));
+        }
+
+        let config = self.config;
+        let bucket = self
+            .buckets
+            .entry(key)
+            .or_insert_with(|| TokenBucket::new(config, now));
+        bucket.refill(config, now);
+        bucket
+    }
+}
+
+#[derive(Debug)]
+struct TokenBucketThrottleState {
+    users: Option<TokenBuckets<serenity::UserId>>,
+    channels: Option<TokenBuckets<serenity::ChannelId>>,
+}
+
+/// An [`AbuseDetector`] with a token bucket per user and per channel. Each invocation takes a
+/// token from the bucket of the invoking user and of the channel; if either is empty, the
+/// invocation is dropped.
+///
+/// ```rust
+/// # use std::time::Duration;
+/// // Bursts of up to 5 invocations, then one every 2 seconds
+/// let throttle = poise::TokenBucketThrottle::new(
+///     Some(poise::TokenBucketConfig { capacity: 5, refill_interval: Duration::from_secs(2) }),
+///     None,
+/// );
+/// ```
+#[derive(Debug)]
+pub struct TokenBucketThrottle {
+    state: parking_lot::Mutex<TokenBucketThrottleState>,
+}
+
+impl TokenBucketThrottle {
+    /// Create a throttle with the given bucket configurations. Passing None disables the
+    /// respective buckets.
+    pub fn new(
+        per_user: Option<TokenBucketConfig>,
+        per_channel: Option<TokenBucketConfig>,
+    ) -> Self {
+        Self {
+            state: parking_lot::Mutex::new(TokenBucketThrottleState {
+                users: per_user.map(TokenBuckets::new),
+                channels: per_channel.map(TokenBuckets::new),
+            }),
+        }
+    }
+
+    fn is_throttled(
+        &self,
+        user_id: serenity::UserId,
+        channel_id: serenity::ChannelId,
+        now: Instant,
+    ) -> bool {
+        let mut state = self.state.lock();
+        let state = &mut *state;
+        let user_bucket = state.users.as_mut().map(|x| x.bucket(user_id, now));
+        let channel_bucket = state.channels.as_mut().map(|x| x.bucket(channel_id, now));
+
+        // Only take tokens if the invocation goes through, so that spamming doesn't keep the
+        // buckets empty forever
+        let has_tokens = user_bucket.as_ref().map_or(true, |x| x.has_token())
+            && channel_bucket.as_ref().map_or(true, |x| x.has_token());
+        if has_tokens {
+            for bucket in user_bucket.into_iter().chain(channel_bucket) {
+                bucket.tokens -= 1.0;
+            }
+        }
+        !has_tokens
+    }
+}
+
+impl AbuseDetector for TokenBucketThrottle {
+    fn is_abusive(
+        &self,
+        user_id: serenity::UserId,
+        _: Option<serenity::GuildId>,
+        channel_id: serenity::ChannelId,
+    ) -> bool {
+        self.is_throttled(user_id, channel_id, Instant::now())
+    }
+}
+
+/// Whether the invocation should be dropped according to
+/// [`crate::FrameworkOptions::abuse_detector`]
+pub(crate) fn is_abusive_invocation<U, E>(
+    framework: &crate::Framework<U, E>,
+    user_id: serenity::UserId,
+    guild_id: Option<serenity::GuildId>,
+    channel_id: serenity::ChannelId,
+) -> bool {
+    match &framework.options().abuse_detector {
+        Some(detector) => detector.is_abusive(user_id, guild_id, channel_id),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_token_bucket_throttle() {
+    let throttle = TokenBucketThrottle::new(
+        Some(TokenBucketConfig {
+            capacity: 2,
+            refill_interval: Duration::from_secs(10),
+        }),
+        Some(TokenBucketConfig {
+            capacity: 3,
+            refill_interval: Duration::from_secs(10),
+        }),
+    );
+    let (alice, bob) = (serenity::UserId(1), serenity::UserId(2));
+    let channel = serenity::ChannelId(3);
+    let start = Instant::now();
+
+    assert!(!throttle.is_throttled(alice, channel, start));
+    assert!(!throttle.is_throttled(alice, channel, start));
+    // Alice's bucket is empty
+    assert!(throttle.is_throttled(alice, channel, start));
+    assert!(!throttle.is_throttled(bob, channel, start));
+    // The channel's bucket is empty
+    assert!(throttle.is_throttled(bob, channel, start));
+
+    let later = start + Duration::from_secs(10);
+    assert!(!throttle.is_throttled(alice, channel, later));
+    assert!(throttle.is_throttled(alice, channel, later));
+}
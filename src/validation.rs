@@ -0,0 +1,100 @@
+//! Runtime support for the `#[regex]`, `#[validate]`, `#[min]` and `#[max]` command parameter
+//! attributes
+
+/// Returned when a command argument was parsed successfully, but was rejected by the parameter's
+/// `#[regex = "..."]`, `#[validate = "..."]`, `#[min = ...]` or `#[max = ...]` attribute.
+///
+/// Like argument parse errors, this error is converted into the user error type via `?`, so
+/// commands using those attributes require `E: From<ArgumentValidationError>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArgumentValidationError {
+    /// Name of the parameter whose argument was rejected
+    pub parameter_name: &'static str,
+    /// Why the argument was rejected
+    pub reason: String,
+}
+
+impl std::fmt::Display for ArgumentValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Invalid value for `{}`: {}",
+            self.parameter_name, self.reason
+        )
+    }
+}
+
+impl std::error::Error for ArgumentValidationError {}
+
+/// Lazily compiled regex for the `#[regex = "..."]` parameter attribute, stored in a static
+#[doc(hidden)]
+pub struct _ArgumentRegex {
+    pattern: &'static str,
+    regex: once_cell::sync::OnceCell<regex::Regex>,
+}
+
+impl _ArgumentRegex {
+    pub const fn new(pattern: &'static str) -> Self {
+        Self {
+            pattern,
+            regex: once_cell::sync::OnceCell::new(),
+        }
+    }
+
+    pub fn validate(
+        &self,
+        parameter_name: &'static str,
+        value: &str,
+    ) -> Result<(), ArgumentValidationError> {
+        let pattern = self.pattern;
+        let regex = self.regex.get_or_init(|| {
+            // Anchor the pattern so that it has to match the entire argument
+            regex::Regex::new(&format!("^(?:{})$", pattern))
+                .expect("#[regex] patterns are validated by the command macro")
+        });
+
+        if regex.is_match(value) {
+            Ok(())
+        } else {
+            Err(ArgumentValidationError {
+                parameter_name,
+                reason: format!("must match `{}`", pattern),
+            })
+        }
+    }
+}
+
+/// Adapter for the `#[validate = "..."]` parameter attribute. Validation functions may return
+/// any displayable error, which becomes the [`ArgumentValidationError::reason`]
+#[doc(hidden)]
+pub fn _validate_argument<T: ?Sized, R: std::fmt::Display>(
+    parameter_name: &'static str,
+    value: &T,
+    validate: impl FnOnce(&T) -> Result<(), R>,
+) -> Result<(), ArgumentValidationError> {
+    validate(value).map_err(|reason| ArgumentValidationError {
+        parameter_name,
+        reason: reason.to_string(),
+    })
+}
+
+/// Checks the `#[min = ...]` and `#[max = ...]` parameter attributes
+#[doc(hidden)]
+pub fn _validate_range(
+    parameter_name: &'static str,
+    value: f64,
+    min: Option<f64>,
+    max: Option<f64>,
+) -> Result<(), ArgumentValidationError> {
+    let reason = match (min, max) {
+        (Some(min), _) if value < min => format!("must be at least {}", min),
+        (_, Some(max)) if value > max => format!("must be at most {}", max),
+        // NaN doesn't compare, so reject it explicitly when bounds are set
+        _ if value.is_nan() => "must be a number".to_owned(),
+        _ => return Ok(()),
+    };
+    Err(ArgumentValidationError {
+        parameter_name,
+        reason,
+    })
+}